@@ -4,7 +4,7 @@
 //  Created:
 //    06 May 2025, 11:09:11
 //  Last edited:
-//    06 May 2025, 11:17:02
+//    16 Oct 2026, 00:58:02
 //  Auto updated?
 //    Yes
 //
@@ -20,6 +20,7 @@ use console::style;
 use miette::{Context, IntoDiagnostic};
 use policy_reasoner::loggers::file::FileLogger;
 use policy_reasoner::reasoners::eflint_haskell::EFlintHaskellReasonerConnector;
+use policy_reasoner::reasoners::eflint_haskell::hash::HashAlgorithm;
 use policy_reasoner::reasoners::eflint_haskell::reasons::SilentHandler;
 use policy_reasoner::spec::auditlogger::SessionedAuditLogger;
 use policy_reasoner::spec::reasonerconn::ReasonerConnector as _;
@@ -93,6 +94,8 @@ async fn run(args: Arguments) -> miette::Result<()> {
         shlex::split(&args.eflint_cmd).into_iter().flatten(),
         &policy as &std::path::Path,
         SilentHandler,
+        None,
+        HashAlgorithm::default(),
         &logger,
     )
     .await
@@ -114,6 +117,12 @@ async fn run(args: Arguments) -> miette::Result<()> {
             println!("{reasons}");
             println!();
         },
+        ReasonerResponse::Indeterminate { reasons } => {
+            println!("{} {}", style("Reasoner says:").bold(), style("INDETERMINATE").bold().yellow());
+            println!("Reason:");
+            println!("{reasons}");
+            println!();
+        },
     }
 
     Ok(())
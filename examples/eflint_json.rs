@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 13:54:17
 //  Last edited:
-//    06 May 2025, 10:57:22
+//    16 Oct 2026, 01:27:15
 //  Auto updated?
 //    Yes
 //
@@ -16,6 +16,7 @@ use std::fs;
 use std::io::{self, Read as _};
 use std::path::PathBuf;
 use std::process::ExitCode;
+use std::time::Duration;
 
 use clap::Parser;
 use console::style;
@@ -70,6 +71,30 @@ struct Arguments {
     /// The address where the reasoner lives.
     #[clap(short, long, default_value = "http://127.0.0.1:8080", help = "The address where the eFLINT reasoner lives.")]
     address: String,
+    /// How often to retry requests to the reasoner.
+    #[clap(
+        short,
+        long,
+        default_value = "0",
+        help = "How often to retry requests to the reasoner if it's unreachable, times out or replies with a server error (5xx). Waits 100ms before \
+                the first retry, doubling for every next one."
+    )]
+    retries: usize,
+    /// Any additional root certificates to trust.
+    #[clap(
+        long,
+        help = "A PEM-encoded root certificate to trust when connecting to an 'https://' reasoner, in addition to the system's ones. Can be given \
+                multiple times."
+    )]
+    ca_cert: Vec<PathBuf>,
+    /// The command to run the reasoner locally with, if any.
+    #[clap(
+        short,
+        long,
+        help = "If given, runs the eFLINT JSON reasoner locally with this command for every request instead of connecting to '--address'. The \
+                command is split like a shell would."
+    )]
+    command: Option<String>,
 }
 
 
@@ -118,7 +143,7 @@ async fn run(args: Arguments) -> miette::Result<()> {
 
         // Compile first
         let mut json: Vec<u8> = Vec::new();
-        eflint_to_json::compile_async(&file, &mut json, args.eflint_path.as_deref())
+        eflint_to_json::compile_async(&file, &mut json, args.eflint_path.as_deref(), &[])
             .await
             .into_diagnostic()
             .with_context(|| format!("Failed to compile input file '{path}' to JSON", path = args.file.display()))?;
@@ -144,18 +169,41 @@ async fn run(args: Arguments) -> miette::Result<()> {
             .into_diagnostic()
             .with_context(|| format!("Failed to parse {path} as an eFLINT JSON phrases request", path = args.file.display()))?;
 
+    // Read any additional root certificates
+    let mut root_certificates: Vec<Vec<u8>> = Vec::with_capacity(args.ca_cert.len());
+    for path in &args.ca_cert {
+        root_certificates
+            .push(fs::read(path).into_diagnostic().with_context(|| format!("Failed to read root certificate '{path}'", path = path.display()))?);
+    }
+
     // Create the reasoner
-    let conn =
-        EFlintJsonReasonerConnector::<EFlintSilentReasonHandler, Vec<Phrase>, ()>::new_async(&args.address, EFlintSilentReasonHandler, &logger)
-            .await
-            .into_diagnostic()
-            .context("Failed to create eFLINT reasoner")?;
+    let (conn, address): (EFlintJsonReasonerConnector<EFlintSilentReasonHandler, Vec<Phrase>, ()>, &str) = match &args.command {
+        Some(cmd) => (
+            EFlintJsonReasonerConnector::new_subprocess_async(shlex::split(cmd).into_iter().flatten(), EFlintSilentReasonHandler, &logger)
+                .await
+                .into_diagnostic()
+                .context("Failed to create eFLINT reasoner")?,
+            cmd,
+        ),
+        None => (
+            EFlintJsonReasonerConnector::builder(&args.address, EFlintSilentReasonHandler)
+                .and_then(|builder| builder.with_root_certificates(root_certificates.as_slice()))
+                .into_diagnostic()
+                .context("Failed to create eFLINT reasoner")?
+                .build_async(&logger)
+                .await
+                .into_diagnostic()
+                .context("Failed to create eFLINT reasoner")?
+                .with_retries(args.retries, Duration::from_millis(100)),
+            &args.address,
+        ),
+    };
 
     let verdict: ReasonerResponse<NoReason> = conn
         .consult(policy.phrases, (), &logger)
         .await
         .into_diagnostic()
-        .with_context(|| format!("Failed to send message to reasoner at {address}", address = args.address))?;
+        .with_context(|| format!("Failed to send message to reasoner at {address}"))?;
 
     // OK, report
     match verdict {
@@ -166,6 +214,12 @@ async fn run(args: Arguments) -> miette::Result<()> {
             println!("{reasons}");
             println!();
         },
+        ReasonerResponse::Indeterminate { reasons } => {
+            println!("{} {}", style("Reasoner says:").bold(), style("INDETERMINATE").bold().yellow());
+            println!("Reason:");
+            println!("{reasons}");
+            println!();
+        },
     }
 
     Ok(())
@@ -0,0 +1,238 @@
+//  EFLINT TO JSON.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 11:02:18
+//  Last edited:
+//    16 Oct 2026, 01:05:30
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Compiles an eFLINT file to eFLINT JSON locally, without talking to
+//!   any reasoner. Useful to inspect what a policy compiles to while
+//!   iterating on it, or to see how two policies differ after includes
+//!   have been expanded.
+//
+
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use console::style;
+use miette::{Context, IntoDiagnostic as _};
+use policy_reasoner::reasoners::eflint_json::json::spec::RequestPhrases;
+use serde_json::Value;
+use share::InputFile;
+use tracing::{Level, error, info};
+
+
+/***** ARGUMENTS *****/
+/// Defines the arguments for this binary.
+#[derive(Parser)]
+struct Arguments {
+    /// Whether to make `info!()` and `debug!()` visible.
+    #[clap(long, help = "If given, enables INFO- and DEBUG-level logging.")]
+    debug: bool,
+    /// Whether to make `trace!()` visible.
+    #[clap(long, help = "If given, enables TRACE-level logging. Implies '--debug'.")]
+    trace: bool,
+
+    /// The file to use as input.
+    #[clap(name = "FILE", default_value = "-", help = "The eFLINT file to compile. Use '-' to read from stdin.")]
+    file: InputFile,
+    /// Where to write the output to.
+    #[clap(short, long, help = "If given, writes the compiled JSON to the given file instead of stdout.")]
+    output: Option<PathBuf>,
+    /// Whether to pretty-print the output.
+    #[clap(short, long, help = "If given, pretty-prints the compiled JSON.")]
+    pretty: bool,
+    /// Which `eflint-to-json` to use.
+    #[clap(short, long, help = "If given, uses an existing 'eflint-to-json' binary instead of downloading one from the internet.")]
+    eflint_to_json_path: Option<PathBuf>,
+    /// Directories to search for included files.
+    #[clap(
+        short = 'I',
+        long = "include-dir",
+        help = "A directory to search for included files that aren't found relative to the file including them. May be given multiple times; \
+                searched in order."
+    )]
+    include_dirs: Vec<PathBuf>,
+
+    /// Whether to only resolve includes.
+    #[clap(
+        long,
+        conflicts_with = "diff",
+        help = "If given, only resolves includes and writes the flattened eFLINT instead of any JSON. Does not require a compiler."
+    )]
+    preprocess: bool,
+
+    /// Another file to compare the input to.
+    #[clap(
+        long,
+        help = "If given, compiles this eFLINT file too and prints which phrases were removed from and added to 'FILE' to get it, instead of any \
+                JSON."
+    )]
+    diff: Option<PathBuf>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Computes a phrase-level diff between two lists of phrases.
+///
+/// Phrases are compared by their JSON serialization. Changed phrases show up as a removal
+/// followed by an addition.
+///
+/// # Arguments
+/// - `old`: The phrases in the original policy.
+/// - `new`: The phrases in the new policy.
+///
+/// # Returns
+/// A list of `(added, phrase)`-pairs for every phrase that is only in one of the two lists, in
+/// order.
+fn diff_phrases(old: &[Value], new: &[Value]) -> Vec<(bool, Value)> {
+    // Compute the longest common subsequence table
+    let mut lcs: Vec<Vec<usize>> = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    // Walk it to find the differences
+    let mut res: Vec<(bool, Value)> = Vec::new();
+    let (mut i, mut j): (usize, usize) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i >= old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            res.push((true, new[j].clone()));
+            j += 1;
+        } else {
+            res.push((false, old[i].clone()));
+            i += 1;
+        }
+    }
+    res
+}
+
+/// Compiles the given eFLINT file to its phrases.
+///
+/// # Arguments
+/// - `path`: The path of the file to compile.
+/// - `compiler`: The path of the `eflint-to-json` compiler to use, if any.
+/// - `include_dirs`: The directories to search for included files.
+///
+/// # Returns
+/// The compiled phrases, as JSON.
+///
+/// # Errors
+/// This function errors if we failed to compile the file or parse the result.
+async fn compile_phrases(
+    path: &std::path::Path,
+    compiler: Option<&std::path::Path>,
+    include_dirs: &[&std::path::Path],
+) -> miette::Result<Vec<Value>> {
+    let mut json: Vec<u8> = Vec::new();
+    eflint_to_json::compile_async(path, &mut json, compiler, include_dirs)
+        .await
+        .into_diagnostic()
+        .with_context(|| format!("Failed to compile input file '{}' to JSON", path.display()))?;
+    let phrases: RequestPhrases = serde_json::from_slice(&json)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to parse compiled {} as an eFLINT JSON phrases request", path.display()))?;
+    phrases.phrases.iter().map(|phrase| serde_json::to_value(phrase).into_diagnostic().context("Failed to serialize eFLINT JSON phrase")).collect()
+}
+
+
+
+
+
+/***** LIBRARY *****/
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    // Parse the arguments
+    let args = Arguments::parse();
+
+    // Setup the logger
+    tracing_subscriber::fmt()
+        .with_max_level(if args.trace {
+            Level::TRACE
+        } else if args.debug {
+            Level::DEBUG
+        } else {
+            Level::WARN
+        })
+        .init();
+    info!("{} - v{}", env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"));
+
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            error!("{err:?}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+async fn run(args: Arguments) -> miette::Result<()> {
+    // First: resolve any stdin to a file
+    let file = args.file.as_file().await?;
+    let include_dirs: Vec<&std::path::Path> = args.include_dirs.iter().map(PathBuf::as_path).collect();
+
+    // Diff instead if asked
+    if let Some(other) = &args.diff {
+        let old: Vec<Value> = compile_phrases(&file, args.eflint_to_json_path.as_deref(), &include_dirs).await?;
+        let new: Vec<Value> = compile_phrases(other, args.eflint_to_json_path.as_deref(), &include_dirs).await?;
+        let diff: Vec<(bool, Value)> = diff_phrases(&old, &new);
+        if diff.is_empty() {
+            println!("{}", style("Policies are equivalent").bold().green());
+        }
+        for (added, phrase) in diff {
+            if added {
+                println!("{} {}", style("+").bold().green(), style(phrase).green());
+            } else {
+                println!("{} {}", style("-").bold().red(), style(phrase).red());
+            }
+        }
+        return Ok(());
+    }
+
+    // Only flatten if asked
+    if args.preprocess {
+        let flattened: String = eflint_to_json::preprocess(&file, &include_dirs)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to preprocess input file '{path}'", path = args.file.display()))?;
+        return match &args.output {
+            Some(path) => fs::write(path, flattened).into_diagnostic().with_context(|| format!("Failed to write output file '{}'", path.display())),
+            None => io::stdout().write_all(flattened.as_bytes()).into_diagnostic().context("Failed to write to stdout"),
+        };
+    }
+
+    // Compile it
+    let mut json: Vec<u8> = Vec::new();
+    eflint_to_json::compile_async(&file, &mut json, args.eflint_to_json_path.as_deref(), &include_dirs)
+        .await
+        .into_diagnostic()
+        .with_context(|| format!("Failed to compile input file '{path}' to JSON", path = args.file.display()))?;
+
+    // Re-serialize it prettily if asked
+    if args.pretty {
+        let phrases: RequestPhrases = serde_json::from_slice(&json)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to parse compiled {path} as an eFLINT JSON phrases request", path = args.file.display()))?;
+        json = serde_json::to_vec_pretty(&phrases).into_diagnostic().context("Failed to serialize eFLINT JSON phrases request")?;
+    }
+
+    // Write it to wherever we need to
+    match &args.output {
+        Some(path) => fs::write(path, &json).into_diagnostic().with_context(|| format!("Failed to write output file '{}'", path.display())),
+        None => io::stdout().write_all(&json).into_diagnostic().context("Failed to write to stdout"),
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 16:17:21
 //  Last edited:
-//    02 Dec 2024, 14:34:47
+//    15 Oct 2026, 23:38:12
 //  Auto updated?
 //    Yes
 //
@@ -84,6 +84,9 @@ async fn run(_args: Arguments) -> miette::Result<()> {
         ReasonerResponse::Violated(_) => {
             println!("{} {}", style("Reasoner says:").bold(), style("VIOLATION").bold().red());
         },
+        ReasonerResponse::Indeterminate { .. } => {
+            println!("{} {}", style("Reasoner says:").bold(), style("INDETERMINATE").bold().yellow());
+        },
     }
 
     Ok(())
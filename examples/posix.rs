@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2024, 16:32:29
 //  Last edited:
-//    17 Oct 2024, 12:00:53
+//    16 Oct 2026, 00:48:52
 //  Auto updated?
 //    Yes
 //
@@ -16,7 +16,7 @@
 use std::path::PathBuf;
 use std::process::ExitCode;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use console::style;
 use file_logger::FileLogger;
 use miette::{Context as _, IntoDiagnostic as _};
@@ -25,10 +25,9 @@ use policy_reasoner::spec::ReasonerConnector as _;
 use policy_reasoner::spec::auditlogger::SessionedAuditLogger;
 use policy_reasoner::workflow::Workflow;
 use posix_reasoner::config::Config;
-use share::InputFile;
+use share::{InputFile, MaybeTempFile};
 use spec::reasonerconn::ReasonerResponse;
 use tokio::fs;
-use tokio::io::{self, AsyncReadExt as _};
 use tracing::{Level, debug, error, info};
 
 
@@ -44,21 +43,13 @@ use tracing::{Level, debug, error, info};
 /// # Errors
 /// This function errors if it failed to read stdin OR the file, or parse it as a valid Workflow.
 async fn load_workflow(input: &InputFile) -> miette::Result<Workflow> {
-    let workflow = match input {
-        InputFile::Stdin => {
-            debug!("Reading workflow from stdin");
-            let mut raw: Vec<u8> = Vec::new();
-            io::stdin().read_buf(&mut raw).await.into_diagnostic().context("Failed to read from stdin")?;
-            String::from_utf8(raw).into_diagnostic().context("Stdin is not valid UTF-8")?
-        },
-        InputFile::File(path) => {
-            debug!("Reading workflow from file");
-            fs::read_to_string(path)
-                .await
-                .into_diagnostic()
-                .with_context(|| format!("Failed to read the workflow from {input}", input = input.display()))?
-        },
-    };
+    // NOTE: Stdin is materialized to a temporary file in its entirety, such that piped workflows are never cut short
+    debug!("Reading workflow from {}", input.display());
+    let file: MaybeTempFile = input.as_file().await.with_context(|| format!("Failed to read the workflow from {input}", input = input.display()))?;
+    let workflow: String = fs::read_to_string(&*file)
+        .await
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read the workflow from {input}", input = input.display()))?;
 
     let workflow = serde_json::from_str(&workflow).into_diagnostic().with_context(|| format!("{input:?} is not a valid workflow"))?;
 
@@ -101,6 +92,18 @@ async fn load_config(path: PathBuf) -> miette::Result<Config> {
 
 
 /***** ARGUMENTS *****/
+/// The formats in which the verdict can be reported.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum OutputFormat {
+    /// A human-friendly message.
+    #[default]
+    Human,
+    /// A JSON object with the verdict, its reasons and the audit log reference.
+    Json,
+}
+
+
+
 /// The arguments for this binary.
 #[derive(Parser)]
 pub struct Arguments {
@@ -117,6 +120,16 @@ pub struct Arguments {
     /// The file containing the config for the reasoner.
     #[clap(short, long, help = "The JSON configuration file to read that configures the policy.")]
     config:   PathBuf,
+    /// How to report the verdict.
+    #[clap(
+        short,
+        long,
+        value_enum,
+        default_value_t,
+        help = "How to report the verdict. 'json' prints an object with 'allowed', 'reasons' and 'reference_id' fields. Either way, the exit code \
+                is 2 if the workflow is not allowed."
+    )]
+    output:   OutputFormat,
 }
 
 
@@ -142,14 +155,15 @@ async fn main() -> ExitCode {
     info!("{} - v{}", env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"));
 
     match run(args).await {
-        Ok(()) => ExitCode::SUCCESS,
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(2),
         Err(err) => {
             error!("{err:?}");
             ExitCode::FAILURE
         },
     }
 }
-async fn run(args: Arguments) -> miette::Result<()> {
+async fn run(args: Arguments) -> miette::Result<bool> {
     // Read the workflow & config
     let workflow: Workflow = load_workflow(&args.workflow).await.context("Could not load workflow")?;
     let config: Config = load_config(args.config).await.context("Could not load config")?;
@@ -164,12 +178,34 @@ async fn run(args: Arguments) -> miette::Result<()> {
     let verdict = conn.consult(State { workflow, config }, (), &logger).await.into_diagnostic().context("Failed to consult the POSIX reasoner")?;
 
     // OK, report
-    match verdict {
-        ReasonerResponse::Success => println!("{} {}", style("Reasoner says:").bold(), style("OK").bold().green()),
-        ReasonerResponse::Violated(_) => {
-            println!("{} {}", style("Reasoner says:").bold(), style("VIOLATION").bold().red());
+    // NOTE: Undecided workflows are not allowed either
+    let allowed: bool = matches!(verdict, ReasonerResponse::Success);
+    match args.output {
+        OutputFormat::Human => match verdict {
+            ReasonerResponse::Success => println!("{} {}", style("Reasoner says:").bold(), style("OK").bold().green()),
+            ReasonerResponse::Violated(reasons) => {
+                println!("{} {}", style("Reasoner says:").bold(), style("VIOLATION").bold().red());
+                for reason in reasons {
+                    println!(" - {reason}");
+                }
+            },
+            ReasonerResponse::Indeterminate { reasons } => {
+                println!("{} {}", style("Reasoner says:").bold(), style("INDETERMINATE").bold().yellow());
+                for reason in reasons {
+                    println!(" - {reason}");
+                }
+            },
+        },
+        OutputFormat::Json => {
+            let reasons: Vec<String> = match verdict {
+                ReasonerResponse::Success => Vec::new(),
+                ReasonerResponse::Violated(reasons) | ReasonerResponse::Indeterminate { reasons } => {
+                    reasons.iter().map(ToString::to_string).collect()
+                },
+            };
+            println!("{}", serde_json::json!({ "allowed": allowed, "reasons": reasons, "reference_id": logger.reference() }));
         },
     }
 
-    Ok(())
+    Ok(allowed)
 }
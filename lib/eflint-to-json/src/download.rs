@@ -4,7 +4,7 @@
 //  Created:
 //    29 Nov 2023, 15:11:58
 //  Last edited:
-//    11 Oct 2024, 16:08:06
+//    15 Oct 2026, 23:53:22
 //  Auto updated?
 //    Yes
 //
@@ -58,6 +58,9 @@ pub enum Error {
     #[error("Directory '{}' not found", path.display())]
     DirNotFound { path: PathBuf },
 
+    /// The download was neither verified by checksum nor HTTPS, and this was not explicitly allowed.
+    #[error("Refusing to download '{address}' without checksum verification or HTTPS (set `DownloadSecurity::insecure` if you really want this)")]
+    InsecureDownload { address: String },
     /// The given address did not have HTTPS enabled.
     #[error("Security policy requires HTTPS is enabled, but '{address}' does not enable it (or we cannot parse the URL)")]
     NotHttps { address: String },
@@ -84,6 +87,10 @@ pub struct DownloadSecurity<'c> {
     pub checksum: Option<&'c [u8]>,
     /// If true, then the file can only be downloaded over HTTPS.
     pub https:    bool,
+    /// If true, then the file may be downloaded without either checksum verification or HTTPS.
+    ///
+    /// If false, such downloads are refused with an [`Error::InsecureDownload`].
+    pub insecure: bool,
 }
 impl<'c> DownloadSecurity<'c> {
     /// Constructor for the DownloadSecurity that enables with all security measures enabled.
@@ -98,7 +105,7 @@ impl<'c> DownloadSecurity<'c> {
     /// # Returns
     /// A new DownloadSecurity instance that will make your downloaded file so secure you can use it to store a country's defecit (not legal advice).
     #[inline]
-    pub fn all(checkum: &'c [u8]) -> Self { Self { checksum: Some(checkum), https: true } }
+    pub fn all(checkum: &'c [u8]) -> Self { Self { checksum: Some(checkum), https: true, insecure: false } }
 
     /// Constructor for the DownloadSecurity that enables checksum verification only.
     ///
@@ -112,7 +119,7 @@ impl<'c> DownloadSecurity<'c> {
     /// # Returns
     /// A new DownloadSecurity instance that will make sure your file has the given checksum before returning.
     #[inline]
-    pub fn checksum(checkum: &'c [u8]) -> Self { Self { checksum: Some(checkum), https: false } }
+    pub fn checksum(checkum: &'c [u8]) -> Self { Self { checksum: Some(checkum), https: false, insecure: false } }
 
     /// Constructor for the DownloadSecurity that forces downloads to go over HTTPS.
     ///
@@ -121,16 +128,45 @@ impl<'c> DownloadSecurity<'c> {
     /// # Returns
     /// A new DownloadSecurity instance that will make sure your file if downloaded over HTTPS only.
     #[inline]
-    pub fn https() -> Self { Self { checksum: None, https: true } }
+    pub fn https() -> Self { Self { checksum: None, https: true, insecure: false } }
 
     /// Constructor for the DownloadSecurity that disabled all security measures.
     ///
     /// For obvious reasons, this security is not recommended unless you trust both the network _and_ the remote party.
     ///
+    /// Note that downloads with this security are refused unless its `insecure`-field is set. Use
+    /// [`DownloadSecurity::insecure()`] to do so.
+    ///
+    /// # Returns
+    /// A new DownloadSecurity instance that will require no additional security measures on the downloaded file.
+    #[inline]
+    pub fn none() -> Self { Self { checksum: None, https: false, insecure: false } }
+
+    /// Constructor for the DownloadSecurity that disabled all security measures, and explicitly
+    /// allows downloading like that.
+    ///
+    /// This is like [`DownloadSecurity::none()`], except that the download is not refused.
+    ///
     /// # Returns
     /// A new DownloadSecurity instance that will require no additional security measures on the downloaded file.
     #[inline]
-    pub fn none() -> Self { Self { checksum: None, https: false } }
+    pub fn insecure() -> Self { Self { checksum: None, https: false, insecure: true } }
+
+    /// Asserts that this security is sufficient to download a file with.
+    ///
+    /// # Arguments
+    /// - `address`: The address that we're downloading from. Only used for error reporting.
+    ///
+    /// # Errors
+    /// This function errors if neither a checksum nor HTTPS is used, and that was not explicitly
+    /// allowed by setting the `insecure`-field.
+    #[inline]
+    fn validate(&self, address: &str) -> Result<(), Error> {
+        if self.checksum.is_none() && !self.https && !self.insecure {
+            return Err(Error::InsecureDownload { address: address.into() });
+        }
+        Ok(())
+    }
 }
 impl Display for DownloadSecurity<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -143,12 +179,53 @@ impl Display for DownloadSecurity<'_> {
             Ok(())
         } else if self.https {
             write!(f, "HTTPS")
+        } else if self.insecure {
+            write!(f, "None (insecure)")
         } else {
             write!(f, "None")
         }
     }
 }
 
+/// Defines what to print to stdout while downloading.
+#[derive(Clone, Debug)]
+pub struct DownloadOutput {
+    /// The style used to accent parts of the output (use a non-exciting [`Style`] to print without
+    /// styles).
+    pub style: Style,
+    /// Whether to show an (animated) progress bar during the download.
+    pub show_progress: bool,
+    /// Whether to print that the checksum was verified, if any.
+    pub show_checksum_result: bool,
+}
+impl DownloadOutput {
+    /// Constructor for the DownloadOutput that shows everything.
+    ///
+    /// # Arguments
+    /// - `style`: The [`Style`] used to accent parts of the output.
+    ///
+    /// # Returns
+    /// A new DownloadOutput that prints what's being downloaded, a progress bar and the checksum
+    /// result.
+    #[inline]
+    pub fn all(style: Style) -> Self { Self { style, show_progress: true, show_checksum_result: true } }
+
+    /// Constructor for the DownloadOutput that is suitable for non-interactive use.
+    ///
+    /// # Arguments
+    /// - `style`: The [`Style`] used to accent parts of the output.
+    ///
+    /// # Returns
+    /// A new DownloadOutput that prints what's being downloaded and the checksum result, but not
+    /// the progress bar.
+    #[inline]
+    pub fn headless(style: Style) -> Self { Self { style, show_progress: false, show_checksum_result: true } }
+}
+impl From<Style> for DownloadOutput {
+    #[inline]
+    fn from(value: Style) -> Self { Self::all(value) }
+}
+
 
 
 
@@ -162,24 +239,26 @@ impl Display for DownloadSecurity<'_> {
 /// - `source`: The URL to download the file from.
 /// - `target`: The location to download the file to.
 /// - `verification`: Some method to verify the file is what we think it is. See the `VerifyMethod`-enum for more information.
-/// - `verbose`: If not `None`, will print to the output with accents given in the given `Style` (use a non-exciting Style to print without styles).
+/// - `verbose`: If not `None`, will print to the output as configured by the given [`DownloadOutput`].
 ///
 /// # Returns
 /// Nothing, except that when it does you can assume a file exists at the given location.
 ///
 /// # Errors
 /// This function may error if we failed to download the file or write it (which may happen if the parent directory of `local` does not exist, among other things).
+/// It also refuses to download anything if `security` enables neither a checksum nor HTTPS, unless explicitly marked as `insecure`.
 pub fn download_file(
     source_url: impl AsRef<str>,
     target: impl AsRef<Path>,
     security: DownloadSecurity<'_>,
-    verbose: Option<Style>,
+    verbose: Option<DownloadOutput>,
 ) -> Result<(), Error> {
     let source_url: &str = source_url.as_ref();
     let target: &Path = target.as_ref();
     debug!("Downloading '{source_url}' to '{}' (Security: {security})...", target.display());
-    if let Some(style) = &verbose {
-        println!("Downloading {}...", style.apply_to(source_url));
+    security.validate(source_url)?;
+    if let Some(verbose) = &verbose {
+        println!("Downloading {}...", verbose.style.apply_to(source_url));
     }
 
     // Assert the download directory exists
@@ -227,7 +306,7 @@ pub fn download_file(
     // Create the progress bar based on whether if there is a length
     debug!("Downloading response to file '{}'...", target.display());
     let len: Option<u64> = res.headers().get("Content-Length").and_then(|len| len.to_str().ok()).and_then(|len| u64::from_str(len).ok());
-    let prgs: Option<ProgressBar> = if verbose.is_some() {
+    let prgs: Option<ProgressBar> = if verbose.as_ref().is_some_and(|v| v.show_progress) {
         Some(if let Some(len) = len {
             ProgressBar::new(len)
                 .with_style(ProgressStyle::with_template("    {bar:60} {bytes}/{total_bytes} {bytes_per_sec} ETA {eta_precise}").unwrap())
@@ -275,10 +354,10 @@ pub fn download_file(
         }
 
         // Print that the checksums are equal if asked
-        if let Some(style) = verbose {
+        if let Some(verbose) = verbose.filter(|v| v.show_checksum_result) {
             // Create the dim styles
             let dim: Style = Style::new().dim();
-            let accent: Style = style.dim();
+            let accent: Style = verbose.style.dim();
 
             // Write it with those styles
             println!("{}{}{}", dim.apply_to(" > Checksum "), accent.apply_to(hex::encode(&result[..])), dim.apply_to(" OK"));
@@ -297,25 +376,27 @@ pub fn download_file(
 /// - `source`: The URL to download the file from.
 /// - `target`: The location to download the file to.
 /// - `verification`: Some method to verify the file is what we think it is. See the `VerifyMethod`-enum for more information.
-/// - `verbose`: If not `None`, will print to the output with accents given in the given `Style` (use a non-exciting Style to print without styles).
+/// - `verbose`: If not `None`, will print to the output as configured by the given [`DownloadOutput`].
 ///
 /// # Returns
 /// Nothing, except that when it does you can assume a file exists at the given location.
 ///
 /// # Errors
 /// This function may error if we failed to download the file or write it (which may happen if the parent directory of `local` does not exist, among other things).
+/// It also refuses to download anything if `security` enables neither a checksum nor HTTPS, unless explicitly marked as `insecure`.
 #[cfg(feature = "async-tokio")]
 pub async fn download_file_async(
     source_url: impl AsRef<str>,
     target: impl AsRef<Path>,
     security: DownloadSecurity<'_>,
-    verbose: Option<Style>,
+    verbose: Option<DownloadOutput>,
 ) -> Result<(), Error> {
     let source_url: &str = source_url.as_ref();
     let target: &Path = target.as_ref();
     debug!("Downloading '{source_url}' to '{target}' (Security: {security})...", target = target.display());
-    if let Some(style) = &verbose {
-        println!("Downloading {}...", style.apply_to(source_url));
+    security.validate(source_url)?;
+    if let Some(verbose) = &verbose {
+        println!("Downloading {}...", verbose.style.apply_to(source_url));
     }
 
     // Assert the download directory exists
@@ -365,7 +446,7 @@ pub async fn download_file_async(
     // Create the progress bar based on whether if there is a length
     debug!("Downloading response to file '{}'...", target.display());
     let len: Option<u64> = res.headers().get("Content-Length").and_then(|len| len.to_str().ok()).and_then(|len| u64::from_str(len).ok());
-    let prgs: Option<ProgressBar> = if verbose.is_some() {
+    let prgs: Option<ProgressBar> = if verbose.as_ref().is_some_and(|v| v.show_progress) {
         Some(if let Some(len) = len {
             ProgressBar::new(len)
                 .with_style(ProgressStyle::with_template("    {bar:60} {bytes}/{total_bytes} {bytes_per_sec} ETA {eta_precise}").unwrap())
@@ -415,10 +496,10 @@ pub async fn download_file_async(
         }
 
         // Print that the checksums are equal if asked
-        if let Some(style) = verbose {
+        if let Some(verbose) = verbose.filter(|v| v.show_checksum_result) {
             // Create the dim styles
             let dim: Style = Style::new().dim();
-            let accent: Style = style.dim();
+            let accent: Style = verbose.style.dim();
 
             // Write it with those styles
             println!("{}{}{}", dim.apply_to(" > Checksum "), accent.apply_to(hex::encode(&result[..])), dim.apply_to(" OK"));
@@ -4,7 +4,7 @@
 //  Created:
 //    30 Nov 2023, 10:38:50
 //  Last edited:
-//    11 Oct 2024, 16:10:25
+//    16 Oct 2026, 01:51:19
 //  Auto updated?
 //    Yes
 //
@@ -25,19 +25,21 @@ use std::fs::{self, File, Permissions};
 use std::io::{BufRead as _, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use console::Style;
+use sha2::{Digest as _, Sha256};
 #[cfg(feature = "async-tokio")]
 use tokio::fs::{self as tfs, File as TFile};
 #[cfg(feature = "async-tokio")]
-use tokio::io::{AsyncBufReadExt as _, AsyncReadExt, AsyncWriteExt as _, BufReader as TBufReader};
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt, AsyncWrite, AsyncWriteExt as _, BufReader as TBufReader};
 #[cfg(feature = "async-tokio")]
 use tokio::process::{ChildStdin as TChildStdin, ChildStdout as TChildStdout, Command as TCommand};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 #[cfg(feature = "async-tokio")]
 use crate::download::download_file_async;
-use crate::download::{DownloadSecurity, download_file};
+use crate::download::{DownloadOutput, DownloadSecurity, download_file};
 
 
 /***** CONSTANTS *****/
@@ -45,6 +47,12 @@ use crate::download::{DownloadSecurity, download_file};
 const COMPILER_URL: &str = "https://github.com/Olaf-Erkemeij/eflint-server/raw/bd3997df89441f13cbc82bd114223646df41540d/eflint-to-json";
 /// Compiler download checksum.
 const COMPILER_CHECKSUM: [u8; 32] = hex_literal::hex!("4e4e59b158ca31e532ec0a22079951788696ffa5d020b36790b4461dbadec83d");
+/// The time between checks whether another process finished downloading the compiler.
+const COMPILER_LOCK_POLL: Duration = Duration::from_millis(100);
+/// The time after which a lock file of another download is considered abandoned.
+const COMPILER_LOCK_STALE: Duration = Duration::from_secs(600);
+/// The default maximum depth of nested includes.
+const DEFAULT_MAX_DEPTH: usize = 64;
 
 
 
@@ -117,13 +125,88 @@ impl Display for ChildStream {
 impl error::Error for ChildStream {}
 
 
+/// Maps lines of the flattened input given to the compiler back to the files (and lines) they came from.
+#[derive(Clone, Debug, Default)]
+pub struct LineMap {
+    /// Runs of consecutive lines from the same file, as `(first flattened line, file, first line in that file)`.
+    segments: Vec<(usize, PathBuf, usize)>,
+    /// The number of flattened lines seen so far.
+    len:      usize,
+}
+impl LineMap {
+    /// Registers that the next line of the flattened input is the given line of the given file.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file the line comes from.
+    /// - `line`: The (1-indexed) line number in that file.
+    fn push(&mut self, path: &Path, line: usize) {
+        self.len += 1;
+        if let Some((first, last_path, last_line)) = self.segments.last() {
+            if last_path == path && *last_line + (self.len - first) == line {
+                return;
+            }
+        }
+        self.segments.push((self.len, path.into(), line));
+    }
+
+    /// Translates a line in the flattened input back to where it came from.
+    ///
+    /// # Arguments
+    /// - `line`: The (1-indexed) line number in the flattened input, e.g., as reported by the compiler.
+    ///
+    /// # Returns
+    /// The file and (1-indexed) line in that file, or [`None`] if `line` is out of range.
+    pub fn resolve(&self, line: usize) -> Option<(&Path, usize)> {
+        if line == 0 || line > self.len {
+            return None;
+        }
+        let (first, path, orig) = &self.segments[self.segments.partition_point(|(first, _, _)| *first <= line) - 1];
+        Some((path, orig + (line - first)))
+    }
+
+    /// Finds all `line N` mentioned in the given child output and explains where they came from.
+    ///
+    /// # Arguments
+    /// - `output`: The [`ChildStreams`] to search for line numbers.
+    ///
+    /// # Returns
+    /// A string to append to an error message, which is empty if no (known) lines were mentioned.
+    fn explain(&self, output: &ChildStreams) -> String {
+        let mut lines: Vec<usize> = Vec::new();
+        for stream in &output.0 {
+            for (i, _) in stream.1.match_indices("line ") {
+                let digits: &str = &stream.1[i + 5..];
+                let digits: &str = &digits[..digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len())];
+                if let Ok(line) = digits.parse::<usize>() {
+                    if !lines.contains(&line) {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+        let located: Vec<String> = lines
+            .into_iter()
+            .filter_map(|line| self.resolve(line).map(|(path, orig)| format!("compiler line {line} is line {orig} of '{}'", path.display())))
+            .collect();
+        if located.is_empty() { String::new() } else { format!(" ({})", located.join("; ")) }
+    }
+}
+
+
 
 /// Defines toplevel errors.
 #[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
 pub enum Error {
     /// The child failed
-    #[error("Child process {cmd:?} failed with exit status {status}")]
-    ChildFailed { cmd: String, status: ExitStatus, output: ChildStreams },
+    ///
+    /// The `lines` map the line numbers reported by the compiler back to the original files.
+    #[error("Child process {cmd:?} failed with exit status {status}{}", lines.explain(output))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(eflint_to_json::compile), help("The compiler's output below usually points at the offending eFLINT phrase"))
+    )]
+    ChildFailed { cmd: String, status: ExitStatus, output: ChildStreams, lines: LineMap },
     /// Failed to read from child stdout.
     #[error("Failed to read from child stdin")]
     ChildRead { source: std::io::Error },
@@ -133,6 +216,13 @@ pub enum Error {
     /// Failed to write to child stdin.
     #[error("Failed to write to child stdin")]
     ChildWrite { source: std::io::Error },
+    /// The downloaded compiler did not have the checksum we expected.
+    #[error("Compiler downloaded from '{from}' has checksum '{got}', but expected '{expected}' (is the mirror serving a different binary?)")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(eflint_to_json::checksum), help("Check the `CompilerSource`'s checksum matches the binary it points to"))
+    )]
+    CompilerChecksum { from: String, expected: String, got: String },
     /// Failed to download the compiler.
     ///
     /// NOTE: `err` is boxed to not make this variant much larger in memory than the rest.
@@ -150,15 +240,54 @@ pub enum Error {
     /// Failed to set permissions of file.
     #[error("Failed to set permissions of file '{}'", path.display())]
     FilePermissions { path: PathBuf, source: std::io::Error },
+    /// Failed to move a downloaded file into place.
+    #[error("Failed to move file '{}' to '{}'", from.display(), to.display())]
+    FileRename { from: PathBuf, to: PathBuf, source: std::io::Error },
     /// Failed to read the input file.
     #[error("Failed to read from input file '{}'", path.display())]
     FileRead { path: PathBuf, source: std::io::Error },
+    /// A glob in an include matched no files.
+    #[error("Include pattern '{pattern}' matches no files (in file '{}')", parent.display())]
+    GlobEmpty { parent: PathBuf, pattern: String },
+    /// A glob in an include was not a valid pattern.
+    #[error("Invalid include pattern '{pattern}' (in file '{}')", parent.display())]
+    GlobPattern { parent: PathBuf, pattern: String, source: glob::PatternError },
+    /// Failed to read an entry matched by a glob in an include.
+    #[error("Failed to read entry matching include pattern '{pattern}' (in file '{}')", parent.display())]
+    GlobRead { parent: PathBuf, pattern: String, source: glob::GlobError },
+    /// A file (indirectly) `#include`s itself.
+    #[error("Circular include: {}", stack.iter().map(|p| format!("'{}'", p.display())).collect::<Vec<String>>().join(" -> "))]
+    IncludeCycle { stack: Vec<PathBuf> },
+    /// Includes were nested deeper than allowed.
+    #[error("File '{}' is included {depth} levels deep, which exceeds the maximum (are the includes generated?)", path.display())]
+    IncludeTooDeep { path: PathBuf, depth: usize },
     /// Failed to open included file.
-    #[error("Failed to open included file '{}' (in file '{}')", path.display(), parent.display())]
-    IncludeOpen { parent: PathBuf, path: PathBuf, source: std::io::Error },
+    ///
+    /// `tried` lists every path at which the file was looked for, in order.
+    #[error(
+        "Failed to open included file '{}' (in file '{}'; tried {})",
+        path.display(),
+        parent.display(),
+        tried.iter().map(|p| format!("'{}'", p.display())).collect::<Vec<String>>().join(", ")
+    )]
+    IncludeOpen { parent: PathBuf, path: PathBuf, tried: Vec<PathBuf>, source: std::io::Error },
+    /// Failed to create the lock file guarding the compiler download.
+    #[error("Failed to create lock file '{}'", path.display())]
+    LockCreate { path: PathBuf, source: std::io::Error },
     /// Missing a quote in the `#include`-string.
     #[error("Missing quotes (\") in '{raw}' (in file '{}')", parent.display())]
+    #[cfg_attr(feature = "miette", diagnostic(code(eflint_to_json::include), help("Write includes as `#include \"path/to/file.eflint\".`")))]
     MissingQuote { parent: PathBuf, raw: String },
+    /// The compiler produced output that is not valid UTF-8.
+    #[error("Compiler output is not valid UTF-8")]
+    OutputUtf8 { source: std::string::FromUtf8Error },
+    /// The compiler produced JSON that could not be parsed as an eFLINT JSON request.
+    #[error("Compiler output is not a valid eFLINT JSON request (is the compiler out-of-sync with the eflint-json crate?)")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(eflint_to_json::verify), help("Use a compiler version that matches the version of the `eflint-json` crate"))
+    )]
+    OutputVerify { source: serde_json::Error },
     /// Failed to canonicalize the given path.
     #[error("Failed to canonicalize path '{}' (in file '{}')", path.display(), parent.display())]
     PathCanonicalize { parent: PathBuf, path: PathBuf, source: std::io::Error },
@@ -174,20 +303,525 @@ pub enum Error {
 
 
 
+/***** HELPERS *****/
+/// Removes the lock file guarding a compiler download when dropped.
+#[derive(Debug)]
+struct CompilerLock {
+    /// The path of the lock file.
+    path: PathBuf,
+}
+impl Drop for CompilerLock {
+    #[inline]
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            warn!("Failed to remove compiler lock file '{}': {}", self.path.display(), err);
+        }
+    }
+}
+
+/// Describes the outcome of attempting to lock a compiler download.
+enum LockAttempt {
+    /// We hold the lock, and should download the compiler.
+    Acquired(CompilerLock),
+    /// Somebody else is downloading the compiler; we should wait for them.
+    Busy,
+}
+
+/// Describes how the input is fed to the compiler.
+enum CompilerInput<F> {
+    /// The input file (and its includes) are streamed to the compiler as they are read.
+    Streamed(F),
+    /// The input was already flattened to check the cache, and the compiler's output should be
+    /// stored in the given cache entry.
+    Cached { flattened: Vec<u8>, entry: PathBuf },
+}
+
+/// Keeps track of what we've seen while resolving the includes of the input.
+#[derive(Debug)]
+struct LoadContext {
+    /// The set of already imported files (relevant for require).
+    imported: HashSet<PathBuf>,
+    /// The files and includes found so far.
+    manifest: IncludeManifest,
+    /// Where the lines written so far came from.
+    lines: LineMap,
+    /// The (canonicalized) files we're currently loading, from the input file to the current one.
+    stack: Vec<PathBuf>,
+    /// The maximum number of nested includes.
+    max_depth: usize,
+    /// Directories to search for includes not found relative to the including file.
+    include_dirs: Vec<PathBuf>,
+}
+impl Default for LoadContext {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            imported: HashSet::new(),
+            manifest: IncludeManifest::default(),
+            lines: LineMap::default(),
+            stack: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            include_dirs: Vec::new(),
+        }
+    }
+}
+
+
+
+
+
 /***** HELPER FUNCTIONS *****/
+/// Checks whether the given compiler output parses as an [`eflint_json::spec::Request`].
+///
+/// # Arguments
+/// - `compiled`: The raw output of the compiler.
+///
+/// # Errors
+/// This function errors if the output does not deserialize.
+fn verify_output(compiled: &[u8]) -> Result<(), Error> {
+    debug!("Verifying compiler output parses as an eFLINT JSON request...");
+    serde_json::from_slice::<eflint_json::spec::Request>(compiled).map_err(|source| Error::OutputVerify { source })?;
+    Ok(())
+}
+
+/// Computes the key under which the compilation of some input is cached.
+///
+/// # Arguments
+/// - `compiler_path`: The path of the compiler used. Its modification time is included, such that
+///   replacing the compiler invalidates the cache.
+/// - `flattened`: The input to compile with all includes resolved, such that changes to included
+///   files invalidate the cache too.
+///
+/// # Returns
+/// The hex-encoded SHA-256 hash of the above.
+fn cache_key(compiler_path: &Path, flattened: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(compiler_path.as_os_str().as_encoded_bytes());
+    hasher.update([0]);
+    if let Ok(modified) = fs::metadata(compiler_path).and_then(|md| md.modified()) {
+        hasher.update(modified.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_le_bytes());
+    }
+    hasher.update([0]);
+    hasher.update(flattened);
+    hex::encode(hasher.finalize())
+}
+
+/// Returns a unique temporary path next to a cache entry, to write the entry to before moving it
+/// into place.
+///
+/// # Arguments
+/// - `entry`: The path of the cache entry.
+///
+/// # Returns
+/// The temporary path.
+fn cache_part_path(entry: &Path) -> PathBuf {
+    let nanos: u128 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    entry.with_extension(format!("json.{}.{}.part", std::process::id(), nanos))
+}
+
+/// Reads a cache entry, if it exists.
+///
+/// Failing to read it is not an error, since we can always compile instead.
+///
+/// # Arguments
+/// - `entry`: The path of the cache entry.
+///
+/// # Returns
+/// The cached compiler output, or [`None`] if it wasn't cached (or could not be read).
+fn read_cache(entry: &Path) -> Option<Vec<u8>> {
+    match fs::read(entry) {
+        Ok(compiled) => Some(compiled),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => {
+            warn!("Failed to read cached compiler output '{}' (will compile instead): {}", entry.display(), err);
+            None
+        },
+    }
+}
+
+/// Reads a cache entry, if it exists.
+///
+/// Failing to read it is not an error, since we can always compile instead.
+///
+/// # Arguments
+/// - `entry`: The path of the cache entry.
+///
+/// # Returns
+/// The cached compiler output, or [`None`] if it wasn't cached (or could not be read).
+#[cfg(feature = "async-tokio")]
+async fn read_cache_async(entry: &Path) -> Option<Vec<u8>> {
+    match tfs::read(entry).await {
+        Ok(compiled) => Some(compiled),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => {
+            warn!("Failed to read cached compiler output '{}' (will compile instead): {}", entry.display(), err);
+            None
+        },
+    }
+}
+
+/// Writes a cache entry.
+///
+/// The entry is written to a unique temporary file first, and then atomically moved into place.
+/// As such, concurrent processes writing the same entry never observe a partial one. Failing to
+/// write it is not an error, since the compilation itself succeeded.
+///
+/// # Arguments
+/// - `entry`: The path of the cache entry.
+/// - `compiled`: The compiler output to cache.
+fn write_cache(entry: &Path, compiled: &[u8]) {
+    debug!("Caching compiler output to '{}'...", entry.display());
+    let part_path: PathBuf = cache_part_path(entry);
+    let res: std::io::Result<()> = entry
+        .parent()
+        .map(fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| fs::write(&part_path, compiled))
+        .and_then(|_| fs::rename(&part_path, entry));
+    if let Err(err) = res {
+        warn!("Failed to cache compiler output to '{}': {}", entry.display(), err);
+        // NOTE: Ignoring errors, as the file may not have been created at all
+        let _ = fs::remove_file(&part_path);
+    }
+}
+
+/// Writes a cache entry.
+///
+/// The entry is written to a unique temporary file first, and then atomically moved into place.
+/// As such, concurrent processes writing the same entry never observe a partial one. Failing to
+/// write it is not an error, since the compilation itself succeeded.
+///
+/// # Arguments
+/// - `entry`: The path of the cache entry.
+/// - `compiled`: The compiler output to cache.
+#[cfg(feature = "async-tokio")]
+async fn write_cache_async(entry: &Path, compiled: &[u8]) {
+    debug!("Caching compiler output to '{}'...", entry.display());
+    let part_path: PathBuf = cache_part_path(entry);
+    let mut res: std::io::Result<()> = Ok(());
+    if let Some(parent) = entry.parent() {
+        res = tfs::create_dir_all(parent).await;
+    }
+    if res.is_ok() {
+        res = tfs::write(&part_path, compiled).await;
+    }
+    if res.is_ok() {
+        res = tfs::rename(&part_path, entry).await;
+    }
+    if let Err(err) = res {
+        warn!("Failed to cache compiler output to '{}': {}", entry.display(), err);
+        // NOTE: Ignoring errors, as the file may not have been created at all
+        let _ = tfs::remove_file(&part_path).await;
+    }
+}
+
+/// Returns the paths of the compiler we download, its lock file and a unique temporary file to
+/// download to.
+///
+/// # Arguments
+/// - `source`: The [`CompilerSource`] we download from, which determines the compiler's name.
+///
+/// # Returns
+/// A tuple of the compiler path, the lock path and the (unique) temporary download path.
+fn compiler_paths(source: &CompilerSource) -> (PathBuf, PathBuf, PathBuf) {
+    let dir: PathBuf = std::env::temp_dir();
+    let name: String = source.file_name();
+    let nanos: u128 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    (dir.join(&name), dir.join(format!("{name}.lock")), dir.join(format!("{name}.{}.{}.part", std::process::id(), nanos)))
+}
+
+/// Wraps an error downloading the compiler, surfacing checksum mismatches separately.
+///
+/// # Arguments
+/// - `source`: The [`CompilerSource`] we downloaded from.
+/// - `to`: The path we downloaded the compiler to.
+/// - `err`: The [`download::Error`](crate::download::Error) that occurred.
+///
+/// # Returns
+/// An [`Error::CompilerChecksum`] if the checksum did not match, or an
+/// [`Error::CompilerDownload`] otherwise.
+fn compiler_download_error(source: &CompilerSource, to: &Path, err: crate::download::Error) -> Error {
+    match err {
+        crate::download::Error::FileChecksum { expected, got, .. } => Error::CompilerChecksum { from: source.url.clone(), expected, got },
+        err => Error::CompilerDownload { from: source.url.clone(), to: to.into(), source: Box::new(err) },
+    }
+}
+
+/// Attempts to acquire the lock file guarding a compiler download.
+///
+/// If the lock is held but older than [`COMPILER_LOCK_STALE`], it is assumed to be abandoned and
+/// removed.
+///
+/// # Arguments
+/// - `lock_path`: The path of the lock file.
+///
+/// # Returns
+/// Whether we [acquired](LockAttempt::Acquired) the lock or someone else holds it.
+///
+/// # Errors
+/// This function errors if we failed to create the lock file for other reasons than it already
+/// existing.
+fn try_lock_compiler(lock_path: &Path) -> Result<LockAttempt, Error> {
+    match fs::OpenOptions::new().write(true).create_new(true).open(lock_path) {
+        Ok(_) => Ok(LockAttempt::Acquired(CompilerLock { path: lock_path.into() })),
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+            // See if it's stale
+            let age: Option<Duration> = fs::metadata(lock_path).and_then(|md| md.modified()).ok().and_then(|m| m.elapsed().ok());
+            if age.is_some_and(|age| age > COMPILER_LOCK_STALE) {
+                warn!("Removing stale compiler lock file '{}'", lock_path.display());
+                // NOTE: Ignoring errors, as somebody else may have removed it in the meantime
+                let _ = fs::remove_file(lock_path);
+            }
+            Ok(LockAttempt::Busy)
+        },
+        Err(source) => Err(Error::LockCreate { path: lock_path.into(), source }),
+    }
+}
+
+/// Makes the given file executable.
+///
+/// # Arguments
+/// - `path`: The path of the file to make executable.
+///
+/// # Errors
+/// This function errors if we failed to read or set the file's permissions.
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    debug!("Making compiler '{}' executable...", path.display());
+    let mut perms: Permissions = fs::metadata(path).map_err(|source| Error::FileMetadata { path: path.into(), source })?.permissions();
+    perms.set_mode(perms.mode() | 0o500);
+    fs::set_permissions(path, perms).map_err(|source| Error::FilePermissions { path: path.into(), source })
+}
+
+/// Ensures the compiler is downloaded to the temporary directory.
+///
+/// The compiler is downloaded to a unique temporary file first, and then atomically moved into
+/// place. A lock file prevents concurrent processes from downloading it simultaneously; they
+/// wait for the first download to complete instead.
+///
+/// The checksum of the compiler is verified before it is made executable.
+///
+/// # Arguments
+/// - `source`: The [`CompilerSource`] to download the compiler from.
+///
+/// # Returns
+/// The path of the downloaded compiler.
+///
+/// # Errors
+/// This function errors if we failed to download the compiler, it did not have the expected
+/// checksum or we failed to move it into place.
+fn ensure_compiler(source: &CompilerSource) -> Result<PathBuf, Error> {
+    let (compiler_path, lock_path, part_path): (PathBuf, PathBuf, PathBuf) = compiler_paths(source);
+    let _lock: CompilerLock = loop {
+        if compiler_path.exists() {
+            return Ok(compiler_path);
+        }
+        match try_lock_compiler(&lock_path)? {
+            LockAttempt::Acquired(lock) => break lock,
+            LockAttempt::Busy => {
+                debug!("Waiting for other process to download compiler to '{}'...", compiler_path.display());
+                std::thread::sleep(COMPILER_LOCK_POLL);
+            },
+        }
+    };
+    // Somebody may have finished in between us checking and locking
+    if compiler_path.exists() {
+        return Ok(compiler_path);
+    }
+
+    // Download the file...
+    let res: Result<(), Error> =
+        download_file(&source.url, &part_path, DownloadSecurity::all(&source.checksum), Some(DownloadOutput::all(Style::new().bold().green())))
+            .map_err(|err| compiler_download_error(source, &compiler_path, err));
+
+    // ...make it executable...
+    #[cfg(unix)]
+    let res: Result<(), Error> = res.and_then(|_| make_executable(&part_path));
+
+    // ...and move it into place
+    let res: Result<(), Error> = res.and_then(|_| {
+        fs::rename(&part_path, &compiler_path).map_err(|source| Error::FileRename { from: part_path.clone(), to: compiler_path.clone(), source })
+    });
+    if res.is_err() {
+        // NOTE: Ignoring errors, as the file may not have been created at all
+        let _ = fs::remove_file(&part_path);
+    }
+    res.map(|_| compiler_path)
+}
+
+/// Ensures the compiler is downloaded to the temporary directory.
+///
+/// The compiler is downloaded to a unique temporary file first, and then atomically moved into
+/// place. A lock file prevents concurrent processes from downloading it simultaneously; they
+/// wait for the first download to complete instead.
+///
+/// The checksum of the compiler is verified before it is made executable.
+///
+/// # Arguments
+/// - `source`: The [`CompilerSource`] to download the compiler from.
+///
+/// # Returns
+/// The path of the downloaded compiler.
+///
+/// # Errors
+/// This function errors if we failed to download the compiler, it did not have the expected
+/// checksum or we failed to move it into place.
+#[cfg(feature = "async-tokio")]
+async fn ensure_compiler_async(source: &CompilerSource) -> Result<PathBuf, Error> {
+    let (compiler_path, lock_path, part_path): (PathBuf, PathBuf, PathBuf) = compiler_paths(source);
+    let _lock: CompilerLock = loop {
+        if tfs::try_exists(&compiler_path).await.unwrap_or(false) {
+            return Ok(compiler_path);
+        }
+        match try_lock_compiler(&lock_path)? {
+            LockAttempt::Acquired(lock) => break lock,
+            LockAttempt::Busy => {
+                debug!("Waiting for other process to download compiler to '{}'...", compiler_path.display());
+                tokio::time::sleep(COMPILER_LOCK_POLL).await;
+            },
+        }
+    };
+    // Somebody may have finished in between us checking and locking
+    if tfs::try_exists(&compiler_path).await.unwrap_or(false) {
+        return Ok(compiler_path);
+    }
+
+    // Download the file...
+    let mut res: Result<(), Error> =
+        download_file_async(&source.url, &part_path, DownloadSecurity::all(&source.checksum), Some(DownloadOutput::all(Style::new().bold().green())))
+            .await
+            .map_err(|err| compiler_download_error(source, &compiler_path, err));
+
+    // ...make it executable...
+    #[cfg(unix)]
+    if res.is_ok() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        debug!("Making compiler '{}' executable...", part_path.display());
+        res = match tfs::metadata(&part_path).await {
+            Ok(md) => {
+                let mut perms: Permissions = md.permissions();
+                perms.set_mode(perms.mode() | 0o500);
+                tfs::set_permissions(&part_path, perms).await.map_err(|source| Error::FilePermissions { path: part_path.clone(), source })
+            },
+            Err(source) => Err(Error::FileMetadata { path: part_path.clone(), source }),
+        };
+    }
+
+    // ...and move it into place
+    if res.is_ok() {
+        res = tfs::rename(&part_path, &compiler_path).await.map_err(|source| Error::FileRename {
+            from: part_path.clone(),
+            to: compiler_path.clone(),
+            source,
+        });
+    }
+    if res.is_err() {
+        // NOTE: Ignoring errors, as the file may not have been created at all
+        let _ = tfs::remove_file(&part_path).await;
+    }
+    res.map(|_| compiler_path)
+}
+
+/// Checks whether an included path is a glob pattern.
+///
+/// # Arguments
+/// - `incl_path`: The path as given in the `#include`.
+///
+/// # Returns
+/// True if it contains any wildcard characters (`*`, `?` or `[`), false if it's a literal path.
+fn is_glob(incl_path: &str) -> bool { incl_path.contains(['*', '?', '[']) }
+
+/// Expands a glob pattern in an include to the files it matches.
+///
+/// # Arguments
+/// - `path`: The path of the current file.
+/// - `pattern`: The glob pattern as given in the include, relative to `path`'s parent.
+/// - `optional`: Whether the include was optional, in which case matching nothing is fine.
+///
+/// # Returns
+/// The canonicalized matched files, sorted lexicographically by their (uncanonicalized) path.
+///
+/// # Errors
+/// This function errors if the pattern is invalid, we failed to read a matched entry, or nothing
+/// matched and the include was not optional.
+fn expand_glob(path: &Path, pattern: &str, optional: bool) -> Result<Vec<PathBuf>, Error> {
+    // Resolve it like normal includes, escaping the parent directory since it is taken literally
+    let parent: Option<&Path> = path.parent().filter(|parent| !parent.as_os_str().is_empty() && Path::new(pattern).is_relative());
+    let raw: String = match parent {
+        Some(parent) => format!("{}/{pattern}", glob::Pattern::escape(&parent.to_string_lossy())),
+        None => pattern.into(),
+    };
+
+    // Expand it
+    let mut matches: Vec<PathBuf> = Vec::new();
+    for entry in glob::glob(&raw).map_err(|source| Error::GlobPattern { parent: path.into(), pattern: raw.clone(), source })? {
+        let entry: PathBuf = entry.map_err(|source| Error::GlobRead { parent: path.into(), pattern: raw.clone(), source })?;
+        if entry.is_file() {
+            matches.push(entry);
+        }
+    }
+    if matches.is_empty() {
+        if optional {
+            debug!("Skipping optional include '{}' matching nothing (in file '{}')", raw, path.display());
+            return Ok(matches);
+        }
+        return Err(Error::GlobEmpty { parent: path.into(), pattern: raw });
+    }
+    matches.sort();
+
+    // Canonicalize them like normal includes
+    matches
+        .into_iter()
+        .map(|entry| entry.canonicalize().map_err(|source| Error::PathCanonicalize { parent: path.into(), path: entry, source }))
+        .collect()
+}
+
+/// Lists the paths at which a (non-glob) included file may be found, in order of preference.
+///
+/// Absolute paths are used as-is. Relative ones are first resolved relative to the including
+/// file, and then relative to every [include directory](LoadContext::include_dirs) in order.
+///
+/// # Arguments
+/// - `ctx`: The [`LoadContext`] that defines the include directories.
+/// - `path`: The path of the current file.
+/// - `incl_path`: The path as written in the include.
+///
+/// # Returns
+/// The candidate paths. Never empty.
+fn include_candidates(ctx: &LoadContext, path: &Path, incl_path: PathBuf) -> Vec<PathBuf> {
+    if incl_path.is_absolute() {
+        return vec![incl_path];
+    }
+    let mut candidates: Vec<PathBuf> = Vec::with_capacity(1 + ctx.include_dirs.len());
+    candidates.push(match path.parent() {
+        Some(parent) => parent.join(&incl_path),
+        None => incl_path.clone(),
+    });
+    candidates.extend(ctx.include_dirs.iter().map(|dir| dir.join(&incl_path)));
+    candidates
+}
+
 /// Analyses a potential `#input(...)` or `#require(...)` line from eFLINT.
 ///
 /// # Arguments
-/// - `imported`: The set of already imported files (relevant for require).
+/// - `ctx`: The [`LoadContext`] that keeps track of already imported files.
 /// - `path`: The path of the current file.
 /// - `line`: The parsed line.
 ///
 /// # Returns
-/// A handle to the included file (as a tuple of the path + the handle) if any, or else [`None`].
+/// Handles to the included files (as tuples of the path + the handle) if the line is an include,
+/// or else [`None`]. There may be multiple files if the include is a glob (e.g.,
+/// `#include "rules/*.eflint".`), or none if nothing needs including (i.e., it was already
+/// required or it was optional and missing).
 ///
 /// # Errors
-/// This function can error if we failed to open the included file.
-fn potentially_include(imported: &mut HashSet<PathBuf>, path: &Path, line: &str) -> Result<Option<Option<(PathBuf, File)>>, Error> {
+/// This function can error if we failed to open an included file, if it does not exist (or a
+/// glob matches nothing) and the include was not optional, or if it includes a file that is
+/// already being included (i.e., a cycle).
+fn potentially_include(ctx: &mut LoadContext, path: &Path, line: &str) -> Result<Option<Vec<(PathBuf, File)>>, Error> {
     // Strip whitespace
     let line: &str = line.trim();
 
@@ -196,46 +830,103 @@ fn potentially_include(imported: &mut HashSet<PathBuf>, path: &Path, line: &str)
         return Ok(None);
     }
 
+    // Optional includes/requires are silently skipped if they don't exist
+    let optional: bool = line.starts_with("#include?") || line.starts_with("#require?");
+
     // Extract the text
     let squote: usize = line.find('"').ok_or_else(|| Error::MissingQuote { parent: path.into(), raw: line.into() })?;
     let equote: usize = line.rfind('"').ok_or_else(|| Error::MissingQuote { parent: path.into(), raw: line.into() })?;
     let incl_path: PathBuf = PathBuf::from(&line[squote + 1..equote]);
 
     // Build the path
-    let parent: Option<&Path> = path.parent();
-    // NOTE: Allowing the `is_none()`, `unwrap()` because else we ruin the logic
-    #[allow(clippy::unnecessary_unwrap)]
-    let incl_path: PathBuf = if incl_path.is_absolute() || parent.is_none() { incl_path } else { parent.unwrap().join(incl_path) };
-    let incl_path: PathBuf =
-        incl_path.canonicalize().map_err(|source| Error::PathCanonicalize { parent: path.into(), path: incl_path.clone(), source })?;
+    let incl_paths: Vec<PathBuf> = if is_glob(&line[squote + 1..equote]) {
+        expand_glob(path, &line[squote + 1..equote], optional)?
+    } else {
+        // Try every candidate in order until one exists
+        let candidates: Vec<PathBuf> = include_candidates(ctx, path, incl_path);
+        let mut found: Option<PathBuf> = None;
+        let mut not_found: Option<std::io::Error> = None;
+        for candidate in &candidates {
+            match candidate.canonicalize() {
+                Ok(incl_path) => {
+                    found = Some(incl_path);
+                    break;
+                },
+                Err(source) if source.kind() == std::io::ErrorKind::NotFound => not_found = Some(source),
+                Err(source) => return Err(Error::PathCanonicalize { parent: path.into(), path: candidate.clone(), source }),
+            }
+        }
+        match found {
+            Some(incl_path) => vec![incl_path],
+            None if optional => {
+                debug!("Skipping missing optional include '{}' (in file '{}')", candidates[0].display(), path.display());
+                return Ok(Some(vec![]));
+            },
+            None => {
+                return Err(Error::IncludeOpen {
+                    parent: path.into(),
+                    path:   candidates[0].clone(),
+                    tried:  candidates,
+                    source: not_found.unwrap_or_else(|| std::io::ErrorKind::NotFound.into()),
+                });
+            },
+        }
+    };
 
-    // Check if we've seen this before if it's require
-    if line.starts_with("#require") && imported.contains(&incl_path) {
-        return Ok(Some(None));
-    }
-    imported.insert(incl_path.clone());
+    // Open every file that needs including
+    let mut files: Vec<(PathBuf, File)> = Vec::with_capacity(incl_paths.len());
+    for incl_path in incl_paths {
+        // Check if we've seen this before if it's require
+        // NOTE: We record the edge regardless, such that cycles show up in the manifest
+        ctx.manifest.edges.push((path.into(), incl_path.clone()));
+        if line.starts_with("#require") && ctx.imported.contains(&incl_path) {
+            continue;
+        }
+        if ctx.imported.insert(incl_path.clone()) && !ctx.manifest.files.contains(&incl_path) {
+            ctx.manifest.files.push(incl_path.clone());
+        }
 
-    // Build the path and attempt to open it
-    let handle = File::open(&incl_path).map_err(|source| Error::IncludeOpen { parent: path.into(), path: incl_path.clone(), source })?;
+        // Including a file we're still in the middle of would never end
+        // NOTE: Requires are already deduplicated by `imported`, so we leave those be
+        if let Some(pos) = ctx.stack.iter().position(|p| p == &incl_path).filter(|_| !line.starts_with("#require")) {
+            let mut stack: Vec<PathBuf> = ctx.stack[pos..].to_vec();
+            stack.push(incl_path);
+            return Err(Error::IncludeCycle { stack });
+        }
+
+        // Build the path and attempt to open it
+        let handle = File::open(&incl_path).map_err(|source| Error::IncludeOpen {
+            parent: path.into(),
+            path: incl_path.clone(),
+            tried: vec![incl_path.clone()],
+            source,
+        })?;
+        files.push((incl_path, handle));
+    }
 
     // OK
-    Ok(Some(Some((incl_path, handle))))
+    Ok(Some(files))
 }
 
 /// Analyses a potential `#input(...)` or `#require(...)` line from eFLINT.
 ///
 /// # Arguments
-/// - `imported`: The set of already imported files (relevant for require).
+/// - `ctx`: The [`LoadContext`] that keeps track of already imported files.
 /// - `path`: The path of the current file.
 /// - `line`: The parsed line.
 ///
 /// # Returns
-/// A handle to the included file (as a tuple of the path + the handle) if any, or else [`None`].
+/// Handles to the included files (as tuples of the path + the handle) if the line is an include,
+/// or else [`None`]. There may be multiple files if the include is a glob (e.g.,
+/// `#include "rules/*.eflint".`), or none if nothing needs including (i.e., it was already
+/// required or it was optional and missing).
 ///
 /// # Errors
-/// This function can error if we failed to open the included file.
+/// This function can error if we failed to open an included file, if it does not exist (or a
+/// glob matches nothing) and the include was not optional, or if it includes a file that is
+/// already being included (i.e., a cycle).
 #[cfg(feature = "async-tokio")]
-async fn potentially_include_async(imported: &mut HashSet<PathBuf>, path: &Path, line: &str) -> Result<Option<Option<(PathBuf, TFile)>>, Error> {
+async fn potentially_include_async(ctx: &mut LoadContext, path: &Path, line: &str) -> Result<Option<Vec<(PathBuf, TFile)>>, Error> {
     // Strip whitespace
     let line: &str = line.trim();
 
@@ -244,58 +935,118 @@ async fn potentially_include_async(imported: &mut HashSet<PathBuf>, path: &Path,
         return Ok(None);
     }
 
+    // Optional includes/requires are silently skipped if they don't exist
+    let optional: bool = line.starts_with("#include?") || line.starts_with("#require?");
+
     // Extract the text
     let squote: usize = line.find('"').ok_or_else(|| Error::MissingQuote { parent: path.into(), raw: line.into() })?;
     let equote: usize = line.rfind('"').ok_or_else(|| Error::MissingQuote { parent: path.into(), raw: line.into() })?;
     let incl_path: PathBuf = PathBuf::from(&line[squote + 1..equote]);
 
     // Build the path
-    let parent: Option<&Path> = path.parent();
-    // NOTE: Allowing the `is_none()`, `unwrap()` because else we ruin the logic
-    #[allow(clippy::unnecessary_unwrap)]
-    let incl_path: PathBuf = if incl_path.is_absolute() || parent.is_none() { incl_path } else { parent.unwrap().join(incl_path) };
-    let incl_path: PathBuf =
-        tfs::canonicalize(&incl_path).await.map_err(|source| Error::PathCanonicalize { parent: path.into(), path: incl_path, source })?;
+    let incl_paths: Vec<PathBuf> = if is_glob(&line[squote + 1..equote]) {
+        // NOTE: Glob expansion is blocking, but only lists (usually small) directories
+        expand_glob(path, &line[squote + 1..equote], optional)?
+    } else {
+        // Try every candidate in order until one exists
+        let candidates: Vec<PathBuf> = include_candidates(ctx, path, incl_path);
+        let mut found: Option<PathBuf> = None;
+        let mut not_found: Option<std::io::Error> = None;
+        for candidate in &candidates {
+            match tfs::canonicalize(candidate).await {
+                Ok(incl_path) => {
+                    found = Some(incl_path);
+                    break;
+                },
+                Err(source) if source.kind() == std::io::ErrorKind::NotFound => not_found = Some(source),
+                Err(source) => return Err(Error::PathCanonicalize { parent: path.into(), path: candidate.clone(), source }),
+            }
+        }
+        match found {
+            Some(incl_path) => vec![incl_path],
+            None if optional => {
+                debug!("Skipping missing optional include '{}' (in file '{}')", candidates[0].display(), path.display());
+                return Ok(Some(vec![]));
+            },
+            None => {
+                return Err(Error::IncludeOpen {
+                    parent: path.into(),
+                    path:   candidates[0].clone(),
+                    tried:  candidates,
+                    source: not_found.unwrap_or_else(|| std::io::ErrorKind::NotFound.into()),
+                });
+            },
+        }
+    };
 
-    // Check if we've seen this before if it's require
-    if line.starts_with("#require") && imported.contains(&incl_path) {
-        return Ok(Some(None));
-    }
-    imported.insert(incl_path.clone());
+    // Open every file that needs including
+    let mut files: Vec<(PathBuf, TFile)> = Vec::with_capacity(incl_paths.len());
+    for incl_path in incl_paths {
+        // Check if we've seen this before if it's require
+        // NOTE: We record the edge regardless, such that cycles show up in the manifest
+        ctx.manifest.edges.push((path.into(), incl_path.clone()));
+        if line.starts_with("#require") && ctx.imported.contains(&incl_path) {
+            continue;
+        }
+        if ctx.imported.insert(incl_path.clone()) && !ctx.manifest.files.contains(&incl_path) {
+            ctx.manifest.files.push(incl_path.clone());
+        }
 
-    // Build the path and attempt to open it
-    let handle = TFile::open(&incl_path).await.map_err(|source| Error::IncludeOpen { parent: path.into(), path: incl_path.clone(), source })?;
+        // Including a file we're still in the middle of would never end
+        // NOTE: Requires are already deduplicated by `imported`, so we leave those be
+        if let Some(pos) = ctx.stack.iter().position(|p| p == &incl_path).filter(|_| !line.starts_with("#require")) {
+            let mut stack: Vec<PathBuf> = ctx.stack[pos..].to_vec();
+            stack.push(incl_path);
+            return Err(Error::IncludeCycle { stack });
+        }
+
+        // Build the path and attempt to open it
+        let handle = TFile::open(&incl_path).await.map_err(|source| Error::IncludeOpen {
+            parent: path.into(),
+            path: incl_path.clone(),
+            tried: vec![incl_path.clone()],
+            source,
+        })?;
+        files.push((incl_path, handle));
+    }
 
     // OK
-    Ok(Some(Some((incl_path, handle))))
+    Ok(Some(files))
 }
 
 /// Streams the given file's contents to the stdin of the given process, including files as necessary halfway.
 ///
 /// # Arguments
-/// - `imported`: The set of already imported files (relevant for require).
-/// - `path`: The path of the file we're currently importing. Only used for debugging purposes.
+/// - `ctx`: The [`LoadContext`] that keeps track of already imported files.
+/// - `path`: The path of the file we're currently importing.
 /// - `handle`: Handle to the [`File`] we're going to read.
-/// - `child`: The [`ChildStdin`] to write the stream of input files to.
+/// - `child`: The [`ChildStdin`] (or other writer) to write the stream of input files to.
 ///
 /// # Errors
-/// This function may error if we at any point failed to open/read a file, found `#include`s or `#require`s pointing to non-existant files or if we could not write to the `child`.
-fn load_input(imported: &mut HashSet<PathBuf>, path: &Path, handle: BufReader<File>, child: &mut ChildStdin) -> Result<(), Error> {
+/// This function may error if we at any point failed to open/read a file, found `#include`s or `#require`s pointing to non-existant files, nested includes too deeply or if we could not write to the `child`.
+fn load_input(ctx: &mut LoadContext, path: &Path, handle: BufReader<File>, child: &mut impl Write) -> Result<(), Error> {
     debug!("Importing file '{}'", path.display());
+    // NOTE: Included files are already canonicalized, only the input file may not be
+    ctx.stack.push(if ctx.stack.is_empty() { path.canonicalize().unwrap_or_else(|_| path.into()) } else { path.into() });
+    if ctx.stack.len() - 1 > ctx.max_depth {
+        return Err(Error::IncludeTooDeep { path: path.into(), depth: ctx.stack.len() - 1 });
+    }
 
     // Read the lines for the file
-    for line in handle.lines() {
+    for (i, line) in handle.lines().enumerate() {
         // Unwrap the line
         let line: String = line.map_err(|source| Error::FileRead { path: path.into(), source })?;
 
         // See if a file is included
-        match potentially_include(imported, path, &line)? {
-            Some(Some((child_path, child_handle))) => {
-                load_input(imported, &child_path, BufReader::new(child_handle), child)?;
+        match potentially_include(ctx, path, &line)? {
+            // NOTE: We never write the include line itself
+            Some(files) => {
+                for (child_path, child_handle) in files {
+                    load_input(ctx, &child_path, BufReader::new(child_handle), child)?;
+                }
             },
-            // We don't want to write the line since we already imported it
-            Some(None) => {},
             None => {
+                ctx.lines.push(path, i + 1);
                 child.write_all(line.as_bytes()).map_err(|source| Error::ChildWrite { source })?;
                 child.write_all(b"\n").map_err(|source| Error::ChildWrite { source })?;
             },
@@ -303,38 +1054,54 @@ fn load_input(imported: &mut HashSet<PathBuf>, path: &Path, handle: BufReader<Fi
     }
 
     // Done!
+    ctx.stack.pop();
     Ok(())
 }
 
 /// Streams the given file's contents to the stdin of the given process, including files as necessary halfway.
 ///
 /// # Arguments
-/// - `imported`: The set of already imported files (relevant for require).
-/// - `path`: The path of the file we're currently importing. Only used for debugging purposes.
+/// - `ctx`: The [`LoadContext`] that keeps track of already imported files.
+/// - `path`: The path of the file we're currently importing.
 /// - `handle`: Handle to the [`TFile`]we're going to read.
-/// - `child`: The [`TChildStdin`] to write the stream of input files to.
+/// - `child`: The [`TChildStdin`] (or other writer) to write the stream of input files to.
 ///
 /// # Errors
-/// This function may error if we at any point failed to open/read a file, found `#include`s or `#require`s pointing to non-existant files or if we could not write to the `child`.
+/// This function may error if we at any point failed to open/read a file, found `#include`s or `#require`s pointing to non-existant files, nested includes too deeply or if we could not write to the `child`.
 #[cfg(feature = "async-tokio")]
 #[async_recursion::async_recursion]
-async fn load_input_async(imported: &mut HashSet<PathBuf>, path: &Path, handle: TBufReader<TFile>, child: &mut TChildStdin) -> Result<(), Error> {
+async fn load_input_async<W: Send + Unpin + AsyncWrite>(
+    ctx: &mut LoadContext,
+    path: &Path,
+    handle: TBufReader<TFile>,
+    child: &mut W,
+) -> Result<(), Error> {
     debug!("Importing file '{}'", path.display());
+    // NOTE: Included files are already canonicalized, only the input file may not be
+    ctx.stack.push(if ctx.stack.is_empty() { tfs::canonicalize(path).await.unwrap_or_else(|_| path.into()) } else { path.into() });
+    if ctx.stack.len() - 1 > ctx.max_depth {
+        return Err(Error::IncludeTooDeep { path: path.into(), depth: ctx.stack.len() - 1 });
+    }
 
     // Read the lines for the file
     let mut lines = handle.lines();
+    let mut i: usize = 0;
     while let Some(line) = lines.next_line().await.transpose() {
+        i += 1;
+
         // Unwrap the line
         let line: String = line.map_err(|source| Error::FileRead { path: path.into(), source })?;
 
         // See if a file is included
-        match potentially_include_async(imported, path, &line).await? {
-            Some(Some((child_path, child_handle))) => {
-                load_input_async(imported, &child_path, TBufReader::new(child_handle), child).await?;
+        match potentially_include_async(ctx, path, &line).await? {
+            // NOTE: We never write the include line itself
+            Some(files) => {
+                for (child_path, child_handle) in files {
+                    load_input_async(ctx, &child_path, TBufReader::new(child_handle), child).await?;
+                }
             },
-            // We don't want to write the line since we already imported it
-            Some(None) => {},
             None => {
+                ctx.lines.push(path, i);
                 child.write_all(line.as_bytes()).await.map_err(|source| Error::ChildWrite { source })?;
                 child.write_all(b"\n").await.map_err(|source| Error::ChildWrite { source })?;
             },
@@ -342,6 +1109,7 @@ async fn load_input_async(imported: &mut HashSet<PathBuf>, path: &Path, handle:
     }
 
     // Done!
+    ctx.stack.pop();
     Ok(())
 }
 
@@ -350,58 +1118,268 @@ async fn load_input_async(imported: &mut HashSet<PathBuf>, path: &Path, handle:
 
 
 /***** LIBRARY *****/
+/// Describes which files were included while compiling a policy. Returned by
+/// [`compile_with_manifest()`] and [`compile_async_with_manifest()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IncludeManifest {
+    /// All files that make up the policy, starting with the input file itself and then in the
+    /// order they were first included. All but the input file are canonicalized.
+    pub files: Vec<PathBuf>,
+    /// Pairs of `(includer, included)` for every `#include` and `#require` that was resolved, in
+    /// the order they were encountered.
+    ///
+    /// This includes `#require`s of files that were already imported (and thus skipped), such
+    /// that cycles are represented faithfully. Missing optional includes are not listed.
+    pub edges: Vec<(PathBuf, PathBuf)>,
+}
+
+
+
+/// Defines where to download the `eflint-to-json` compiler from if no local one is given.
+///
+/// The [default](Default) is the compiler from Olaf's repository that this crate was written
+/// against. Use another one to, e.g., download from an internal mirror in an air-gapped
+/// environment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompilerSource {
+    /// The URL to download the compiler from.
+    pub url:      String,
+    /// The SHA-256 checksum the downloaded compiler must have.
+    pub checksum: [u8; 32],
+    /// If given, a version tag for the compiler.
+    ///
+    /// It becomes part of the path the compiler is downloaded to, such that different versions
+    /// don't clobber each other. Sources without a tag that aren't the default one use (part of)
+    /// their checksum instead.
+    pub version:  Option<String>,
+}
+impl Default for CompilerSource {
+    #[inline]
+    fn default() -> Self { Self { url: COMPILER_URL.into(), checksum: COMPILER_CHECKSUM, version: None } }
+}
+impl CompilerSource {
+    /// Constructor for the CompilerSource.
+    ///
+    /// # Arguments
+    /// - `url`: The URL to download the compiler from.
+    /// - `checksum`: The SHA-256 checksum the downloaded compiler must have.
+    ///
+    /// # Returns
+    /// A new CompilerSource without a version tag.
+    #[inline]
+    pub fn new(url: impl Into<String>, checksum: [u8; 32]) -> Self { Self { url: url.into(), checksum, version: None } }
+
+    /// Sets the version tag of the compiler.
+    ///
+    /// # Arguments
+    /// - `version`: The version tag to set.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Returns the name of the file to download this compiler to.
+    ///
+    /// # Returns
+    /// `eflint-to-json` for the default source, or `eflint-to-json-<version>` otherwise (where
+    /// the version is sanitized and, if absent, replaced by a checksum prefix).
+    fn file_name(&self) -> String {
+        match &self.version {
+            Some(version) => {
+                format!(
+                    "eflint-to-json-{}",
+                    version.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' }).collect::<String>()
+                )
+            },
+            None if self.url == COMPILER_URL && self.checksum == COMPILER_CHECKSUM => "eflint-to-json".into(),
+            None => format!("eflint-to-json-{}", &hex::encode(self.checksum)[..16]),
+        }
+    }
+}
+
+
+
+/// Defines additional options for [`compile_with_options()`] and [`compile_async_with_options()`].
+#[derive(Clone, Debug)]
+pub struct CompileOptions {
+    /// If true, re-parses the compiler's output as an [`eflint_json::spec::Request`] after
+    /// compilation and fails if that does not succeed. This catches version skew between the
+    /// external compiler and the `eflint-json` crate before the policy reaches a reasoner.
+    pub verify_output: bool,
+    /// If given, caches compiler output in this directory.
+    ///
+    /// Entries are keyed by a hash of the input with all includes resolved (and of the compiler
+    /// used), so changing any included file invalidates them. On a hit, the compiler isn't run
+    /// at all. Entries are written atomically, so concurrent compilations may share a directory.
+    /// Note that the cache is never cleaned up automatically.
+    pub cache_dir: Option<PathBuf>,
+    /// If given, downloads the compiler from here instead of from the [default](CompilerSource::default) location.
+    ///
+    /// Only used if no compiler is given explicitly.
+    pub compiler_source: Option<CompilerSource>,
+    /// The maximum number of nested includes (64 by default). Deeper nesting is an error rather
+    /// than a stack overflow.
+    pub max_depth: usize,
+    /// Directories searched (in order) for includes that don't exist relative to the including
+    /// file. Globs are always resolved relative to the including file.
+    pub include_dirs: Vec<PathBuf>,
+}
+impl Default for CompileOptions {
+    #[inline]
+    fn default() -> Self {
+        Self { verify_output: false, cache_dir: None, compiler_source: None, max_depth: DEFAULT_MAX_DEPTH, include_dirs: Vec::new() }
+    }
+}
+impl CompileOptions {
+    /// Sets whether to verify that the compiler's output parses as an eFLINT JSON request.
+    ///
+    /// # Arguments
+    /// - `verify_output`: Whether to verify the output.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub const fn with_verify_output(mut self, verify_output: bool) -> Self {
+        self.verify_output = verify_output;
+        self
+    }
+
+    /// Sets the directory in which to cache compiler output.
+    ///
+    /// See [`CompileOptions::cache_dir`] for more information.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory to cache in. Will be created if it does not exist.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets where to download the compiler from.
+    ///
+    /// # Arguments
+    /// - `source`: The [`CompilerSource`] to download from.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_compiler_source(mut self, source: CompilerSource) -> Self {
+        self.compiler_source = Some(source);
+        self
+    }
+
+    /// Sets the maximum number of nested includes.
+    ///
+    /// # Arguments
+    /// - `max_depth`: The maximum depth, where `0` means the input file may not include anything.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Adds directories in which to search for included files.
+    ///
+    /// See [`CompileOptions::include_dirs`] for more information.
+    ///
+    /// # Arguments
+    /// - `dirs`: The directories to search, in order.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_include_dirs(mut self, dirs: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        self.include_dirs.extend(dirs.into_iter().map(Into::into));
+        self
+    }
+}
+
+
+
 /// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler.
 ///
-/// Resolves relative paths in the files as relative to the file in which they occur.
+/// Resolves relative paths in the files as relative to the file in which they occur or, if they
+/// don't exist there, relative to each of the given `include_dirs` in order.
+///
+/// Includes can be made optional by writing `#include? "overlay.eflint".` (or
+/// `#require? "overlay.eflint".`). These are silently skipped if the file does not exist, which
+/// is useful for layering environment-specific overlays on top of a base policy. Normal includes
+/// to non-existing files are still errors.
 ///
 /// # Arguments
 /// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
 /// - `output`: Some writer to compile to.
 /// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+///   Downloads are atomic, so concurrent first uses do not corrupt the downloaded compiler.
+/// - `include_dirs`: Directories to search for included files that aren't found relative to the file including them.
 ///
 /// # Errors
 /// This function may error for a plethora of reasons.
-pub fn compile(input_path: &Path, mut output: impl Write, compiler_path: Option<&Path>) -> Result<(), Error> {
+#[inline]
+pub fn compile(input_path: &Path, output: impl Write, compiler_path: Option<&Path>, include_dirs: &[&Path]) -> Result<(), Error> {
+    compile_with_options(input_path, output, compiler_path, &CompileOptions::default().with_include_dirs(include_dirs.iter().copied()))
+}
+
+/// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler, with additional
+/// [`CompileOptions`].
+///
+/// See [`compile()`] for more information.
+///
+/// # Arguments
+/// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
+/// - `output`: Some writer to compile to.
+/// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+/// - `opts`: The [`CompileOptions`] that configure the compilation.
+///
+/// # Errors
+/// This function may error for the same reasons as [`compile()`], and additionally if
+/// [`CompileOptions::verify_output`] is given and the compiler's output is not a valid eFLINT
+/// JSON request. Note that in that case, the output has already been written to `output`.
+#[inline]
+pub fn compile_with_options(input_path: &Path, output: impl Write, compiler_path: Option<&Path>, opts: &CompileOptions) -> Result<(), Error> {
+    compile_with_manifest(input_path, output, compiler_path, opts).map(|_| ())
+}
+
+/// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler, and reports which
+/// files were included to do so.
+///
+/// See [`compile()`] for more information. The [`IncludeManifest`] is useful to, e.g., watch all
+/// files a policy depends on.
+///
+/// # Arguments
+/// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
+/// - `output`: Some writer to compile to.
+/// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+/// - `opts`: The [`CompileOptions`] that configure the compilation.
+///
+/// # Returns
+/// An [`IncludeManifest`] describing the included files.
+///
+/// # Errors
+/// This function may error for the same reasons as [`compile_with_options()`].
+pub fn compile_with_manifest(
+    input_path: &Path,
+    mut output: impl Write,
+    compiler_path: Option<&Path>,
+    opts: &CompileOptions,
+) -> Result<IncludeManifest, Error> {
     info!("Compiling input at '{}'", input_path.display());
 
     // Resolve the compiler
     let compiler_path: Cow<Path> = match compiler_path {
         Some(path) => Cow::Borrowed(path),
-        None => {
-            // Get the output path
-            let compiler_path: PathBuf = std::env::temp_dir().join("eflint-to-json");
-
-            // Download it if it does not exist (or at least, give it a try)
-            if !compiler_path.exists() {
-                // Download the file...
-                download_file(
-                    COMPILER_URL,
-                    &compiler_path,
-                    DownloadSecurity { checksum: Some(&COMPILER_CHECKSUM), https: true },
-                    Some(Style::new().bold().green()),
-                )
-                .map_err(|source| Error::CompilerDownload {
-                    from:   COMPILER_URL.into(),
-                    to:     compiler_path.clone(),
-                    source: Box::new(source),
-                })?;
-
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt as _;
-
-                    // ...and make it executable
-                    let mut perms: Permissions =
-                        fs::metadata(&compiler_path).map_err(|source| Error::FileMetadata { path: compiler_path.clone(), source })?.permissions();
-
-                    perms.set_mode(perms.mode() | 0o500);
-                    fs::set_permissions(&compiler_path, perms).map_err(|source| Error::FilePermissions { path: compiler_path.clone(), source })?;
-                }
-            }
-
-            // Return the path
-            Cow::Owned(compiler_path)
-        },
+        None => Cow::Owned(ensure_compiler(opts.compiler_source.as_ref().unwrap_or(&CompilerSource::default()))?),
     };
     debug!("Using compiler at: '{}'", compiler_path.display());
 
@@ -409,6 +1387,28 @@ pub fn compile(input_path: &Path, mut output: impl Write, compiler_path: Option<
     debug!("Opening input file '{}'", input_path.display());
     let input = File::open(input_path).map_err(|source| Error::FileOpen { path: input_path.into(), source })?;
 
+    // If we're caching, resolve the input first to find out if we have compiled it before
+    let mut ctx: LoadContext = LoadContext { max_depth: opts.max_depth, include_dirs: opts.include_dirs.clone(), ..Default::default() };
+    ctx.manifest.files.push(input_path.into());
+    let input: CompilerInput<File> = match &opts.cache_dir {
+        Some(cache_dir) => {
+            debug!("Flattening input to check cache in '{}'...", cache_dir.display());
+            let mut flattened: Vec<u8> = Vec::new();
+            load_input(&mut ctx, input_path, BufReader::new(input), &mut flattened)?;
+            let entry: PathBuf = cache_dir.join(format!("{}.json", cache_key(&compiler_path, &flattened)));
+            if let Some(compiled) = read_cache(&entry) {
+                debug!("Using cached compiler output '{}'", entry.display());
+                output.write_all(&compiled).map_err(|source| Error::WriterWrite { source })?;
+                if opts.verify_output {
+                    verify_output(&compiled)?;
+                }
+                return Ok(ctx.manifest);
+            }
+            CompilerInput::Cached { flattened, entry }
+        },
+        None => CompilerInput::Streamed(input),
+    };
+
     // Alrighty well open a handle to the compiler
     debug!("Spawning compiler '{}'", compiler_path.display());
     let mut cmd: Command = Command::new(compiler_path.to_string_lossy().as_ref());
@@ -420,8 +1420,16 @@ pub fn compile(input_path: &Path, mut output: impl Write, compiler_path: Option<
     // Feed the input to the compiler, analyzing for `#input(...)` and `#require(...)`
     debug!("Reading input to child process...");
     let mut stdin: ChildStdin = handle.stdin.take().unwrap();
-    let mut included: HashSet<PathBuf> = HashSet::new();
-    load_input(&mut included, input_path, BufReader::new(input), &mut stdin)?;
+    let cache_entry: Option<PathBuf> = match input {
+        CompilerInput::Streamed(input) => {
+            load_input(&mut ctx, input_path, BufReader::new(input), &mut stdin)?;
+            None
+        },
+        CompilerInput::Cached { flattened, entry } => {
+            stdin.write_all(&flattened).map_err(|source| Error::ChildWrite { source })?;
+            Some(entry)
+        },
+    };
     drop(stdin);
 
     // Wait until the process is finished
@@ -436,6 +1444,7 @@ pub fn compile(input_path: &Path, mut output: impl Write, compiler_path: Option<
                 ChildStream::new("stdout", handle.stdout.take().unwrap()),
                 ChildStream::new("stderr", handle.stderr.take().unwrap()),
             ]),
+            lines: ctx.lines,
         });
     }
 
@@ -443,6 +1452,7 @@ pub fn compile(input_path: &Path, mut output: impl Write, compiler_path: Option<
     debug!("Writing child process output to given output...");
     let mut chunk: [u8; 65535] = [0; 65535];
     let mut stdout: ChildStdout = handle.stdout.take().unwrap();
+    let mut compiled: Vec<u8> = Vec::new();
     loop {
         // Read the next chunk
         let chunk_len: usize = stdout.read(&mut chunk).map_err(|source| Error::ChildRead { source })?;
@@ -453,71 +1463,162 @@ pub fn compile(input_path: &Path, mut output: impl Write, compiler_path: Option<
 
         // Write to the file
         output.write_all(&chunk[..chunk_len]).map_err(|source| Error::WriterWrite { source })?;
+        if opts.verify_output || cache_entry.is_some() {
+            compiled.extend_from_slice(&chunk[..chunk_len]);
+        }
+    }
+
+    // Check the output round-trips, if asked
+    if opts.verify_output {
+        verify_output(&compiled)?;
+    }
+
+    // Remember it for next time, if asked
+    if let Some(entry) = cache_entry {
+        write_cache(&entry, &compiled);
     }
 
     // Done
-    Ok(())
+    Ok(ctx.manifest)
+}
+
+/// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler, returning the
+/// result as a [`String`].
+///
+/// See [`compile_with_options()`] for more information. The compiler's output is streamed
+/// straight into the returned string, so there is no need to go through a temporary file.
+///
+/// # Arguments
+/// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
+/// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+/// - `opts`: The [`CompileOptions`] that configure the compilation (e.g., its include directories).
+///
+/// # Returns
+/// The compiled eFLINT JSON.
+///
+/// # Errors
+/// This function may error for the same reasons as [`compile_with_options()`], and additionally
+/// if the compiler's output is not valid UTF-8.
+pub fn compile_to_string(input_path: &Path, compiler_path: Option<&Path>, opts: &CompileOptions) -> Result<String, Error> {
+    let mut output: Vec<u8> = Vec::new();
+    compile_with_options(input_path, &mut output, compiler_path, opts)?;
+    String::from_utf8(output).map_err(|source| Error::OutputUtf8 { source })
+}
+
+/// Flattens a (tree of) `.eflint` files without compiling them.
+///
+/// All `#include`s and `#require`s are resolved exactly as [`compile()`] would, but the result is
+/// returned as eFLINT text instead of being fed to the compiler. As such, no compiler is
+/// downloaded or run, making this useful for reviewing or diffing what would be compiled.
+///
+/// # Arguments
+/// - `input`: The input file to flatten.
+/// - `include_dirs`: Directories to search for included files that aren't found relative to the file including them.
+///
+/// # Returns
+/// The flattened eFLINT.
+///
+/// # Errors
+/// This function may error if we failed to read any of the files, or for the same include-related
+/// reasons as [`compile()`] (e.g., missing files or cycles).
+pub fn preprocess(input_path: &Path, include_dirs: &[&Path]) -> Result<String, Error> {
+    info!("Preprocessing input at '{}'", input_path.display());
+
+    // Open the input file
+    debug!("Opening input file '{}'", input_path.display());
+    let input = File::open(input_path).map_err(|source| Error::FileOpen { path: input_path.into(), source })?;
+
+    // Flatten it
+    let mut ctx: LoadContext = LoadContext { include_dirs: include_dirs.iter().map(|dir| dir.to_path_buf()).collect(), ..Default::default() };
+    ctx.manifest.files.push(input_path.into());
+    let mut flattened: Vec<u8> = Vec::new();
+    load_input(&mut ctx, input_path, BufReader::new(input), &mut flattened)?;
+
+    // NOTE: Only ever consists of lines read as `String`s, so this never actually replaces anything
+    Ok(String::from_utf8_lossy(&flattened).into_owned())
 }
 
 /// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler.
 ///
-/// Resolves relative paths in the files as relative to the file in which they occur.
+/// Resolves relative paths in the files as relative to the file in which they occur or, if they
+/// don't exist there, relative to each of the given `include_dirs` in order.
+///
+/// Includes can be made optional by writing `#include? "overlay.eflint".` (or
+/// `#require? "overlay.eflint".`). These are silently skipped if the file does not exist, which
+/// is useful for layering environment-specific overlays on top of a base policy. Normal includes
+/// to non-existing files are still errors.
 ///
 /// # Arguments
 /// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
 /// - `output`: Some writer to compile to.
 /// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+///   Downloads are atomic, so concurrent first uses do not corrupt the downloaded compiler.
+/// - `include_dirs`: Directories to search for included files that aren't found relative to the file including them.
 ///
 /// # Errors
 /// This function may error for a plethora of reasons.
 #[cfg(feature = "async-tokio")]
-pub async fn compile_async(input_path: &Path, mut output: impl Write, compiler_path: Option<&Path>) -> Result<(), Error> {
+#[inline]
+pub async fn compile_async(input_path: &Path, output: impl Write, compiler_path: Option<&Path>, include_dirs: &[&Path]) -> Result<(), Error> {
+    compile_async_with_options(input_path, output, compiler_path, &CompileOptions::default().with_include_dirs(include_dirs.iter().copied())).await
+}
+
+/// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler, with additional
+/// [`CompileOptions`].
+///
+/// See [`compile_async()`] for more information.
+///
+/// # Arguments
+/// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
+/// - `output`: Some writer to compile to.
+/// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+/// - `opts`: The [`CompileOptions`] that configure the compilation.
+///
+/// # Errors
+/// This function may error for the same reasons as [`compile_async()`], and additionally if
+/// [`CompileOptions::verify_output`] is given and the compiler's output is not a valid eFLINT
+/// JSON request. Note that in that case, the output has already been written to `output`.
+#[cfg(feature = "async-tokio")]
+#[inline]
+pub async fn compile_async_with_options(
+    input_path: &Path,
+    output: impl Write,
+    compiler_path: Option<&Path>,
+    opts: &CompileOptions,
+) -> Result<(), Error> {
+    compile_async_with_manifest(input_path, output, compiler_path, opts).await.map(|_| ())
+}
+
+/// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler, and reports which
+/// files were included to do so.
+///
+/// See [`compile_async()`] for more information. The [`IncludeManifest`] is useful to, e.g.,
+/// watch all files a policy depends on.
+///
+/// # Arguments
+/// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
+/// - `output`: Some writer to compile to.
+/// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+/// - `opts`: The [`CompileOptions`] that configure the compilation.
+///
+/// # Returns
+/// An [`IncludeManifest`] describing the included files.
+///
+/// # Errors
+/// This function may error for the same reasons as [`compile_async_with_options()`].
+#[cfg(feature = "async-tokio")]
+pub async fn compile_async_with_manifest(
+    input_path: &Path,
+    mut output: impl Write,
+    compiler_path: Option<&Path>,
+    opts: &CompileOptions,
+) -> Result<IncludeManifest, Error> {
     info!("Compiling input at '{}'", input_path.display());
 
     // Resolve the compiler
     let compiler_path: Cow<Path> = match compiler_path {
         Some(path) => Cow::Borrowed(path),
-        None => {
-            // Get the output path
-            let compiler_path: PathBuf = std::env::temp_dir().join("eflint-to-json");
-
-            // Download it if it does not exist (or at least, give it a try)
-            if !compiler_path.exists() {
-                // Download the file...
-                download_file_async(
-                    COMPILER_URL,
-                    &compiler_path,
-                    DownloadSecurity { checksum: Some(&COMPILER_CHECKSUM), https: true },
-                    Some(Style::new().bold().green()),
-                )
-                .await
-                .map_err(|source| Error::CompilerDownload {
-                    from:   COMPILER_URL.into(),
-                    to:     compiler_path.clone(),
-                    source: Box::new(source),
-                })?;
-
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt as _;
-
-                    // ...and make it executable
-                    debug!("Making compiler '{}' executable...", compiler_path.display());
-                    let mut perms: Permissions = tfs::metadata(&compiler_path)
-                        .await
-                        .map_err(|source| Error::FileMetadata { path: compiler_path.clone(), source })?
-                        .permissions();
-                    perms.set_mode(perms.mode() | 0o500);
-
-                    tfs::set_permissions(&compiler_path, perms)
-                        .await
-                        .map_err(|source| Error::FilePermissions { path: compiler_path.clone(), source })?;
-                }
-            }
-
-            // Return the path
-            Cow::Owned(compiler_path)
-        },
+        None => Cow::Owned(ensure_compiler_async(opts.compiler_source.as_ref().unwrap_or(&CompilerSource::default())).await?),
     };
     debug!("Using compiler at: '{}'", compiler_path.display());
 
@@ -525,6 +1626,28 @@ pub async fn compile_async(input_path: &Path, mut output: impl Write, compiler_p
     debug!("Opening input file '{}'", input_path.display());
     let input = TFile::open(input_path).await.map_err(|source| Error::FileOpen { path: input_path.into(), source })?;
 
+    // If we're caching, resolve the input first to find out if we have compiled it before
+    let mut ctx: LoadContext = LoadContext { max_depth: opts.max_depth, include_dirs: opts.include_dirs.clone(), ..Default::default() };
+    ctx.manifest.files.push(input_path.into());
+    let input: CompilerInput<TFile> = match &opts.cache_dir {
+        Some(cache_dir) => {
+            debug!("Flattening input to check cache in '{}'...", cache_dir.display());
+            let mut flattened: Vec<u8> = Vec::new();
+            load_input_async(&mut ctx, input_path, TBufReader::new(input), &mut flattened).await?;
+            let entry: PathBuf = cache_dir.join(format!("{}.json", cache_key(&compiler_path, &flattened)));
+            if let Some(compiled) = read_cache_async(&entry).await {
+                debug!("Using cached compiler output '{}'", entry.display());
+                output.write_all(&compiled).map_err(|source| Error::WriterWrite { source })?;
+                if opts.verify_output {
+                    verify_output(&compiled)?;
+                }
+                return Ok(ctx.manifest);
+            }
+            CompilerInput::Cached { flattened, entry }
+        },
+        None => CompilerInput::Streamed(input),
+    };
+
     // Alrighty well open a handle to the compiler
     debug!("Spawning compiler '{}'", compiler_path.display());
     let mut cmd: TCommand = TCommand::new(compiler_path.to_string_lossy().as_ref());
@@ -536,8 +1659,16 @@ pub async fn compile_async(input_path: &Path, mut output: impl Write, compiler_p
     // Feed the input to the compiler, analyzing for `#input(...)` and `#require(...)`
     debug!("Reading input to child process...");
     let mut stdin: TChildStdin = handle.stdin.take().unwrap();
-    let mut included: HashSet<PathBuf> = HashSet::new();
-    load_input_async(&mut included, input_path, TBufReader::new(input), &mut stdin).await?;
+    let cache_entry: Option<PathBuf> = match input {
+        CompilerInput::Streamed(input) => {
+            load_input_async(&mut ctx, input_path, TBufReader::new(input), &mut stdin).await?;
+            None
+        },
+        CompilerInput::Cached { flattened, entry } => {
+            stdin.write_all(&flattened).await.map_err(|source| Error::ChildWrite { source })?;
+            Some(entry)
+        },
+    };
     drop(stdin);
 
     // Wait until the process is finished
@@ -552,6 +1683,7 @@ pub async fn compile_async(input_path: &Path, mut output: impl Write, compiler_p
                 ChildStream::new_async("stdout", handle.stdout.take().unwrap()).await,
                 ChildStream::new_async("stderr", handle.stderr.take().unwrap()).await,
             ]),
+            lines: ctx.lines,
         });
     }
 
@@ -559,6 +1691,7 @@ pub async fn compile_async(input_path: &Path, mut output: impl Write, compiler_p
     debug!("Writing child process output to given output...");
     let mut chunk = [0; 65535];
     let mut stdout: TChildStdout = handle.stdout.take().unwrap();
+    let mut compiled: Vec<u8> = Vec::new();
     loop {
         // Read the next chunk
         let chunk_len: usize = stdout.read(&mut chunk).await.map_err(|source| Error::ChildRead { source })?;
@@ -569,8 +1702,292 @@ pub async fn compile_async(input_path: &Path, mut output: impl Write, compiler_p
 
         // Write to the file
         output.write_all(&chunk[..chunk_len]).map_err(|source| Error::WriterWrite { source })?;
+        if opts.verify_output || cache_entry.is_some() {
+            compiled.extend_from_slice(&chunk[..chunk_len]);
+        }
+    }
+
+    // Check the output round-trips, if asked
+    if opts.verify_output {
+        verify_output(&compiled)?;
+    }
+
+    // Remember it for next time, if asked
+    if let Some(entry) = cache_entry {
+        write_cache_async(&entry, &compiled).await;
     }
 
     // Done
-    Ok(())
+    Ok(ctx.manifest)
+}
+
+/// Compiles a (tree of) `.eflint` files using Olaf's `eflint-to-json` compiler, returning the
+/// result as a [`String`].
+///
+/// See [`compile_async_with_options()`] for more information. The compiler's output is streamed
+/// straight into the returned string, so there is no need to go through a temporary file.
+///
+/// # Arguments
+/// - `input`: The input file to compile. Any `#include`s and `#require`s will be handled, building a tree of files to import.
+/// - `compiler`: If given, will not download a compiler to `/tmp/eflint-to-json` but will instead use the given one.
+/// - `opts`: The [`CompileOptions`] that configure the compilation (e.g., its include directories).
+///
+/// # Returns
+/// The compiled eFLINT JSON.
+///
+/// # Errors
+/// This function may error for the same reasons as [`compile_async_with_options()`], and
+/// additionally if the compiler's output is not valid UTF-8.
+#[cfg(feature = "async-tokio")]
+pub async fn compile_async_to_string(input_path: &Path, compiler_path: Option<&Path>, opts: &CompileOptions) -> Result<String, Error> {
+    let mut output: Vec<u8> = Vec::new();
+    compile_async_with_options(input_path, &mut output, compiler_path, opts).await?;
+    String::from_utf8(output).map_err(|source| Error::OutputUtf8 { source })
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+
+    #[test]
+    fn test_optional_include() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::write(dir.join("overlay.eflint"), "").unwrap();
+        let main: PathBuf = dir.join("main.eflint");
+        let mut ctx: LoadContext = LoadContext::default();
+
+        // Present optional includes are included like normal ones
+        let res = potentially_include(&mut ctx, &main, "#include? \"overlay.eflint\".").unwrap();
+        assert!(matches!(res.as_deref(), Some([(path, _)]) if path.ends_with("overlay.eflint")));
+
+        // Absent ones are skipped...
+        assert!(matches!(potentially_include(&mut ctx, &main, "#include? \"missing.eflint\".").unwrap().as_deref(), Some([])));
+        assert!(matches!(potentially_include(&mut ctx, &main, "#require? \"missing.eflint\".").unwrap().as_deref(), Some([])));
+        // ...but only if they're optional
+        assert!(matches!(potentially_include(&mut ctx, &main, "#include \"missing.eflint\"."), Err(Error::IncludeOpen { .. })));
+    }
+
+    #[test]
+    fn test_include_dirs() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::create_dir_all(dir.join("policy")).unwrap();
+        fs::create_dir_all(dir.join("shared1")).unwrap();
+        fs::create_dir_all(dir.join("shared2")).unwrap();
+        fs::write(dir.join("policy/local.eflint"), "").unwrap();
+        fs::write(dir.join("shared1/local.eflint"), "").unwrap();
+        fs::write(dir.join("shared2/common.eflint"), "").unwrap();
+        let main: PathBuf = dir.join("policy/main.eflint");
+        let mut ctx: LoadContext = LoadContext { include_dirs: vec![dir.join("shared1"), dir.join("shared2")], ..Default::default() };
+
+        // The including file's directory takes precedence, then the include dirs in order
+        let res = potentially_include(&mut ctx, &main, "#include \"local.eflint\".").unwrap();
+        assert!(matches!(res.as_deref(), Some([(path, _)]) if path.ends_with("policy/local.eflint")));
+        let res = potentially_include(&mut ctx, &main, "#include \"common.eflint\".").unwrap();
+        assert!(matches!(res.as_deref(), Some([(path, _)]) if path.ends_with("shared2/common.eflint")));
+
+        // All paths tried are reported
+        match potentially_include(&mut ctx, &main, "#include \"missing.eflint\".") {
+            Err(Error::IncludeOpen { tried, .. }) => {
+                assert_eq!(tried, vec![dir.join("policy/missing.eflint"), dir.join("shared1/missing.eflint"), dir.join("shared2/missing.eflint")])
+            },
+            res => panic!("Expected an IncludeOpen-error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_preprocess() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(dir.join("main.eflint"), "#require \"base.eflint\".\n#require \"base.eflint\".\n#include \"common.eflint\".\n+a.\n").unwrap();
+        fs::write(dir.join("shared/base.eflint"), "Fact a.\n").unwrap();
+        fs::write(dir.join("shared/common.eflint"), "Fact b.\n").unwrap();
+        fs::write(dir.join("cycle.eflint"), "#include \"cycle.eflint\".\n").unwrap();
+
+        // Includes are resolved and requires deduplicated, without needing a compiler
+        let shared: PathBuf = dir.join("shared");
+        assert_eq!(preprocess(&dir.join("main.eflint"), &[shared.as_path()]).unwrap(), "Fact a.\nFact b.\n+a.\n");
+        assert!(matches!(preprocess(&dir.join("cycle.eflint"), &[]), Err(Error::IncludeCycle { .. })));
+    }
+
+    #[test]
+    fn test_glob_include() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::create_dir_all(dir.join("rules")).unwrap();
+        fs::write(dir.join("main.eflint"), "#include \"rules/*.eflint\".\n").unwrap();
+        fs::write(dir.join("rules/b.eflint"), "Fact b.\n").unwrap();
+        fs::write(dir.join("rules/a.eflint"), "Fact a.\n").unwrap();
+        fs::write(dir.join("rules/c.txt"), "Not eFLINT\n").unwrap();
+        let main: PathBuf = dir.join("main.eflint");
+
+        // Globs expand relative to the parent, in sorted order
+        let mut ctx: LoadContext = LoadContext::default();
+        let mut flattened: Vec<u8> = Vec::new();
+        load_input(&mut ctx, &main, BufReader::new(File::open(&main).unwrap()), &mut flattened).unwrap();
+        assert_eq!(String::from_utf8(flattened).unwrap(), "Fact a.\nFact b.\n");
+
+        // Globs matching nothing are errors, unless optional
+        assert!(matches!(potentially_include(&mut ctx, &main, "#include \"missing/*.eflint\"."), Err(Error::GlobEmpty { .. })));
+        assert!(matches!(potentially_include(&mut ctx, &main, "#include? \"missing/*.eflint\".").unwrap().as_deref(), Some([])));
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::write(dir.join("main.eflint"), "#include \"a.eflint\".\n").unwrap();
+        fs::write(dir.join("a.eflint"), "#include \"b.eflint\".\n").unwrap();
+        fs::write(dir.join("b.eflint"), "#include \"a.eflint\".\n").unwrap();
+        fs::write(dir.join("diamond.eflint"), "#require \"left.eflint\".\n#require \"right.eflint\".\n").unwrap();
+        fs::write(dir.join("left.eflint"), "#require \"base.eflint\".\n").unwrap();
+        fs::write(dir.join("right.eflint"), "#require \"base.eflint\".\n").unwrap();
+        fs::write(dir.join("base.eflint"), "Fact a.\n").unwrap();
+        let load = |name: &str| {
+            let path: PathBuf = dir.join(name);
+            let mut flattened: Vec<u8> = Vec::new();
+            load_input(&mut LoadContext::default(), &path, BufReader::new(File::open(&path).unwrap()), &mut flattened).map(|_| flattened)
+        };
+
+        // Cycles are errors...
+        let a: PathBuf = dir.join("a.eflint").canonicalize().unwrap();
+        let b: PathBuf = dir.join("b.eflint").canonicalize().unwrap();
+        assert!(matches!(load("main.eflint"), Err(Error::IncludeCycle { stack }) if stack == vec![a.clone(), b, a]));
+        // ...but diamonds are not
+        assert_eq!(String::from_utf8(load("diamond.eflint").unwrap()).unwrap(), "Fact a.\n");
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::write(dir.join("main.eflint"), "#include \"a.eflint\".\n#include \"b.eflint\".\n").unwrap();
+        fs::write(dir.join("a.eflint"), "Fact a.\n").unwrap();
+        fs::write(dir.join("b.eflint"), "#include \"c.eflint\".\n").unwrap();
+        fs::write(dir.join("c.eflint"), "Fact c.\n").unwrap();
+        let main: PathBuf = dir.join("main.eflint");
+        let load = |max_depth: usize| {
+            let mut ctx: LoadContext = LoadContext { max_depth, ..Default::default() };
+            load_input(&mut ctx, &main, BufReader::new(File::open(&main).unwrap()), &mut std::io::sink())
+        };
+
+        // Sibling includes do not add up...
+        assert!(load(2).is_ok());
+        // ...but nested ones do
+        assert!(matches!(load(1), Err(Error::IncludeTooDeep { path, depth: 2 }) if path.ends_with("c.eflint")));
+    }
+
+    #[test]
+    fn test_manifest() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::write(dir.join("main.eflint"), "#require \"a.eflint\".\n#include \"b.eflint\".\n").unwrap();
+        fs::write(dir.join("a.eflint"), "#require \"b.eflint\".\n").unwrap();
+        fs::write(dir.join("b.eflint"), "#require \"a.eflint\".\n").unwrap();
+        let main: PathBuf = dir.join("main.eflint");
+        let a: PathBuf = dir.join("a.eflint").canonicalize().unwrap();
+        let b: PathBuf = dir.join("b.eflint").canonicalize().unwrap();
+
+        // NOTE: `cat` makes a great compiler for testing, as it simply echoes the flattened input
+        let manifest: IncludeManifest = compile_with_manifest(&main, std::io::sink(), Some(Path::new("cat")), &CompileOptions::default()).unwrap();
+        assert_eq!(manifest.files, vec![main.clone(), a.clone(), b.clone()]);
+        // The cycle between `a` and `b` is kept, as is the include of the already required `b` (which walks it again)
+        assert_eq!(manifest.edges, vec![(main.clone(), a.clone()), (a.clone(), b.clone()), (b.clone(), a.clone()), (main, b.clone()), (b, a)]);
+    }
+
+    #[test]
+    fn test_line_map() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::write(dir.join("main.eflint"), "+a.\n#include \"b.eflint\".\n+c.\n").unwrap();
+        fs::write(dir.join("b.eflint"), "Fact x.\n#include \"c.eflint\".\nFact y.\n").unwrap();
+        fs::write(dir.join("c.eflint"), "Fact z.\n").unwrap();
+        let main: PathBuf = dir.join("main.eflint");
+        let b: PathBuf = dir.join("b.eflint").canonicalize().unwrap();
+        let c: PathBuf = dir.join("c.eflint").canonicalize().unwrap();
+
+        // Flatten the input, which gives us the map
+        let mut ctx: LoadContext = LoadContext::default();
+        let mut flattened: Vec<u8> = Vec::new();
+        load_input(&mut ctx, &main, BufReader::new(File::open(&main).unwrap()), &mut flattened).unwrap();
+        assert_eq!(String::from_utf8(flattened).unwrap(), "+a.\nFact x.\nFact z.\nFact y.\n+c.\n");
+
+        // Every line should map back to its origin, also after returning from nested includes
+        assert_eq!(ctx.lines.resolve(1), Some((main.as_path(), 1)));
+        assert_eq!(ctx.lines.resolve(2), Some((b.as_path(), 1)));
+        assert_eq!(ctx.lines.resolve(3), Some((c.as_path(), 1)));
+        assert_eq!(ctx.lines.resolve(4), Some((b.as_path(), 3)));
+        assert_eq!(ctx.lines.resolve(5), Some((main.as_path(), 3)));
+        assert_eq!(ctx.lines.resolve(0), None);
+        assert_eq!(ctx.lines.resolve(6), None);
+
+        // Which is used to explain compiler errors
+        let output = ChildStreams(vec![ChildStream("stderr", "(line 4, column 1):\nunexpected end of input".into())]);
+        assert_eq!(ctx.lines.explain(&output), format!(" (compiler line 4 is line 3 of '{}')", b.display()));
+    }
+
+    #[test]
+    fn test_compile_to_string() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(dir.join("main.eflint"), "#include \"base.eflint\".\n#include \"common.eflint\".\n+a.\n").unwrap();
+        fs::write(dir.join("base.eflint"), "Fact a.\n").unwrap();
+        fs::write(dir.join("shared/common.eflint"), "Fact b.\n").unwrap();
+
+        // NOTE: `cat` makes a great compiler for testing, as it simply echoes the flattened input
+        let opts: CompileOptions = CompileOptions::default().with_include_dirs([dir.join("shared")]);
+        assert_eq!(compile_to_string(&dir.join("main.eflint"), Some(Path::new("cat")), &opts).unwrap(), "Fact a.\nFact b.\n+a.\n");
+
+        // Without the include directory, the shared file can't be found
+        let res = compile_to_string(&dir.join("main.eflint"), Some(Path::new("cat")), &CompileOptions::default());
+        assert!(matches!(res, Err(Error::IncludeOpen { .. })), "{res:?}");
+    }
+
+    #[test]
+    fn test_compiler_source_file_name() {
+        assert_eq!(CompilerSource::default().file_name(), "eflint-to-json");
+        assert_eq!(CompilerSource::default().with_version("v0.1.0").file_name(), "eflint-to-json-v0.1.0");
+        assert_eq!(CompilerSource::new("https://mirror.internal/eflint-to-json", [0xAB; 32]).file_name(), "eflint-to-json-abababababababab");
+        assert_eq!(CompilerSource::default().with_version("../evil").file_name(), "eflint-to-json-.._evil");
+    }
+
+    #[test]
+    fn test_cache() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        let cache_dir: PathBuf = dir.join("cache");
+        fs::write(dir.join("main.eflint"), "#include \"base.eflint\".\n+a.\n").unwrap();
+        fs::write(dir.join("base.eflint"), "Fact a.\n").unwrap();
+        let n_entries = || fs::read_dir(&cache_dir).unwrap().count();
+
+        // NOTE: `cat` makes a great compiler for testing, as it simply echoes the flattened input
+        let opts = CompileOptions::default().with_cache_dir(&cache_dir);
+        let compile = || {
+            let mut out: Vec<u8> = Vec::new();
+            compile_with_options(&dir.join("main.eflint"), &mut out, Some(Path::new("cat")), &opts).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        // The first compile populates the cache, the second hits it
+        assert_eq!(compile(), "Fact a.\n+a.\n");
+        assert_eq!(n_entries(), 1);
+        assert_eq!(compile(), "Fact a.\n+a.\n");
+        assert_eq!(n_entries(), 1);
+
+        // Changing an included file invalidates it
+        fs::write(dir.join("base.eflint"), "Fact a.\nFact b.\n").unwrap();
+        assert_eq!(compile(), "Fact a.\nFact b.\n+a.\n");
+        assert_eq!(n_entries(), 2);
+    }
 }
@@ -0,0 +1,135 @@
+//  JSONL.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 10:12:31
+//  Last edited:
+//    16 Oct 2026, 01:38:41
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines an [`AuditLogger`] that writes one JSON-serialized
+//!   [`LogStatement`] per line.
+//
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use enum_debug::EnumDebug as _;
+use serde::Serialize;
+use spec::auditlogger::AuditLogger;
+use spec::reasonerconn::{ReasonerContext, ReasonerResponse};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt as _;
+use tokio::sync::Mutex;
+use tracing::{debug, instrument};
+
+use crate::logger::Error;
+use crate::stmt::LogStatement;
+
+
+/***** LIBRARY *****/
+/// Implements an [`AuditLogger`] that writes every statement as a single line of JSON.
+///
+/// Unlike the [`FileLogger`](crate::FileLogger), lines carry no writer identifier or timestamp,
+/// which makes every line a plain [`LogStatement`]. Use a
+/// [`JsonLinesReader`](crate::reader::JsonLinesReader) to read them back.
+#[derive(Clone, Debug)]
+pub struct JsonLinesLogger {
+    /// The path we log to.
+    path: PathBuf,
+    /// Serializes writes to the file.
+    lock: Arc<Mutex<()>>,
+}
+impl JsonLinesLogger {
+    /// Constructor for the JsonLinesLogger that initializes it pointing to the given file.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the file to log to. It is created if it doesn't exist yet, and
+    ///   appended to otherwise.
+    ///
+    /// # Returns
+    /// A new instance of self, ready for action.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self { Self { path: path.into(), lock: Arc::new(Mutex::new(())) } }
+
+    /// Returns the path of the file logged to.
+    ///
+    /// # Returns
+    /// A [`Path`] to the log file.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Writes a log statement to the logging file.
+    ///
+    /// # Arguments
+    /// - `stmt`: The [`LogStatement`] that determines what we're gonna log.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize the statement, or to write or flush it.
+    async fn log(&self, stmt: LogStatement<'_>) -> Result<(), Error> {
+        let mut line: String = serde_json::to_string(&stmt).map_err(|source| Error::LogStatementSerialize { kind: format!("{stmt:?}"), source })?;
+        line.push('\n');
+
+        // Only one writer at a time, such that lines are never interleaved
+        let _guard: tokio::sync::MutexGuard<()> = self.lock.lock().await;
+        debug!("Writing {}-statement to JSON lines log file '{}'...", stmt.variant(), self.path.display());
+        let mut handle: File = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|source| Error::FileOpen { path: self.path.clone(), source })?;
+        handle.write_all(line.as_bytes()).await.map_err(|source| Error::FileWrite { path: self.path.clone(), source })?;
+        handle.shutdown().await.map_err(|source| Error::FileShutdown { path: self.path.clone(), source })
+    }
+}
+impl AuditLogger for JsonLinesLogger {
+    type Error = Error;
+
+    #[inline]
+    #[instrument(name = "JsonLinesLogger::log_context", skip_all)]
+    async fn log_context<'a, C>(&'a self, context: &'a C) -> Result<(), Self::Error>
+    where
+        C: ?Sized + Sync + ReasonerContext,
+    {
+        let stmt: LogStatement = LogStatement::context(context).map_err(|source| Error::LogStatementSerialize { kind: "Context".into(), source })?;
+        self.log(stmt).await
+    }
+
+    #[inline]
+    #[instrument(name = "JsonLinesLogger::log_response", skip_all, fields(reference = reference))]
+    async fn log_response<'a, R>(&'a self, reference: &'a str, response: &'a ReasonerResponse<R>, raw: Option<&'a str>) -> Result<(), Self::Error>
+    where
+        R: Sync + Display,
+    {
+        let stmt: LogStatement = LogStatement::reasoner_response(reference, response, raw)
+            .map_err(|source| Error::LogStatementSerialize { kind: "ReasonerResponse".into(), source })?;
+        self.log(stmt).await
+    }
+
+    #[inline]
+    #[instrument(name = "JsonLinesLogger::log_question", skip_all, fields(reference = reference))]
+    async fn log_question<'a, S, Q>(&'a self, reference: &'a str, state: &'a S, question: &'a Q) -> Result<(), Self::Error>
+    where
+        S: Sync + Serialize,
+        Q: Sync + Serialize,
+    {
+        let stmt: LogStatement = LogStatement::reasoner_consult(reference, state, question)
+            .map_err(|source| Error::LogStatementSerialize { kind: "ReasonerConsult".into(), source })?;
+        self.log(stmt).await
+    }
+
+    #[inline]
+    #[instrument(name = "JsonLinesLogger::log_event", skip_all, fields(reference = reference, tag = tag))]
+    async fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> Result<(), Self::Error>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        let stmt: LogStatement =
+            LogStatement::event(reference, tag, value).map_err(|source| Error::LogStatementSerialize { kind: "Event".into(), source })?;
+        self.log(stmt).await
+    }
+}
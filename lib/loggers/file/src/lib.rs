@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 14:11:09
 //  Last edited:
-//    10 Oct 2024, 14:24:54
+//    16 Oct 2026, 00:35:23
 //  Auto updated?
 //    Yes
 //
@@ -13,8 +13,12 @@
 //
 
 // Declare modules
+mod jsonl;
 mod logger;
+pub mod reader;
 mod stmt;
 
 // Bring some of it into this namespace.
+pub use jsonl::JsonLinesLogger;
 pub use logger::*;
+pub use stmt::LogStatement;
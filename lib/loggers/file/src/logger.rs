@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 14:16:24
 //  Last edited:
-//    02 Dec 2024, 14:21:52
+//    16 Oct 2026, 01:46:23
 //  Auto updated?
 //    Yes
 //
@@ -12,18 +12,22 @@
 //!   Defines the actual [`AuditLogger`] itself.
 //
 
-use std::borrow::Cow;
+use std::ffi::OsString;
 use std::fmt::{Debug, Display};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use chrono::{DateTime, Local, NaiveDate};
 use enum_debug::EnumDebug as _;
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use serde::Serialize;
-use serde_json::Value;
 use spec::auditlogger::AuditLogger;
 use spec::reasonerconn::{ReasonerContext, ReasonerResponse};
 use tokio::fs::{File, OpenOptions};
 use tokio::io::AsyncWriteExt as _;
-use tracing::debug;
+use tokio::sync::Mutex;
+use tracing::{debug, instrument};
 
 use crate::stmt::LogStatement;
 
@@ -41,26 +45,6 @@ macro_rules! write_file {
     };
 }
 
-/// Wraps a [`writeln!`]-macro to return its error as a [`Error`].
-macro_rules! writeln_file {
-    ($path:expr, $handle:expr) => {
-        // Psych we actually don't wrap that macro, since we're doing async ofc
-        async {
-            use tokio::io::AsyncWriteExt as _;
-            $handle.write_all(b"\n").await.map_err(|source| Error::FileWrite { path: ($path), source })
-        }
-    };
-    ($path:expr, $handle:expr, $($t:tt)+) => {
-        // Psych we actually don't wrap that macro, since we're doing async ofc
-        async {
-            use tokio::io::AsyncWriteExt as _;
-            let mut contents: String = format!($($t)*);
-            contents.push('\n');
-            $handle.write_all(contents.as_bytes()).await.map_err(|source| Error::FileWrite { path: ($path), source })
-        }
-    };
-}
-
 
 
 
@@ -69,12 +53,24 @@ macro_rules! writeln_file {
 /// Defines the errors emitted by the [`FileLogger`].
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    /// Failed to compress a rotated log file.
+    #[error("Failed to compress rotated log file: {}", path.display())]
+    FileCompress { path: PathBuf, source: std::io::Error },
     /// Failed to create a new file.
     #[error("Failed to create a new file at: {}", path.display())]
     FileCreate { path: PathBuf, source: std::io::Error },
+    /// Failed to get the metadata of an existing file.
+    #[error("Failed to get metadata of file: {}", path.display())]
+    FileMetadata { path: PathBuf, source: std::io::Error },
     /// Failed to open an existing file.
     #[error("Failed to open existing file: {}", path.display())]
     FileOpen { path: PathBuf, source: std::io::Error },
+    /// Failed to remove an old file.
+    #[error("Failed to remove file: {}", path.display())]
+    FileRemove { path: PathBuf, source: std::io::Error },
+    /// Failed to rename a file while rotating.
+    #[error("Failed to rename file {} to {}", from.display(), to.display())]
+    FileRename { from: PathBuf, to: PathBuf, source: std::io::Error },
     /// Failed to shutdown an open file.
     #[error("Failed to shutdown open file: {}", path.display())]
     FileShutdown { path: PathBuf, source: std::io::Error },
@@ -90,6 +86,98 @@ pub enum Error {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Computes the path of the `n`th rotated log file.
+///
+/// # Arguments
+/// - `path`: The path of the active log file.
+/// - `n`: The index of the rotated file, where `1` is the most recent one.
+/// - `gzip`: Whether the rotated file is compressed.
+///
+/// # Returns
+/// The `path` with `.<n>` (and `.gz`, if compressed) appended.
+fn rotated_path(path: &Path, n: usize, gzip: bool) -> PathBuf {
+    let mut path: OsString = path.as_os_str().to_owned();
+    path.push(format!(".{n}"));
+    if gzip {
+        path.push(".gz");
+    }
+    PathBuf::from(path)
+}
+
+/// Compresses a file with gzip.
+///
+/// This function is blocking, and should be called with [`tokio::task::spawn_blocking()`].
+///
+/// # Arguments
+/// - `from`: The path of the file to compress.
+/// - `to`: The path to write the compressed file to.
+///
+/// # Errors
+/// This function errors if we failed to open `from`, create `to` or compress between them.
+fn compress_file(from: &Path, to: &Path) -> Result<(), Error> {
+    let mut source: std::fs::File = std::fs::File::open(from).map_err(|source| Error::FileOpen { path: from.into(), source })?;
+    let target: std::fs::File = std::fs::File::create(to).map_err(|source| Error::FileCreate { path: to.into(), source })?;
+    let mut enc: GzEncoder<std::fs::File> = GzEncoder::new(target, Compression::default());
+    std::io::copy(&mut source, &mut enc).and_then(|_| enc.finish()).map_err(|source| Error::FileCompress { path: from.into(), source })?;
+    Ok(())
+}
+
+/// Renames a file if it exists.
+///
+/// # Arguments
+/// - `from`: The path of the file to rename.
+/// - `to`: The path to rename it to.
+///
+/// # Errors
+/// This function errors if `from` exists but could not be renamed.
+async fn rename_if_exists(from: &Path, to: &Path) -> Result<(), Error> {
+    if !from.exists() {
+        return Ok(());
+    }
+    tokio::fs::rename(from, to).await.map_err(|source| Error::FileRename { from: from.into(), to: to.into(), source })
+}
+
+/// Removes a file if it exists.
+///
+/// # Arguments
+/// - `path`: The path of the file to remove.
+///
+/// # Errors
+/// This function errors if `path` exists but could not be removed.
+async fn remove_if_exists(path: &Path) -> Result<(), Error> {
+    if !path.exists() {
+        return Ok(());
+    }
+    tokio::fs::remove_file(path).await.map_err(|source| Error::FileRemove { path: path.into(), source })
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines when and how a [`FileLogger`] rotates its log file.
+///
+/// When the log file is rotated, it's renamed to `<path>.1`, the previous `<path>.1` to
+/// `<path>.2`, etc. Statements are never split across files, so every file remains readable on
+/// its own (e.g., by a [`LogReader`](crate::reader::LogReader)).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Rotation {
+    /// If given, the log file is rotated before it would grow beyond this many bytes.
+    pub max_size: Option<u64>,
+    /// Whether the log file is rotated when the first statement of a new (local) day is written.
+    pub daily:    bool,
+    /// The number of rotated files to keep. Older files are removed.
+    pub keep:     usize,
+    /// Whether to compress rotated files with gzip (as `<path>.<n>.gz`).
+    pub gzip:     bool,
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Implements an [`AuditLogger`] that writes everything to a local file.
 #[derive(Clone, Debug)]
@@ -98,6 +186,12 @@ pub struct FileLogger {
     id: String,
     /// The path we log to.
     path: PathBuf,
+    /// The clock used to timestamp log statements.
+    clock: fn() -> DateTime<Local>,
+    /// When to rotate the log file, if at all.
+    rotation: Option<Rotation>,
+    /// Serializes writes (and rotations). Remembers the day of the last write, if any.
+    lock: Arc<Mutex<Option<NaiveDate>>>,
     /// Whether the user has already printed the context or not.
     #[cfg(debug_assertions)]
     logged_context: std::sync::Arc<std::sync::atomic::AtomicBool>,
@@ -116,20 +210,119 @@ impl FileLogger {
         Self {
             id: id.into(),
             path: path.into(),
+            clock: Local::now,
+            rotation: None,
+            lock: Arc::new(Mutex::new(None)),
             #[cfg(debug_assertions)]
             logged_context: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
         }
     }
 
+    /// Replaces the clock used to timestamp log statements.
+    ///
+    /// This is mostly useful in tests, where a fixed clock makes the written log file
+    /// byte-for-byte deterministic. By default, [`Local::now()`] is used.
+    ///
+    /// # Arguments
+    /// - `clock`: A function returning the current time.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_clock(mut self, clock: fn() -> DateTime<Local>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Enables rotating the log file.
+    ///
+    /// # Arguments
+    /// - `rotation`: The [`Rotation`] that determines when to rotate and what to keep. Default is
+    ///   to never rotate.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = Some(rotation);
+        self
+    }
+
+    /// Rotates the log file if the given [`Rotation`] says it's time.
+    ///
+    /// # Arguments
+    /// - `rotation`: The [`Rotation`] to apply.
+    /// - `last`: The day of the last write, if known.
+    /// - `now`: The current time.
+    /// - `len`: The length (in bytes) of the statement about to be written.
+    ///
+    /// # Errors
+    /// This function errors if we failed to inspect the log file, or to rename, compress or remove
+    /// any of the (rotated) log files.
+    async fn rotate(&self, rotation: &Rotation, last: Option<NaiveDate>, now: DateTime<Local>, len: u64) -> Result<(), Error> {
+        // Decide if we have to rotate at all
+        if !self.path.exists() {
+            return Ok(());
+        }
+        let metadata: std::fs::Metadata =
+            tokio::fs::metadata(&self.path).await.map_err(|source| Error::FileMetadata { path: self.path.clone(), source })?;
+        if metadata.len() == 0 {
+            return Ok(());
+        }
+        let too_big: bool = rotation.max_size.is_some_and(|max_size| metadata.len() + len > max_size);
+        let new_day: bool = rotation.daily && {
+            // NOTE: If we haven't written anything yet, go by when the file was last modified
+            let last: Option<NaiveDate> = last.or_else(|| metadata.modified().ok().map(|time| DateTime::<Local>::from(time).date_naive()));
+            last.is_some_and(|last| last != now.date_naive())
+        };
+        if !too_big && !new_day {
+            return Ok(());
+        }
+        debug!("Rotating log file '{}'...", self.path.display());
+
+        // Make room by shifting all rotated files one up, dropping the oldest
+        if rotation.keep == 0 {
+            return remove_if_exists(&self.path).await;
+        }
+        remove_if_exists(&rotated_path(&self.path, rotation.keep, rotation.gzip)).await?;
+        for n in (1..rotation.keep).rev() {
+            rename_if_exists(&rotated_path(&self.path, n, rotation.gzip), &rotated_path(&self.path, n + 1, rotation.gzip)).await?;
+        }
+
+        // Then move the current file in place
+        if !rotation.gzip {
+            return rename_if_exists(&self.path, &rotated_path(&self.path, 1, false)).await;
+        }
+        // NOTE: Compressing is CPU-bound, so we do it off the runtime's worker threads
+        let (source, target): (PathBuf, PathBuf) = (self.path.clone(), rotated_path(&self.path, 1, true));
+        tokio::task::spawn_blocking(move || compress_file(&source, &target))
+            .await
+            .map_err(|err| Error::FileCompress { path: self.path.clone(), source: std::io::Error::other(err) })??;
+        remove_if_exists(&self.path).await
+    }
+
     /// Writes a log statement to the logging file.
     ///
     /// # Arguments
     /// - `stmt`: The [`LogStatement`] that determines what we're gonna log.
     ///
     /// # Errors
-    /// This function errors if we failed to perform the logging completely (i.e., either write or flush).
+    /// This function errors if we failed to perform the logging completely (i.e., either write or flush), or
+    /// failed to rotate the log file.
     async fn log(&self, stmt: LogStatement<'_>) -> Result<(), Error> {
-        // Step 1: Open the log file
+        // Step 1: Build the full line, such that we can decide on rotation
+        let now: DateTime<Local> = (self.clock)();
+        let message = serde_json::to_string(&stmt).map_err(|source| Error::LogStatementSerialize { kind: format!("{stmt:?}"), source })?;
+        let line: String = format!("[{}][{}] {message}\n", self.id, now.format("%Y-%m-%d %H:%M:%S"));
+
+        // Step 2: Lock out other writers, then rotate if needed
+        // NOTE: Holding the lock while rotating ensures no statement is written to a file that is being moved
+        let mut last: tokio::sync::MutexGuard<Option<NaiveDate>> = self.lock.lock().await;
+        if let Some(rotation) = &self.rotation {
+            self.rotate(rotation, *last, now, line.len() as u64).await?;
+        }
+
+        // Step 3: Open the log file
         let mut handle: File = if !self.path.exists() {
             debug!("Creating new log file at '{}'...", self.path.display());
             File::create(&self.path).await.map_err(|source| Error::FileCreate { path: self.path.clone(), source })?
@@ -150,21 +343,16 @@ impl FileLogger {
         // };
         // debug!("End of file is after {end_pos} bytes");
 
-        // Write the message
+        // Write the message (who wrote it, the timestamp and then the logged message) in one go
         debug!("Writing {}-statement to logfile...", stmt.variant());
-        // Write who wrote it
-        write_file!(self.path.clone(), &mut handle, "[{}]", self.id).await?;
-        // Print the timestamp
-        write_file!(self.path.clone(), &mut handle, "[{}]", chrono::Local::now().format("%Y-%m-%d %H:%M:%S")).await?;
-        // Then write the logged message
-        let message = serde_json::to_string(&stmt).map_err(|source| Error::LogStatementSerialize { kind: format!("{stmt:?}"), source })?;
-        writeln_file!(self.path.clone(), &mut handle, " {message}").await?;
+        write_file!(self.path.clone(), &mut handle, "{line}").await?;
 
         // Finally flush the file
         debug!("Flushing log file...");
         handle.shutdown().await.map_err(|source| Error::FileShutdown { path: self.path.clone(), source })?;
 
         drop(handle);
+        *last = Some(now.date_naive());
 
         // Done, a smashing success
         Ok(())
@@ -174,22 +362,20 @@ impl AuditLogger for FileLogger {
     type Error = Error;
 
     #[inline]
+    #[instrument(name = "FileLogger::log_context", skip_all)]
     async fn log_context<'a, C>(&'a self, context: &'a C) -> Result<(), Self::Error>
     where
         C: ?Sized + Sync + ReasonerContext,
     {
-        // Serialize the context first
-        let context: Value =
-            serde_json::to_value(context).map_err(|source| Error::LogStatementSerialize { kind: "LogStatement::Context".into(), source })?;
-
-        // Log it
-        self.log(LogStatement::Context { context }).await?;
+        let stmt: LogStatement = LogStatement::context(context).map_err(|source| Error::LogStatementSerialize { kind: "Context".into(), source })?;
+        self.log(stmt).await?;
         #[cfg(debug_assertions)]
         self.logged_context.store(true, std::sync::atomic::Ordering::Relaxed);
         Ok(())
     }
 
     #[inline]
+    #[instrument(name = "FileLogger::log_response", skip_all, fields(reference = reference))]
     async fn log_response<'a, R>(&'a self, reference: &'a str, response: &'a ReasonerResponse<R>, raw: Option<&'a str>) -> Result<(), Self::Error>
     where
         R: Sync + Display,
@@ -199,18 +385,13 @@ impl AuditLogger for FileLogger {
             tracing::warn!("Logging reasoner response without having logged the reasoner context; please call FileLogger::log_context() first.");
         }
 
-        // Serialize the response first
-        let response: Value = serde_json::to_value(&match response {
-            ReasonerResponse::Success => ReasonerResponse::Success,
-            ReasonerResponse::Violated(reasons) => ReasonerResponse::Violated(reasons.to_string()),
-        })
-        .map_err(|source| Error::LogStatementSerialize { kind: "LogStatement::ReasonerResponse".into(), source })?;
-
-        // Log it
-        self.log(LogStatement::ReasonerResponse { reference: Cow::Borrowed(reference), response, raw: raw.map(Cow::Borrowed) }).await
+        let stmt: LogStatement = LogStatement::reasoner_response(reference, response, raw)
+            .map_err(|source| Error::LogStatementSerialize { kind: "ReasonerResponse".into(), source })?;
+        self.log(stmt).await
     }
 
     #[inline]
+    #[instrument(name = "FileLogger::log_question", skip_all, fields(reference = reference))]
     async fn log_question<'a, S, Q>(&'a self, reference: &'a str, state: &'a S, question: &'a Q) -> Result<(), Self::Error>
     where
         S: Sync + Serialize,
@@ -221,13 +402,140 @@ impl AuditLogger for FileLogger {
             tracing::warn!("Logging reasoner response without having logged the reasoner context; please call FileLogger::log_context() first.");
         }
 
-        // Serialize the state & question first
-        let state: Value =
-            serde_json::to_value(state).map_err(|source| Error::LogStatementSerialize { kind: "LogStatement::ReasonerConsult".into(), source })?;
-        let question: Value =
-            serde_json::to_value(question).map_err(|source| Error::LogStatementSerialize { kind: "LogStatement::ReasonerConsult".into(), source })?;
+        let stmt: LogStatement = LogStatement::reasoner_consult(reference, state, question)
+            .map_err(|source| Error::LogStatementSerialize { kind: "ReasonerConsult".into(), source })?;
+        self.log(stmt).await
+    }
+
+    #[inline]
+    #[instrument(name = "FileLogger::log_event", skip_all, fields(reference = reference, tag = tag))]
+    async fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> Result<(), Self::Error>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        let stmt: LogStatement =
+            LogStatement::event(reference, tag, value).map_err(|source| Error::LogStatementSerialize { kind: "Event".into(), source })?;
+        self.log(stmt).await
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::io::Read as _;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use chrono::TimeZone as _;
+    use flate2::read::GzDecoder;
+    use spec::auditlogger::SessionedAuditLogger;
+    use tempfile::TempDir;
+
+    use super::*;
+
+
+    /// The day of the month returned by [`clock()`].
+    static DAY: AtomicU32 = AtomicU32::new(16);
+
+    /// A clock that is always at noon on the [`DAY`] of October 2026.
+    fn clock() -> DateTime<Local> { Local.with_ymd_and_hms(2026, 10, DAY.load(Ordering::SeqCst), 12, 0, 0).unwrap() }
+
+    /// Reads a (possibly compressed) log file.
+    fn read(path: &Path) -> String {
+        let raw: Vec<u8> = std::fs::read(path).unwrap();
+        if path.extension().is_some_and(|ext| ext == "gz") {
+            let mut res: String = String::new();
+            GzDecoder::new(raw.as_slice()).read_to_string(&mut res).unwrap();
+            res
+        } else {
+            String::from_utf8(raw).unwrap()
+        }
+    }
+
+    /// A clock that is always at the same moment.
+    fn fixed_clock() -> DateTime<Local> { Local.with_ymd_and_hms(2026, 10, 16, 9, 30, 0).unwrap() }
+
+    /// Logs an event with the given index as value.
+    async fn log(logger: &FileLogger, i: usize) { logger.log_event("ref", "test", &i).await.unwrap(); }
+
+
+    #[tokio::test]
+    async fn test_deterministic_output() {
+        let dir: TempDir = TempDir::new().unwrap();
+
+        // Logging the same session twice with a fixed clock and reference gives identical files
+        let mut logs: Vec<String> = Vec::with_capacity(2);
+        for i in 0..2 {
+            let path: PathBuf = dir.path().join(format!("audit-{i}.log"));
+            let logger: SessionedAuditLogger<FileLogger> =
+                SessionedAuditLogger::generated_with(|| "session".into(), FileLogger::new("test", &path).with_clock(fixed_clock));
+            logger.log_question(&"state", &42).await.unwrap();
+            logger.log_event("trace", &["delta"]).await.unwrap();
+            logger.log_response(&ReasonerResponse::Violated("not allowed"), None).await.unwrap();
+            logs.push(read(&path));
+        }
+        assert_eq!(logs[0], logs[1]);
+
+        // Every line carries the fixed timestamp and reference
+        assert_eq!(logs[0].lines().count(), 3);
+        for line in logs[0].lines() {
+            assert!(line.starts_with("[test][2026-10-16 09:30:00] "), "{line}");
+            assert!(line.contains(r#""reference":"session""#), "{line}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rotate_size() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("audit.log");
+
+        // Nothing is rotated below the maximum size
+        let logger =
+            FileLogger::new("test", &path).with_rotation(Rotation { max_size: Some(1 << 20), daily: false, keep: 2, gzip: false });
+        log(&logger, 0).await;
+        log(&logger, 1).await;
+        assert_eq!(read(&path).lines().count(), 2);
+        assert!(!rotated_path(&path, 1, false).exists());
+
+        // Above it, every statement starts a new file, keeping only two old ones
+        let logger = FileLogger::new("test", &path).with_rotation(Rotation { max_size: Some(1), daily: false, keep: 2, gzip: false });
+        for i in 2..5 {
+            log(&logger, i).await;
+        }
+        assert!(read(&path).contains(r#""value":4"#));
+        assert!(read(&rotated_path(&path, 1, false)).contains(r#""value":3"#));
+        assert!(read(&rotated_path(&path, 2, false)).contains(r#""value":2"#));
+        assert!(!rotated_path(&path, 3, false).exists());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_daily_gzip() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("audit.log");
+        let logger =
+            FileLogger::new("test", &path).with_clock(clock).with_rotation(Rotation { max_size: None, daily: true, keep: 1, gzip: true });
+
+        // The same day goes in the same file
+        log(&logger, 0).await;
+        log(&logger, 1).await;
+        let first: String = read(&path);
+        assert_eq!(first.lines().count(), 2);
+        assert!(first.starts_with("[test][2026-10-16 12:00:00] "), "{first}");
+
+        // A new day is rotated and compressed
+        DAY.store(17, Ordering::SeqCst);
+        log(&logger, 2).await;
+        assert_eq!(read(&rotated_path(&path, 1, true)), first);
+        assert!(!rotated_path(&path, 1, false).exists());
+        assert!(read(&path).starts_with("[test][2026-10-17 12:00:00] "));
 
-        // Log it
-        self.log(LogStatement::ReasonerConsult { reference: Cow::Borrowed(reference), state, question }).await
+        // Older files are pruned
+        DAY.store(18, Ordering::SeqCst);
+        log(&logger, 3).await;
+        assert!(read(&rotated_path(&path, 1, true)).contains(r#""value":2"#));
+        assert!(!rotated_path(&path, 2, true).exists());
     }
 }
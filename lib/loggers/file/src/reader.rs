@@ -0,0 +1,425 @@
+//  READER.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:52:38
+//  Last edited:
+//    16 Oct 2026, 02:31:06
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a reader for log files written by the
+//!   [`FileLogger`](crate::FileLogger), which can resume from where it
+//!   left off, and one for those written by the
+//!   [`JsonLinesLogger`](crate::JsonLinesLogger).
+//
+
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+
+use spec::reasonerconn::ReasonerResponse;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncSeekExt as _, BufReader};
+use tracing::debug;
+
+use crate::stmt::LogStatement;
+
+
+/***** ERRORS *****/
+/// Defines the errors emitted by the [`LogReader`].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Failed to get the metadata of the log file.
+    #[error("Failed to get metadata of file: {}", path.display())]
+    FileMetadata { path: PathBuf, source: std::io::Error },
+    /// Failed to open the log file.
+    #[error("Failed to open existing file: {}", path.display())]
+    FileOpen { path: PathBuf, source: std::io::Error },
+    /// Failed to read from the log file.
+    #[error("Failed to read from file: {}", path.display())]
+    FileRead { path: PathBuf, source: std::io::Error },
+    /// Failed to seek in the log file.
+    #[error("Failed to seek to byte {offset} in file: {}", path.display())]
+    FileSeek { path: PathBuf, offset: u64, source: std::io::Error },
+    /// A line in the log file was not a log statement.
+    #[error("Line at byte {offset} in file {} is not a log statement", path.display())]
+    LogStatementIllegal { path: PathBuf, offset: u64 },
+    /// Failed to deserialize a logging statement.
+    #[error("Failed to deserialize statement at byte {offset} in file {}", path.display())]
+    LogStatementDeserialize { path: PathBuf, offset: u64, source: serde_json::Error },
+    /// The offset to resume from does not start a log statement.
+    #[error("Byte {offset} in file {} is not the start of a log statement", path.display())]
+    OffsetUnaligned { path: PathBuf, offset: u64 },
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a single statement read from a log file.
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    /// The identifier of who/what wrote the statement.
+    pub id: String,
+    /// The (local) time at which the statement was written, as `%Y-%m-%d %H:%M:%S`.
+    pub timestamp: String,
+    /// The statement itself.
+    pub stmt: LogStatement<'static>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Reads the statements from a log file written by a [`FileLogger`](crate::FileLogger).
+///
+/// The reader tracks the byte offset up to which it has read. This can be stored and given to
+/// [`LogReader::from_offset()`] later to resume reading where it left off, e.g., to follow a log
+/// file without re-reading it in its entirety every time.
+#[derive(Debug)]
+pub struct LogReader {
+    /// The path of the log file.
+    path:   PathBuf,
+    /// The handle to the log file.
+    handle: BufReader<File>,
+    /// The offset right after the last statement read.
+    offset: u64,
+}
+impl LogReader {
+    /// Constructor for the LogReader that reads a log file from the start.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the log file to read.
+    ///
+    /// # Returns
+    /// A new LogReader that will read the first statement in the file first.
+    ///
+    /// # Errors
+    /// This function errors if we failed to open the file.
+    #[inline]
+    pub async fn new(path: impl Into<PathBuf>) -> Result<Self, Error> { Self::from_offset(path, 0).await }
+
+    /// Constructor for the LogReader that resumes reading a log file from a given offset.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the log file to read.
+    /// - `offset`: The byte offset to resume from. Should be an offset previously returned by
+    ///   [`LogReader::offset()`] or [`LogReader::next_entry()`].
+    ///
+    /// # Returns
+    /// A new LogReader that will read the statement at `offset` first.
+    ///
+    /// # Errors
+    /// This function errors if we failed to open the file, or if `offset` is not the start of a
+    /// statement (or the end of the file).
+    pub async fn from_offset(path: impl Into<PathBuf>, offset: u64) -> Result<Self, Error> {
+        let path: PathBuf = path.into();
+        debug!("Opening log file '{}' at byte {offset}...", path.display());
+        let mut handle: File = File::open(&path).await.map_err(|source| Error::FileOpen { path: path.clone(), source })?;
+
+        // Check the offset is in range and follows a statement
+        let len: u64 = handle.metadata().await.map_err(|source| Error::FileMetadata { path: path.clone(), source })?.len();
+        if offset > len {
+            return Err(Error::OffsetUnaligned { path, offset });
+        }
+        if offset > 0 {
+            handle.seek(SeekFrom::Start(offset - 1)).await.map_err(|source| Error::FileSeek { path: path.clone(), offset: offset - 1, source })?;
+            let prev: u8 = handle.read_u8().await.map_err(|source| Error::FileRead { path: path.clone(), source })?;
+            if prev != b'\n' {
+                return Err(Error::OffsetUnaligned { path, offset });
+            }
+        }
+
+        Ok(Self { path, handle: BufReader::new(handle), offset })
+    }
+
+    /// Returns the path of the log file being read.
+    ///
+    /// # Returns
+    /// A [`Path`] to the log file.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the offset up to which the log file has been read.
+    ///
+    /// # Returns
+    /// The byte offset right after the last statement read, to give to
+    /// [`LogReader::from_offset()`].
+    #[inline]
+    pub const fn offset(&self) -> u64 { self.offset }
+
+    /// Reads the next statement from the log file.
+    ///
+    /// Statements that are still being written (i.e., that are not terminated by a newline yet)
+    /// are not returned, but are read again on the next call instead. As such, this function can
+    /// be polled to follow a log file that is being written to.
+    ///
+    /// # Returns
+    /// The next [`LogEntry`] and the offset right after it, or [`None`] if there are no (complete)
+    /// statements left.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the file or if it contains something else than
+    /// log statements. In the latter case, the offending line is skipped, such that the next call
+    /// continues with the statement after it.
+    pub async fn next_entry(&mut self) -> Result<Option<(LogEntry, u64)>, Error> {
+        let mut line: String = String::new();
+        let n: usize = self.handle.read_line(&mut line).await.map_err(|source| Error::FileRead { path: self.path.clone(), source })?;
+        if n == 0 {
+            return Ok(None);
+        }
+        if !line.ends_with('\n') {
+            // Rewind to read it again once it's complete
+            self.handle.seek(SeekFrom::Start(self.offset)).await.map_err(|source| Error::FileSeek {
+                path: self.path.clone(),
+                offset: self.offset,
+                source,
+            })?;
+            return Ok(None);
+        }
+
+        // Parse the line as `[<id>][<timestamp>] <statement>`
+        let start: u64 = self.offset;
+        self.offset += n as u64;
+        let line: &str = line.trim_end();
+        let illegal = || Error::LogStatementIllegal { path: self.path.clone(), offset: start };
+        let (head, _): (&str, &str) = line.split_once("] {").ok_or_else(illegal)?;
+        let (id, timestamp): (&str, &str) = head.strip_prefix('[').and_then(|head| head.rsplit_once("][")).ok_or_else(illegal)?;
+        let stmt: LogStatement<'static> = serde_json::from_str(&line[head.len() + 2..]).map_err(|source| Error::LogStatementDeserialize {
+            path: self.path.clone(),
+            offset: start,
+            source,
+        })?;
+        Ok(Some((LogEntry { id: id.into(), timestamp: timestamp.into(), stmt }, self.offset)))
+    }
+}
+
+
+
+/// Reads the statements from a log file written by a [`JsonLinesLogger`](crate::JsonLinesLogger).
+#[derive(Debug)]
+pub struct JsonLinesReader {
+    /// The path of the log file.
+    path:   PathBuf,
+    /// The handle to the log file.
+    handle: BufReader<File>,
+    /// The offset right after the last statement read.
+    offset: u64,
+}
+impl JsonLinesReader {
+    /// Constructor for the JsonLinesReader that reads a log file from the start.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the log file to read.
+    ///
+    /// # Returns
+    /// A new JsonLinesReader that will read the first statement in the file first.
+    ///
+    /// # Errors
+    /// This function errors if we failed to open the file.
+    pub async fn new(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path: PathBuf = path.into();
+        debug!("Opening JSON lines log file '{}'...", path.display());
+        let handle: File = File::open(&path).await.map_err(|source| Error::FileOpen { path: path.clone(), source })?;
+        Ok(Self { path, handle: BufReader::new(handle), offset: 0 })
+    }
+
+    /// Returns the path of the log file being read.
+    ///
+    /// # Returns
+    /// A [`Path`] to the log file.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Reads the next statement from the log file.
+    ///
+    /// Empty lines are skipped. Like [`LogReader::next_entry()`], a last line that is not
+    /// terminated by a newline yet is not returned, but read again on the next call instead.
+    ///
+    /// # Returns
+    /// The next [`LogStatement`], or [`None`] if there are no (complete) statements left.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the file or if a line is not a log statement.
+    /// In the latter case, the offending line is skipped, such that the next call continues with
+    /// the statement after it.
+    pub async fn next_statement(&mut self) -> Result<Option<LogStatement<'static>>, Error> {
+        loop {
+            let mut line: String = String::new();
+            let n: usize = self.handle.read_line(&mut line).await.map_err(|source| Error::FileRead { path: self.path.clone(), source })?;
+            if n == 0 {
+                return Ok(None);
+            }
+            if !line.ends_with('\n') {
+                // Rewind to read it again once it's complete
+                self.handle.seek(SeekFrom::Start(self.offset)).await.map_err(|source| Error::FileSeek {
+                    path: self.path.clone(),
+                    offset: self.offset,
+                    source,
+                })?;
+                return Ok(None);
+            }
+
+            let start: u64 = self.offset;
+            self.offset += n as u64;
+            if line.trim().is_empty() {
+                continue;
+            }
+            return serde_json::from_str(&line).map(Some).map_err(|source| Error::LogStatementDeserialize {
+                path: self.path.clone(),
+                offset: start,
+                source,
+            });
+        }
+    }
+
+    /// Reads the rest of the log file and collects the responses logged for the given reference.
+    ///
+    /// # Arguments
+    /// - `reference`: The reference of the consult to find the responses of.
+    ///
+    /// # Returns
+    /// The [`ReasonerResponse`]s logged for `reference`, in the order they were written. The
+    /// reasons are in the serialized form they were logged in.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the file, or if any line is not a log statement
+    /// or its response is not a [`ReasonerResponse`].
+    pub async fn responses(&mut self, reference: &str) -> Result<Vec<ReasonerResponse<String>>, Error> {
+        let mut responses: Vec<ReasonerResponse<String>> = Vec::new();
+        loop {
+            let start: u64 = self.offset;
+            match self.next_statement().await? {
+                Some(LogStatement::ReasonerResponse { reference: r, response, .. }) if r == reference => {
+                    responses.push(serde_json::from_value(response).map_err(|source| Error::LogStatementDeserialize {
+                        path: self.path.clone(),
+                        offset: start,
+                        source,
+                    })?);
+                },
+                Some(_) => continue,
+                None => return Ok(responses),
+            }
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use serde::Serialize;
+    use serde_json::Value;
+    use spec::auditlogger::AuditLogger as _;
+    use spec::reasonerconn::ReasonerContext;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::JsonLinesLogger;
+
+
+    /// A [`ReasonerContext`] to log.
+    #[derive(Serialize)]
+    struct TestContext;
+    impl ReasonerContext for TestContext {
+        #[inline]
+        fn version(&self) -> Cow<'_, str> { Cow::Borrowed("0.1.0") }
+
+        #[inline]
+        fn language(&self) -> Cow<'_, str> { Cow::Borrowed("test") }
+
+        #[inline]
+        fn language_version(&self) -> Cow<'_, str> { Cow::Borrowed("v1") }
+    }
+
+
+    #[tokio::test]
+    async fn test_json_lines_responses() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("audit.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"Context":{"context":{"version":"0.2.0","language":"no-op","language_version":"v1"}}}"#,
+                "\n",
+                r#"{"ReasonerConsult":{"reference":"abc","state":null,"question":null}}"#,
+                "\n",
+                r#"{"Event":{"reference":"abc","tag":"trace","value":{"deltas":[]}}}"#,
+                "\n",
+                r#"{"ReasonerResponse":{"reference":"abc","response":{"Violated":"not allowed"},"raw":null}}"#,
+                "\n\n",
+                r#"{"ReasonerConsult":{"reference":"def","state":null,"question":null}}"#,
+                "\n",
+                r#"{"ReasonerResponse":{"reference":"def","response":"Success","raw":"{}"}}"#,
+                "\n",
+                r#"{"ReasonerResponse":{"reference":"abc","response":{"Indeterminate":{"reasons":"no data"}},"raw":null}}"#,
+                "\n",
+                r#"{"ReasonerResponse":{"reference":"abc","response":"Success""#,
+            ),
+        )
+        .unwrap();
+
+        // Every complete line is a statement
+        let mut reader: JsonLinesReader = JsonLinesReader::new(&path).await.unwrap();
+        let mut n_stmts: usize = 0;
+        while reader.next_statement().await.unwrap().is_some() {
+            n_stmts += 1;
+        }
+        assert_eq!(n_stmts, 7);
+
+        // Only the responses for the reference are collected
+        let mut reader: JsonLinesReader = JsonLinesReader::new(&path).await.unwrap();
+        assert_eq!(reader.responses("abc").await.unwrap(), vec![ReasonerResponse::Violated("not allowed".into()), ReasonerResponse::Indeterminate {
+            reasons: "no data".into(),
+        }]);
+        let mut reader: JsonLinesReader = JsonLinesReader::new(&path).await.unwrap();
+        assert_eq!(reader.responses("def").await.unwrap(), vec![ReasonerResponse::Success]);
+        let mut reader: JsonLinesReader = JsonLinesReader::new(&path).await.unwrap();
+        assert_eq!(reader.responses("ghi").await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_json_lines_logger_roundtrip() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("audit.jsonl");
+
+        // Write a couple of interleaved consults
+        let logger: JsonLinesLogger = JsonLinesLogger::new(&path);
+        logger.log_context(&TestContext).await.unwrap();
+        logger.log_question("abc", &"state", &42).await.unwrap();
+        logger.log_question("def", &(), &()).await.unwrap();
+        logger.log_event("abc", "trace", &["delta"]).await.unwrap();
+        logger.log_response("abc", &ReasonerResponse::Violated("not allowed"), None).await.unwrap();
+        logger.log_response("def", &ReasonerResponse::<&str>::Success, Some("{}")).await.unwrap();
+        logger.log_response("abc", &ReasonerResponse::Indeterminate { reasons: "no data" }, None).await.unwrap();
+
+        // Every call is one statement, in order
+        let mut reader: JsonLinesReader = JsonLinesReader::new(&path).await.unwrap();
+        let mut stmts: Vec<LogStatement<'static>> = Vec::new();
+        while let Some(stmt) = reader.next_statement().await.unwrap() {
+            stmts.push(stmt);
+        }
+        assert_eq!(stmts.len(), 7);
+        assert!(matches!(&stmts[0], LogStatement::Context { context } if *context == serde_json::to_value(TestContext).unwrap()));
+        assert!(matches!(&stmts[1], LogStatement::ReasonerConsult { reference, state, question }
+            if reference == "abc" && *state == Value::from("state") && *question == Value::from(42)));
+        assert!(matches!(&stmts[3], LogStatement::Event { reference, tag, .. } if reference == "abc" && tag == "trace"));
+        assert!(matches!(&stmts[5], LogStatement::ReasonerResponse { raw: Some(raw), .. } if raw == "{}"));
+
+        // The responses can be found back by reference
+        let mut reader: JsonLinesReader = JsonLinesReader::new(&path).await.unwrap();
+        assert_eq!(reader.responses("abc").await.unwrap(), vec![ReasonerResponse::Violated("not allowed".into()), ReasonerResponse::Indeterminate {
+            reasons: "no data".into(),
+        }]);
+        let mut reader: JsonLinesReader = JsonLinesReader::new(&path).await.unwrap();
+        assert_eq!(reader.responses("def").await.unwrap(), vec![ReasonerResponse::Success]);
+    }
+}
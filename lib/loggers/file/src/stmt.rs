@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 14:24:22
 //  Last edited:
-//    17 Oct 2024, 13:15:51
+//    16 Oct 2026, 01:38:41
 //  Auto updated?
 //    Yes
 //
@@ -13,10 +13,12 @@
 //
 
 use std::borrow::Cow;
+use std::fmt::Display;
 
 use enum_debug::EnumDebug;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use spec::reasonerconn::{ReasonerContext, ReasonerResponse};
 
 
 /***** LIBRARY *****/
@@ -29,4 +31,93 @@ pub enum LogStatement<'a> {
     ReasonerConsult { reference: Cow<'a, str>, state: Value, question: Value },
     /// Logging a reasoner response.
     ReasonerResponse { reference: Cow<'a, str>, response: Value, raw: Option<Cow<'a, str>> },
+    /// Logging some other event that occurred while answering a question.
+    Event { reference: Cow<'a, str>, tag: Cow<'a, str>, value: Value },
+}
+impl<'a> LogStatement<'a> {
+    /// Constructor for a [`LogStatement::Context`].
+    ///
+    /// # Arguments
+    /// - `context`: The [`ReasonerContext`] to log.
+    ///
+    /// # Returns
+    /// A new LogStatement with the serialized `context`.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize the `context`.
+    #[inline]
+    pub fn context<C: ?Sized + ReasonerContext>(context: &C) -> Result<Self, serde_json::Error> {
+        Ok(Self::Context { context: serde_json::to_value(context)? })
+    }
+
+    /// Constructor for a [`LogStatement::ReasonerConsult`].
+    ///
+    /// # Arguments
+    /// - `reference`: The reference of the consult.
+    /// - `state`: The state asked about.
+    /// - `question`: The question asked.
+    ///
+    /// # Returns
+    /// A new LogStatement with the serialized `state` and `question`.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize the `state` or `question`.
+    #[inline]
+    pub fn reasoner_consult<S: ?Sized + Serialize, Q: ?Sized + Serialize>(
+        reference: &'a str,
+        state: &S,
+        question: &Q,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self::ReasonerConsult {
+            reference: Cow::Borrowed(reference),
+            state:     serde_json::to_value(state)?,
+            question:  serde_json::to_value(question)?,
+        })
+    }
+
+    /// Constructor for a [`LogStatement::ReasonerResponse`].
+    ///
+    /// The reasons of the response are logged in their [`Display`]ed form, such that they can be
+    /// read back as a `ReasonerResponse<String>`.
+    ///
+    /// # Arguments
+    /// - `reference`: The reference of the consult.
+    /// - `response`: The [`ReasonerResponse`] of the reasoner.
+    /// - `raw`: The raw response of the reasoner, if any.
+    ///
+    /// # Returns
+    /// A new LogStatement with the serialized `response`.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize the `response`.
+    #[inline]
+    pub fn reasoner_response<R: Display>(
+        reference: &'a str,
+        response: &ReasonerResponse<R>,
+        raw: Option<&'a str>,
+    ) -> Result<Self, serde_json::Error> {
+        let response: Value = serde_json::to_value(match response {
+            ReasonerResponse::Success => ReasonerResponse::Success,
+            ReasonerResponse::Violated(reasons) => ReasonerResponse::Violated(reasons.to_string()),
+            ReasonerResponse::Indeterminate { reasons } => ReasonerResponse::Indeterminate { reasons: reasons.to_string() },
+        })?;
+        Ok(Self::ReasonerResponse { reference: Cow::Borrowed(reference), response, raw: raw.map(Cow::Borrowed) })
+    }
+
+    /// Constructor for a [`LogStatement::Event`].
+    ///
+    /// # Arguments
+    /// - `reference`: The reference of the consult during which the event occurred.
+    /// - `tag`: The kind of event.
+    /// - `value`: The event itself.
+    ///
+    /// # Returns
+    /// A new LogStatement with the serialized `value`.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize the `value`.
+    #[inline]
+    pub fn event<E: ?Sized + Serialize>(reference: &'a str, tag: &'a str, value: &E) -> Result<Self, serde_json::Error> {
+        Ok(Self::Event { reference: Cow::Borrowed(reference), tag: Cow::Borrowed(tag), value: serde_json::to_value(value)? })
+    }
 }
@@ -0,0 +1,19 @@
+//  LIB.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 10:41:12
+//  Last edited:
+//    16 Oct 2026, 10:41:12
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements an audit logger that sends everything to syslog.
+//
+
+// Declare modules
+mod logger;
+
+// Bring some of it into this namespace.
+pub use logger::*;
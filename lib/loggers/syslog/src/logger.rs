@@ -0,0 +1,585 @@
+//  LOGGER.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 10:42:03
+//  Last edited:
+//    16 Oct 2026, 02:28:45
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the actual [`AuditLogger`] itself.
+//
+
+use std::fmt::Display;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use chrono::{Local, SecondsFormat};
+use enum_debug::EnumDebug as _;
+use file_logger::LogStatement;
+use serde::Serialize;
+use spec::auditlogger::AuditLogger;
+use spec::reasonerconn::{ReasonerContext, ReasonerResponse};
+use tokio::io::AsyncWriteExt as _;
+#[cfg(unix)]
+use tokio::net::UnixDatagram;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tracing::{debug, instrument, warn};
+
+
+/***** CONSTANTS *****/
+/// The number of statements buffered while the syslog endpoint is slow or unreachable.
+const BUFFER_SIZE: usize = 1024;
+
+/// The time we give the syslog endpoint to accept a statement before it is dropped.
+const SEND_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The largest message we send over UDP, which is the largest payload of an IPv4 UDP datagram.
+const MAX_UDP_LEN: usize = 65_507;
+
+/// The largest message we send over a Unix datagram socket.
+///
+/// NOTE: The actual limit depends on the socket's send buffer, but this is well within the default.
+#[cfg(unix)]
+const MAX_UNIX_LEN: usize = 65_536;
+
+/// The structured data ID under which we send the reference of a statement.
+///
+/// NOTE: Uses the enterprise number reserved for documentation, as the policy reasoner has none
+/// of its own.
+const SD_ID: &str = "policy-reasoner@32473";
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors emitted by the [`SyslogLogger`].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A statement is too large to be sent as a single datagram.
+    #[error(
+        "Cannot log statement LogStatement::{kind} because it is {len} bytes, which exceeds the maximum datagram size of {max} bytes (use TCP \
+         instead)"
+    )]
+    TooLarge { kind: String, len: usize, max: usize },
+    /// The buffer of statements waiting to be sent is full.
+    #[error("Cannot log statement LogStatement::{kind} because the syslog buffer is full")]
+    BufferFull { kind: String },
+    /// The background task sending statements has stopped.
+    #[error("Cannot log statement LogStatement::{kind} because the syslog task has stopped")]
+    TaskStopped { kind: String },
+    /// Failed to serialize a logging statement.
+    #[error("Failed to serialize statement LogStatement::{kind}")]
+    LogStatementSerialize { kind: String, source: serde_json::Error },
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Escapes a value for use as a structured data parameter value (RFC 5424, section 6.3.3).
+///
+/// # Arguments
+/// - `value`: The value to escape.
+///
+/// # Returns
+/// The `value` with `"`, `\` and `]` escaped.
+fn escape_param(value: &str) -> String {
+    let mut res: String = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// Sends statements to the syslog endpoint until all [`SyslogLogger`]s are dropped.
+///
+/// Statements that cannot be delivered within [`SEND_TIMEOUT`] are dropped with a warning, after
+/// which the next statement attempts to reconnect.
+///
+/// # Arguments
+/// - `transport`: The [`Transport`] to reach the endpoint with.
+/// - `rx`: The channel to receive formatted statements on.
+async fn run(transport: Transport, mut rx: Receiver<Vec<u8>>) {
+    let mut conn: Option<Connection> = None;
+    while let Some(msg) = rx.recv().await {
+        let res: Result<(), String> = match tokio::time::timeout(SEND_TIMEOUT, async {
+            if conn.is_none() {
+                debug!("Connecting to syslog at {transport:?}...");
+                conn = Some(Connection::connect(&transport).await?);
+            }
+            // NOTE: Unwrap is OK because we just ensured it's there
+            conn.as_mut().unwrap().send(&msg).await
+        })
+        .await
+        {
+            Ok(res) => res.map_err(|err| err.to_string()),
+            Err(_) => Err(format!("timed out after {SEND_TIMEOUT:?}")),
+        };
+        if let Err(err) = res {
+            warn!("Dropping audit log statement because syslog at {transport:?} is unreachable: {err}");
+            conn = None;
+        }
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines how to reach the syslog endpoint.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Transport {
+    /// Sends datagrams to a local Unix socket, typically `/dev/log`.
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// Sends datagrams over UDP (RFC 5426).
+    Udp(SocketAddr),
+    /// Sends octet-counted frames over TCP (RFC 6587).
+    Tcp(SocketAddr),
+}
+
+/// Defines the syslog facilities (RFC 5424, section 6.2.1).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Facility {
+    /// Kernel messages.
+    Kern  = 0,
+    /// User-level messages.
+    User  = 1,
+    /// Mail system.
+    Mail  = 2,
+    /// System daemons.
+    Daemon = 3,
+    /// Security/authorization messages.
+    Auth  = 4,
+    /// Messages generated internally by syslogd.
+    Syslog = 5,
+    /// Line printer subsystem.
+    Lpr   = 6,
+    /// Network news subsystem.
+    News  = 7,
+    /// UUCP subsystem.
+    Uucp  = 8,
+    /// Clock daemon (cron).
+    Cron  = 9,
+    /// Security/authorization messages (private).
+    AuthPriv = 10,
+    /// FTP daemon.
+    Ftp   = 11,
+    /// NTP subsystem.
+    Ntp   = 12,
+    /// Log audit.
+    Audit = 13,
+    /// Log alert.
+    Alert = 14,
+    /// Clock daemon (alternative).
+    Clock = 15,
+    /// Locally used facility 0.
+    Local0 = 16,
+    /// Locally used facility 1.
+    Local1 = 17,
+    /// Locally used facility 2.
+    Local2 = 18,
+    /// Locally used facility 3.
+    Local3 = 19,
+    /// Locally used facility 4.
+    Local4 = 20,
+    /// Locally used facility 5.
+    Local5 = 21,
+    /// Locally used facility 6.
+    Local6 = 22,
+    /// Locally used facility 7.
+    Local7 = 23,
+}
+
+/// An open connection to a syslog endpoint.
+enum Connection {
+    /// A connected Unix datagram socket.
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    /// A connected UDP socket.
+    Udp(UdpSocket),
+    /// A TCP stream.
+    Tcp(TcpStream),
+}
+impl Connection {
+    /// Connects to the given syslog endpoint.
+    ///
+    /// # Arguments
+    /// - `transport`: The [`Transport`] describing the endpoint.
+    ///
+    /// # Returns
+    /// A new Connection that statements can be sent over.
+    ///
+    /// # Errors
+    /// This function errors if we failed to connect.
+    async fn connect(transport: &Transport) -> Result<Self, std::io::Error> {
+        match transport {
+            #[cfg(unix)]
+            Transport::Unix(path) => {
+                let sock: UnixDatagram = UnixDatagram::unbound()?;
+                sock.connect(path)?;
+                Ok(Self::Unix(sock))
+            },
+            Transport::Udp(addr) => {
+                let sock: UdpSocket = UdpSocket::bind(if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }).await?;
+                sock.connect(addr).await?;
+                Ok(Self::Udp(sock))
+            },
+            Transport::Tcp(addr) => Ok(Self::Tcp(TcpStream::connect(addr).await?)),
+        }
+    }
+
+    /// Sends a single formatted statement.
+    ///
+    /// # Arguments
+    /// - `msg`: The formatted syslog message to send.
+    ///
+    /// # Errors
+    /// This function errors if we failed to send it.
+    async fn send(&mut self, msg: &[u8]) -> Result<(), std::io::Error> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(sock) => sock.send(msg).await.map(|_| ()),
+            Self::Udp(sock) => sock.send(msg).await.map(|_| ()),
+            Self::Tcp(stream) => {
+                stream.write_all(format!("{} ", msg.len()).as_bytes()).await?;
+                stream.write_all(msg).await
+            },
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Implements an [`AuditLogger`] that sends everything to syslog.
+///
+/// Statements are formatted as RFC 5424 messages, with the statement's kind as message ID, its
+/// reference (if any) as structured data and the same JSON as written by the
+/// [`FileLogger`](file_logger::FileLogger) as message.
+///
+/// Logging never blocks the reasoner. Statements are handed to a background task, which buffers
+/// up to a fixed number of them. If that buffer is full (e.g., because the syslog endpoint is
+/// slow or unreachable), statements are dropped with a warning.
+///
+/// Over datagram transports, every statement must fit in a single datagram. Larger statements
+/// (typically questions carrying a large state) are dropped with a warning too rather than lost
+/// in transit; use [`Transport::Tcp`] if they occur.
+///
+/// The number of dropped statements is available through [`SyslogLogger::dropped()`]. Use
+/// [`SyslogLogger::with_fail_closed()`] to have logging fail instead.
+#[derive(Clone, Debug)]
+pub struct SyslogLogger {
+    /// The facility to log with.
+    facility: Facility,
+    /// The name of the application to log as.
+    app_name: String,
+    /// The channel to the background task sending the statements.
+    tx: Sender<Vec<u8>>,
+    /// The maximum size of a single message, if the transport limits it.
+    max_len: Option<usize>,
+    /// Whether to fail logging instead of dropping statements.
+    fail_closed: bool,
+    /// The number of statements dropped so far, shared between clones.
+    dropped: Arc<AtomicU64>,
+}
+impl SyslogLogger {
+    /// Constructor for the SyslogLogger.
+    ///
+    /// This spawns the background task sending the statements, so must be called from within a
+    /// Tokio runtime. The connection itself is only made once the first statement is logged.
+    ///
+    /// # Arguments
+    /// - `transport`: The [`Transport`] to reach the syslog endpoint with.
+    /// - `facility`: The [`Facility`] to log with.
+    /// - `app_name`: The name of the application to log as. E.g., `policy-reasoner`.
+    ///
+    /// # Returns
+    /// A new instance of self, ready for action.
+    pub fn new(transport: Transport, facility: Facility, app_name: impl Into<String>) -> Self {
+        let max_len: Option<usize> = match &transport {
+            #[cfg(unix)]
+            Transport::Unix(_) => Some(MAX_UNIX_LEN),
+            Transport::Udp(_) => Some(MAX_UDP_LEN),
+            Transport::Tcp(_) => None,
+        };
+        let (tx, rx): (Sender<Vec<u8>>, Receiver<Vec<u8>>) = mpsc::channel(BUFFER_SIZE);
+        tokio::spawn(run(transport, rx));
+        Self { facility, app_name: app_name.into(), tx, max_len, fail_closed: false, dropped: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Overrides the maximum size of a single message for datagram transports.
+    ///
+    /// This is useful if the receiving end accepts less than the transport does (e.g., a syslog
+    /// daemon limited to 8 KiB messages). Has no effect for [`Transport::Tcp`].
+    ///
+    /// # Arguments
+    /// - `max_len`: The maximum size of a message, in bytes.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_max_datagram_len(mut self, max_len: usize) -> Self {
+        if self.max_len.is_some() {
+            self.max_len = Some(max_len);
+        }
+        self
+    }
+
+    /// Determines whether statements that cannot be buffered or sent make logging fail.
+    ///
+    /// By default, such statements are dropped with a warning such that the reasoner is never
+    /// blocked by syslog. If fail-closed, logging them fails instead, which (typically) fails the
+    /// consult as well.
+    ///
+    /// # Arguments
+    /// - `fail_closed`: Whether to fail instead of dropping.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_fail_closed(mut self, fail_closed: bool) -> Self {
+        self.fail_closed = fail_closed;
+        self
+    }
+
+    /// Returns the number of statements dropped so far.
+    ///
+    /// This counts the statements dropped by this logger and all its clones, but not those
+    /// dropped by the background task because the endpoint was unreachable.
+    ///
+    /// # Returns
+    /// The number of statements that were too large or did not fit in the buffer.
+    #[inline]
+    pub fn dropped(&self) -> u64 { self.dropped.load(Ordering::Relaxed) }
+
+    /// Drops a statement, or fails if fail-closed.
+    ///
+    /// # Arguments
+    /// - `err`: The [`Error`] describing why the statement is dropped.
+    ///
+    /// # Errors
+    /// This function returns `err` if we're fail-closed.
+    fn drop_stmt(&self, err: Error) -> Result<(), Error> {
+        if self.fail_closed {
+            return Err(err);
+        }
+        let dropped: u64 = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        warn!("Dropping audit log statement ({dropped} dropped so far): {err}");
+        Ok(())
+    }
+
+    /// Formats a log statement as an RFC 5424 message.
+    ///
+    /// # Arguments
+    /// - `stmt`: The [`LogStatement`] to format.
+    ///
+    /// # Returns
+    /// The raw message, ready to be sent.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize the statement.
+    fn format(&self, stmt: &LogStatement<'_>) -> Result<Vec<u8>, Error> {
+        let reference: Option<&str> = match stmt {
+            LogStatement::Context { .. } => None,
+            LogStatement::ReasonerConsult { reference, .. }
+            | LogStatement::ReasonerResponse { reference, .. }
+            | LogStatement::Event { reference, .. } => Some(reference),
+        };
+        let message: String = serde_json::to_string(stmt).map_err(|source| Error::LogStatementSerialize { kind: format!("{stmt:?}"), source })?;
+
+        // NOTE: Severity is always 'Informational' (6)
+        let pri: u8 = (self.facility as u8) * 8 + 6;
+        let timestamp: String = Local::now().to_rfc3339_opts(SecondsFormat::Micros, false);
+        let sd: String = match reference {
+            Some(reference) => format!("[{SD_ID} reference=\"{}\"]", escape_param(reference)),
+            None => "-".into(),
+        };
+        Ok(format!("<{pri}>1 {timestamp} - {} {} {} {sd} {message}", self.app_name, std::process::id(), stmt.variant()).into_bytes())
+    }
+
+    /// Hands a log statement to the background task.
+    ///
+    /// # Arguments
+    /// - `stmt`: The [`LogStatement`] that determines what we're gonna log.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize the statement, or if the background task
+    /// has stopped. If fail-closed, it also errors if the statement is too large for the transport
+    /// or if the background task's buffer is full; otherwise, it is dropped with a warning.
+    fn log(&self, stmt: LogStatement<'_>) -> Result<(), Error> {
+        let msg: Vec<u8> = self.format(&stmt)?;
+        if let Some(max) = self.max_len.filter(|max| msg.len() > *max) {
+            return self.drop_stmt(Error::TooLarge { kind: stmt.variant().to_string(), len: msg.len(), max });
+        }
+        match self.tx.try_send(msg) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => self.drop_stmt(Error::BufferFull { kind: stmt.variant().to_string() }),
+            Err(TrySendError::Closed(_)) => Err(Error::TaskStopped { kind: stmt.variant().to_string() }),
+        }
+    }
+}
+impl AuditLogger for SyslogLogger {
+    type Error = Error;
+
+    #[inline]
+    #[instrument(name = "SyslogLogger::log_context", skip_all)]
+    async fn log_context<'a, C>(&'a self, context: &'a C) -> Result<(), Self::Error>
+    where
+        C: ?Sized + Sync + ReasonerContext,
+    {
+        let stmt: LogStatement = LogStatement::context(context).map_err(|source| Error::LogStatementSerialize { kind: "Context".into(), source })?;
+        self.log(stmt)
+    }
+
+    #[inline]
+    #[instrument(name = "SyslogLogger::log_response", skip_all, fields(reference = reference))]
+    async fn log_response<'a, R>(&'a self, reference: &'a str, response: &'a ReasonerResponse<R>, raw: Option<&'a str>) -> Result<(), Self::Error>
+    where
+        R: Sync + Display,
+    {
+        let stmt: LogStatement = LogStatement::reasoner_response(reference, response, raw)
+            .map_err(|source| Error::LogStatementSerialize { kind: "ReasonerResponse".into(), source })?;
+        self.log(stmt)
+    }
+
+    #[inline]
+    #[instrument(name = "SyslogLogger::log_question", skip_all, fields(reference = reference))]
+    async fn log_question<'a, S, Q>(&'a self, reference: &'a str, state: &'a S, question: &'a Q) -> Result<(), Self::Error>
+    where
+        S: Sync + Serialize,
+        Q: Sync + Serialize,
+    {
+        let stmt: LogStatement = LogStatement::reasoner_consult(reference, state, question)
+            .map_err(|source| Error::LogStatementSerialize { kind: "ReasonerConsult".into(), source })?;
+        self.log(stmt)
+    }
+
+    #[inline]
+    #[instrument(name = "SyslogLogger::log_event", skip_all, fields(reference = reference, tag = tag))]
+    async fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> Result<(), Self::Error>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        let stmt: LogStatement =
+            LogStatement::event(reference, tag, value).map_err(|source| Error::LogStatementSerialize { kind: "Event".into(), source })?;
+        self.log(stmt)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+
+    /// Creates a logger whose background task never runs, returning the receiving end too.
+    fn gen_logger(buffer: usize, max_len: Option<usize>) -> (SyslogLogger, Receiver<Vec<u8>>) {
+        let (tx, rx): (Sender<Vec<u8>>, Receiver<Vec<u8>>) = mpsc::channel(buffer);
+        (
+            SyslogLogger {
+                facility: Facility::Local3,
+                app_name: "test".into(),
+                tx,
+                max_len,
+                fail_closed: false,
+                dropped: Arc::new(AtomicU64::new(0)),
+            },
+            rx,
+        )
+    }
+
+
+    #[test]
+    fn test_escape_param() {
+        assert_eq!(escape_param("plain"), "plain");
+        assert_eq!(escape_param(r#"a"b\c]d"#), r#"a\"b\\c\]d"#);
+        assert_eq!(escape_param("[ok]"), "[ok\\]");
+    }
+
+    #[test]
+    fn test_format() {
+        let (logger, _rx) = gen_logger(1, None);
+
+        // Statements with a reference carry it as structured data
+        let stmt = LogStatement::Event { reference: "a\"b]".into(), tag: "trace".into(), value: Value::Bool(true) };
+        let msg: String = String::from_utf8(logger.format(&stmt).unwrap()).unwrap();
+        // NOTE: Local3 (19) * 8 + Informational (6)
+        assert!(msg.starts_with("<158>1 "), "{msg}");
+        let parts: Vec<&str> = msg.splitn(7, ' ').collect();
+        assert_eq!(parts[2], "-");
+        assert_eq!(parts[3], "test");
+        assert_eq!(parts[4], std::process::id().to_string());
+        assert_eq!(parts[5], "Event");
+        assert_eq!(parts[6], format!("[{SD_ID} reference=\"a\\\"b\\]\"] {}", serde_json::to_string(&stmt).unwrap()));
+
+        // Those without have none
+        let stmt = LogStatement::Context { context: Value::Null };
+        let msg: String = String::from_utf8(logger.format(&stmt).unwrap()).unwrap();
+        assert!(msg.ends_with(&format!(" Context - {}", serde_json::to_string(&stmt).unwrap())), "{msg}");
+    }
+
+    #[test]
+    fn test_log_drops_statements() {
+        let stmt = || LogStatement::Context { context: Value::Null };
+
+        // Too large for a datagram
+        let (logger, mut rx) = gen_logger(1, Some(16));
+        logger.log(stmt()).unwrap();
+        assert_eq!(logger.dropped(), 1);
+        assert!(rx.try_recv().is_err());
+
+        // Buffer full; clones share the counter
+        let (logger, mut rx) = gen_logger(1, None);
+        logger.log(stmt()).unwrap();
+        logger.clone().log(stmt()).unwrap();
+        assert_eq!(logger.dropped(), 1);
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+
+        // Task stopped still fails, as nothing will ever be logged again
+        let (logger, rx) = gen_logger(1, None);
+        drop(rx);
+        assert!(matches!(logger.log(stmt()), Err(Error::TaskStopped { .. })));
+    }
+
+    #[test]
+    fn test_log_fail_closed() {
+        let stmt = || LogStatement::Context { context: Value::Null };
+
+        // Too large for a datagram
+        let (logger, _rx) = gen_logger(1, Some(16));
+        let logger: SyslogLogger = logger.with_fail_closed(true);
+        assert!(matches!(logger.log(stmt()), Err(Error::TooLarge { max: 16, .. })));
+
+        // Buffer full
+        let (logger, _rx) = gen_logger(1, None);
+        let logger: SyslogLogger = logger.with_fail_closed(true);
+        logger.log(stmt()).unwrap();
+        assert!(matches!(logger.log(stmt()), Err(Error::BufferFull { .. })));
+        assert_eq!(logger.dropped(), 0);
+    }
+}
@@ -0,0 +1,32 @@
+//  TRACE.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:58:12
+//  Last edited:
+//    15 Oct 2026, 23:58:12
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Feeds arbitrary input to the [`Trace`] parser to assert it never
+//!   panics, no matter what the reasoner outputs.
+//!
+//!   Run with `cargo +nightly fuzz run trace` from the `fuzz/`-directory's
+//!   parent.
+//
+
+#![no_main]
+
+use std::str::FromStr as _;
+
+use eflint_haskell_reasoner::trace::Trace;
+use libfuzzer_sys::fuzz_target;
+
+
+/***** LIBRARY *****/
+fuzz_target!(|data: &[u8]| {
+    // NOTE: The reasoner's output is decoded lossily before parsing, so do the same here
+    let s = String::from_utf8_lossy(data);
+    let _ = Trace::from_str(&s);
+});
@@ -4,7 +4,7 @@
 //  Created:
 //    01 May 2025, 14:33:06
 //  Last edited:
-//    06 May 2025, 12:53:18
+//    16 Oct 2026, 01:39:06
 //  Auto updated?
 //    Yes
 //
@@ -13,10 +13,13 @@
 //
 
 use std::borrow::Cow;
-use std::collections::BTreeSet;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter, Result as FResult};
 use std::path::{Path, PathBuf};
 
-use sha2::{Digest, Sha256};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
 use share::formatters::PathListFormatter;
 use thiserror::Error;
 use tokio::fs::File;
@@ -42,9 +45,33 @@ pub enum Error {
 
 
 /***** HELPERS *****/
+/// Defines the parsing states.
+enum State {
+    /// Waiting for the pound `#` symbol.
+    Pound,
+    /// Waiting to see if the pound symbol is followed by `i` or `r`.
+    IncludeOrRequire,
+    /// Parsing the full `#include`- or `#require`-keyword. The number indicates the index up to which point we've already parsed.
+    Import(Import, usize),
+    /// We've parsed the keyword. Now parse the start of the path string.
+    PathStart(Import),
+    /// We're now actively parsing the path.
+    Path(Import, Vec<u8>),
+    /// We've parsed the path. Just the dot to go now.
+    Dot(Import, Vec<u8>),
+}
+
+
+
+
+
+/***** AUXILLARY *****/
 /// Represents which of the two import types we've seen.
-enum Import {
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Import {
+    /// An `#include`, which inserts the file every time it occurs.
     Include,
+    /// A `#require`, which inserts the file only if it hasn't been yet.
     Require,
 }
 impl Import {
@@ -58,20 +85,74 @@ impl Import {
     }
 }
 
-/// Defines the parsing states.
-enum State {
-    /// Waiting for the pound `#` symbol.
-    Pound,
-    /// Waiting to see if the pound symbol is followed by `i` or `r`.
-    IncludeOrRequire,
-    /// Parsing the full `#include`- or `#require`-keyword. The number indicates the index up to which point we've already parsed.
-    Import(Import, usize),
-    /// We've parsed the keyword. Now parse the start of the path string.
-    PathStart,
-    /// We're now actively parsing the path.
-    Path(Vec<u8>),
-    /// We've parsed the path. Just the dot to go now.
-    Dot(Vec<u8>),
+/// Represents how the path of an import was resolved to a file.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Resolution {
+    /// The path was absolute, so no resolving was needed.
+    Absolute,
+    /// The path was found relative to the current working directory.
+    ///
+    /// Note that this depends on where the reasoner is started from, and is thus fragile.
+    Cwd,
+    /// The path was found relative to the directory of the file given to [`find_deps()`].
+    BaseDir,
+    /// The path was found relative to the directory of the file containing the import.
+    ImporterDir,
+    /// The path was found relative to the given additional include directory.
+    IncludeDir(PathBuf),
+}
+
+/// Defines the algorithms with which [`compute_policy_hash()`] can hash policies.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    /// SHA-256. This is the default.
+    #[default]
+    Sha256,
+    /// SHA-512, e.g., for FIPS environments.
+    Sha512,
+    /// BLAKE3, which is much faster than the SHA-2 family.
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+impl Display for HashAlgorithm {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Sha512 => write!(f, "sha512"),
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => write!(f, "blake3"),
+        }
+    }
+}
+
+/// Represents the hash of a policy, tagged with the algorithm that computed it.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PolicyHash {
+    /// The algorithm used to compute the hash.
+    pub algorithm: HashAlgorithm,
+    /// The hash itself.
+    pub digest:    Vec<u8>,
+}
+impl Display for PolicyHash {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{}:", self.algorithm)?;
+        for b in &self.digest {
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes how a dependency was found by [`find_deps_detailed()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Dependency {
+    /// The kinds of imports through which the file was reached.
+    pub imports:     BTreeSet<Import>,
+    /// The ways in which those imports were resolved to the file.
+    pub resolutions: BTreeSet<Resolution>,
 }
 
 
@@ -80,7 +161,13 @@ enum State {
 
 /***** IMPLEMENTATION *****/
 /// Does the heavy-lifting of [`compute_policy_hash()`].
-async fn find_deps_of(mut handle: File, path: &Path, base_path: &Path, include_dirs: &[&Path], files: &mut BTreeSet<PathBuf>) -> Result<(), Error> {
+async fn find_deps_of(
+    mut handle: File,
+    path: &Path,
+    base_path: &Path,
+    include_dirs: &[&Path],
+    files: &mut BTreeMap<PathBuf, Dependency>,
+) -> Result<(), Error> {
     debug!("Searching for dependencies of eFLINT file {path}", path = path.display());
 
     // Go through the file chunk-by-chunk
@@ -122,7 +209,7 @@ async fn find_deps_of(mut handle: File, path: &Path, base_path: &Path, include_d
                 // Either we've 1) completed, 2) found an correct char or 3) found an incorrect char.
                 State::Import(imp, j) if j >= imp.keyword().len() => {
                     // Don't increment, this byte may be the start already
-                    state = State::PathStart;
+                    state = State::PathStart(imp);
                 },
                 State::Import(imp, j) if j < imp.keyword().len() && b == imp.keyword()[j] => {
                     i += 1;
@@ -133,81 +220,90 @@ async fn find_deps_of(mut handle: File, path: &Path, base_path: &Path, include_d
                     state = State::Pound;
                 },
 
-                State::PathStart if b == b'"' => {
+                State::PathStart(imp) if b == b'"' => {
                     i += 1;
-                    state = State::Path(Vec::with_capacity(32));
+                    state = State::Path(imp, Vec::with_capacity(32));
                 },
-                State::PathStart if (b as char).is_whitespace() => {
+                State::PathStart(imp) if (b as char).is_whitespace() => {
                     // These we skip idly. We don't even write the hash, as it's idle space AND we don't want to accidentally mix up the order if the next byte reveals we were wrong.
                     i += 1;
-                    state = State::PathStart;
+                    state = State::PathStart(imp);
                 },
-                State::PathStart => {
+                State::PathStart(_) => {
                     // Not it after all
                     state = State::Pound;
                 },
 
                 // Note: we don't escape these strings
-                State::Path(mut imppath) if b != b'"' => {
+                State::Path(imp, mut imppath) if b != b'"' => {
                     imppath.push(b);
                     i += 1;
-                    state = State::Path(imppath);
+                    state = State::Path(imp, imppath);
                 },
-                State::Path(imppath) => {
+                State::Path(imp, imppath) => {
                     // Just wait for the dot!
                     i += 1;
-                    state = State::Dot(imppath);
+                    state = State::Dot(imp, imppath);
                 },
 
-                State::Dot(imppath) if b == b'.' => {
+                State::Dot(imp, imppath) if b == b'.' => {
                     // We've successfully parsed a chunk! Let's get the path as a path
                     let imppath: Cow<str> = String::from_utf8_lossy(&imppath);
                     let imppath: &Path = <str as AsRef<Path>>::as_ref(imppath.as_ref());
 
                     // Attempt to resolve the path if it's relative
-                    let imppaths: Vec<Cow<Path>> = if imppath.is_relative() {
+                    let imppaths: Vec<(Resolution, Cow<Path>)> = if imppath.is_relative() {
                         let mut imppaths = Vec::with_capacity(3 + include_dirs.len());
-                        imppaths.push(Cow::Owned(std::env::current_dir().map_err(|source| Error::GetCwd { source })?.join(imppath)));
+                        imppaths
+                            .push((Resolution::Cwd, Cow::Owned(std::env::current_dir().map_err(|source| Error::GetCwd { source })?.join(imppath))));
                         if let Some(parent) = base_path.parent() {
-                            imppaths.push(Cow::Owned(parent.join(imppath)));
+                            imppaths.push((Resolution::BaseDir, Cow::Owned(parent.join(imppath))));
                         }
                         if let Some(parent) = path.parent() {
-                            imppaths.push(Cow::Owned(parent.join(imppath)));
+                            imppaths.push((Resolution::ImporterDir, Cow::Owned(parent.join(imppath))));
                         }
                         for dir in include_dirs {
-                            imppaths.push(Cow::Owned(dir.join(imppath)));
+                            imppaths.push((Resolution::IncludeDir(dir.to_path_buf()), Cow::Owned(dir.join(imppath))));
                         }
                         imppaths
                     } else {
-                        vec![Cow::Borrowed(imppath)]
+                        vec![(Resolution::Absolute, Cow::Borrowed(imppath))]
                     };
 
                     // Recurse & then update the dependency list
                     let mut found: bool = false;
-                    for imppath in &imppaths {
+                    for (resolution, imppath) in &imppaths {
                         // Ensure it exists
                         if !imppath.exists() {
                             continue;
                         }
                         found = true;
 
-                        // If we haven't added it yet, add it
-                        if files.insert(imppath.clone().into_owned()) {
-                            Box::pin(find_deps_of(
-                                File::open(imppath).await.map_err(|source| Error::FileOpen { path: imppath.clone().into_owned(), source })?,
-                                imppath.as_ref(),
-                                base_path,
-                                include_dirs,
-                                files,
-                            ))
-                            .await?;
+                        // If we haven't added it yet, add it; otherwise, only remember how we got here
+                        match files.entry(imppath.clone().into_owned()) {
+                            Entry::Vacant(entry) => {
+                                entry.insert(Dependency { imports: BTreeSet::from([imp]), resolutions: BTreeSet::from([resolution.clone()]) });
+                                Box::pin(find_deps_of(
+                                    File::open(imppath).await.map_err(|source| Error::FileOpen { path: imppath.clone().into_owned(), source })?,
+                                    imppath.as_ref(),
+                                    base_path,
+                                    include_dirs,
+                                    files,
+                                ))
+                                .await?;
+                            },
+                            Entry::Occupied(mut entry) => {
+                                let dep: &mut Dependency = entry.get_mut();
+                                dep.imports.insert(imp);
+                                dep.resolutions.insert(resolution.clone());
+                            },
                         }
                         break;
                     }
                     if !found {
                         return Err(Error::ImportNotFound {
                             path:     imppath.into(),
-                            imppaths: imppaths.into_iter().map(Cow::into_owned).collect(),
+                            imppaths: imppaths.into_iter().map(|(_, imppath)| imppath.into_owned()).collect(),
                         });
                     }
 
@@ -215,12 +311,12 @@ async fn find_deps_of(mut handle: File, path: &Path, base_path: &Path, include_d
                     i += 1;
                     state = State::Pound;
                 },
-                State::Dot(imppath) if (b as char).is_whitespace() => {
+                State::Dot(imp, imppath) if (b as char).is_whitespace() => {
                     // Idly skip
                     i += 1;
-                    state = State::Dot(imppath);
+                    state = State::Dot(imp, imppath);
                 },
-                State::Dot(_) => {
+                State::Dot(_, _) => {
                     // Not it AFTER ALL
                     state = State::Pound;
                 },
@@ -251,12 +347,38 @@ async fn find_deps_of(mut handle: File, path: &Path, base_path: &Path, include_d
 /// # Errors
 /// This function may error if we failed to open the given `path` as a file, or failed to find any
 /// of the (recursive) dependencies.
-#[instrument(skip_all, fields(file=%path.as_ref().display()))]
+#[inline]
 pub async fn find_deps(path: impl AsRef<Path>, include_dirs: &[&Path]) -> Result<BTreeSet<PathBuf>, Error> {
+    Ok(find_deps_detailed(path, include_dirs).await?.into_keys().collect())
+}
+
+/// Finds all dependencies of the given eFLINT file, including how they were found.
+///
+/// This works like [`find_deps()`], but additionally records for every file which kind(s) of
+/// [`Import`] reached it and how their paths were [resolved](Resolution). This can be used to,
+/// e.g., warn about policies that rely on the current working directory to find their imports.
+///
+/// Note that files are only searched once, even if imported multiple times.
+///
+/// # Arguments
+/// - `path`: The path to the eFLINT file to search (which, in turn, specifies the dependencies).
+/// - `include_dirs`: Any additional include directories to use for the search. By default, the
+///   current working directory, the directory of the given file and the directory of the currently
+///   recursed file are included.
+///
+/// # Returns
+/// A [`BTreeMap`] mapping the path of every found file to the [`Dependency`] describing how it was
+/// found.
+///
+/// # Errors
+/// This function may error if we failed to open the given `path` as a file, or failed to find any
+/// of the (recursive) dependencies.
+#[instrument(skip_all, fields(file=%path.as_ref().display()))]
+pub async fn find_deps_detailed(path: impl AsRef<Path>, include_dirs: &[&Path]) -> Result<BTreeMap<PathBuf, Dependency>, Error> {
     let path: &Path = path.as_ref();
 
     // Delegate to the recursive function
-    let mut res = BTreeSet::new();
+    let mut res = BTreeMap::new();
     find_deps_of(File::open(path).await.map_err(|source| Error::FileOpen { path: path.into(), source })?, path, path, include_dirs, &mut res).await?;
 
     // Done
@@ -275,18 +397,49 @@ pub async fn find_deps(path: impl AsRef<Path>, include_dirs: &[&Path]) -> Result
 ///   current working directory, the directory of the given file and the directory of the currently
 ///   recursed file are included.
 ///
+/// - `algorithm`: The [`HashAlgorithm`] to hash with.
+///
+/// # Returns
+/// The [`PolicyHash`] of the policy.
+///
+/// # Errors
+/// This function may error if we failed to open the given `path` as a file, or failed to find any
+/// of the (recursive) dependencies.
+pub async fn compute_policy_hash(path: impl AsRef<Path>, include_dirs: &[&Path], algorithm: HashAlgorithm) -> Result<PolicyHash, Error> {
+    let digest: Vec<u8> = match algorithm {
+        HashAlgorithm::Sha256 => compute_policy_hash_with::<Sha256>(path, include_dirs).await?,
+        HashAlgorithm::Sha512 => compute_policy_hash_with::<Sha512>(path, include_dirs).await?,
+        #[cfg(feature = "blake3")]
+        HashAlgorithm::Blake3 => compute_policy_hash_with::<blake3::Hasher>(path, include_dirs).await?,
+    };
+    Ok(PolicyHash { algorithm, digest })
+}
+
+/// Recursively computes the hash of the given eFLINT file with any [`Digest`].
+///
+/// This is like [`compute_policy_hash()`], but for algorithms not covered by [`HashAlgorithm`].
+///
+/// # Generics
+/// - `D`: The [`Digest`] to hash with.
+///
+/// # Arguments
+/// - `path`: The path to the eFLINT file to hash (which, in turn, specifies the dependencies).
+/// - `include_dirs`: Any additional include directories to use for the search. By default, the
+///   current working directory, the directory of the given file and the directory of the currently
+///   recursed file are included.
+///
 /// # Returns
-/// The hash of the policy, as a 256-bit byte array.
+/// The raw hash of the policy.
 ///
 /// # Errors
 /// This function may error if we failed to open the given `path` as a file, or failed to find any
 /// of the (recursive) dependencies.
-pub async fn compute_policy_hash(path: impl AsRef<Path>, include_dirs: &[&Path]) -> Result<[u8; 32], Error> {
+pub async fn compute_policy_hash_with<D: Digest>(path: impl AsRef<Path>, include_dirs: &[&Path]) -> Result<Vec<u8>, Error> {
     // Find the set of all files first
     let files = find_deps(path, include_dirs).await?;
 
     // Now hash all of the files
-    let mut hasher = Sha256::new();
+    let mut hasher = D::new();
     for file in files {
         // Open the file
         debug!("Hashing eFLINT file {}", file.display());
@@ -295,11 +448,14 @@ pub async fn compute_policy_hash(path: impl AsRef<Path>, include_dirs: &[&Path])
     }
 
     // Done
-    Ok(hasher.finalize().into())
+    Ok(hasher.finalize().to_vec())
 }
 
+/// The size of the chunks in which files are read while hashing them.
+const HASH_CHUNK_SIZE: usize = 1 << 14;
+
 async fn hash_async_reader(hasher: &mut impl Digest, mut reader: impl AsyncRead + Unpin) -> std::io::Result<()> {
-    let mut buf = [0_u8; 2 ^ 14];
+    let mut buf = [0_u8; HASH_CHUNK_SIZE];
     loop {
         // Read a chunk
         let buf_len: usize = reader.read(&mut buf).await?;
@@ -315,10 +471,28 @@ async fn hash_async_reader(hasher: &mut impl Digest, mut reader: impl AsyncRead
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use tempfile::TempDir;
+    use tokio::io::ReadBuf;
 
     use super::*;
 
 
+    /// Wraps an [`AsyncRead`] to count how often it's read from.
+    struct CountingReader<R> {
+        inner: R,
+        reads: usize,
+    }
+    impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R> {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+            self.reads += 1;
+            Pin::new(&mut self.inner).poll_read(cx, buf)
+        }
+    }
+
+
     #[tokio::test]
     async fn test_hash_empty_reader() {
         let mut hasher = Sha256::new();
@@ -384,4 +558,57 @@ mod tests {
         let expected = hex::decode("f3f59afe21c99e2471439dbdda0cf583f296ebe6995f8e3d3dac23a87d6afe78").unwrap();
         assert_eq!(hash.as_slice(), expected.as_slice());
     }
+
+    #[tokio::test]
+    async fn test_hash_read_count() {
+        let mut hasher = Sha256::new();
+        // 4 MiB, which should take 256 full chunks (and one more read to find the end)
+        let data = vec![0x42u8; 4 * 1024 * 1024];
+        let mut reader = CountingReader { inner: Cursor::new(data.clone()), reads: 0 };
+
+        hash_async_reader(&mut hasher, &mut reader).await.unwrap();
+        assert_eq!(reader.reads, data.len() / HASH_CHUNK_SIZE + 1);
+        assert_eq!(hasher.finalize(), Sha256::digest(&data));
+    }
+
+    #[tokio::test]
+    async fn test_find_deps_detailed() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("main.eflint"), "#require \"lib/a.eflint\".\n#include \"lib/a.eflint\".\n#include \"b.eflint\".\n").unwrap();
+        std::fs::write(dir.join("lib/a.eflint"), "#require \"b.eflint\".\n").unwrap();
+        std::fs::write(dir.join("lib/b.eflint"), "").unwrap();
+        std::fs::write(dir.join("b.eflint"), "").unwrap();
+
+        let deps: BTreeMap<PathBuf, Dependency> = find_deps_detailed(dir.join("main.eflint"), &[]).await.unwrap();
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[&dir.join("lib/a.eflint")], Dependency {
+            imports:     BTreeSet::from([Import::Include, Import::Require]),
+            resolutions: BTreeSet::from([Resolution::BaseDir]),
+        });
+        // NOTE: The base directory is tried before the importer's directory
+        assert_eq!(deps[&dir.join("b.eflint")], Dependency {
+            imports:     BTreeSet::from([Import::Include, Import::Require]),
+            resolutions: BTreeSet::from([Resolution::BaseDir]),
+        });
+        assert!(!deps.contains_key(&dir.join("lib/b.eflint")));
+        assert_eq!(find_deps(dir.join("main.eflint"), &[]).await.unwrap(), deps.keys().cloned().collect());
+    }
+
+    #[tokio::test]
+    async fn test_compute_policy_hash_algorithms() {
+        let tmp: TempDir = TempDir::new().unwrap();
+        let dir: PathBuf = tmp.path().into();
+        std::fs::write(dir.join("main.eflint"), "#require \"a.eflint\".\n").unwrap();
+        std::fs::write(dir.join("a.eflint"), "hello world").unwrap();
+
+        let hash: PolicyHash = compute_policy_hash(dir.join("main.eflint"), &[], HashAlgorithm::default()).await.unwrap();
+        assert_eq!(hash.algorithm, HashAlgorithm::Sha256);
+        assert_eq!(hash.digest, compute_policy_hash_with::<Sha256>(dir.join("main.eflint"), &[]).await.unwrap());
+        assert_eq!(hash.to_string(), format!("sha256:{}", hex::encode(&hash.digest)));
+        let hash: PolicyHash = compute_policy_hash(dir.join("main.eflint"), &[], HashAlgorithm::Sha512).await.unwrap();
+        assert_eq!(hash.digest.len(), 64);
+        assert_eq!(hash.digest, compute_policy_hash_with::<Sha512>(dir.join("main.eflint"), &[]).await.unwrap());
+    }
 }
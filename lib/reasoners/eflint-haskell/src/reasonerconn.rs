@@ -4,7 +4,7 @@
 //  Created:
 //    16 Apr 2025, 23:09:26
 //  Last edited:
-//    06 May 2025, 12:53:34
+//    16 Oct 2026, 02:29:42
 //  Auto updated?
 //    Yes
 //
@@ -15,33 +15,52 @@
 
 use std::borrow::Cow;
 use std::marker::PhantomData;
-use std::path::PathBuf;
-use std::process::{ExitStatus, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output, Stdio};
 use std::str::FromStr as _;
+use std::time::Duration;
 
 use error_trace::ErrorTrace as _;
+use futures_util::future::try_join4;
+use futures_util::{StreamExt as _, stream};
 use serde::{Deserialize, Serialize};
 use share::formatters::BlockFormatter;
 use spec::auditlogger::SessionedAuditLogger;
 use spec::reasonerconn::{ReasonerContext, ReasonerResponse};
 use spec::{AuditLogger, ReasonerConnector};
 use thiserror::Error;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
 use tokio::process::Command;
-use tracing::{debug, warn};
+use tracing::{debug, instrument, warn};
 
-use crate::hash::compute_policy_hash;
-use crate::reasons::{Problem, ReasonHandler};
+use crate::hash::{HashAlgorithm, PolicyHash, compute_policy_hash};
+use crate::reasons::{Explanation, Problem, ReasonHandler};
 use crate::spec::{EFlintable, EFlintableExt as _};
-use crate::trace::{Delta, Trace};
+use crate::trace::{Delta, ParseDiagnostic, Trace, strip_repl_prompts};
+
+/***** CONSTANTS *****/
+/// The maximum number of bytes of the spec submitted to the reasoner that is included in errors.
+const MAX_ERROR_SPEC_LEN: usize = 16 * 1024;
+
+/// The default number of reasoner processes that [`ReasonerConnector::consult_questions()`] runs at
+/// the same time.
+const DEFAULT_MAX_CONCURRENT_QUESTIONS: usize = 4;
+
+
+
+
 
 /***** ERRORS *****/
 /// Defines errors originating from the [`EFlintHaskellReasonerConnector`].
 #[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
 pub enum Error {
     /// Failed to log the context of the reasoner.
     #[error("Failed to log the reasoner's context to {to}")]
     LogContext { to: &'static str, source: error_trace::Trace },
+    /// Failed to log an event (e.g., the reasoner's trace) to the given logger.
+    #[error("Failed to log {tag:?}-event to {to}")]
+    LogEvent { to: &'static str, tag: &'static str, source: error_trace::Trace },
     /// Failed to log the question to the given logger.
     #[error("Failed to log the question to {to}")]
     LogQuestion { to: &'static str, source: error_trace::Trace },
@@ -51,6 +70,12 @@ pub enum Error {
 
     #[error("Empty REPL-command given")]
     EmptyReplCommand,
+    /// The base policy file does not exist (anymore).
+    #[error("Base policy {path} is not a file", path = path.display())]
+    BasePolicyMissing { path: PathBuf },
+    /// The program to run the reasoner with is not an executable file.
+    #[error("Reasoner command {exec:?} is not an executable file (also not on the PATH)")]
+    ReplCommandNotExecutable { exec: String },
 
     #[error("Failed to spawn command {cmd:?}")]
     CommandSpawn { cmd: Command, source: std::io::Error },
@@ -58,15 +83,99 @@ pub enum Error {
     CommandStdinWrite { source: std::io::Error },
     #[error("Failed to wait for command {cmd:?} to complete")]
     CommandJoin { cmd: Command, source: std::io::Error },
+    #[error("Command {cmd:?} did not complete within {after:?}")]
+    Timeout { cmd: Command, after: Duration },
     #[error(
-        "Command {cmd:?} failed with exit code {code}\n\n{stdout}\n\n{stderr}",
+        "Command {cmd:?} failed with exit code {code}\n\n{stdout}\n\n{stderr}\n\n{spec}",
         code = status.code().unwrap_or(-1),
         stdout = BlockFormatter::new("stdout:", stdout),
-        stderr = BlockFormatter::new("stderr:", stderr)
+        stderr = BlockFormatter::new("stderr:", stderr),
+        spec = BlockFormatter::new("spec:", spec)
     )]
-    CommandFailure { cmd: Command, status: ExitStatus, stdout: String, stderr: String },
-    #[error("Failed to parse reasoner output\n{output}", output = BlockFormatter::new("stdout:", output))]
-    IllegalReasonerResponse { output: String, source: crate::trace::Error },
+    CommandFailure { cmd: Command, status: ExitStatus, stdout: String, stderr: String, spec: String },
+    #[error(
+        "Failed to parse reasoner output\n{output}\n\n{spec}",
+        output = BlockFormatter::new("stdout:", source.input()),
+        spec = BlockFormatter::new("spec:", spec)
+    )]
+    IllegalReasonerResponse {
+        spec:   String,
+        #[cfg_attr(feature = "miette", diagnostic_source)]
+        source: crate::trace::ParseDiagnostic,
+    },
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Bounds the spec submitted to the reasoner to include it in an error.
+///
+/// # Arguments
+/// - `spec`: The full spec submitted to the reasoner.
+///
+/// # Returns
+/// The first [`MAX_ERROR_SPEC_LEN`] bytes of the `spec` (rounded down to a character boundary),
+/// with a note of how much was omitted if anything.
+fn bounded_spec(spec: &str) -> String {
+    if spec.len() <= MAX_ERROR_SPEC_LEN {
+        return spec.into();
+    }
+    let mut end: usize = MAX_ERROR_SPEC_LEN;
+    while !spec.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n<{} more bytes omitted>", &spec[..end], spec.len() - end)
+}
+
+/// Checks whether the given program can be executed.
+///
+/// # Arguments
+/// - `exec`: The program to check. If it is a bare name, it is searched for on the `PATH`, like
+///   [`Command`] does.
+///
+/// # Returns
+/// True if `exec` (or any match on the `PATH`) is a file that is executable by someone.
+async fn is_executable(exec: &str) -> bool {
+    // Find the candidates
+    let exec: &Path = Path::new(exec);
+    let candidates: Vec<PathBuf> = if exec.components().count() > 1 {
+        vec![exec.into()]
+    } else {
+        std::env::var_os("PATH").map(|path| std::env::split_paths(&path).map(|dir| dir.join(exec)).collect()).unwrap_or_default()
+    };
+
+    // See if any of them is executable
+    for candidate in candidates {
+        let Ok(md) = tokio::fs::metadata(&candidate).await else { continue };
+        #[cfg(unix)]
+        if md.is_file() && std::os::unix::fs::PermissionsExt::mode(&md.permissions()) & 0o111 != 0 {
+            return true;
+        }
+        #[cfg(not(unix))]
+        if md.is_file() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Writes the trace produced by the reasoner to the audit log, such that the reasoning can be
+/// inspected after the fact.
+///
+/// # Arguments
+/// - `logger`: The [`SessionedAuditLogger`] to write the trace to.
+/// - `trace`: The [`Trace`] to write.
+///
+/// # Errors
+/// This function errors if we failed to write to the `logger`.
+async fn log_trace<L: AuditLogger>(logger: &SessionedAuditLogger<L>, trace: &Trace) -> Result<(), Error> {
+    logger.log_event("trace", trace).await.map_err(|err| Error::LogEvent {
+        to:     std::any::type_name::<SessionedAuditLogger<L>>(),
+        tag:    "trace",
+        source: err.freeze(),
+    })
 }
 
 
@@ -88,7 +197,7 @@ pub struct EFlintHaskellReasonerContext {
     /// The version identifier of the language targeted by this reasoner.
     pub language_version: String,
     /// A hash of the base policy calculated at construction time.
-    pub base_policy_hash: [u8; 32],
+    pub base_policy_hash: PolicyHash,
 }
 impl ReasonerContext for EFlintHaskellReasonerContext {
     #[inline]
@@ -114,6 +223,8 @@ pub struct EFlintHaskellReasonerContextFull {
     pub cmd: (String, Vec<String>),
     /// The base policy to provide to the eFLINT reasoner.
     pub base_policy: PathBuf,
+    /// The maximum time a single consult may take before the reasoner is killed, if any.
+    pub timeout: Option<Duration>,
 }
 impl ReasonerContext for EFlintHaskellReasonerContextFull {
     #[inline]
@@ -138,6 +249,9 @@ pub struct EFlintHaskellReasonerConnector<R, S, Q> {
     context: EFlintHaskellReasonerContextFull,
     /// A handler for determining what kind of reasons to give back to the user.
     handler: R,
+    /// The maximum number of reasoner processes to run at the same time when asking many
+    /// questions at once.
+    max_concurrent_questions: usize,
 
     /// For us to remember the state we're configured for.
     _state:    PhantomData<S>,
@@ -153,6 +267,9 @@ impl<R, S, Q> EFlintHaskellReasonerConnector<R, S, Q> {
     ///   this as a file instead of a string since that is MUCH more efficient than feeding large
     ///   files to eFLINT by pipe.
     /// - `handler`: Some [`ReasonHandler`] that can be used to determine what information to return to the user upon failure.
+    /// - `timeout`: The maximum time a single consult may take before the reasoner is killed, or
+    ///   [`None`] to wait indefinitely.
+    /// - `hash_algorithm`: The [`HashAlgorithm`] used to hash the base policy for the context.
     /// - `logger`: An [`AuditLogger`] for logging the reasoning context with.
     ///
     /// # Returns
@@ -164,6 +281,8 @@ impl<R, S, Q> EFlintHaskellReasonerConnector<R, S, Q> {
         cmd: impl IntoIterator<Item = String>,
         base_policy_path: impl Into<PathBuf>,
         handler: R,
+        timeout: Option<Duration>,
+        hash_algorithm: HashAlgorithm,
         logger: &L,
     ) -> Result<Self, Error> {
         let base_policy: PathBuf = base_policy_path.into();
@@ -174,8 +293,8 @@ impl<R, S, Q> EFlintHaskellReasonerConnector<R, S, Q> {
         let cmd: (String, Vec<String>) = (exec.ok_or(Error::EmptyReplCommand)?, cmd);
 
         // Compute the hash of the input policy
-        let base_policy_hash: [u8; 32] =
-            compute_policy_hash(&base_policy, &[]).await.map_err(|source| Error::PolicyHash { path: base_policy.clone(), source })?;
+        let base_policy_hash: PolicyHash =
+            compute_policy_hash(&base_policy, &[], hash_algorithm).await.map_err(|source| Error::PolicyHash { path: base_policy.clone(), source })?;
 
         // Build the context & log it
         let context: EFlintHaskellReasonerContextFull = EFlintHaskellReasonerContextFull {
@@ -188,11 +307,28 @@ impl<R, S, Q> EFlintHaskellReasonerConnector<R, S, Q> {
             },
             cmd,
             base_policy,
+            timeout,
         };
         logger.log_context(&context).await.map_err(|err| Error::LogContext { to: std::any::type_name::<L>(), source: err.freeze() })?;
 
         // OK, return ourselves
-        Ok(Self { context, handler, _state: PhantomData, _question: PhantomData })
+        Ok(Self { context, handler, max_concurrent_questions: DEFAULT_MAX_CONCURRENT_QUESTIONS, _state: PhantomData, _question: PhantomData })
+    }
+
+    /// Sets the maximum number of reasoner processes that
+    /// [`ReasonerConnector::consult_questions()`] runs at the same time.
+    ///
+    /// Defaults to 4.
+    ///
+    /// # Arguments
+    /// - `max`: The maximum number of processes. Values below 1 are treated as 1.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_max_concurrent_questions(mut self, max: usize) -> Self {
+        self.max_concurrent_questions = max.max(1);
+        self
     }
 
     /// Returns the command used to call the `eflint-repl` binary.
@@ -212,28 +348,108 @@ impl<R, S, Q> EFlintHaskellReasonerConnector<R, S, Q> {
     #[inline]
     pub const fn base_policy(&self) -> &PathBuf { &self.context.base_policy }
 }
-impl<R, S, Q> ReasonerConnector for EFlintHaskellReasonerConnector<R, S, Q>
+impl<R: ReasonHandler, S, Q> EFlintHaskellReasonerConnector<R, S, Q> {
+    /// Derives the verdict from the trace of the reasoner.
+    ///
+    /// The rule is:
+    /// 1. Check the last delta
+    ///    a. If it's a query, then it must succeed; or
+    ///    b. If it's a violation, then it's a violation; or
+    ///    c. If it's anything else, the policy did not decide and the verdict is indeterminate.
+    /// 2. If there is no last delta, then we default to **success**.
+    ///
+    /// Violations are downgraded to successes if the handler deems their reasons non-blocking.
+    ///
+    /// # Arguments
+    /// - `trace`: The [`Trace`] produced by the reasoner.
+    /// - `locale`: The language in which the question's asker would prefer to receive reasons, if
+    ///   any.
+    ///
+    /// # Returns
+    /// The [`ReasonerResponse`] that the trace represents.
+    fn verdict(&self, trace: &Trace, locale: Option<&str>) -> ReasonerResponse<R::Reason> {
+        let problems: Vec<Problem> = trace
+            .deltas
+            .iter()
+            .filter_map(|delta| match delta {
+                Delta::Query(query) if !query.is_success() => Some(Problem::QueryFailed),
+                Delta::Violation(viol) => Some(Problem::Violation(viol.clone())),
+                _ => None,
+            })
+            .collect();
+        let res: ReasonerResponse<R::Reason> = trace
+            .deltas
+            .iter()
+            .next_back()
+            .map(|delta| match delta {
+                Delta::Query(query) if query.is_success() => ReasonerResponse::Success,
+                Delta::Query(_) => ReasonerResponse::Violated(self.handler.handle_localized(problems, locale)),
+                Delta::Violation(_) => ReasonerResponse::Violated(self.handler.handle_localized(problems, locale)),
+                delta => {
+                    warn!("Got non-query, non-violation delta as last delta ({delta:?}); verdict is indeterminate");
+                    ReasonerResponse::Indeterminate { reasons: self.handler.handle_localized(problems, locale) }
+                },
+            })
+            .unwrap_or(ReasonerResponse::Success);
+
+        // Let the handler decide whether the reasons are worth blocking over
+        match res {
+            ReasonerResponse::Violated(reason) if !self.handler.is_blocking(&reason) => {
+                warn!("Allowing despite non-blocking reasons: {reason}");
+                ReasonerResponse::Success
+            },
+            res => res,
+        }
+    }
+}
+impl<R, S, Q> EFlintHaskellReasonerConnector<R, S, Q>
 where
     R: Sync + ReasonHandler,
     S: Send + Sync + EFlintable + Serialize,
     Q: Send + Sync + EFlintable + Serialize,
 {
-    type Context = EFlintHaskellReasonerContext;
-    type Error = Error;
-    type Question = Q;
-    type Reason = R::Reason;
-    type State = S;
-
-    #[inline]
-    fn context(&self) -> Self::Context { self.context.public.clone() }
+    /// Consults the reasoner like [`ReasonerConnector::consult()`], but also explains positive
+    /// verdicts.
+    ///
+    /// # Arguments
+    /// - `state`: The state to reason about.
+    /// - `question`: The question to ask about the `state`.
+    /// - `logger`: A [`SessionedAuditLogger`] to write the question to.
+    ///
+    /// # Returns
+    /// The reasoner's verdict, paired with an [`Explanation`] of which permitting rules applied if
+    /// (and only if) that verdict is [`ReasonerResponse::Success`].
+    ///
+    /// # Errors
+    /// This function errors in the same cases as [`ReasonerConnector::consult()`].
+    #[instrument(name = "EFlintHaskellReasonerConnector::consult_explain", skip_all, fields(reference = logger.reference()))]
+    pub async fn consult_explain<L>(
+        &self,
+        state: S,
+        question: Q,
+        logger: &SessionedAuditLogger<L>,
+    ) -> Result<(ReasonerResponse<R::Reason>, Option<Explanation>), Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        let (res, trace): (ReasonerResponse<R::Reason>, Trace) = self.reason(state, question, logger).await?;
+        let explanation: Option<Explanation> = matches!(res, ReasonerResponse::Success).then(|| Explanation::from_trace(&trace));
+        Ok((res, explanation))
+    }
 
-    #[inline]
-    async fn consult<'a, L>(
-        &'a self,
-        state: Self::State,
-        question: Self::Question,
-        logger: &'a SessionedAuditLogger<L>,
-    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+    /// Runs the reasoner on the given state and question.
+    ///
+    /// # Arguments
+    /// - `state`: The state to reason about.
+    /// - `question`: The question to ask about the `state`.
+    /// - `logger`: A [`SessionedAuditLogger`] to write the question and the reasoner's trace to.
+    ///
+    /// # Returns
+    /// The reasoner's verdict, together with the full [`Trace`] it was derived from.
+    ///
+    /// # Errors
+    /// This function errors if we failed to log, run the reasoner or parse its output.
+    async fn reason<L>(&self, state: S, question: Q, logger: &SessionedAuditLogger<L>) -> Result<(ReasonerResponse<R::Reason>, Trace), Error>
     where
         L: Sync + AuditLogger,
     {
@@ -244,6 +460,24 @@ where
 
         // Prepare the full file to send
         let spec: String = format!("{}{}", state.eflint(), question.eflint());
+        let (res, trace): (ReasonerResponse<R::Reason>, Trace) = self.run(spec, question.locale()).await?;
+        log_trace(logger, &trace).await?;
+        Ok((res, trace))
+    }
+
+    /// Runs the reasoner on the given, already serialized state and question.
+    ///
+    /// # Arguments
+    /// - `spec`: The eFLINT of the state and question to submit to the reasoner.
+    /// - `locale`: The language in which the question's asker would prefer to receive reasons, if
+    ///   any.
+    ///
+    /// # Returns
+    /// The reasoner's verdict, together with the full [`Trace`] it was derived from.
+    ///
+    /// # Errors
+    /// This function errors if we failed to run the reasoner or parse its output.
+    async fn run(&self, spec: String, locale: Option<&str>) -> Result<(ReasonerResponse<R::Reason>, Trace), Error> {
         debug!("{}", BlockFormatter::new("Full spec to submit to reasoner:", &spec));
 
         // Prepare the command to execute
@@ -261,16 +495,41 @@ where
             Ok(handle) => handle,
             Err(source) => return Err(Error::CommandSpawn { cmd, source }),
         };
-        handle
-            .stdin
-            .as_mut()
-            .expect("No stdin on subprocess even though it's piped!")
-            .write_all(spec.as_bytes())
-            .await
-            .map_err(|source| Error::CommandStdinWrite { source })?;
-        debug!("Inputs submitted, waiting for reasoner to complete...");
-        let output = match handle.wait_with_output().await {
-            Ok(handle) => handle,
+        let mut stdin = handle.stdin.take().expect("No stdin on subprocess even though it's piped!");
+        let mut stdout = handle.stdout.take().expect("No stdout on subprocess even though it's piped!");
+        let mut stderr = handle.stderr.take().expect("No stderr on subprocess even though it's piped!");
+
+        // NOTE: We write the spec while reading the output, such that neither blocks on a full
+        // pipe buffer and a reasoner that doesn't read its input is still subject to the timeout.
+        // Closing stdin afterwards tells the reasoner the spec is complete.
+        let (mut stdout_buf, mut stderr_buf): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
+        let input: &[u8] = spec.as_bytes();
+        let write = async move {
+            let res: Result<(), std::io::Error> = match stdin.write_all(input).await {
+                Ok(()) => stdin.shutdown().await,
+                Err(err) => Err(err),
+            };
+            // NOTE: Any failure is only reported once the reasoner completed, since its exit
+            // status tells us more if it closed its input early
+            Ok::<Result<(), std::io::Error>, std::io::Error>(res)
+        };
+        let wait = try_join4(write, handle.wait(), stdout.read_to_end(&mut stdout_buf), stderr.read_to_end(&mut stderr_buf));
+        let res: Result<(Result<(), std::io::Error>, ExitStatus, usize, usize), std::io::Error> = match self.context.timeout {
+            Some(after) => match tokio::time::timeout(after, wait).await {
+                Ok(res) => res,
+                Err(_) => {
+                    // NOTE: `kill()` also waits for the child, so it doesn't linger as a zombie
+                    warn!("Reasoner did not complete within {after:?}, killing it...");
+                    if let Err(err) = handle.kill().await {
+                        warn!("{}", err.trace());
+                    }
+                    return Err(Error::Timeout { cmd, after });
+                },
+            },
+            None => wait.await,
+        };
+        let (written, output): (Result<(), std::io::Error>, Output) = match res {
+            Ok((written, status, _, _)) => (written, Output { status, stdout: stdout_buf, stderr: stderr_buf }),
             Err(source) => return Err(Error::CommandJoin { cmd, source }),
         };
         if !output.status.success() {
@@ -279,88 +538,217 @@ where
                 status: output.status,
                 stdout: String::from_utf8_lossy(&output.stdout).into(),
                 stderr: String::from_utf8_lossy(&output.stderr).into(),
+                spec: bounded_spec(&spec),
             });
         }
+        written.map_err(|source| Error::CommandStdinWrite { source })?;
 
         // Stript the prompts from the eFLINT output
-        let output: Cow<str> = String::from_utf8_lossy(&output.stdout);
-        let mut clean_output: String = String::with_capacity(output.len());
-        let mut buf: String = String::new();
-        let mut state: usize = 0;
-        for c in output.chars() {
-            // Loop exists to be able to examine some chars again
-            loop {
-                match state {
-                    // Finding pounds
-                    0 if c == '#' => {
-                        buf.push('#');
-                        state = 1;
-                        break;
-                    },
-                    0 => {
-                        clean_output.push(c);
-                        break;
-                    },
-
-                    // Parsing numbers & whitespace
-                    1 if c.is_ascii_digit() || c.is_whitespace() => {
-                        buf.push(c);
-                        break;
-                    },
-                    1 if c == '>' => {
-                        state = 0;
-                        break;
-                    },
-                    1 => {
-                        clean_output.push_str(&buf);
-                        buf.clear();
-                        state = 0;
-                        // Don't break, re-try this character
-                    },
-
-                    _ => unreachable!(),
-                }
-            }
-        }
+        let clean_output: String = strip_repl_prompts(&String::from_utf8_lossy(&output.stdout));
 
         // Attempt to parse the output
         debug!("{}", BlockFormatter::new("Reasoner output:", &clean_output));
         let trace: Trace = match Trace::from_str(clean_output.as_ref()) {
             Ok(trace) => trace,
-            Err(source) => return Err(Error::IllegalReasonerResponse { output: clean_output, source }),
+            Err(err) => {
+                return Err(Error::IllegalReasonerResponse { spec: bounded_spec(&spec), source: ParseDiagnostic::new(clean_output, err) });
+            },
         };
         debug!("{}", BlockFormatter::new("Reasoner trace:", &trace));
 
         // Analyze the output to find violations
-        // The rule is:
-        // 1. Check the last delta
-        //    a. If it's a query, then it must succeed; or
-        //    b. If it's not a query, it must not be a violation.
-        // 2. If there is no last delta, then we default to **success**.
-        let problems: Vec<Problem> = trace
-            .deltas
-            .iter()
-            .filter_map(|delta| match delta {
-                Delta::Query(query) if query.is_success() => Some(Problem::QueryFailed),
-                Delta::Violation(viol) => Some(Problem::Violation(viol.clone())),
-                _ => None,
-            })
-            .collect();
-        let res: ReasonerResponse<R::Reason> = trace
-            .deltas
-            .into_iter()
-            .next_back()
-            .map(|delta| match delta {
-                Delta::Query(query) if query.is_success() => ReasonerResponse::Success,
-                Delta::Query(_) => ReasonerResponse::Violated(self.handler.handle(problems)),
-                Delta::Violation(_) => ReasonerResponse::Violated(self.handler.handle(problems)),
-                delta => {
-                    warn!("Got non-query, non-violation delta as last delta ({delta:?}); assuming OK");
-                    ReasonerResponse::Success
+        let res: ReasonerResponse<R::Reason> = self.verdict(&trace, locale);
+        Ok((res, trace))
+    }
+}
+impl<R, S, Q> ReasonerConnector for EFlintHaskellReasonerConnector<R, S, Q>
+where
+    R: Sync + ReasonHandler,
+    S: Send + Sync + EFlintable + Serialize,
+    Q: Send + Sync + EFlintable + Serialize,
+{
+    type Context = EFlintHaskellReasonerContext;
+    type Error = Error;
+    type Question = Q;
+    type Reason = R::Reason;
+    type State = S;
+
+    #[inline]
+    fn context(&self) -> Self::Context { self.context.public.clone() }
+
+    /// Checks that the base policy is still a file and that the reasoner's program is executable.
+    ///
+    /// Note that this does not run the reasoner, so it cannot detect a reasoner that is executable
+    /// but broken.
+    #[instrument(name = "EFlintHaskellReasonerConnector::health_check", skip_all)]
+    async fn health_check(&self) -> Result<(), Self::Error> {
+        if !tokio::fs::metadata(&self.context.base_policy).await.is_ok_and(|md| md.is_file()) {
+            return Err(Error::BasePolicyMissing { path: self.context.base_policy.clone() });
+        }
+        if !is_executable(&self.context.cmd.0).await {
+            return Err(Error::ReplCommandNotExecutable { exec: self.context.cmd.0.clone() });
+        }
+        debug!("Reasoner {:?} is healthy", self.context.cmd.0);
+        Ok(())
+    }
+
+    #[inline]
+    #[instrument(name = "EFlintHaskellReasonerConnector::consult", skip_all, fields(reference = logger.reference()))]
+    async fn consult<'a, L>(
+        &'a self,
+        state: Self::State,
+        question: Self::Question,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        self.reason(state, question, logger).await.map(|(res, _)| res)
+    }
+
+    /// Serializes the state only once, and then runs the reasoner for all questions concurrently.
+    ///
+    /// Note that every question still gets its own reasoner process, as eFLINT statements
+    /// update the knowledge base and would otherwise leak into the next question. At most
+    /// [`EFlintHaskellReasonerConnector::with_max_concurrent_questions()`] processes run at the
+    /// same time.
+    #[instrument(name = "EFlintHaskellReasonerConnector::consult_questions", skip_all, fields(reference = logger.reference(), questions = questions.len()))]
+    async fn consult_questions<'a, L>(
+        &'a self,
+        state: Self::State,
+        questions: Vec<Self::Question>,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Vec<Result<ReasonerResponse<Self::Reason>, Self::Error>>
+    where
+        Self: Sync,
+        Self::State: 'a + Send + Clone,
+        Self::Question: 'a + Send,
+        Self::Reason: Send,
+        Self::Error: Send,
+        L: Sync + AuditLogger,
+    {
+        let state_spec: String = state.eflint();
+        stream::iter(questions.into_iter().map(|question| {
+            let (state, state_spec): (&S, &str) = (&state, &state_spec);
+            async move {
+                logger
+                    .log_question(state, &question)
+                    .await
+                    .map_err(|err| Error::LogQuestion { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
+                let (res, trace): (ReasonerResponse<R::Reason>, Trace) =
+                    self.run(format!("{state_spec}{}", question.eflint()), question.locale()).await?;
+                log_trace(logger, &trace).await?;
+                Ok(res)
+            }
+        }))
+        .buffered(self.max_concurrent_questions)
+        .collect()
+        .await
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use spec::reasons::ManyReason;
+
+    use super::*;
+    use crate::reasons::VerboseHandler;
+
+
+    /// Builds a connector without hashing or logging anything.
+    ///
+    /// # Arguments
+    /// - `cmd`: The command to call as reasoner. The base policy's path is given as last argument.
+    /// - `timeout`: The maximum time a single consult may take, if any.
+    fn gen_conn(cmd: (&str, &[&str]), timeout: Option<Duration>) -> EFlintHaskellReasonerConnector<VerboseHandler, (), ()> {
+        EFlintHaskellReasonerConnector {
+            context: EFlintHaskellReasonerContextFull {
+                public: EFlintHaskellReasonerContext {
+                    version: "0.0.0".into(),
+                    language: "eflint".into(),
+                    language_version: "4.0.0.1".into(),
+                    base_policy_hash: PolicyHash { algorithm: HashAlgorithm::Sha256, digest: vec![] },
                 },
-            })
-            .unwrap_or(ReasonerResponse::Success);
+                cmd: (cmd.0.into(), cmd.1.iter().map(|arg| arg.to_string()).collect()),
+                base_policy: "policy.eflint".into(),
+                timeout,
+            },
+            handler: VerboseHandler,
+            max_concurrent_questions: DEFAULT_MAX_CONCURRENT_QUESTIONS,
+            _state: PhantomData,
+            _question: PhantomData,
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_run_timeout() {
+        // The reasoner neither reads its input nor completes, so even writing the spec must time out
+        let conn = gen_conn(("sh", &["-c", "sleep 5"]), Some(Duration::from_millis(100)));
+        let start: Instant = Instant::now();
+        let err = conn.run("+foo.\n".repeat(1 << 20), None).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout { .. }), "{err:?}");
+        assert!(start.elapsed() < Duration::from_secs(5), "Took {:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_run_failure() {
+        // A reasoner that fails without reading its input reports its own status and stderr
+        let conn = gen_conn(("sh", &["-c", "echo oops >&2; exit 3"]), None);
+        match conn.run("+foo.\n".repeat(1 << 20), None).await.unwrap_err() {
+            Error::CommandFailure { status, stderr, .. } => {
+                assert_eq!(status.code(), Some(3));
+                assert_eq!(stderr, "oops\n");
+            },
+            err => panic!("Expected a command failure, got {err:?}"),
+        }
+    }
 
-        Ok(res)
+    #[tokio::test]
+    async fn test_consult_questions_concurrency() {
+        use no_op_logger::MockLogger;
+
+        // Every reasoner takes 200ms, so at most two at a time makes four questions take 400ms
+        let conn = gen_conn(("sh", &["-c", "cat >/dev/null; sleep 0.2"]), None).with_max_concurrent_questions(2);
+        let start: Instant = Instant::now();
+        let res = conn.consult_questions((), vec![(); 4], &SessionedAuditLogger::new("test", MockLogger::new())).await;
+        assert!(start.elapsed() >= Duration::from_millis(400), "Took {:?}", start.elapsed());
+        assert_eq!(res.len(), 4);
+        assert!(res.iter().all(|res| matches!(res, Ok(ReasonerResponse::Success))), "{res:?}");
+    }
+
+    #[test]
+    fn test_verdict() {
+        let conn = gen_conn(("eflint-repl", &[]), None);
+
+        // Pairs of a trace and the expected verdict, with the number of reasons given for it
+        let cases: [(&str, &str, usize); 9] = [
+            ("", "success", 0),
+            ("query successful\n", "success", 0),
+            ("query failed\nquery successful\n", "success", 0),
+            ("query failed\n", "violated", 1),
+            ("query successful\nquery failed\nquery failed\n", "violated", 2),
+            ("+foo(\"Amy\")\nviolations:\nviolated invariant!: baz\n", "violated", 1),
+            ("query failed\nviolations:\nviolated duty!: bar(123)\n", "violated", 2),
+            ("+foo(\"Amy\")\n", "indeterminate", 0),
+            ("query failed\n~foo(\"Amy\")\n", "indeterminate", 1),
+        ];
+        for (input, verdict, n) in cases {
+            let trace: Trace = Trace::from_str(input).unwrap_or_else(|err| panic!("Failed to parse {input:?}: {err}"));
+            let res: ReasonerResponse<ManyReason<String>> = conn.verdict(&trace, None);
+            let (kind, reasons): (&str, usize) = match &res {
+                ReasonerResponse::Success => ("success", 0),
+                ReasonerResponse::Violated(reasons) => ("violated", reasons.len()),
+                ReasonerResponse::Indeterminate { reasons } => ("indeterminate", reasons.len()),
+            };
+            assert_eq!((kind, reasons), (verdict, n), "Trace {input:?} gave {res:?}");
+        }
     }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    25 Apr 2025, 16:36:41
 //  Last edited:
-//    06 May 2025, 12:44:08
+//    16 Oct 2026, 01:35:31
 //  Auto updated?
 //    Yes
 //
@@ -13,12 +13,61 @@
 //
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result as FResult};
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use spec::reasons::{ManyReason, NoReason};
+use spec::reasons::{ManyReason, NoReason, Severity, SeverityReason};
+
+use crate::trace::{Composite, Delta, Instance, Trace, Violation};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Renders a message template by substituting `{argN}` with the `N`th argument of the given
+/// [`Composite`].
+///
+/// String literal arguments are substituted without their quotes. Placeholders referring to
+/// arguments that do not exist are left as-is. The template is scanned only once, so substituted
+/// arguments are never substituted themselves.
+///
+/// # Arguments
+/// - `template`: The template to render.
+/// - `inst`: The [`Composite`] who's arguments to substitute, if any.
+///
+/// # Returns
+/// The rendered message.
+fn render_template(template: &str, inst: Option<&Composite>) -> String {
+    let args: &[Instance] = inst.map(|inst| inst.args.as_slice()).unwrap_or_default();
+    let mut res: String = String::with_capacity(template.len());
+    let mut rest: &str = template;
+    while let Some(start) = rest.find("{arg") {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Find the argument it refers to, if it's a placeholder at all
+        let digits: &str = &rest[4..];
+        let n: usize = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+        let arg: Option<&Instance> =
+            if n > 0 && digits[n..].starts_with('}') { digits[..n].parse::<usize>().ok().and_then(|i| args.get(i)) } else { None };
+        match arg {
+            Some(Instance::StringLit(sl)) => res.push_str(&sl.0),
+            Some(arg) => res.push_str(&arg.to_string()),
+            None => {
+                // Not something we substitute; skip the brace and keep on scanning
+                res.push('{');
+                rest = &rest[1..];
+                continue;
+            },
+        }
+        rest = &digits[n + 1..];
+    }
+    res.push_str(rest);
+    res
+}
+
+
 
-use crate::trace::Violation;
 
 
 /***** AUXILLARY *****/
@@ -28,6 +77,35 @@ pub enum Problem {
     QueryFailed,
     Violation(Violation),
 }
+impl Problem {
+    /// Returns what kind of problem this is.
+    ///
+    /// # Returns
+    /// A [`ProblemKind`] describing this problem.
+    #[inline]
+    pub const fn kind(&self) -> ProblemKind {
+        match self {
+            Self::QueryFailed => ProblemKind::QueryFailed,
+            Self::Violation(Violation::Act(_)) => ProblemKind::Act,
+            Self::Violation(Violation::Duty(_)) => ProblemKind::Duty,
+            Self::Violation(Violation::Invariant(_)) => ProblemKind::Invariant,
+        }
+    }
+
+    /// Returns the name of the violated type, if any.
+    ///
+    /// # Returns
+    /// The name of the violated act, duty or invariant, or [`None`] for failed queries.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::QueryFailed => None,
+            Self::Violation(Violation::Act(a)) => Some(&a.inst.name),
+            Self::Violation(Violation::Duty(d)) => Some(&d.inst.name),
+            Self::Violation(Violation::Invariant(i)) => Some(&i.name),
+        }
+    }
+}
 impl Display for Problem {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -38,6 +116,66 @@ impl Display for Problem {
     }
 }
 
+/// Describes the kind of a [`Problem`] without its contents.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ProblemKind {
+    QueryFailed,
+    Act,
+    Duty,
+    Invariant,
+}
+
+/// Explains why a question was answered positively.
+///
+/// This is the dual of a [`Problem`]: it lists which permitting rules applied, such that allowed
+/// workflows can be audited too.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Explanation {
+    /// The instances (e.g., acts) that were enabled when they were triggered, in order.
+    pub enabled: Vec<Instance>,
+}
+impl Explanation {
+    /// Collects the explanation from a reasoner trace.
+    ///
+    /// # Arguments
+    /// - `trace`: The [`Trace`] of a successful deliberation.
+    ///
+    /// # Returns
+    /// A new Explanation listing every trigger in the trace that was enabled, including those
+    /// synchronized with others.
+    pub fn from_trace(trace: &Trace) -> Self {
+        Self {
+            enabled: trace
+                .deltas
+                .iter()
+                .filter_map(|delta| match delta {
+                    Delta::Trigger(trigger) => {
+                        Some(trigger.iter().filter(|trigger| trigger.enabled == Some(true)).map(|trigger| trigger.inst.clone()))
+                    },
+                    _ => None,
+                })
+                .flatten()
+                .collect(),
+        }
+    }
+}
+impl Display for Explanation {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        if self.enabled.is_empty() {
+            return write!(f, "Allowed, but no permitting rules applied");
+        }
+        write!(f, "Allowed because ")?;
+        for (i, inst) in self.enabled.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{inst}")?;
+        }
+        write!(f, " applied")
+    }
+}
+
 
 
 
@@ -57,6 +195,40 @@ pub trait ReasonHandler {
     /// # Returns
     /// A [`Self::Reason`](ReasonHandler::Reason) that represents the outputted reason.
     fn handle(&self, problems: impl IntoIterator<Item = Problem>) -> Self::Reason;
+
+    /// Maps a query/violation to a reason in the preferred language of the caller.
+    ///
+    /// By default, this ignores the locale and calls [`ReasonHandler::handle()`].
+    ///
+    /// # Arguments
+    /// - `problems`: A sequence of [`Problem::QueryFailed`]s and/or [`Problem::Violation`]s that
+    ///   describes the reasons.
+    /// - `locale`: The preferred language of the reasons (e.g., `nl-NL`), if any.
+    ///
+    /// # Returns
+    /// A [`Self::Reason`](ReasonHandler::Reason) that represents the outputted reason.
+    #[inline]
+    fn handle_localized(&self, problems: impl IntoIterator<Item = Problem>, locale: Option<&str>) -> Self::Reason {
+        let _ = locale;
+        self.handle(problems)
+    }
+
+    /// Decides whether a reason returned by this handler should actually block the question.
+    ///
+    /// If not, the reasoner answers with success instead (but still logs the reason).
+    ///
+    /// By default, every reason is blocking.
+    ///
+    /// # Arguments
+    /// - `reason`: The [`Self::Reason`](ReasonHandler::Reason) to decide upon.
+    ///
+    /// # Returns
+    /// True if the reason should fail the question, or false otherwise.
+    #[inline]
+    fn is_blocking(&self, reason: &Self::Reason) -> bool {
+        let _ = reason;
+        true
+    }
 }
 
 
@@ -136,3 +308,306 @@ impl ReasonHandler for VerboseHandler {
         ManyReason::from_iter(problems.into_iter().map(|p| p.to_string()))
     }
 }
+
+
+
+/// Reason handler that maps violations to operator-defined messages.
+///
+/// This allows policy authors to ship a "message catalog" that explains violations to end users
+/// without exposing eFLINT internals. Catalogs can be given per locale, in which case the default
+/// catalog is used as a fallback.
+#[derive(Clone, Debug)]
+pub struct MappingReasonHandler {
+    /// Maps violated type names to message templates.
+    ///
+    /// Templates may refer to the arguments of the violated instance using `{arg0}`, `{arg1}`,
+    /// etc.
+    pub messages: HashMap<String, String>,
+    /// Maps locales to catalogs like [`MappingReasonHandler::messages`] in that language.
+    pub locales:  HashMap<String, HashMap<String, String>>,
+}
+impl MappingReasonHandler {
+    /// Constructor for the MappingReasonHandler.
+    ///
+    /// # Arguments
+    /// - `messages`: A map from violated type names to message templates. Templates may refer to
+    ///   the arguments of the violated instance using `{arg0}`, `{arg1}`, etc.
+    ///
+    /// # Returns
+    /// A new MappingReasonHandler that renders mapped violations using their template, and any
+    /// other problems using their default rendering.
+    #[inline]
+    pub fn new(messages: impl Into<HashMap<String, String>>) -> Self { Self { messages: messages.into(), locales: HashMap::new() } }
+
+    /// Adds a localized catalog to this handler.
+    ///
+    /// # Arguments
+    /// - `locale`: The locale of the catalog (e.g., `nl-NL`).
+    /// - `messages`: A map from violated type names to message templates in that language.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_locale(mut self, locale: impl Into<String>, messages: impl Into<HashMap<String, String>>) -> Self {
+        self.locales.insert(locale.into(), messages.into());
+        self
+    }
+}
+impl ReasonHandler for MappingReasonHandler {
+    type Reason = ManyReason<String>;
+
+    #[inline]
+    fn handle(&self, problems: impl IntoIterator<Item = Problem>) -> Self::Reason { self.handle_localized(problems, None) }
+
+    #[inline]
+    fn handle_localized(&self, problems: impl IntoIterator<Item = Problem>, locale: Option<&str>) -> Self::Reason {
+        let catalog: Option<&HashMap<String, String>> = locale.and_then(|locale| self.locales.get(locale));
+        ManyReason::from_iter(problems.into_iter().map(|problem| {
+            let (name, inst): (&str, Option<&Composite>) = match &problem {
+                Problem::QueryFailed => return problem.to_string(),
+                Problem::Violation(Violation::Act(a)) => (a.inst.name.as_str(), Some(&a.inst)),
+                Problem::Violation(Violation::Duty(d)) => (d.inst.name.as_str(), Some(&d.inst)),
+                Problem::Violation(Violation::Invariant(i)) => (i.name.as_str(), None),
+            };
+            match catalog.and_then(|catalog| catalog.get(name)).or_else(|| self.messages.get(name)) {
+                Some(template) => render_template(template, inst),
+                None => problem.to_string(),
+            }
+        }))
+    }
+}
+
+
+
+
+
+/// Reason handler that wraps another handler, and tags every reason it produces with a
+/// [`Severity`].
+///
+/// The severity of a problem is decided by the first rule that matches it, or else by a default.
+/// This allows operators to soft-launch new rules by reporting their violations as warnings,
+/// optionally without blocking anything (see [`SeverityReasonHandler::with_warnings_allowed()`]).
+///
+/// Failed queries are always [`Severity::Deny`], regardless of the rules or default. Otherwise,
+/// a catch-all rule or default meant for violations would silently allow failed queries.
+#[derive(Clone, Debug)]
+pub struct SeverityReasonHandler<R> {
+    /// The handler producing the reasons to tag.
+    pub inner: R,
+    /// The rules deciding the severity of problems, as `(kind, name pattern, severity)`-tuples.
+    ///
+    /// A rule matches a problem if it has the given kind (if any) and its name matches the
+    /// pattern. Failed queries are never matched.
+    pub rules: Vec<(Option<ProblemKind>, Regex, Severity)>,
+    /// The severity of problems not matched by any rule.
+    pub default: Severity,
+    /// Whether to answer with success if there are no [`Severity::Deny`]-reasons.
+    pub allow_warnings: bool,
+}
+impl<R> SeverityReasonHandler<R> {
+    /// Constructor for the SeverityReasonHandler.
+    ///
+    /// # Arguments
+    /// - `inner`: The [`ReasonHandler`] producing the reasons to tag.
+    ///
+    /// # Returns
+    /// A new SeverityReasonHandler without any rules, which marks everything as
+    /// [`Severity::Deny`].
+    #[inline]
+    pub const fn new(inner: R) -> Self { Self { inner, rules: Vec::new(), default: Severity::Deny, allow_warnings: false } }
+
+    /// Adds a rule to this handler.
+    ///
+    /// Rules are tried in the order they are added.
+    ///
+    /// # Arguments
+    /// - `kind`: The [`ProblemKind`] of the problems to match, or [`None`] to match any kind.
+    /// - `pattern`: A [`Regex`] that the violated type's name must match.
+    /// - `severity`: The [`Severity`] of matching problems.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_rule(mut self, kind: Option<ProblemKind>, pattern: Regex, severity: Severity) -> Self {
+        self.rules.push((kind, pattern, severity));
+        self
+    }
+
+    /// Sets the severity of violations not matched by any rule.
+    ///
+    /// Failed queries are always [`Severity::Deny`].
+    ///
+    /// # Arguments
+    /// - `severity`: The default [`Severity`]. Default is [`Severity::Deny`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub const fn with_default(mut self, severity: Severity) -> Self {
+        self.default = severity;
+        self
+    }
+
+    /// Sets whether the reasoner answers with success if no reason is [`Severity::Deny`].
+    ///
+    /// # Arguments
+    /// - `allow`: Whether to allow questions with only [`Severity::Info`]- and
+    ///   [`Severity::Warning`]-reasons. Default is false.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub const fn with_warnings_allowed(mut self, allow: bool) -> Self {
+        self.allow_warnings = allow;
+        self
+    }
+
+    /// Decides the severity of a problem.
+    ///
+    /// # Arguments
+    /// - `problem`: The [`Problem`] to classify.
+    ///
+    /// # Returns
+    /// The [`Severity`] of the first matching rule, or the default one. Failed queries are always
+    /// [`Severity::Deny`].
+    pub fn classify(&self, problem: &Problem) -> Severity {
+        if let Problem::QueryFailed = problem {
+            return Severity::Deny;
+        }
+        let (kind, name): (ProblemKind, &str) = (problem.kind(), problem.name().unwrap_or(""));
+        self.rules
+            .iter()
+            .find(|(rule_kind, pattern, _)| rule_kind.map_or(true, |rule_kind| rule_kind == kind) && pattern.is_match(name))
+            .map(|(_, _, severity)| *severity)
+            .unwrap_or(self.default)
+    }
+}
+impl<R: ReasonHandler> ReasonHandler for SeverityReasonHandler<R> {
+    type Reason = ManyReason<SeverityReason<R::Reason>>;
+
+    #[inline]
+    fn handle(&self, problems: impl IntoIterator<Item = Problem>) -> Self::Reason { self.handle_localized(problems, None) }
+
+    #[inline]
+    fn handle_localized(&self, problems: impl IntoIterator<Item = Problem>, locale: Option<&str>) -> Self::Reason {
+        // NOTE: The inner handler is given one problem at a time, such that its reasons can be told apart
+        problems
+            .into_iter()
+            .map(|problem| SeverityReason { severity: self.classify(&problem), reason: self.inner.handle_localized([problem], locale) })
+            .collect()
+    }
+
+    #[inline]
+    fn is_blocking(&self, reason: &Self::Reason) -> bool { !self.allow_warnings || reason.iter().any(|reason| reason.severity == Severity::Deny) }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{DutyViolation, IntLit, InvariantViolation, Query, StringLit, Trigger};
+
+
+    #[test]
+    fn test_explanation_from_trace() {
+        let trigger = |name: &str, enabled: Option<bool>| {
+            Delta::Trigger(Trigger {
+                inst: Instance::Composite(Composite { name: name.into(), args: vec![] }),
+                enabled,
+                disabled_by: None,
+                children: vec![],
+            })
+        };
+        let trace =
+            Trace { deltas: vec![trigger("foo", Some(true)), trigger("bar", Some(false)), trigger("baz", None), Delta::Query(Query::Success)] };
+        let explanation = Explanation::from_trace(&trace);
+        assert_eq!(explanation.enabled, vec![Instance::Composite(Composite { name: "foo".into(), args: vec![] })]);
+        assert_eq!(explanation.to_string(), format!("Allowed because {} applied", explanation.enabled[0]));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let inst = Composite {
+            name: "foo".into(),
+            args: vec![Instance::StringLit(StringLit("{arg1}".into())), Instance::IntLit(IntLit(42)), Instance::StringLit(StringLit("Bob".into()))],
+        };
+        assert_eq!(render_template("{arg0} and {arg1}", Some(&inst)), "{arg1} and 42");
+        assert_eq!(render_template("{arg2}{arg2}{{arg1}}", Some(&inst)), "BobBob{42}");
+        assert_eq!(render_template("{arg3} {arg} {arg1 {arg01} {arg1", Some(&inst)), "{arg3} {arg} {arg1 42 {arg1");
+        assert_eq!(render_template("{arg0}", None), "{arg0}");
+    }
+
+    #[test]
+    fn test_mapping_reason_handler() {
+        let handler = MappingReasonHandler::new([("foo".to_string(), "User {arg0} may not access {arg1} ({arg2})".to_string())]);
+        let reason = handler.handle([
+            Problem::Violation(Violation::Duty(DutyViolation {
+                inst: Composite { name: "foo".into(), args: vec![Instance::StringLit(StringLit("amy".into())), Instance::IntLit(IntLit(42))] },
+            })),
+            Problem::Violation(Violation::Invariant(InvariantViolation { name: "bar".into() })),
+        ]);
+        assert_eq!(reason.into_iter().collect::<Vec<String>>(), vec![
+            "User amy may not access 42 ({arg2})".to_string(),
+            Violation::Invariant(InvariantViolation { name: "bar".into() }).to_string()
+        ]);
+    }
+
+    #[test]
+    fn test_mapping_reason_handler_localized() {
+        let handler = MappingReasonHandler::new([("foo".to_string(), "Not allowed".to_string()), ("bar".to_string(), "Broken".to_string())])
+            .with_locale("nl-NL", [("foo".to_string(), "Niet toegestaan".to_string())]);
+        let problems = [
+            Problem::Violation(Violation::Invariant(InvariantViolation { name: "foo".into() })),
+            Problem::Violation(Violation::Invariant(InvariantViolation { name: "bar".into() })),
+        ];
+        assert_eq!(handler.handle_localized(problems.clone(), Some("nl-NL")).into_iter().collect::<Vec<String>>(), vec![
+            "Niet toegestaan".to_string(),
+            "Broken".to_string()
+        ]);
+        assert_eq!(handler.handle_localized(problems, Some("de-DE")).into_iter().collect::<Vec<String>>(), vec![
+            "Not allowed".to_string(),
+            "Broken".to_string()
+        ]);
+    }
+
+    #[test]
+    fn test_severity_reason_handler() {
+        let handler = SeverityReasonHandler::new(VerboseHandler)
+            .with_rule(Some(ProblemKind::Invariant), Regex::new("^new-").unwrap(), Severity::Warning)
+            .with_rule(Some(ProblemKind::Duty), Regex::new("").unwrap(), Severity::Warning)
+            .with_rule(None, Regex::new("^fyi-").unwrap(), Severity::Info)
+            .with_warnings_allowed(true);
+        let invariant = |name: &str| Problem::Violation(Violation::Invariant(InvariantViolation { name: name.into() }));
+        let duty = Problem::Violation(Violation::Duty(DutyViolation { inst: Composite { name: "new-pay".into(), args: vec![] } }));
+
+        // Rules are matched in order, falling back to the default
+        let reason = handler.handle([invariant("new-foo"), duty.clone(), invariant("fyi-bar"), invariant("baz"), Problem::QueryFailed]);
+        assert_eq!(reason.iter().map(|reason| reason.severity).collect::<Vec<Severity>>(), vec![
+            Severity::Warning,
+            Severity::Warning,
+            Severity::Info,
+            Severity::Deny,
+            Severity::Deny
+        ]);
+        assert_eq!(reason[0].to_string(), format!("[warning] {}", invariant("new-foo")));
+
+        // Only denials are blocking, if so configured
+        assert!(handler.is_blocking(&reason));
+        assert!(!handler.is_blocking(&handler.handle([invariant("new-foo"), duty.clone()])));
+        assert!(handler.clone().with_warnings_allowed(false).is_blocking(&handler.handle([duty])));
+
+        // Failed queries are never downgraded, not even by catch-alls
+        let handler = SeverityReasonHandler::new(VerboseHandler)
+            .with_rule(None, Regex::new("").unwrap(), Severity::Info)
+            .with_rule(Some(ProblemKind::QueryFailed), Regex::new("").unwrap(), Severity::Info)
+            .with_default(Severity::Info)
+            .with_warnings_allowed(true);
+        assert_eq!(handler.classify(&Problem::QueryFailed), Severity::Deny);
+        assert_eq!(handler.classify(&invariant("baz")), Severity::Info);
+        assert!(handler.is_blocking(&handler.handle([invariant("baz"), Problem::QueryFailed])));
+    }
+}
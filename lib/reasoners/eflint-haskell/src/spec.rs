@@ -4,7 +4,7 @@
 //  Created:
 //    16 Apr 2025, 23:43:13
 //  Last edited:
-//    06 May 2025, 12:53:46
+//    15 Oct 2026, 23:45:10
 //  Auto updated?
 //    Yes
 //
@@ -16,6 +16,8 @@
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter, Result as FResult};
 
+use serde::{Deserialize, Serialize};
+
 
 /***** FORMATTERS *****/
 /// Formatter wrapping an [`EFlintable`] such that it implements [`Display`].
@@ -46,6 +48,16 @@ pub trait EFlintable {
     /// # Errors
     /// This function should error if it failed to write to the given formatter.
     fn eflint_fmt(&self, f: &mut Formatter<'_>) -> FResult;
+
+    /// Returns the language in which the caller would prefer to receive reasons, if any.
+    ///
+    /// This is passed to [`ReasonHandler::handle_localized()`](crate::reasons::ReasonHandler::handle_localized())
+    /// for questions, such that handlers can localize their messages.
+    ///
+    /// # Returns
+    /// Some locale identifier (e.g., `nl-NL`), or [`None`] if there is no preference.
+    #[inline]
+    fn locale(&self) -> Option<&str> { None }
 }
 
 // Practical impls
@@ -62,14 +74,23 @@ impl EFlintable for String {
 impl<T: ?Sized + EFlintable> EFlintable for &T {
     #[inline]
     fn eflint_fmt(&self, f: &mut Formatter<'_>) -> FResult { <T as EFlintable>::eflint_fmt(self, f) }
+
+    #[inline]
+    fn locale(&self) -> Option<&str> { <T as EFlintable>::locale(self) }
 }
 impl<T: ?Sized + EFlintable> EFlintable for &mut T {
     #[inline]
     fn eflint_fmt(&self, f: &mut Formatter<'_>) -> FResult { <T as EFlintable>::eflint_fmt(self, f) }
+
+    #[inline]
+    fn locale(&self) -> Option<&str> { <T as EFlintable>::locale(self) }
 }
 impl<T: Clone + EFlintable> EFlintable for Cow<'_, T> {
     #[inline]
     fn eflint_fmt(&self, f: &mut Formatter<'_>) -> FResult { <T as EFlintable>::eflint_fmt(self, f) }
+
+    #[inline]
+    fn locale(&self) -> Option<&str> { <T as EFlintable>::locale(self) }
 }
 
 // Container impls
@@ -103,6 +124,38 @@ impl<T: EFlintable> EFlintable for Vec<T> {
 
 
 
+/// Wraps an [`EFlintable`] (typically, a question) to carry the caller's preferred language for
+/// reasons.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Localized<T> {
+    /// The wrapped object.
+    pub inner:  T,
+    /// The preferred language of the reasons, if any (e.g., `nl-NL`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+impl<T> Localized<T> {
+    /// Constructor for the Localized.
+    ///
+    /// # Arguments
+    /// - `inner`: The object to wrap.
+    /// - `locale`: The preferred language of the reasons, if any (e.g., `nl-NL`).
+    ///
+    /// # Returns
+    /// A new Localized that serializes as `inner` but reports the given `locale`.
+    #[inline]
+    pub fn new(inner: T, locale: Option<String>) -> Self { Self { inner, locale } }
+}
+impl<T: EFlintable> EFlintable for Localized<T> {
+    #[inline]
+    fn eflint_fmt(&self, f: &mut Formatter<'_>) -> FResult { self.inner.eflint_fmt(f) }
+
+    #[inline]
+    fn locale(&self) -> Option<&str> { self.locale.as_deref() }
+}
+
+
+
 /// Extension upon an [`EFlintable`] to make it optionally nicer to work with.
 pub trait EFlintableExt: EFlintable {
     /// Returns some formatter that implements [`Display`].
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Apr 2025, 00:06:39
 //  Last edited:
-//    06 May 2025, 12:55:32
+//    16 Oct 2026, 02:23:12
 //  Auto updated?
 //    Yes
 //
@@ -24,6 +24,7 @@ use thiserror::Error;
 
 /***** CONSTANTS *****/
 const DISABLED_ACTION: &str = "disabled action:";
+const DISABLED_BY: &str = "disabled by:";
 const EXEC_TRANS: &str = "executed transition:";
 const NEW_INVARIANT: &str = "New invariant";
 const NEW_TYPE: &str = "New type";
@@ -35,46 +36,383 @@ const VIOLATIONS: &str = "violations:";
 const VIOLATED_DUTY: &str = "violated duty!:";
 const VIOLATED_INVARIANT: &str = "violated invariant!:";
 
+/// The maximum depth to which [`Composite`]s may be nested before we refuse to parse them.
+///
+/// This prevents malformed reasoner output from overflowing the stack.
+const MAX_DEPTH: usize = 256;
+
 
 
 
 
 /***** ERRORS *****/
 /// Defines fatal parsing errors for parsing traces [from strings](FromStrHead::from_str_head()).
+///
+/// Every error carries the byte offset (`pos`) at which it occurred, as well as the remainder of the
+/// input (`s`) from that point onwards.
 #[derive(Debug, Error, Eq, PartialEq)]
 pub enum Error {
-    #[error("Expected a comma at {s:?}")]
-    ExpectedComma { s: String },
-    #[error("Expected \"`\" to follow \"|\" while parsing transition trees at {s:?}")]
-    ExpectedHookAfterBar { s: String },
-    #[error("Expected instance to follow \"disabled action\" at {s:?}")]
-    ExpectedInstanceAfterDisabledAction { s: String },
-    #[error("Expected instance to follow \"violated duty!\" at {s:?}")]
-    ExpectedInstanceAfterViolatedDuty { s: String },
-    #[error("Expected \"-\" to follow \"`\" while parsing transition trees at {s:?}")]
-    ExpectedPipeAfterHook { s: String },
-    #[error("Expected type name to follow \"New invariant\" at {s:?}")]
-    ExpectedTypeNameAfterNewInvariant { s: String },
-    #[error("Expected type name to follow \"New type\" at {s:?}")]
-    ExpectedTypeNameAfterNewType { s: String },
-    #[error("Expected type name to follow \"violated invariant!\" at {s:?}")]
-    ExpectedTypeNameAfterViolatedInvariant { s: String },
-    #[error("Expected instance after magic 'executed transition' keyword at {s:?}")]
-    MissingInstanceAfterExecuted { s: String },
-    #[error("Out-of-range integer at {s:?}")]
-    OutOfRangeInt { s: String },
-    #[error("Failed to parse instance following postulation op {op} at {s:?}")]
-    PostulationOpWithoutInstance { op: PostulationOp, s: String },
-    #[error("Unparsable input at {s:?}")]
-    UnparsableInput { s: String },
-    #[error("Expected closing delimiter {delim:?} for opening delimiter starting at {s:?}")]
-    UnterminatedDelim { delim: char, s: String },
-    #[error("Expected closing parenthesis at {s:?}")]
-    UnterminatedParen { s: String },
-    #[error("Unterminated string at {s:?}")]
-    UnterminatedString { s: String },
+    #[error("Expected a comma at offset {pos} ({s:?})")]
+    ExpectedComma { pos: usize, s: String },
+    #[error("Expected \"`\" to follow \"|\" while parsing transition trees at offset {pos} ({s:?})")]
+    ExpectedHookAfterBar { pos: usize, s: String },
+    #[error("Expected instance to follow \"disabled action\" at offset {pos} ({s:?})")]
+    ExpectedInstanceAfterDisabledAction { pos: usize, s: String },
+    #[error("Expected instance to follow \"disabled by\" at offset {pos} ({s:?})")]
+    ExpectedInstanceAfterDisabledBy { pos: usize, s: String },
+    #[error("Expected instance to follow \"violated duty!\" at offset {pos} ({s:?})")]
+    ExpectedInstanceAfterViolatedDuty { pos: usize, s: String },
+    #[error("Expected \"-\" to follow \"`\" while parsing transition trees at offset {pos} ({s:?})")]
+    ExpectedPipeAfterHook { pos: usize, s: String },
+    #[error("Expected type name to follow \"New invariant\" at offset {pos} ({s:?})")]
+    ExpectedTypeNameAfterNewInvariant { pos: usize, s: String },
+    #[error("Expected type name to follow \"New type\" at offset {pos} ({s:?})")]
+    ExpectedTypeNameAfterNewType { pos: usize, s: String },
+    #[error("Expected type name to follow \"violated invariant!\" at offset {pos} ({s:?})")]
+    ExpectedTypeNameAfterViolatedInvariant { pos: usize, s: String },
+    #[error("Expected instance after magic 'executed transition' keyword at offset {pos} ({s:?})")]
+    MissingInstanceAfterExecuted { pos: usize, s: String },
+    #[error("Out-of-range float at offset {pos} ({s:?})")]
+    OutOfRangeFloat { pos: usize, s: String },
+    #[error("Out-of-range integer at offset {pos} ({s:?})")]
+    OutOfRangeInt { pos: usize, s: String },
+    #[error("Failed to parse instance following postulation op {op} at offset {pos} ({s:?})")]
+    PostulationOpWithoutInstance { op: PostulationOp, pos: usize, s: String },
+    #[error("Unparsable input at offset {pos} ({s:?})")]
+    UnparsableInput { pos: usize, s: String },
+    #[error("Expected closing delimiter {delim:?} for opening delimiter starting at offset {pos} ({s:?})")]
+    UnterminatedDelim { delim: char, pos: usize, s: String },
+    #[error("Expected closing parenthesis at offset {pos} ({s:?})")]
+    UnterminatedParen { pos: usize, s: String },
+    #[error("Unterminated string at offset {pos} ({s:?})")]
+    UnterminatedString { pos: usize, s: String },
+    #[error("Instance nested deeper than {MAX_DEPTH} levels at offset {pos} ({s:?})")]
+    TooDeeplyNested { pos: usize, s: String },
+}
+impl Error {
+    /// Returns the remainder of the input at which parsing failed.
+    ///
+    /// # Returns
+    /// A suffix of the string given to the parser, starting where the problem was found.
+    #[inline]
+    pub fn remainder(&self) -> &str {
+        match self {
+            Self::ExpectedComma { s, .. }
+            | Self::ExpectedHookAfterBar { s, .. }
+            | Self::ExpectedInstanceAfterDisabledAction { s, .. }
+            | Self::ExpectedInstanceAfterDisabledBy { s, .. }
+            | Self::ExpectedInstanceAfterViolatedDuty { s, .. }
+            | Self::ExpectedPipeAfterHook { s, .. }
+            | Self::ExpectedTypeNameAfterNewInvariant { s, .. }
+            | Self::ExpectedTypeNameAfterNewType { s, .. }
+            | Self::ExpectedTypeNameAfterViolatedInvariant { s, .. }
+            | Self::MissingInstanceAfterExecuted { s, .. }
+            | Self::OutOfRangeFloat { s, .. }
+            | Self::OutOfRangeInt { s, .. }
+            | Self::PostulationOpWithoutInstance { s, .. }
+            | Self::UnparsableInput { s, .. }
+            | Self::UnterminatedDelim { s, .. }
+            | Self::UnterminatedParen { s, .. }
+            | Self::UnterminatedString { s, .. }
+            | Self::TooDeeplyNested { s, .. } => s,
+        }
+    }
+
+    /// Returns the byte offset in the input at which parsing failed.
+    ///
+    /// Note that this is only meaningful once the error has been [located](Error::locate()).
+    /// [`Trace::from_str()`], [`Trace::from_str_head()`] and the [`TraceParser`] do this for you.
+    ///
+    /// # Returns
+    /// The offset of [`Error::remainder()`] in the original input.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        match self {
+            Self::ExpectedComma { pos, .. }
+            | Self::ExpectedHookAfterBar { pos, .. }
+            | Self::ExpectedInstanceAfterDisabledAction { pos, .. }
+            | Self::ExpectedInstanceAfterDisabledBy { pos, .. }
+            | Self::ExpectedInstanceAfterViolatedDuty { pos, .. }
+            | Self::ExpectedPipeAfterHook { pos, .. }
+            | Self::ExpectedTypeNameAfterNewInvariant { pos, .. }
+            | Self::ExpectedTypeNameAfterNewType { pos, .. }
+            | Self::ExpectedTypeNameAfterViolatedInvariant { pos, .. }
+            | Self::MissingInstanceAfterExecuted { pos, .. }
+            | Self::OutOfRangeFloat { pos, .. }
+            | Self::OutOfRangeInt { pos, .. }
+            | Self::PostulationOpWithoutInstance { pos, .. }
+            | Self::UnparsableInput { pos, .. }
+            | Self::UnterminatedDelim { pos, .. }
+            | Self::UnterminatedParen { pos, .. }
+            | Self::UnterminatedString { pos, .. }
+            | Self::TooDeeplyNested { pos, .. } => *pos,
+        }
+    }
+
+    /// Computes the byte offset at which parsing failed relative to the given input.
+    ///
+    /// # Arguments
+    /// - `input`: The original input given to the parser. The [remainder](Error::remainder())
+    ///   should be a suffix of it.
+    ///
+    /// # Returns
+    /// Self with [`Error::pos()`] updated.
+    #[inline]
+    pub fn locate(self, input: &str) -> Self {
+        let pos: usize = input.len().saturating_sub(self.remainder().len());
+        self.with_pos(pos)
+    }
+
+    /// Overwrites the byte offset at which parsing failed.
+    ///
+    /// # Arguments
+    /// - `pos`: The new offset.
+    ///
+    /// # Returns
+    /// Self with [`Error::pos()`] set to `pos`.
+    #[inline]
+    fn with_pos(mut self, pos: usize) -> Self {
+        match &mut self {
+            Self::ExpectedComma { pos: p, .. }
+            | Self::ExpectedHookAfterBar { pos: p, .. }
+            | Self::ExpectedInstanceAfterDisabledAction { pos: p, .. }
+            | Self::ExpectedInstanceAfterDisabledBy { pos: p, .. }
+            | Self::ExpectedInstanceAfterViolatedDuty { pos: p, .. }
+            | Self::ExpectedPipeAfterHook { pos: p, .. }
+            | Self::ExpectedTypeNameAfterNewInvariant { pos: p, .. }
+            | Self::ExpectedTypeNameAfterNewType { pos: p, .. }
+            | Self::ExpectedTypeNameAfterViolatedInvariant { pos: p, .. }
+            | Self::MissingInstanceAfterExecuted { pos: p, .. }
+            | Self::OutOfRangeFloat { pos: p, .. }
+            | Self::OutOfRangeInt { pos: p, .. }
+            | Self::PostulationOpWithoutInstance { pos: p, .. }
+            | Self::UnparsableInput { pos: p, .. }
+            | Self::UnterminatedDelim { pos: p, .. }
+            | Self::UnterminatedParen { pos: p, .. }
+            | Self::UnterminatedString { pos: p, .. }
+            | Self::TooDeeplyNested { pos: p, .. } => *p = pos,
+        }
+        self
+    }
+}
+
+/// Wraps an [`Error`] together with the input it occurred in, such that it can point at the
+/// offending part of the reasoner's output.
+///
+/// With the `miette` feature enabled, this is a [`miette::Diagnostic`] that labels that part.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+#[cfg_attr(feature = "miette", diagnostic(code(eflint_haskell::trace::parse)))]
+#[error("Failed to parse the output of the eFLINT reasoner")]
+pub struct ParseDiagnostic {
+    /// The full input that was parsed.
+    #[cfg_attr(feature = "miette", source_code)]
+    input:  String,
+    /// The part of the input where parsing failed, as an offset and a length.
+    #[cfg_attr(feature = "miette", label("parsing failed here"))]
+    span:   (usize, usize),
+    /// The error that occurred.
+    source: Error,
+}
+impl ParseDiagnostic {
+    /// Constructor for the ParseDiagnostic.
+    ///
+    /// # Arguments
+    /// - `input`: The full input that was given to the parser (e.g., [`Trace::from_str()`]).
+    /// - `source`: The [`Error`] that parsing it produced.
+    ///
+    /// # Returns
+    /// A new ParseDiagnostic that labels the line in `input` at which `source` occurred.
+    pub fn new(input: impl Into<String>, source: Error) -> Self {
+        let input: String = input.into();
+        let source: Error = source.locate(&input);
+        let rem: &str = source.remainder();
+        // NOTE: The remainder should always be a suffix of the input, but `locate()` clamps in case
+        // somebody passes a different input
+        let start: usize = source.pos();
+        let len: usize = rem.find('\n').unwrap_or(rem.len()).max(1).min(input.len() - start);
+        Self { input, span: (start, len), source }
+    }
+
+    /// Returns the input in which parsing failed.
+    ///
+    /// # Returns
+    /// The full input that was given to the parser.
+    #[inline]
+    pub fn input(&self) -> &str { &self.input }
+
+    /// Returns the part of the input where parsing failed.
+    ///
+    /// # Returns
+    /// The offending part of [`ParseDiagnostic::input()`], i.e., the rest of the line at which
+    /// parsing failed.
+    #[inline]
+    pub fn snippet(&self) -> &str { &self.input[self.span.0..self.span.0 + self.span.1] }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses the optional `(ENABLED)`/`(DISABLED)` marker that follows a triggered instance, as well
+/// as the optional `disabled by: <instance>` clause that may follow that.
+///
+/// # Arguments
+/// - `s`: The input string to parse from.
+///
+/// # Returns
+/// A tuple of the remaining string, whether the instance was enabled (if given) and the instance
+/// that disabled it (if given).
+///
+/// # Errors
+/// This function errors if a `disabled by` clause was given but not followed by an instance.
+fn parse_trigger_status(s: &str) -> Result<(&str, Option<bool>, Option<Instance>), Error> {
+    // Parse the optional 'ENABLED|DISABLED' bizz
+    let rem = s.trim_start();
+    let (rem, enabled): (&str, Option<bool>) = if let Some(rem) = rem.strip_prefix(TRANS_ENABLED) {
+        (rem.trim_start(), Some(true))
+    } else if let Some(rem) = rem.strip_prefix(TRANS_DISABLED) {
+        (rem.trim_start(), Some(false))
+    } else {
+        (rem, None)
+    };
+
+    // Then parse the optional 'disabled by' clause
+    let Some(rem) = rem.strip_prefix(DISABLED_BY) else {
+        return Ok((rem, enabled, None));
+    };
+    let rem = rem.trim_start();
+    let (rem, inst): (&str, Instance) =
+        Instance::from_str_head(rem)?.ok_or_else(|| Error::ExpectedInstanceAfterDisabledBy { pos: 0, s: rem.into() })?;
+    Ok((rem.trim_start(), enabled, Some(inst)))
+}
+
+
+
+
+
+/// Parses the tree of synchronized triggers that eFLINT draws below a triggered instance (in the
+/// style of Haskell's `Data.Tree.drawTree`), e.g.,
+/// ```plain
+/// foo() (ENABLED)
+/// |
+/// +- bar() (ENABLED)
+/// |  |
+/// |  `- baz() (ENABLED)
+/// |
+/// `- quz() (ENABLED)
+/// ```
+///
+/// The depth of every trigger is derived from its column relative to the first `|`.
+///
+/// # Arguments
+/// - `s`: The input that `rem` is a suffix of, used to find where lines start.
+/// - `rem`: The remaining input, starting at the first `|`.
+/// - `root`: The already parsed trigger at the root of the tree.
+///
+/// # Returns
+/// A tuple of the remaining string and the `root` with its children attached.
+///
+/// # Errors
+/// This function errors if the tree is malformed or nested deeper than [`MAX_DEPTH`].
+fn parse_trigger_tree<'s>(s: &'s str, rem: &'s str, root: Trigger) -> Result<(&'s str, Trigger), Error> {
+    // Find the column of the first bar, which is where the tree starts on every line
+    let start: usize = s.len() - rem.len();
+    let line_start: usize = s[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let base: usize = start - line_start;
+
+    // Go line-by-line, keeping track of the path from the root to the last parsed trigger
+    let mut path: Vec<Trigger> = vec![root];
+    let mut line: &str = &s[line_start..];
+    let mut end: &str = rem;
+    let mut expecting: bool = false;
+    loop {
+        // Strip the indentation up to the tree
+        let text: &str = line.split('\n').next().unwrap_or("");
+        let content: Option<&str> = text.get(..base).filter(|indent| indent.trim().is_empty()).map(|_| text[base..].trim_end());
+
+        // Bars only announce the next trigger
+        if content.is_some_and(|c| c.ends_with('|') && c.chars().all(|c| c == '|' || c == ' ')) {
+            expecting = true;
+            line = &line[text.len()..];
+            line = line.strip_prefix('\n').unwrap_or(line);
+            continue;
+        }
+        if !expecting {
+            break;
+        }
+
+        // If it isn't a bar, then it must be a trigger with a hook and a pipe after some indentation
+        let (newrem, depth): (&str, usize) = match content {
+            Some(content) if !content.is_empty() => {
+                let mut depth: usize = 1;
+                let mut marker: &str = content;
+                while let Some(next) = marker.strip_prefix("|  ").or_else(|| marker.strip_prefix("   ")) {
+                    depth += 1;
+                    marker = next;
+                }
+                (&line[base + content.len() - marker.len()..], depth)
+            },
+            _ => (line.trim_start(), 1),
+        };
+        if !newrem.starts_with('`') && !newrem.starts_with('+') {
+            return Err(Error::ExpectedHookAfterBar { pos: 0, s: newrem.trim_start().into() });
+        }
+        let newrem: &str = newrem[1..].trim_start();
+        if !newrem.starts_with('-') {
+            return Err(Error::ExpectedPipeAfterHook { pos: 0, s: newrem.into() });
+        }
+        let newrem: &str = newrem[1..].trim_start();
+        if depth > MAX_DEPTH {
+            return Err(Error::TooDeeplyNested { pos: 0, s: newrem.into() });
+        }
+
+        // Now parse a new instance / enabled pair
+        let (newrem, inst): (&str, Instance) = match Instance::from_str_head(newrem)? {
+            Some(res) => res,
+            None => return Err(Error::MissingInstanceAfterExecuted { pos: 0, s: newrem.into() }),
+        };
+        let (newrem, enabled, disabled_by): (&str, Option<bool>, Option<Instance>) = parse_trigger_status(newrem)?;
+
+        // Attach it to its parent, which is the last trigger one level up
+        while path.len() > depth {
+            let child: Trigger = path.pop().unwrap();
+            path.last_mut().unwrap().children.push(child);
+        }
+        path.push(Trigger { inst, enabled, disabled_by, children: Vec::new() });
+
+        // Continue at the start of the line we ended up on, if we moved to a new one
+        expecting = false;
+        end = newrem;
+        match s[..s.len() - newrem.len()].rfind('\n') {
+            Some(i) if i >= s.len() - line.len() => line = &s[i + 1..],
+            _ => break,
+        }
+    }
+
+    // Fold the path back into the root
+    while path.len() > 1 {
+        let child: Trigger = path.pop().unwrap();
+        path.last_mut().unwrap().children.push(child);
+    }
+    Ok((end, path.pop().unwrap()))
 }
 
+/// Decides whether a parse failure at the given remainder may be caused by the input being cut off
+/// halfway, such that more input could still resolve it.
+///
+/// eFLINT ends every delta with a newline, so if the offending input already runs past one, no
+/// amount of extra input will fix it.
+///
+/// # Arguments
+/// - `rem`: The remainder of the input at which parsing failed.
+///
+/// # Returns
+/// True if the failure might be resolved by more input, or false if it's definitive.
+#[inline]
+fn may_be_truncated(rem: &str) -> bool { !rem.trim_start().contains('\n') }
+
 
 
 
@@ -109,6 +447,62 @@ pub trait FromStrHead {
 
 
 /***** LIBRARY *****/
+/// Removes the `#<n>>`-prompts that the eFLINT REPL interleaves with its output.
+///
+/// Any `#` followed by digits and/or whitespace and then a `>` is considered a prompt. If the
+/// sequence is interrupted by any other character, it is kept as-is.
+///
+/// # Arguments
+/// - `output`: The raw output of the eFLINT REPL.
+///
+/// # Returns
+/// The output without any prompts, ready to be parsed as a [`Trace`].
+pub fn strip_repl_prompts(output: &str) -> String {
+    let mut clean_output: String = String::with_capacity(output.len());
+    let mut buf: String = String::new();
+    let mut state: usize = 0;
+    for c in output.chars() {
+        // Loop exists to be able to examine some chars again
+        loop {
+            match state {
+                // Finding pounds
+                0 if c == '#' => {
+                    buf.push('#');
+                    state = 1;
+                    break;
+                },
+                0 => {
+                    clean_output.push(c);
+                    break;
+                },
+
+                // Parsing numbers & whitespace
+                1 if c.is_ascii_digit() || c.is_whitespace() => {
+                    buf.push(c);
+                    break;
+                },
+                1 if c == '>' => {
+                    buf.clear();
+                    state = 0;
+                    break;
+                },
+                1 => {
+                    clean_output.push_str(&buf);
+                    buf.clear();
+                    state = 0;
+                    // Don't break, re-try this character
+                },
+
+                _ => unreachable!(),
+            }
+        }
+    }
+    // NOTE: An unterminated prompt at the very end of the output is dropped
+    clean_output
+}
+
+
+
 /// Defines a trace as a whole.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Trace {
@@ -133,7 +527,7 @@ impl FromStr for Trace {
         // Ensure all of the input is consumed this time
         // SAFETY: Note that `Self::from_str_head()` actually never yields `None`
         let (rem, this): (&str, Self) = Self::from_str_head(s)?.unwrap();
-        if rem.trim().is_empty() { Ok(this) } else { Err(Error::UnparsableInput { s: rem.into() }) }
+        if rem.trim().is_empty() { Ok(this) } else { Err(Error::UnparsableInput { pos: s.len() - rem.len(), s: rem.into() }) }
     }
 }
 impl FromStrHead for Trace {
@@ -143,7 +537,7 @@ impl FromStrHead for Trace {
     fn from_str_head(s: &str) -> Result<Option<(&str, Self)>, Self::Error> {
         let mut deltas: Vec<Delta> = Vec::new();
         let mut rem = s.trim_start();
-        while let Some((newrem, newdeltas)) = Vec::<Delta>::from_str_head(rem)? {
+        while let Some((newrem, newdeltas)) = Vec::<Delta>::from_str_head(rem).map_err(|err| err.locate(s))? {
             deltas.extend(newdeltas);
             rem = newrem.trim_start();
         }
@@ -153,6 +547,108 @@ impl FromStrHead for Trace {
 
 
 
+/// Parses a [`Trace`] incrementally from chunks of eFLINT output as they come in.
+///
+/// Chunks may be split anywhere, including halfway through a [`Composite`] or a [`StringLit`].
+/// Any input that may still be extended by the next chunk is retained and re-attempted on the next
+/// [`TraceParser::push()`].
+#[derive(Clone, Debug, Default)]
+pub struct TraceParser {
+    /// The input that has been pushed but not yet turned into deltas.
+    buf:    String,
+    /// The number of bytes before `buf` that have already been turned into deltas.
+    offset: usize,
+}
+impl TraceParser {
+    /// Constructor for the TraceParser that creates it with an empty buffer.
+    ///
+    /// # Returns
+    /// A new TraceParser that is ready to receive input.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Feeds a new chunk of input to the parser.
+    ///
+    /// # Arguments
+    /// - `chunk`: The next piece of the eFLINT output.
+    ///
+    /// # Returns
+    /// The deltas that are complete now that `chunk` has been added. Deltas that may still be
+    /// extended by future input are held back until the next push (or [`TraceParser::finish()`]).
+    ///
+    /// # Errors
+    /// This function errors if the buffered input is malformed in a way that more input cannot fix.
+    /// After that, the parser should be discarded.
+    pub fn push(&mut self, chunk: &str) -> Result<Vec<Delta>, Error> {
+        self.buf.push_str(chunk);
+
+        // Parse as many groups of deltas as we can, remembering where each of them started
+        let mut groups: Vec<(usize, Vec<Delta>)> = Vec::new();
+        let mut rem: &str = self.buf.trim_start();
+        loop {
+            let start: usize = self.buf.len() - rem.len();
+            match Vec::<Delta>::from_str_head(rem) {
+                Ok(Some((newrem, deltas))) => {
+                    groups.push((start, deltas));
+                    rem = newrem.trim_start();
+                },
+                Ok(None) => {
+                    if !may_be_truncated(rem) {
+                        return Err(self.locate(Error::UnparsableInput { pos: 0, s: rem.into() }));
+                    }
+                    break;
+                },
+                Err(err) => {
+                    if !may_be_truncated(err.remainder()) {
+                        return Err(self.locate(err));
+                    }
+                    break;
+                },
+            }
+        }
+
+        // The last group may still be extended by more input (e.g., more violations or digits), so
+        // we hold it back. Earlier groups are final, as they are followed by input we've already seen.
+        let keep: usize = match groups.pop() {
+            Some((start, _)) => start,
+            None => self.buf.len() - rem.len(),
+        };
+        self.buf.drain(..keep);
+        self.offset += keep;
+        Ok(groups.into_iter().flat_map(|(_, deltas)| deltas).collect())
+    }
+
+    /// Signals the end of the input, parsing anything that was still held back.
+    ///
+    /// # Returns
+    /// The remaining deltas.
+    ///
+    /// # Errors
+    /// This function errors if the remaining input is not a complete trace.
+    #[inline]
+    pub fn finish(self) -> Result<Vec<Delta>, Error> {
+        match Trace::from_str(&self.buf) {
+            Ok(trace) => Ok(trace.deltas),
+            Err(err) => Err(self.locate(err)),
+        }
+    }
+
+    /// Locates an error relative to all the input pushed so far (instead of just what's buffered).
+    ///
+    /// # Arguments
+    /// - `err`: The [`Error`] to locate. Its remainder should be a suffix of the buffer.
+    ///
+    /// # Returns
+    /// The same `err`, but with its [position](Error::pos()) in the whole input.
+    #[inline]
+    fn locate(&self, err: Error) -> Error {
+        let pos: usize = self.offset + self.buf.len().saturating_sub(err.remainder().len());
+        err.with_pos(pos)
+    }
+}
+
+
+
 /// Defines a delta, which is like the toplevel instance of the trace.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Delta {
@@ -201,8 +697,8 @@ impl FromStrHead for Vec<Delta> {
         if let Some((rem, quer)) = Query::from_str_head(s).unwrap() {
             return Ok(Some((rem, vec![Delta::Query(quer)])));
         }
-        if let Some((rem, trigs)) = Vec::<Trigger>::from_str_head(s)? {
-            return Ok(Some((rem, trigs.into_iter().map(Delta::Trigger).collect())));
+        if let Some((rem, trig)) = Trigger::from_str_head(s)? {
+            return Ok(Some((rem, vec![Delta::Trigger(trig)])));
         }
         if let Some((rem, viols)) = Vec::<Violation>::from_str_head(s)? {
             return Ok(Some((rem, viols.into_iter().map(Delta::Violation).collect())));
@@ -236,7 +732,7 @@ impl FromStrHead for NewInvariant {
         // Then parse the type name
         match TypeName::from_str_head(rem)? {
             Some((rem, TypeName(name))) => Ok(Some((rem, Self { name }))),
-            None => Err(Error::ExpectedTypeNameAfterNewInvariant { s: rem.into() }),
+            None => Err(Error::ExpectedTypeNameAfterNewInvariant { pos: 0, s: rem.into() }),
         }
     }
 }
@@ -265,7 +761,7 @@ impl FromStrHead for NewType {
         // Then parse the type name
         match TypeName::from_str_head(rem)? {
             Some((rem, TypeName(name))) => Ok(Some((rem, Self { name }))),
-            None => Err(Error::ExpectedTypeNameAfterNewType { s: rem.into() }),
+            None => Err(Error::ExpectedTypeNameAfterNewType { pos: 0, s: rem.into() }),
         }
     }
 }
@@ -344,7 +840,7 @@ impl FromStrHead for Postulation {
         // Finally the instance
         match Instance::from_str_head(rem)? {
             Some((rem, inst)) => Ok(Some((rem, Self { op, inst }))),
-            None => Err(Error::PostulationOpWithoutInstance { op, s: s.into() }),
+            None => Err(Error::PostulationOpWithoutInstance { op, pos: 0, s: s.into() }),
         }
     }
 }
@@ -387,23 +883,78 @@ impl FromStrHead for PostulationOp {
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Trigger {
     /// The triggered instance.
-    pub inst:    Instance,
+    pub inst: Instance,
     /// Whether it was enabled or not.
     ///
     /// Not given for events.
     pub enabled: Option<bool>,
+    /// The instance (e.g., an unmet precondition) that disabled this trigger, if eFLINT reported
+    /// it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled_by: Option<Instance>,
+    /// The triggers that were synchronized with this one (i.e., `Syncs with`), if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Trigger>,
 }
-impl Display for Trigger {
-    #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+impl Trigger {
+    /// Iterates over this trigger and all triggers synchronized with it, depth-first.
+    ///
+    /// # Returns
+    /// An iterator yielding this trigger first, followed by its children (and their children).
+    pub fn iter(&self) -> impl Iterator<Item = &Trigger> {
+        let mut todo: Vec<&Trigger> = vec![self];
+        std::iter::from_fn(move || {
+            let next: &Trigger = todo.pop()?;
+            todo.extend(next.children.iter().rev());
+            Some(next)
+        })
+    }
+
+    /// Writes the children of this trigger as a tree.
+    ///
+    /// # Arguments
+    /// - `f`: The [`Formatter`] to write to.
+    /// - `prefix`: The prefix to write before every line, as determined by our parents.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write to the formatter.
+    fn fmt_children(&self, f: &mut Formatter<'_>, prefix: &str) -> FResult {
+        for (i, child) in self.children.iter().enumerate() {
+            let last: bool = i + 1 == self.children.len();
+            write!(f, "\n{prefix}|\n{prefix}{}", if last { "`- " } else { "+- " })?;
+            child.fmt_head(f)?;
+            child.fmt_children(f, &format!("{prefix}{}", if last { "   " } else { "|  " }))?;
+        }
+        Ok(())
+    }
+
+    /// Writes this trigger without its children.
+    ///
+    /// # Arguments
+    /// - `f`: The [`Formatter`] to write to.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write to the formatter.
+    fn fmt_head(&self, f: &mut Formatter<'_>) -> FResult {
         write!(f, "Triggered {}{}", self.inst, match self.enabled {
             Some(true) => " (ENABLED)",
             Some(false) => " (DISABLED)",
             None => "",
-        })
+        })?;
+        if let Some(disabled_by) = &self.disabled_by {
+            write!(f, " {DISABLED_BY} {disabled_by}")?;
+        }
+        Ok(())
     }
 }
-impl FromStrHead for Vec<Trigger> {
+impl Display for Trigger {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        self.fmt_head(f)?;
+        self.fmt_children(f, "")
+    }
+}
+impl FromStrHead for Trigger {
     type Error = Error;
 
     #[inline]
@@ -416,55 +967,38 @@ impl FromStrHead for Vec<Trigger> {
         let rem = rem.trim_start();
 
         // Now parse the triggered instance
-        let (rem, inst): (&str, Instance) = Instance::from_str_head(rem)?.ok_or_else(|| Error::MissingInstanceAfterExecuted { s: rem.into() })?;
+        let (rem, inst): (&str, Instance) =
+            Instance::from_str_head(rem)?.ok_or_else(|| Error::MissingInstanceAfterExecuted { pos: 0, s: rem.into() })?;
 
-        // Parse the optional 'ENABLED|DISABLED' bizz
-        let rem = rem.trim_start();
-        let (mut rem, enabled): (&str, Option<bool>) = if let Some(rem) = rem.strip_prefix(TRANS_ENABLED) {
-            (rem.trim_start(), Some(true))
-        } else if let Some(rem) = rem.strip_prefix(TRANS_DISABLED) {
-            (rem.trim_start(), Some(false))
-        } else {
-            (rem, None)
-        };
+        // Parse the optional 'ENABLED|DISABLED' bizz (and what disabled it)
+        let (rem, enabled, disabled_by): (&str, Option<bool>, Option<Instance>) = parse_trigger_status(rem)?;
 
         // Now we will parse an optional tree of triggered instances, if `Syncs with` is used.
-        let mut result: Vec<Trigger> = vec![Trigger { inst, enabled }];
-        while rem.starts_with('|') {
-            // Pop the rest of the tree symbol
-            let newrem = rem[1..].trim_start();
-            if !newrem.starts_with('`') {
-                return Err(Error::ExpectedHookAfterBar { s: newrem.into() });
-            }
-            let newrem = newrem[1..].trim_start();
-            if !newrem.starts_with('-') {
-                return Err(Error::ExpectedPipeAfterHook { s: newrem.into() });
-            }
-            let newrem = newrem[1..].trim_start();
-
-            // Now parse a new instance / enabled pair
-            let (newrem, inst): (&str, Instance) = match Instance::from_str_head(newrem)? {
-                Some(res) => res,
-                None => return Err(Error::MissingInstanceAfterExecuted { s: newrem.into() }),
-            };
-
-            // Parse the optional 'ENABLED|DISABLED' bizz
-            let newrem = newrem.trim_start();
-            let (newrem, enabled): (&str, Option<bool>) = if let Some(rem) = newrem.strip_prefix(TRANS_ENABLED) {
-                (rem.trim_start(), Some(true))
-            } else if let Some(rem) = newrem.strip_prefix(TRANS_DISABLED) {
-                (rem.trim_start(), Some(false))
-            } else {
-                (newrem, None)
-            };
+        let this = Trigger { inst, enabled, disabled_by, children: Vec::new() };
+        if rem.starts_with('|') { parse_trigger_tree(s, rem, this).map(Some) } else { Ok(Some((rem, this))) }
+    }
+}
+/// Parses a [`Trigger`] with its tree of synchronized triggers flattened into a list.
+///
+/// The triggers are listed depth-first, starting with the root, and have no children themselves.
+impl FromStrHead for Vec<Trigger> {
+    type Error = Error;
 
-            // Try again
-            result.push(Trigger { inst, enabled });
-            rem = newrem;
-        }
+    #[inline]
+    fn from_str_head(s: &str) -> Result<Option<(&str, Self)>, Self::Error> {
+        let Some((rem, root)) = Trigger::from_str_head(s)? else {
+            return Ok(None);
+        };
 
-        // Done!
-        Ok(Some((rem, result)))
+        // Flatten the tree, moving the children out of their parents
+        let mut res: Vec<Trigger> = Vec::new();
+        let mut todo: Vec<Trigger> = vec![root];
+        while let Some(mut next) = todo.pop() {
+            let children: Vec<Trigger> = std::mem::take(&mut next.children);
+            res.push(next);
+            todo.extend(children.into_iter().rev());
+        }
+        Ok(Some((rem, res)))
     }
 }
 
@@ -543,7 +1077,7 @@ impl FromStrHead for ActViolation {
         // Then parse the instance that was violated
         match Composite::from_str_head(rem)? {
             Some((rem, inst)) => Ok(Some((rem, ActViolation { inst }))),
-            None => Err(Error::ExpectedInstanceAfterDisabledAction { s: rem.into() }),
+            None => Err(Error::ExpectedInstanceAfterDisabledAction { pos: 0, s: rem.into() }),
         }
     }
 }
@@ -572,7 +1106,7 @@ impl FromStrHead for DutyViolation {
         // Then parse the instance that was violated
         match Composite::from_str_head(rem)? {
             Some((rem, inst)) => Ok(Some((rem, DutyViolation { inst }))),
-            None => Err(Error::ExpectedInstanceAfterViolatedDuty { s: rem.into() }),
+            None => Err(Error::ExpectedInstanceAfterViolatedDuty { pos: 0, s: rem.into() }),
         }
     }
 }
@@ -601,7 +1135,7 @@ impl FromStrHead for InvariantViolation {
         // Then parse the invariant that was violated
         match TypeName::from_str_head(rem)? {
             Some((rem, TypeName(name))) => Ok(Some((rem, InvariantViolation { name }))),
-            None => Err(Error::ExpectedTypeNameAfterViolatedInvariant { s: rem.into() }),
+            None => Err(Error::ExpectedTypeNameAfterViolatedInvariant { pos: 0, s: rem.into() }),
         }
     }
 }
@@ -615,6 +1149,8 @@ pub enum Instance {
     StringLit(StringLit),
     /// A naked int literal.
     IntLit(IntLit),
+    /// A naked float literal.
+    FloatLit(FloatLit),
     /// A composite type.
     Composite(Composite),
 }
@@ -624,27 +1160,46 @@ impl Display for Instance {
         match self {
             Self::StringLit(sl) => sl.fmt(f),
             Self::IntLit(il) => il.fmt(f),
+            Self::FloatLit(fl) => fl.fmt(f),
             Self::Composite(c) => c.fmt(f),
         }
     }
 }
-impl FromStrHead for Instance {
-    type Error = Error;
-
-    #[inline]
-    fn from_str_head(s: &str) -> Result<Option<(&str, Self)>, Self::Error> {
+impl Instance {
+    /// Parses an instance that is nested in `depth` other instances.
+    ///
+    /// # Arguments
+    /// - `s`: The string to parse the instance from.
+    /// - `depth`: The number of [`Composite`]s this instance is nested in.
+    ///
+    /// # Returns
+    /// The same as [`FromStrHead::from_str_head()`].
+    ///
+    /// # Errors
+    /// This function errors if the instance is malformed or nested deeper than [`MAX_DEPTH`].
+    fn from_str_head_nested(s: &str, depth: usize) -> Result<Option<(&str, Self)>, Error> {
         if let Some((rem, lit)) = StringLit::from_str_head(s)? {
             return Ok(Some((rem, Instance::StringLit(lit))));
         }
+        // NOTE: Floats go before integers, as the latter would stop at the dot
+        if let Some((rem, lit)) = FloatLit::from_str_head(s)? {
+            return Ok(Some((rem, Instance::FloatLit(lit))));
+        }
         if let Some((rem, lit)) = IntLit::from_str_head(s)? {
             return Ok(Some((rem, Instance::IntLit(lit))));
         }
-        if let Some((rem, comp)) = Composite::from_str_head(s)? {
+        if let Some((rem, comp)) = Composite::from_str_head_nested(s, depth)? {
             return Ok(Some((rem, Instance::Composite(comp))));
         }
         Ok(None)
     }
 }
+impl FromStrHead for Instance {
+    type Error = Error;
+
+    #[inline]
+    fn from_str_head(s: &str) -> Result<Option<(&str, Self)>, Self::Error> { Self::from_str_head_nested(s, 0) }
+}
 
 /// Defines a string literal.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -699,7 +1254,7 @@ impl FromStrHead for StringLit {
         }
 
         // We ran out of input
-        Err(Error::UnterminatedString { s: s.into() })
+        Err(Error::UnterminatedString { pos: 0, s: s.into() })
     }
 }
 
@@ -731,14 +1286,14 @@ impl FromStrHead for IntLit {
             if c.is_ascii_digit() {
                 // Multiply the existing value by 10 to "move them left"
                 if modifier > 0 && value > i64::MAX / 10 || modifier < 0 && value < i64::MIN / 10 {
-                    return Err(Error::OutOfRangeInt { s: s.into() });
+                    return Err(Error::OutOfRangeInt { pos: 0, s: s.into() });
                 }
                 value *= 10;
 
                 // Add the digit
                 let digit: i64 = modifier * ((c as i64) - ('0' as i64));
                 if modifier > 0 && value > i64::MAX - digit || modifier < 0 && value < i64::MIN - digit {
-                    return Err(Error::OutOfRangeInt { s: s.into() });
+                    return Err(Error::OutOfRangeInt { pos: 0, s: s.into() });
                 }
                 value += digit;
 
@@ -756,6 +1311,77 @@ impl FromStrHead for IntLit {
     }
 }
 
+/// Defines a float literal.
+///
+/// Note that equality is defined on the bit patterns of the values, such that floats can be [`Eq`]
+/// (i.e., `NaN == NaN` but `0.0 != -0.0`).
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct FloatLit(pub f64);
+impl Eq for FloatLit {}
+impl PartialEq for FloatLit {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.0.to_bits() == other.0.to_bits() }
+}
+impl Display for FloatLit {
+    // NOTE: We use `Debug`, as that always writes a fractional part or exponent (`1.0` instead
+    // of `1`), such that the result parses as a float again.
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> FResult { write!(f, "{:?}", self.0) }
+}
+impl FromStrHead for FloatLit {
+    type Error = Error;
+
+    #[inline]
+    fn from_str_head(s: &str) -> Result<Option<(&str, Self)>, Self::Error> {
+        // Parse negatives, like for integers
+        let mut negative: bool = false;
+        let mut rem: &str = s;
+        while let Some(newrem) = rem.strip_prefix('-') {
+            negative = !negative;
+            rem = newrem;
+        }
+        let body: &str = rem;
+
+        // Parse the integral part
+        let n_int: usize = rem.find(|c: char| !c.is_ascii_digit()).unwrap_or(rem.len());
+        if n_int == 0 {
+            return Ok(None);
+        }
+        rem = &rem[n_int..];
+
+        // Parse the optional fractional part
+        let mut seen_frac: bool = false;
+        if let Some(frac) = rem.strip_prefix('.') {
+            let n_frac: usize = frac.find(|c: char| !c.is_ascii_digit()).unwrap_or(frac.len());
+            if n_frac > 0 {
+                rem = &frac[n_frac..];
+                seen_frac = true;
+            }
+        }
+
+        // Parse the optional exponent (as Haskell likes to write, e.g., `1.0e-2`)
+        let mut seen_exp: bool = false;
+        if let Some(exp) = rem.strip_prefix(['e', 'E']) {
+            let digits: &str = exp.strip_prefix(['-', '+']).unwrap_or(exp);
+            let n_exp: usize = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+            if n_exp > 0 {
+                rem = &digits[n_exp..];
+                seen_exp = true;
+            }
+        }
+
+        // Without either, it's an integer
+        if !seen_frac && !seen_exp {
+            return Ok(None);
+        }
+        let value: f64 = match body[..body.len() - rem.len()].parse::<f64>() {
+            Ok(value) if value.is_finite() => value,
+            _ => return Err(Error::OutOfRangeFloat { pos: 0, s: s.into() }),
+        };
+        Ok(Some((rem, FloatLit(if negative { -value } else { value }))))
+    }
+}
+
 /// Defines a composite type.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Composite {
@@ -770,11 +1396,23 @@ impl Display for Composite {
         write!(f, "{}({})", self.name, self.args.iter().map(Instance::to_string).collect::<Vec<String>>().join(", "))
     }
 }
-impl FromStrHead for Composite {
-    type Error = Error;
+impl Composite {
+    /// Parses a composite that is nested in `depth` other composites.
+    ///
+    /// # Arguments
+    /// - `s`: The string to parse the composite from.
+    /// - `depth`: The number of [`Composite`]s this composite is nested in.
+    ///
+    /// # Returns
+    /// The same as [`FromStrHead::from_str_head()`].
+    ///
+    /// # Errors
+    /// This function errors if the composite is malformed or nested deeper than [`MAX_DEPTH`].
+    fn from_str_head_nested(s: &str, depth: usize) -> Result<Option<(&str, Self)>, Error> {
+        if depth >= MAX_DEPTH {
+            return Err(Error::TooDeeplyNested { pos: 0, s: s.into() });
+        }
 
-    #[inline]
-    fn from_str_head(s: &str) -> Result<Option<(&str, Self)>, Self::Error> {
         // Parse an identifier type first
         let (rem, name): (&str, String) = match TypeName::from_str_head(s)? {
             Some((rem, TypeName(name))) => (rem, name),
@@ -790,7 +1428,7 @@ impl FromStrHead for Composite {
 
         // Parse instances delimited by commas
         let mut args: Vec<Instance> = Vec::new();
-        while let Some((newrem, inst)) = Instance::from_str_head(rem)? {
+        while let Some((newrem, inst)) = Instance::from_str_head_nested(rem, depth + 1)? {
             // Accept the instance
             args.push(inst);
             rem = newrem;
@@ -801,7 +1439,7 @@ impl FromStrHead for Composite {
             if matches!(next, Some(')')) {
                 return Ok(Some((&rem[1..], Self { name, args })));
             } else if !matches!(next, Some(',')) {
-                return Err(Error::ExpectedComma { s: newrem.into() });
+                return Err(Error::ExpectedComma { pos: 0, s: newrem.into() });
             }
             rem = rem[1..].trim_start();
         }
@@ -810,10 +1448,16 @@ impl FromStrHead for Composite {
         if matches!(rem.chars().next(), Some(')')) {
             Ok(Some((&rem[1..], Self { name, args })))
         } else {
-            Err(Error::UnterminatedParen { s: rem.into() })
+            Err(Error::UnterminatedParen { pos: 0, s: rem.into() })
         }
     }
 }
+impl FromStrHead for Composite {
+    type Error = Error;
+
+    #[inline]
+    fn from_str_head(s: &str) -> Result<Option<(&str, Self)>, Self::Error> { Self::from_str_head_nested(s, 0) }
+}
 
 
 
@@ -879,7 +1523,7 @@ impl FromStrHead for TypeName {
         } else if matches!(kind, Kind::Uninit) {
             Ok(None)
         } else {
-            Err(Error::UnterminatedDelim { delim: (if matches!(kind, Kind::Brackets) { ']' } else { '>' }), s: s.into() })
+            Err(Error::UnterminatedDelim { delim: (if matches!(kind, Kind::Brackets) { ']' } else { '>' }), pos: 0, s: s.into() })
         }
     }
 }
@@ -893,12 +1537,96 @@ impl FromStrHead for TypeName {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_strip_repl_prompts() {
+        // Prompts of various widths
+        assert_eq!(strip_repl_prompts("#1> +foo\n#2> query successful\n"), " +foo\n query successful\n");
+        assert_eq!(strip_repl_prompts("#12>#345>+foo"), "+foo");
+        assert_eq!(strip_repl_prompts("#1 2\n >bar"), "bar");
+
+        // Interrupted prompts are kept
+        assert_eq!(strip_repl_prompts("#foo"), "#foo");
+        assert_eq!(strip_repl_prompts("#12x>"), "#12x>");
+        assert_eq!(strip_repl_prompts("##1>x"), "#x");
+        assert_eq!(strip_repl_prompts("a > b"), "a > b");
+
+        // Unterminated prompts at the end are dropped
+        assert_eq!(strip_repl_prompts("foo#12"), "foo");
+    }
+
+    #[test]
+    fn test_trace_parser() {
+        let input: &str = "New type foo\nNew invariant bar\n+foo(\"Amy\\n\", -42)\nquery successful\nexecuted transition: \ngo(string(\"y\")) \
+                           (DISABLED) disabled by: foo(\"Bob\")\n|\n+- go(string(\"x\")) (ENABLED)\n|  |\n|  `- go(\"z\")\n|\n`- go(\"w\") \
+                           (DISABLED)\nviolations:\ndisabled action: foo()\nviolated duty!: bar(123)\nviolated invariant!: baz\n~foo(\"Amy\\n\", \
+                           -42)\nquery failed\n";
+        let expected: Vec<Delta> = Trace::from_str(input).unwrap().deltas;
+        assert_eq!(expected.len(), 10);
+
+        // Feeding it all at once should give the same result as parsing it in one go
+        let mut parser = TraceParser::new();
+        let mut deltas: Vec<Delta> = parser.push(input).unwrap();
+        deltas.extend(parser.finish().unwrap());
+        assert_eq!(deltas, expected);
+
+        // Then split it at every possible boundary
+        for i in (0..=input.len()).filter(|i| input.is_char_boundary(*i)) {
+            let mut parser = TraceParser::new();
+            let mut deltas: Vec<Delta> = parser.push(&input[..i]).unwrap_or_else(|err| panic!("Failed to push {:?}: {err}", &input[..i]));
+            deltas.extend(parser.push(&input[i..]).unwrap_or_else(|err| panic!("Failed to push {:?}: {err}", &input[i..])));
+            deltas.extend(parser.finish().unwrap());
+            assert_eq!(deltas, expected, "Split at byte {i}");
+        }
+
+        // And byte-by-byte
+        let mut parser = TraceParser::new();
+        let mut deltas: Vec<Delta> = Vec::new();
+        for i in 0..input.len() {
+            deltas.extend(parser.push(&input[i..i + 1]).unwrap());
+        }
+        deltas.extend(parser.finish().unwrap());
+        assert_eq!(deltas, expected);
+
+        // Malformed input is reported as soon as it's clear more input won't fix it
+        let mut parser = TraceParser::new();
+        assert_eq!(parser.push("+foo(\"a\" \"b\")"), Ok(vec![]));
+        assert_eq!(parser.push("\n"), Err(Error::ExpectedComma { pos: 8, s: " \"b\")\n".into() }));
+        let mut parser = TraceParser::new();
+        assert_eq!(parser.push("+foo(\"a"), Ok(vec![]));
+        assert_eq!(parser.finish(), Err(Error::UnterminatedString { pos: 5, s: "\"a".into() }));
+    }
+
+    #[test]
+    fn test_error_offsets() {
+        // Errors are located in the input given to the trace
+        let err: Error = Trace::from_str("New type foo\n+foo(quz() bar())\n").unwrap_err();
+        assert_eq!(err, Error::ExpectedComma { pos: 23, s: " bar())\n".into() });
+        assert_eq!(err.to_string(), "Expected a comma at offset 23 (\" bar())\\n\")");
+        assert_eq!(Trace::from_str("query failed\nfoo"), Err(Error::UnparsableInput { pos: 13, s: "foo".into() }));
+
+        // Or in the whole stream, when parsing in chunks
+        let mut parser = TraceParser::new();
+        assert_eq!(parser.push("New type foo\nNew type bar\n").map(|deltas| deltas.len()), Ok(1));
+        assert_eq!(parser.push("+foo(\"a\" \"b\")\n"), Err(Error::ExpectedComma { pos: 34, s: " \"b\")\n".into() }));
+
+        // Diagnostics point at the rest of the offending line
+        let input: &str = "New type foo\n+foo(quz() bar())\n";
+        assert_eq!(ParseDiagnostic::new(input, Trace::from_str(input).unwrap_err()).snippet(), " bar())");
+        let input: &str = "query failed\nfoo";
+        assert_eq!(ParseDiagnostic::new(input, Trace::from_str(input).unwrap_err()).snippet(), "foo");
+
+        // Locating an error directly is idempotent
+        let err: Error = Error::UnterminatedParen { pos: 0, s: "quz".into() }.locate("foo(quz");
+        assert_eq!(err.pos(), 4);
+        assert_eq!(err.locate("foo(quz").pos(), 4);
+    }
+
     #[test]
     fn test_parse_newtype() {
         assert_eq!(NewType::from_str_head("New type foo"), Ok(Some(("", NewType { name: "foo".into() }))));
         assert_eq!(NewType::from_str_head("New type a"), Ok(Some(("", NewType { name: "a".into() }))));
         assert_eq!(NewType::from_str_head("New type a a"), Ok(Some((" a", NewType { name: "a".into() }))));
-        assert_eq!(NewType::from_str_head("New type"), Err(Error::ExpectedTypeNameAfterNewType { s: "".into() }));
+        assert_eq!(NewType::from_str_head("New type"), Err(Error::ExpectedTypeNameAfterNewType { pos: 0, s: "".into() }));
         assert_eq!(NewType::from_str_head("ew type foo"), Ok(None));
         assert_eq!(NewType::from_str_head(""), Ok(None));
     }
@@ -963,62 +1691,157 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_trigger() {
-        assert_eq!(
-            Vec::<Trigger>::from_str_head("executed transition:\ngo(string(\"y\"))"),
-            Ok(Some(("", vec![Trigger {
-                inst:    Instance::Composite(Composite {
-                    name: "go".into(),
-                    args: vec![Instance::Composite(Composite { name: "string".into(), args: vec![Instance::StringLit(StringLit("y".into()))] })],
-                }),
-                enabled: None,
-            }])))
-        );
+    fn test_parse_trigger_flat() {
+        let go = |arg: &str, enabled: Option<bool>| Trigger {
+            inst: Instance::Composite(Composite {
+                name: "go".into(),
+                args: vec![Instance::Composite(Composite { name: "string".into(), args: vec![Instance::StringLit(StringLit(arg.into()))] })],
+            }),
+            enabled,
+            disabled_by: None,
+            children: vec![],
+        };
+        assert_eq!(Vec::<Trigger>::from_str_head("executed transition:\ngo(string(\"y\"))"), Ok(Some(("", vec![go("y", None)]))));
+        assert_eq!(Vec::<Trigger>::from_str_head("executed transition: \ngo(string(\"y\")) (ENABLED)"), Ok(Some(("", vec![go("y", Some(true))]))));
+        assert_eq!(Vec::<Trigger>::from_str_head("executed transition: go(string(\"y\")) (DISABLED)"), Ok(Some(("", vec![go("y", Some(false))]))));
         assert_eq!(
-            Vec::<Trigger>::from_str_head("executed transition: \ngo(string(\"y\")) (ENABLED)"),
-            Ok(Some(("", vec![Trigger {
-                inst:    Instance::Composite(Composite {
-                    name: "go".into(),
-                    args: vec![Instance::Composite(Composite { name: "string".into(), args: vec![Instance::StringLit(StringLit("y".into()))] })],
-                }),
-                enabled: Some(true),
-            }])))
+            Vec::<Trigger>::from_str_head(
+                r#"executed transition: 
+                go(string("y")) (DISABLED)
+                |
+                `- go(string("x")) (DISABLED)
+                "#
+            ),
+            Ok(Some(("", vec![go("y", Some(false)), go("x", Some(false))])))
         );
         assert_eq!(
-            Vec::<Trigger>::from_str_head("executed transition: go(string(\"y\")) (DISABLED)"),
-            Ok(Some(("", vec![Trigger {
-                inst:    Instance::Composite(Composite {
-                    name: "go".into(),
-                    args: vec![Instance::Composite(Composite { name: "string".into(), args: vec![Instance::StringLit(StringLit("y".into()))] })],
-                }),
-                enabled: Some(false),
-            }])))
+            Vec::<Trigger>::from_str_head(
+                "executed transition: go(string(\"a\"))\n|\n+- go(string(\"b\"))\n|  |\n|  `- go(string(\"c\"))\n|\n`- go(string(\"d\"))"
+            ),
+            Ok(Some(("", vec![go("a", None), go("b", None), go("c", None), go("d", None)])))
         );
+        assert_eq!(Vec::<Trigger>::from_str_head("+foo"), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_trigger() {
+        let go = |arg: &str, enabled: Option<bool>, children: Vec<Trigger>| Trigger {
+            inst: Instance::Composite(Composite {
+                name: "go".into(),
+                args: vec![Instance::Composite(Composite { name: "string".into(), args: vec![Instance::StringLit(StringLit(arg.into()))] })],
+            }),
+            enabled,
+            disabled_by: None,
+            children,
+        };
+        assert_eq!(Trigger::from_str_head("executed transition:\ngo(string(\"y\"))"), Ok(Some(("", go("y", None, vec![])))));
+        assert_eq!(Trigger::from_str_head("executed transition: \ngo(string(\"y\")) (ENABLED)"), Ok(Some(("", go("y", Some(true), vec![])))));
+        assert_eq!(Trigger::from_str_head("executed transition: go(string(\"y\")) (DISABLED)"), Ok(Some(("", go("y", Some(false), vec![])))));
         assert_eq!(
-            Vec::<Trigger>::from_str_head(
+            Trigger::from_str_head(
                 r#"executed transition: 
                 go(string("y")) (DISABLED)
                 |
                 `- go(string("x")) (DISABLED)
                 "#
             ),
-            Ok(Some(("", vec![
-                Trigger {
-                    inst:    Instance::Composite(Composite {
-                        name: "go".into(),
-                        args: vec![Instance::Composite(Composite { name: "string".into(), args: vec![Instance::StringLit(StringLit("y".into()))] })],
-                    }),
-                    enabled: Some(false),
-                },
-                Trigger {
-                    inst:    Instance::Composite(Composite {
-                        name: "go".into(),
-                        args: vec![Instance::Composite(Composite { name: "string".into(), args: vec![Instance::StringLit(StringLit("x".into()))] })],
-                    }),
-                    enabled: Some(false),
-                }
-            ])))
+            Ok(Some(("", go("y", Some(false), vec![go("x", Some(false), vec![])]))))
+        );
+        assert_eq!(
+            Trigger::from_str_head("executed transition: go() (DISABLED) disabled by: foo()"),
+            Ok(Some(("", Trigger {
+                inst: Instance::Composite(Composite { name: "go".into(), args: vec![] }),
+                enabled: Some(false),
+                disabled_by: Some(Instance::Composite(Composite { name: "foo".into(), args: vec![] })),
+                children: vec![],
+            })))
+        );
+        assert_eq!(
+            Trigger::from_str_head("executed transition: go() (DISABLED) disabled by: +"),
+            Err(Error::ExpectedInstanceAfterDisabledBy { pos: 0, s: "+".into() })
+        );
+        assert_eq!(
+            Trigger::from_str_head("executed transition: go() (DISABLED)\n|\n+foo()"),
+            Err(Error::ExpectedPipeAfterHook { pos: 0, s: "foo()".into() })
+        );
+        assert_eq!(
+            Trigger::from_str_head("executed transition: go() (DISABLED)\n|\nfoo()"),
+            Err(Error::ExpectedHookAfterBar { pos: 0, s: "foo()".into() })
+        );
+    }
+
+    #[test]
+    fn test_trigger_display_roundtrip() {
+        let go = |enabled: Option<bool>, disabled_by: Option<&str>| Trigger {
+            inst: Instance::Composite(Composite { name: "go".into(), args: vec![Instance::StringLit(StringLit("y".into()))] }),
+            enabled,
+            disabled_by: disabled_by.map(|name| Instance::Composite(Composite { name: name.into(), args: vec![] })),
+            children: vec![],
+        };
+        for trigger in [go(None, None), go(Some(true), None), go(Some(false), None), go(Some(false), Some("foo")), go(None, Some("bar"))] {
+            // NOTE: We display triggers as "Triggered", but eFLINT calls them executed transitions
+            let s: String = trigger.to_string().replacen("Triggered", EXEC_TRANS, 1);
+            assert_eq!(Trigger::from_str_head(&s), Ok(Some(("", trigger))), "Failed to parse back {s:?}");
+        }
+        assert_eq!(go(Some(false), Some("foo")).to_string(), "Triggered go(\"y\") (DISABLED) disabled by: foo()");
+    }
+
+    #[test]
+    fn test_parse_trigger_tree() {
+        let go = |arg: &str, children: Vec<Trigger>| Trigger {
+            inst: Instance::Composite(Composite { name: "go".into(), args: vec![Instance::StringLit(StringLit(arg.into()))] }),
+            enabled: Some(true),
+            disabled_by: None,
+            children,
+        };
+
+        // Two levels, with the next delta following it
+        assert_eq!(
+            Trigger::from_str_head(
+                "executed transition:\ngo(\"a\") (ENABLED)\n|\n+- go(\"b\") (ENABLED)\n|  |\n|  `- go(\"c\") (ENABLED)\n|\n`- go(\"d\") \
+                 (ENABLED)\n+foo()\n"
+            ),
+            Ok(Some(("+foo()\n", go("a", vec![go("b", vec![go("c", vec![])]), go("d", vec![])]))))
+        );
+
+        // Three levels, indented
+        let input: &str = r#"executed transition:
+            go("a") (ENABLED)
+            |
+            `- go("b") (ENABLED)
+               |
+               +- go("c") (ENABLED)
+               |  |
+               |  `- go("d") (ENABLED)
+               |
+               `- go("e") (ENABLED)
+                  |
+                  +- go("f") (ENABLED)
+                  |
+                  `- go("g") (ENABLED)
+            query successful"#;
+        let tree: Trigger = go("a", vec![go("b", vec![go("c", vec![go("d", vec![])]), go("e", vec![go("f", vec![]), go("g", vec![])])])]);
+        assert_eq!(Trigger::from_str_head(input), Ok(Some(("query successful", tree.clone()))));
+        assert_eq!(tree.iter().map(|t| t.inst.to_string()).collect::<Vec<String>>(), vec![
+            "go(\"a\")",
+            "go(\"b\")",
+            "go(\"c\")",
+            "go(\"d\")",
+            "go(\"e\")",
+            "go(\"f\")",
+            "go(\"g\")"
+        ]);
+
+        // Printing it draws the same tree
+        assert_eq!(
+            tree.to_string(),
+            "Triggered go(\"a\") (ENABLED)\n|\n`- Triggered go(\"b\") (ENABLED)\n   |\n   +- Triggered go(\"c\") (ENABLED)\n   |  |\n   |  `- \
+             Triggered go(\"d\") (ENABLED)\n   |\n   `- Triggered go(\"e\") (ENABLED)\n      |\n      +- Triggered go(\"f\") (ENABLED)\n      |\n      `- \
+             Triggered go(\"g\") (ENABLED)"
         );
+
+        // A bar must be followed by a trigger
+        assert_eq!(Trigger::from_str_head("executed transition: go(\"a\")\n|\n"), Err(Error::ExpectedHookAfterBar { pos: 0, s: "".into() }));
     }
 
     #[test]
@@ -1050,7 +1873,7 @@ mod tests {
             ActViolation::from_str_head("disabled action: foo()"),
             Ok(Some(("", ActViolation { inst: Composite { name: "foo".into(), args: vec![] } })))
         );
-        assert_eq!(ActViolation::from_str_head("disabled action: foo"), Err(Error::ExpectedInstanceAfterDisabledAction { s: "foo".into() }));
+        assert_eq!(ActViolation::from_str_head("disabled action: foo"), Err(Error::ExpectedInstanceAfterDisabledAction { pos: 0, s: "foo".into() }));
         assert_eq!(ActViolation::from_str_head("disabled actio: foo()"), Ok(None));
     }
 
@@ -1060,7 +1883,7 @@ mod tests {
             DutyViolation::from_str_head("violated duty!: foo()"),
             Ok(Some(("", DutyViolation { inst: Composite { name: "foo".into(), args: vec![] } })))
         );
-        assert_eq!(DutyViolation::from_str_head("violated duty!: foo"), Err(Error::ExpectedInstanceAfterViolatedDuty { s: "foo".into() }));
+        assert_eq!(DutyViolation::from_str_head("violated duty!: foo"), Err(Error::ExpectedInstanceAfterViolatedDuty { pos: 0, s: "foo".into() }));
         assert_eq!(DutyViolation::from_str_head("violated duty! foo()"), Ok(None));
     }
 
@@ -1070,7 +1893,7 @@ mod tests {
         assert_eq!(InvariantViolation::from_str_head("violated invariant!: foo()"), Ok(Some(("()", InvariantViolation { name: "foo".into() }))));
         assert_eq!(
             InvariantViolation::from_str_head("violated invariant!: AMY"),
-            Err(Error::ExpectedTypeNameAfterViolatedInvariant { s: "AMY".into() })
+            Err(Error::ExpectedTypeNameAfterViolatedInvariant { pos: 0, s: "AMY".into() })
         );
         assert_eq!(InvariantViolation::from_str_head("violated invariant! foo"), Ok(None));
     }
@@ -1092,7 +1915,7 @@ mod tests {
         assert_eq!(StringLit::from_str_head("\"Hello, world!\""), Ok(Some(("", StringLit("Hello, world!".into())))));
         assert_eq!(StringLit::from_str_head("\"Hello, world!\\n\""), Ok(Some(("", StringLit("Hello, world!\n".into())))));
         assert_eq!(StringLit::from_str_head("\"Hello, world!\" skibidi"), Ok(Some((" skibidi", StringLit("Hello, world!".into())))));
-        assert_eq!(StringLit::from_str_head("\"Hello, world!"), Err(Error::UnterminatedString { s: "\"Hello, world!".into() }));
+        assert_eq!(StringLit::from_str_head("\"Hello, world!"), Err(Error::UnterminatedString { pos: 0, s: "\"Hello, world!".into() }));
         assert_eq!(StringLit::from_str_head("Hello, world!\""), Ok(None));
     }
 
@@ -1105,14 +1928,51 @@ mod tests {
         assert_eq!(IntLit::from_str_head("42 skibidi"), Ok(Some((" skibidi", IntLit(42)))));
         assert_eq!(IntLit::from_str_head("9223372036854775807"), Ok(Some(("", IntLit(9223372036854775807)))));
         assert_eq!(IntLit::from_str_head("-9223372036854775808"), Ok(Some(("", IntLit(-9223372036854775808)))));
-        assert_eq!(IntLit::from_str_head("9223372036854775808"), Err(Error::OutOfRangeInt { s: "9223372036854775808".into() }));
-        assert_eq!(IntLit::from_str_head("-9223372036854775809"), Err(Error::OutOfRangeInt { s: "-9223372036854775809".into() }));
-        assert_eq!(IntLit::from_str_head("98216387163871623817623817632"), Err(Error::OutOfRangeInt { s: "98216387163871623817623817632".into() }));
-        assert_eq!(IntLit::from_str_head("-98216387163871623817623817632"), Err(Error::OutOfRangeInt { s: "-98216387163871623817623817632".into() }));
+        assert_eq!(IntLit::from_str_head("9223372036854775808"), Err(Error::OutOfRangeInt { pos: 0, s: "9223372036854775808".into() }));
+        assert_eq!(IntLit::from_str_head("-9223372036854775809"), Err(Error::OutOfRangeInt { pos: 0, s: "-9223372036854775809".into() }));
+        assert_eq!(
+            IntLit::from_str_head("98216387163871623817623817632"),
+            Err(Error::OutOfRangeInt { pos: 0, s: "98216387163871623817623817632".into() })
+        );
+        assert_eq!(
+            IntLit::from_str_head("-98216387163871623817623817632"),
+            Err(Error::OutOfRangeInt { pos: 0, s: "-98216387163871623817623817632".into() })
+        );
         assert_eq!(IntLit::from_str_head("---a"), Ok(None));
         assert_eq!(IntLit::from_str_head("\"Hello, world!\""), Ok(None));
     }
 
+    #[test]
+    fn test_parse_float_lit() {
+        assert_eq!(FloatLit::from_str_head("4.25"), Ok(Some(("", FloatLit(4.25)))));
+        assert_eq!(FloatLit::from_str_head("-4.25"), Ok(Some(("", FloatLit(-4.25)))));
+        assert_eq!(FloatLit::from_str_head("--4.25"), Ok(Some(("", FloatLit(4.25)))));
+        assert_eq!(FloatLit::from_str_head("1.0e-2"), Ok(Some(("", FloatLit(0.01)))));
+        assert_eq!(FloatLit::from_str_head("1e16"), Ok(Some(("", FloatLit(1e16)))));
+        assert_eq!(FloatLit::from_str_head("4.25)"), Ok(Some((")", FloatLit(4.25)))));
+        assert_eq!(FloatLit::from_str_head("3.foo"), Ok(None));
+        assert_eq!(FloatLit::from_str_head("3e"), Ok(None));
+        assert_eq!(FloatLit::from_str_head("42"), Ok(None));
+        assert_eq!(FloatLit::from_str_head(".5"), Ok(None));
+        assert_eq!(FloatLit::from_str_head("1e999"), Err(Error::OutOfRangeFloat { pos: 0, s: "1e999".into() }));
+
+        // Integers still parse as integers
+        assert_eq!(Instance::from_str_head("42."), Ok(Some((".", Instance::IntLit(IntLit(42))))));
+        assert_eq!(
+            Composite::from_str_head("foo(4.25, -2.5, 7)"),
+            Ok(Some(("", Composite {
+                name: "foo".into(),
+                args: vec![Instance::FloatLit(FloatLit(4.25)), Instance::FloatLit(FloatLit(-2.5)), Instance::IntLit(IntLit(7))],
+            })))
+        );
+
+        // Printing them parses back to the same value
+        for value in [0.0, -0.0, 1.0, -1.0, 4.25, 0.01, 1e-7, 1e16, -123456.789, f64::MAX, f64::MIN_POSITIVE] {
+            let inst: Instance = Instance::FloatLit(FloatLit(value));
+            assert_eq!(Instance::from_str_head(&inst.to_string()), Ok(Some(("", inst))));
+        }
+    }
+
     #[test]
     fn test_parse_composite() {
         assert_eq!(Composite::from_str_head("foo()"), Ok(Some(("", Composite { name: "foo".into(), args: vec![] }))));
@@ -1149,9 +2009,15 @@ mod tests {
             })))
         );
         assert_eq!(Composite::from_str_head("foo"), Ok(None));
-        assert_eq!(Composite::from_str_head("foo("), Err(Error::UnterminatedParen { s: "".into() }));
-        assert_eq!(Composite::from_str_head("foo(quz"), Err(Error::UnterminatedParen { s: "quz".into() }));
-        assert_eq!(Composite::from_str_head("foo(quz() bar())"), Err(Error::ExpectedComma { s: " bar())".into() }));
+        assert_eq!(Composite::from_str_head("foo("), Err(Error::UnterminatedParen { pos: 0, s: "".into() }));
+        assert_eq!(Composite::from_str_head("foo(quz"), Err(Error::UnterminatedParen { pos: 0, s: "quz".into() }));
+        assert_eq!(Composite::from_str_head("foo(quz() bar())"), Err(Error::ExpectedComma { pos: 0, s: " bar())".into() }));
+
+        // Nesting is limited to avoid overflowing the stack
+        let nested: String = format!("{}{}", "a(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert!(matches!(Composite::from_str_head(&nested), Ok(Some(("", _)))));
+        let nested: String = "a(".repeat(MAX_DEPTH + 1);
+        assert!(matches!(Composite::from_str_head(&nested), Err(Error::TooDeeplyNested { .. })));
     }
 
 
@@ -1175,9 +2041,9 @@ mod tests {
         assert_eq!(TypeName::from_str_head("[[nested brackets]]"), Ok(Some(("", TypeName("[[nested brackets]]".into())))));
         assert_eq!(TypeName::from_str_head("<<nested brackets>>"), Ok(Some(("", TypeName("<<nested brackets>>".into())))));
         assert_eq!(TypeName::from_str_head("Foo"), Ok(None));
-        assert_eq!(TypeName::from_str_head("[unterminated"), Err(Error::UnterminatedDelim { delim: ']', s: "[unterminated".into() }));
-        assert_eq!(TypeName::from_str_head("[[unterminated]"), Err(Error::UnterminatedDelim { delim: ']', s: "[[unterminated]".into() }));
-        assert_eq!(TypeName::from_str_head("<unterminated"), Err(Error::UnterminatedDelim { delim: '>', s: "<unterminated".into() }));
-        assert_eq!(TypeName::from_str_head("<<unterminated>"), Err(Error::UnterminatedDelim { delim: '>', s: "<<unterminated>".into() }));
+        assert_eq!(TypeName::from_str_head("[unterminated"), Err(Error::UnterminatedDelim { delim: ']', pos: 0, s: "[unterminated".into() }));
+        assert_eq!(TypeName::from_str_head("[[unterminated]"), Err(Error::UnterminatedDelim { delim: ']', pos: 0, s: "[[unterminated]".into() }));
+        assert_eq!(TypeName::from_str_head("<unterminated"), Err(Error::UnterminatedDelim { delim: '>', pos: 0, s: "<unterminated".into() }));
+        assert_eq!(TypeName::from_str_head("<<unterminated>"), Err(Error::UnterminatedDelim { delim: '>', pos: 0, s: "<<unterminated>".into() }));
     }
 }
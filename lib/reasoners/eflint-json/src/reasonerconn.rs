@@ -4,7 +4,7 @@
 //  Created:
 //    09 Oct 2024, 15:52:06
 //  Last edited:
-//    06 May 2025, 11:21:41
+//    16 Oct 2026, 01:50:03
 //  Auto updated?
 //    Yes
 //
@@ -13,27 +13,82 @@
 //
 
 use std::borrow::Cow;
-use std::collections::HashMap;
-use std::fmt::Display;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display, Formatter, Result as FResult};
+use std::io::Write;
 use std::marker::PhantomData;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use eflint_json::spec::auxillary::Version;
 use eflint_json::spec::{Phrase, PhraseResult, Request, RequestCommon, RequestPhrases, ResponsePhrases};
 use error_trace::{ErrorTrace as _, Trace};
+use futures_util::future::{join_all, try_join4};
+use futures_util::stream;
+use reqwest::header::{CONTENT_TYPE, HeaderMap};
+use reqwest::{Certificate, RequestBuilder, StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use share::formatters::BlockFormatter;
 use spec::auditlogger::{AuditLogger, SessionedAuditLogger};
 use spec::reasonerconn::{ReasonerConnector, ReasonerContext, ReasonerResponse};
 use thiserror::Error;
-use tracing::{debug, instrument};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, instrument, warn};
 
 use crate::reasons::ReasonHandler;
 use crate::spec::EFlintable;
 
+
+/***** CONSTANTS *****/
+/// The size (in bytes) of the chunks in which streamed requests are sent to the reasoner.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of chunks of a streamed request that are buffered before waiting for them to be sent.
+const CHUNK_BUFFER: usize = 4;
+
+/// The empty list of phrases in a serialized [`Request`] that streamed phrases are written into.
+const PHRASES_MARKER: &[u8] = b"\"phrases\":[]";
+
+
+
+
+
 /***** ERRORS *****/
 /// Defines the errors returned by the [`EFlintJsonReasonerConnector`].
 #[derive(Debug, Error)]
 pub enum Error<R, S, Q> {
+    /// One of the given root certificate bundles is not valid PEM.
+    #[error("Failed to parse root certificate bundle {i} as PEM")]
+    Certificate { i: usize, source: reqwest::Error },
+    /// Failed to build the HTTP client used to reach the reasoner.
+    #[error("Failed to build HTTP client")]
+    ClientBuild { source: reqwest::Error },
+    /// The reasoner subprocess exited with a non-zero exit code.
+    #[error(
+        "Reasoner command {cmd:?} failed with {status}\n\n{stderr}\n",
+         stderr = BlockFormatter::new("Stderr:", stderr)
+    )]
+    CommandFailure { cmd: String, status: ExitStatus, stderr: String },
+    /// Failed to communicate with or wait for the reasoner subprocess.
+    #[error("Failed to communicate with reasoner command {cmd:?}")]
+    CommandJoin { cmd: String, source: std::io::Error },
+    /// Failed to spawn the reasoner subprocess.
+    #[error("Failed to spawn reasoner command {cmd:?}")]
+    CommandSpawn { cmd: String, source: std::io::Error },
+    /// The reasoner subprocess did not complete in time.
+    #[error("Reasoner command {cmd:?} did not complete within {after:?}")]
+    CommandTimeout { cmd: String, after: Duration },
+    /// The command given to run the reasoner as a subprocess was empty.
+    #[error("Reasoner command cannot be empty")]
+    EmptyCommand,
+    /// The address of the reasoner is not a valid URL.
+    #[error("Failed to parse reasoner address {addr:?} as a URL")]
+    IllegalAddress { addr: String, source: url::ParseError },
     /// Failed to log the context of the reasoner.
     #[error("Failed to log the reasoner's context to {to}")]
     LogContext { to: &'static str, source: Trace },
@@ -49,18 +104,36 @@ pub enum Error<R, S, Q> {
     /// Failed to send a [`RequestPhrases`] to the remote reasoner.
     #[error("Failed to set PhrasesRequest to reasoner at {addr:?}")]
     ReasonerRequest { addr: String, source: reqwest::Error },
+    /// The remote reasoner replied with a non-success status code.
+    #[error(
+        "Reasoner at {addr:?} replied with status {status}\n\n{raw}\n",
+         raw = BlockFormatter::new("Raw response:", raw)
+    )]
+    ReasonerStatus { addr: String, status: StatusCode, raw: String },
+    /// The remote reasoner did not reply in time.
+    #[error("Timed out waiting for reasoner at {addr:?}")]
+    ReasonerTimeout { addr: String, source: reqwest::Error },
+    /// Failed to serialize the instances returned by the reasoner.
+    #[error("Failed to serialize instances returned by reasoner at {addr:?}")]
+    ResponseInstancesSerialize { addr: String, source: serde_json::Error },
+    /// Failed to serialize the request (or part of it) while streaming it.
+    #[error("Failed to serialize request to JSON")]
+    RequestSerialize { source: serde_json::Error },
+    /// The request did not serialize with exactly one empty `phrases`-field, so we can't stream it.
+    #[error("Cannot stream request that does not serialize with exactly one empty \"phrases\"-field\n\n{raw}\n", raw = BlockFormatter::new("Serialized request:", raw))]
+    RequestUnexpectedLayout { raw: String },
     /// Failed to extract the reasons for failure (i.e., violations) from a parsed [`ResponsePhrases`] object.
     #[error(
         "Failed to extract reasons (i.e., violations) from the response of reasoner at {addr:?}\n\n{raw}\n",
          raw = BlockFormatter::new("Parsed response:", raw)
     )]
     ResponseExtractReasons { addr: String, raw: String, source: R },
-    /// The query returned in the response was of an illegal ending type.
+    /// The response did not end in a boolean query, while the [`VerdictStrategy`] requires it.
     #[error(
-        "Reasoner at {addr:?} returned result of instance query as last state change; this is unsupported!\n\n{raw}\n",
+        "Reasoner at {addr:?} did not end with the result of a boolean query, as required by VerdictStrategy::RequireBooleanTerminator\n\n{raw}\n",
          raw = BlockFormatter::new("Parsed response:", raw)
     )]
-    ResponseIllegalQuery { addr: String, raw: String },
+    ResponseNoBooleanTerminator { addr: String, raw: String },
     /// Failed to parse the response of the reasoner as a valid [`ResponsePhrases`] object.
     #[error(
         "Failed to parse response from reasoner at {addr:?}\n\n{raw}\n",
@@ -73,6 +146,151 @@ pub enum Error<R, S, Q> {
     /// Failed to serialize the question to eFLINT.
     #[error("Failed to serialize given question to eFLINT")]
     QuestionToEFlint { source: Q },
+    /// The address of the reasoner has a scheme other than `http` or `https`.
+    #[error("Reasoner address {addr:?} has unsupported scheme {scheme:?} (expected \"http\" or \"https\")")]
+    UnsupportedScheme { addr: String, scheme: String },
+}
+impl<R, S, Q> Error<R, S, Q> {
+    /// Returns whether this error might go away by asking again.
+    ///
+    /// This is the case if the reasoner could not be reached, did not reply in time or replied
+    /// with a server error (i.e., a 5xx status code).
+    ///
+    /// # Returns
+    /// True if the request that caused this error is worth retrying, or false otherwise.
+    #[inline]
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::ReasonerRequest { source, .. } | Self::ReasonerResponse { source, .. } => source.is_connect() || source.is_timeout(),
+            Self::ReasonerStatus { status, .. } => status.is_server_error(),
+            Self::ReasonerTimeout { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Serializes a phrases request for the given state and question to JSON, without ever having all
+/// of its phrases in memory at the same time.
+///
+/// The written bytes are the same as those of [`serde_json::to_vec()`] on the full [`Request`].
+///
+/// # Arguments
+/// - `state`: The [`EFlintable`] state to serialize.
+/// - `question`: The [`EFlintable`] question to serialize.
+/// - `writer`: The [`Write`]r to write the serialized request to. It is flushed once done.
+///
+/// # Errors
+/// This function errors if we failed to serialize either the state or question to eFLINT, or if
+/// we failed to serialize the request to JSON or write it.
+fn write_request_streamed<R, S, Q>(state: &S, question: &Q, mut writer: impl Write) -> Result<(), Error<R, S::Error, Q::Error>>
+where
+    S: ?Sized + EFlintable,
+    Q: ?Sized + EFlintable,
+{
+    // Serialize the request without any phrases to find where they go
+    // NOTE: We splice the phrases in instead of writing the other fields ourselves, such that the
+    // fields (and thus the bytes) are in the same order as when serializing the request in one go
+    let skeleton: Vec<u8> = serde_json::to_vec(&Request::Phrases(RequestPhrases {
+        common:  RequestCommon { version: Version::v0_1_0(), extensions: HashMap::new() },
+        phrases: Vec::new(),
+        updates: true,
+    }))
+    .map_err(|source| Error::RequestSerialize { source })?;
+    let mut found = skeleton.windows(PHRASES_MARKER.len()).enumerate().filter(|(_, window)| *window == PHRASES_MARKER);
+    let split: usize = match (found.next(), found.next()) {
+        // NOTE: Split between the brackets of the empty list
+        (Some((pos, _)), None) => pos + PHRASES_MARKER.len() - 1,
+        _ => return Err(Error::RequestUnexpectedLayout { raw: String::from_utf8_lossy(&skeleton).into() }),
+    };
+    let (head, tail): (&[u8], &[u8]) = skeleton.split_at(split);
+    writer.write_all(head).map_err(|err| Error::RequestSerialize { source: serde_json::Error::io(err) })?;
+
+    // Then stream the phrases one-by-one in between
+    let mut n_phrases: usize = 0;
+    let mut err: Option<serde_json::Error> = None;
+    {
+        let mut sink = |phrase: Phrase| {
+            if err.is_some() {
+                return;
+            }
+            let sep: Result<(), serde_json::Error> = if n_phrases > 0 { writer.write_all(b",").map_err(serde_json::Error::io) } else { Ok(()) };
+            if let Err(source) = sep.and_then(|()| serde_json::to_writer(&mut writer, &phrase)) {
+                err = Some(source);
+            }
+            n_phrases += 1;
+        };
+        state.to_eflint_streamed(&mut sink).map_err(|source| Error::StateToEFlint { source })?;
+        question.to_eflint_streamed(&mut sink).map_err(|source| Error::QuestionToEFlint { source })?;
+    }
+    if let Some(source) = err {
+        return Err(Error::RequestSerialize { source });
+    }
+    writer.write_all(tail).and_then(|()| writer.flush()).map_err(|err| Error::RequestSerialize { source: serde_json::Error::io(err) })?;
+    debug!("Full request length: {n_phrases} phrase(s)");
+
+    // Done
+    Ok(())
+}
+
+/// Serializes a phrases request for the given state and question on a blocking thread, sending
+/// it in chunks as it goes.
+///
+/// # Arguments
+/// - `input`: The state and question to serialize.
+///
+/// # Returns
+/// A receiver for the chunks of the serialized [`Request`], and a handle that resolves once
+/// serialization is done. If serialization failed, the last chunk is an error.
+///
+/// # Errors
+/// The returned handle resolves to an error if we failed to serialize either the state or
+/// question to eFLINT, or the request to JSON. It is not an error if the receiver is dropped
+/// early.
+fn spawn_request_writer<R, S, Q>(input: Arc<(S, Q)>) -> (mpsc::Receiver<Chunk>, JoinHandle<Result<(), Error<R, S::Error, Q::Error>>>)
+where
+    R: 'static + Send,
+    S: 'static + Send + Sync + EFlintable,
+    S::Error: 'static + Send,
+    Q: 'static + Send + Sync + EFlintable,
+    Q::Error: 'static + Send,
+{
+    let (tx, rx): (mpsc::Sender<Chunk>, mpsc::Receiver<Chunk>) = mpsc::channel(CHUNK_BUFFER);
+    let handle = tokio::task::spawn_blocking(move || {
+        let (state, question): &(S, Q) = &input;
+        match write_request_streamed(state, question, ChunkWriter::new(tx.clone())) {
+            Ok(()) => Ok(()),
+            // NOTE: The only I/O errors come from the channel, i.e., the receiver is no longer interested
+            Err(Error::RequestSerialize { source }) if source.is_io() => {
+                debug!("Request receiver hung up before the full request was written");
+                Ok(())
+            },
+            Err(err) => {
+                // Fail the last chunk, such that the reasoner never mistakes a partial request for a full one
+                let _ = tx.blocking_send(Err(std::io::Error::other("Failed to serialize request")));
+                Err(err)
+            },
+        }
+    });
+    (rx, handle)
+}
+
+/// Waits for a request writer spawned by [`spawn_request_writer()`], if any.
+///
+/// # Arguments
+/// - `writer`: The [`JoinHandle`] of the writer to wait for.
+///
+/// # Errors
+/// This function errors if the writer failed to serialize the request.
+async fn join_request_writer<R, S, Q>(writer: Option<JoinHandle<Result<(), Error<R, S, Q>>>>) -> Result<(), Error<R, S, Q>> {
+    match writer {
+        Some(writer) => writer.await.unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic())),
+        None => Ok(()),
+    }
 }
 
 
@@ -80,6 +298,59 @@ pub enum Error<R, S, Q> {
 
 
 /***** AUXILLARY *****/
+/// A chunk of a streamed request, or an error if the request could not be written in full.
+type Chunk = Result<Vec<u8>, std::io::Error>;
+
+/// The body of a request to the reasoner.
+enum RequestBody<S, Q> {
+    /// A request that was serialized in full before sending it.
+    Full(Vec<u8>),
+    /// A request that is serialized from its state and question while sending it.
+    Streamed(Arc<(S, Q)>),
+}
+
+/// A [`Write`]r that sends everything written to it over a channel, in chunks of (at least)
+/// [`CHUNK_SIZE`] bytes.
+///
+/// NOTE: Sending blocks until there is room in the channel, so this must only be used on a
+/// blocking thread.
+struct ChunkWriter {
+    /// The chunk currently being written.
+    chunk: Vec<u8>,
+    /// The channel to send full chunks on.
+    tx:    mpsc::Sender<Chunk>,
+}
+impl ChunkWriter {
+    /// Constructor for the ChunkWriter.
+    ///
+    /// # Arguments
+    /// - `tx`: The channel to send full chunks on.
+    ///
+    /// # Returns
+    /// A new ChunkWriter with an empty chunk.
+    #[inline]
+    fn new(tx: mpsc::Sender<Chunk>) -> Self { Self { chunk: Vec::with_capacity(CHUNK_SIZE), tx } }
+}
+impl Write for ChunkWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.chunk.extend_from_slice(buf);
+        if self.chunk.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+        let chunk: Vec<u8> = std::mem::replace(&mut self.chunk, Vec::with_capacity(CHUNK_SIZE));
+        self.tx.blocking_send(Ok(chunk)).map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))
+    }
+}
+
 /// Defines the context for the eFLINT reasoner.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct EFlintJsonReasonerContext {
@@ -115,6 +386,11 @@ pub struct EFlintJsonReasonerContextFull<'a> {
     pub context: EFlintJsonReasonerContext,
     /// The address of the reasoner we're connecting to.
     pub addr:    &'a str,
+    /// Whether the connection to the reasoner is encrypted (i.e., uses `https://`).
+    pub tls:     bool,
+    /// The names of the additional headers sent to the reasoner, with their values redacted.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, &'static str>,
 }
 impl<'a> EFlintJsonReasonerContextFull<'a> {
     /// Constructor for the EFlintJsonReasonerContextFull.
@@ -125,7 +401,26 @@ impl<'a> EFlintJsonReasonerContextFull<'a> {
     /// # Returns
     /// A new EFlintJsonReasonerContextFull that can be logged.
     #[inline]
-    pub fn new(addr: &'a str) -> Self { Self { context: EFlintJsonReasonerContext::default(), addr } }
+    pub fn new(addr: &'a str) -> Self {
+        let tls: bool = Url::parse(addr).is_ok_and(|url| url.scheme() == "https");
+        Self { context: EFlintJsonReasonerContext::default(), addr, tls, headers: BTreeMap::new() }
+    }
+
+    /// Adds the additional headers sent to the reasoner to this context.
+    ///
+    /// Only their names are kept; their values are replaced by `<redacted>`, as they typically
+    /// contain secrets.
+    ///
+    /// # Arguments
+    /// - `headers`: The [`RequestHeaders`] sent to the reasoner.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_headers(mut self, headers: &RequestHeaders) -> Self {
+        self.headers = headers.get().keys().map(|name| (name.to_string(), "<redacted>")).collect();
+        self
+    }
 }
 impl ReasonerContext for EFlintJsonReasonerContextFull<'_> {
     #[inline]
@@ -138,18 +433,265 @@ impl ReasonerContext for EFlintJsonReasonerContextFull<'_> {
     fn language_version(&self) -> Cow<'_, str> { Cow::Borrowed(&self.context.language_version) }
 }
 
+/// Defines additional headers that are sent along with every request to the reasoner.
+///
+/// This can be used to, e.g., authenticate to a gateway in front of the reasoner. All header
+/// values are marked as sensitive, and are never written to the audit log.
+#[derive(Clone, Default)]
+pub enum RequestHeaders {
+    /// No additional headers are sent. This is the default.
+    #[default]
+    None,
+    /// The same headers are sent with every request.
+    Static(HeaderMap),
+    /// The headers are produced anew for every request, e.g., to support rotating tokens.
+    Dynamic(Arc<dyn Send + Sync + Fn() -> HeaderMap>),
+}
+impl Debug for RequestHeaders {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Static(headers) => f.debug_tuple("Static").field(&headers.keys().collect::<Vec<_>>()).finish(),
+            Self::Dynamic(_) => write!(f, "Dynamic(..)"),
+        }
+    }
+}
+impl RequestHeaders {
+    /// Produces the headers to send with the next request.
+    ///
+    /// # Returns
+    /// A [`HeaderMap`] with the headers, all marked as sensitive.
+    pub fn get(&self) -> HeaderMap {
+        let mut headers: HeaderMap = match self {
+            Self::None => HeaderMap::new(),
+            Self::Static(headers) => headers.clone(),
+            Self::Dynamic(provider) => provider(),
+        };
+        for value in headers.values_mut() {
+            value.set_sensitive(true);
+        }
+        headers
+    }
+}
+
+/// Defines how the connector reaches the reasoner.
+#[derive(Clone, Debug)]
+enum Transport {
+    /// The reasoner is a remote server reached over HTTP(S).
+    Http {
+        /// The parsed address of the reasoner.
+        url:     Url,
+        /// Additional headers sent along with every request.
+        headers: RequestHeaders,
+        /// The HTTP client used to reach the reasoner, which is reused across consults.
+        client:  reqwest::Client,
+    },
+    /// The reasoner is a local binary that is spawned for every request.
+    Subprocess {
+        /// The program to run.
+        exec: String,
+        /// The arguments to pass to it.
+        args: Vec<String>,
+    },
+}
+
+
+
+/// Defines how the connector tells the reasoner about its deadline.
+///
+/// Cooperative reasoners can use this to stop reasoning once the connector has given up on them.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DeadlineHeader {
+    /// Sends an `X-Deadline`-header with the absolute deadline, in milliseconds since the Unix
+    /// epoch.
+    XDeadline,
+    /// Sends a `grpc-timeout`-header with the relative timeout, in milliseconds (e.g., `5000m`).
+    GrpcTimeout,
+}
+impl DeadlineHeader {
+    /// Computes the name and value of this header for the given timeout.
+    ///
+    /// # Arguments
+    /// - `timeout`: The time the reasoner has to reply, starting now.
+    ///
+    /// # Returns
+    /// A pair of the header's name and its value.
+    pub fn header(&self, timeout: Duration) -> (&'static str, String) {
+        match self {
+            Self::XDeadline => {
+                // NOTE: If the clock is before the epoch, there isn't a sensible deadline anyway
+                let now: Duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                ("X-Deadline", (now + timeout).as_millis().to_string())
+            },
+            Self::GrpcTimeout => ("grpc-timeout", format!("{}m", timeout.as_millis())),
+        }
+    }
+}
+
+/// Defines how the connector derives a verdict from the results of the reasoner.
+///
+/// Every phrase submitted to the reasoner produces a result. Which of those determine the verdict
+/// depends on how the policy is written.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum VerdictStrategy {
+    /// Only the last result counts. If it's a boolean query, it must be true; if it's a state
+    /// change, it must not be a violation. This is the default.
+    ///
+    /// For example, the following is a violation:
+    /// ```eflint
+    /// +user(Amy).
+    /// ?Holds(user(Bob)).
+    /// ```
+    /// but the following is not, even though the query before it failed:
+    /// ```eflint
+    /// ?Holds(user(Bob)).
+    /// +user(Amy).
+    /// ```
+    #[default]
+    LastResult,
+    /// Every result counts. Any failed boolean query or state change causing a violation makes
+    /// the verdict a violation. Instance queries are ignored.
+    ///
+    /// With this strategy, both examples in [`VerdictStrategy::LastResult`] are violations. This
+    /// suits policies that emit trailing state changes after their checks.
+    AnyViolation,
+    /// Only the last result counts, and it must be a boolean query. If it's anything else, the
+    /// consult fails with an [`Error::ResponseNoBooleanTerminator`].
+    ///
+    /// With this strategy, the first example in [`VerdictStrategy::LastResult`] is a violation,
+    /// and the second is an error. This suits policies that are expected to always end with the
+    /// question they answer, catching policies that accidentally don't.
+    RequireBooleanTerminator,
+}
+
 
 
 
 
 /***** LIBRARY *****/
+/// Configures an [`EFlintJsonReasonerConnector`] that reaches the reasoner over HTTP(S).
+///
+/// Everything configured here ends up in the reasoner's context, which is only logged once the
+/// connector is [built](EFlintJsonHttpBuilder::build_async()). Obtain one with
+/// [`EFlintJsonReasonerConnector::builder()`].
+#[derive(Clone)]
+pub struct EFlintJsonHttpBuilder<R, S, Q> {
+    /// The address of the reasoner.
+    addr: String,
+    /// The parsed address of the reasoner.
+    url: Url,
+    /// The reasoner handler that determines if and which reasons to give.
+    handler: R,
+    /// Additional headers sent along with every request.
+    headers: RequestHeaders,
+    /// Certificates to trust in addition to the system's root certificates.
+    root_certificates: Vec<Certificate>,
+
+    /// Dummy variable for remembering which state is being used.
+    _state:    PhantomData<S>,
+    /// Dummy variable for remembering which question is being used.
+    _question: PhantomData<Q>,
+}
+impl<R, S, Q> EFlintJsonHttpBuilder<R, S, Q>
+where
+    R: ReasonHandler,
+    R::Reason: Display,
+    R::Error: 'static,
+    S: EFlintable,
+    S::Error: 'static,
+    Q: EFlintable,
+    Q::Error: 'static,
+{
+    /// Sets additional headers to send along with every request (e.g., for authentication).
+    ///
+    /// Only their names are logged in the reasoner's context.
+    ///
+    /// # Arguments
+    /// - `headers`: The [`RequestHeaders`] to send. Default is [`RequestHeaders::None`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_headers(mut self, headers: RequestHeaders) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Adds certificates to trust in addition to the system's root certificates (e.g., of an
+    /// internal CA).
+    ///
+    /// Only relevant for `https://` addresses.
+    ///
+    /// # Arguments
+    /// - `bundles`: Any number of PEM-encoded files, each of which may contain multiple
+    ///   certificates (e.g., a chain).
+    ///
+    /// # Returns
+    /// Self for chaining.
+    ///
+    /// # Errors
+    /// This function errors if any of the `bundles` is not valid PEM.
+    pub fn with_root_certificates(mut self, bundles: &[impl AsRef<[u8]>]) -> Result<Self, Error<R::Error, S::Error, Q::Error>> {
+        for (i, bundle) in bundles.iter().enumerate() {
+            self.root_certificates.extend(Certificate::from_pem_bundle(bundle.as_ref()).map_err(|source| Error::Certificate { i, source })?);
+        }
+        Ok(self)
+    }
+
+    /// Builds the EFlintJsonReasonerConnector.
+    ///
+    /// This function logs asynchronously.
+    ///
+    /// # Arguments
+    /// - `logger`: A logger to write the reasoner's context to.
+    ///
+    /// # Returns
+    /// A new [`EFlintJsonReasonerConnector`], ready for reasoning.
+    ///
+    /// # Errors
+    /// This function may error if we failed to build the HTTP client, or if it failed to log to
+    /// the given `logger`.
+    pub async fn build_async<L: AuditLogger>(self, logger: &L) -> Result<EFlintJsonReasonerConnector<R, S, Q>, Error<R::Error, S::Error, Q::Error>> {
+        // NOTE: Responses to large instance queries compress very well, so we ask for compressed
+        // bodies. These are decoded transparently by `reqwest`.
+        let mut builder: reqwest::ClientBuilder = reqwest::Client::builder().gzip(true).deflate(true);
+        for cert in self.root_certificates {
+            builder = builder.add_root_certificate(cert);
+        }
+        let client: reqwest::Client = builder.build().map_err(|source| Error::ClientBuild { source })?;
+
+        logger
+            .log_context(&EFlintJsonReasonerContextFull::new(&self.addr).with_headers(&self.headers))
+            .await
+            .map_err(|err| Error::LogContext { to: std::any::type_name::<L>(), source: err.freeze() })?;
+        Ok(EFlintJsonReasonerConnector::from_transport(self.addr, Transport::Http { url: self.url, headers: self.headers, client }, self.handler))
+    }
+}
+
+
+
 /// Defines the interface to abackend eFLINT JSON reasoner.
 #[derive(Clone, Debug)]
 pub struct EFlintJsonReasonerConnector<R, S, Q> {
-    /// The address where we find the reasoner.
+    /// The address where we find the reasoner, or the command that runs it.
     addr: String,
+    /// How we reach the reasoner.
+    transport: Transport,
     /// The reasoner handler that determines if and which reasons to give.
     reason_handler: R,
+    /// Whether to stream the request's phrases into its body instead of building them all first.
+    streaming: bool,
+    /// The time the reasoner has to reply, if any.
+    timeout: Option<Duration>,
+    /// The number of times a request is retried if it failed to connect or timed out.
+    retries: usize,
+    /// The time to wait before the first retry. Doubles for every next retry.
+    backoff: Duration,
+    /// How to tell the reasoner about the timeout, if at all.
+    deadline_header: Option<DeadlineHeader>,
+    /// How to derive a verdict from the reasoner's results.
+    verdict_strategy: VerdictStrategy,
 
     /// Dummy variable for remembering which state is being used.
     _state:    PhantomData<S>,
@@ -159,10 +701,81 @@ pub struct EFlintJsonReasonerConnector<R, S, Q> {
 impl<R, S, Q> EFlintJsonReasonerConnector<R, S, Q> {
     /// Constructor for the EFlintJsonReasonerConnector.
     ///
+    /// This constructor logs asynchronously. Use [`EFlintJsonReasonerConnector::builder()`] to
+    /// send additional headers or trust additional certificates.
+    ///
+    /// # Arguments
+    /// - `addr`: The address of the remote reasoner that we will connect to. Must be an `http://` or
+    ///   `https://` URL.
+    /// - `handler`: The [`ReasonHandler`] that determines how errors from the reasoners are propagated to the user.
+    /// - `logger`: A logger to write this reasoner's context to.
+    ///
+    /// # Returns
+    /// A new instance of Self, ready for reasoning.
+    ///
+    /// # Errors
+    /// This function may error if the `addr` is not a valid `http://` or `https://` URL, or if it
+    /// failed to log to the given `logger`.
+    #[inline]
+    pub async fn new_async<L: AuditLogger>(addr: impl Into<String>, handler: R, logger: &L) -> Result<Self, Error<R::Error, S::Error, Q::Error>>
+    where
+        R: ReasonHandler,
+        R::Reason: Display,
+        R::Error: 'static,
+        S: EFlintable,
+        S::Error: 'static,
+        Q: EFlintable,
+        Q::Error: 'static,
+    {
+        Self::builder(addr, handler)?.build_async(logger).await
+    }
+
+    /// Starts configuring an EFlintJsonReasonerConnector that reaches the reasoner over HTTP(S).
+    ///
+    /// # Arguments
+    /// - `addr`: The address of the remote reasoner that we will connect to. Must be an `http://` or
+    ///   `https://` URL.
+    /// - `handler`: The [`ReasonHandler`] that determines how errors from the reasoners are propagated to the user.
+    ///
+    /// # Returns
+    /// An [`EFlintJsonHttpBuilder`] to configure and then [build](EFlintJsonHttpBuilder::build_async()) the connector with.
+    ///
+    /// # Errors
+    /// This function errors if the `addr` is not a valid `http://` or `https://` URL.
+    pub fn builder(addr: impl Into<String>, handler: R) -> Result<EFlintJsonHttpBuilder<R, S, Q>, Error<R::Error, S::Error, Q::Error>>
+    where
+        R: ReasonHandler,
+        S: EFlintable,
+        Q: EFlintable,
+    {
+        let addr: String = addr.into();
+        let url: Url = Url::parse(&addr).map_err(|source| Error::IllegalAddress { addr: addr.clone(), source })?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(Error::UnsupportedScheme { scheme: url.scheme().into(), addr });
+        }
+        Ok(EFlintJsonHttpBuilder {
+            addr,
+            url,
+            handler,
+            headers: RequestHeaders::default(),
+            root_certificates: Vec::new(),
+            _state: PhantomData,
+            _question: PhantomData,
+        })
+    }
+
+    /// Constructor for the EFlintJsonReasonerConnector that runs the reasoner as a local
+    /// subprocess instead of reaching it over HTTP.
+    ///
+    /// For every request, the given command is spawned, the serialized request is written to its
+    /// stdin and a [`ResponsePhrases`] is read from its stdout. This avoids having to run a
+    /// separate reasoner server.
+    ///
     /// This constructor logs asynchronously.
     ///
     /// # Arguments
-    /// - `addr`: The address of the remote reasoner that we will connect to.
+    /// - `cmd`: The command that runs the eFLINT JSON reasoner, as the program followed by its
+    ///   arguments.
     /// - `handler`: The [`ReasonHandler`] that determines how errors from the reasoners are propagated to the user.
     /// - `logger`: A logger to write this reasoner's context to.
     ///
@@ -170,15 +783,15 @@ impl<R, S, Q> EFlintJsonReasonerConnector<R, S, Q> {
     /// A new instance of Self, ready for reasoning.
     ///
     /// # Errors
-    /// This function may error if it failed to log to the given `logger`.
+    /// This function may error if the `cmd` is empty, or if it failed to log to the given `logger`.
     #[inline]
-    pub async fn new_async<'l, L: AuditLogger>(
-        addr: impl 'l + Into<String>,
+    pub async fn new_subprocess_async<L: AuditLogger>(
+        cmd: impl IntoIterator<Item = String>,
         handler: R,
-        logger: &'l L,
+        logger: &L,
     ) -> Result<Self, Error<R::Error, S::Error, Q::Error>>
     where
-        R: 'l + ReasonHandler,
+        R: ReasonHandler,
         R::Reason: Display,
         R::Error: 'static,
         S: EFlintable,
@@ -186,39 +799,336 @@ impl<R, S, Q> EFlintJsonReasonerConnector<R, S, Q> {
         Q: EFlintable,
         Q::Error: 'static,
     {
-        let addr: String = addr.into();
+        let mut cmd = cmd.into_iter();
+        let exec: String = cmd.next().ok_or(Error::EmptyCommand)?;
+        let args: Vec<String> = cmd.collect();
+
+        // NOTE: We use the command as the "address" of the reasoner, which makes it show up in the
+        // context and in any errors.
+        let addr: String = std::iter::once(&exec).chain(args.iter()).map(String::as_str).collect::<Vec<&str>>().join(" ");
         logger
             .log_context(&EFlintJsonReasonerContextFull::new(&addr))
             .await
             .map_err(|err| Error::LogContext { to: std::any::type_name::<L>(), source: err.freeze() })?;
-        Ok(Self { addr, reason_handler: handler, _state: PhantomData, _question: PhantomData })
+        Ok(Self::from_transport(addr, Transport::Subprocess { exec, args }, handler))
+    }
+
+    /// Builds a new EFlintJsonReasonerConnector with default settings.
+    ///
+    /// # Arguments
+    /// - `addr`: The address of the reasoner, or the command that runs it.
+    /// - `transport`: The [`Transport`] used to reach the reasoner.
+    /// - `handler`: The [`ReasonHandler`] that determines how errors from the reasoners are propagated to the user.
+    ///
+    /// # Returns
+    /// A new instance of Self.
+    fn from_transport(addr: String, transport: Transport, handler: R) -> Self {
+        Self {
+            addr,
+            transport,
+            reason_handler: handler,
+            streaming: false,
+            timeout: None,
+            retries: 0,
+            backoff: Duration::from_millis(100),
+            deadline_header: None,
+            verdict_strategy: VerdictStrategy::default(),
+            _state: PhantomData,
+            _question: PhantomData,
+        }
+    }
+
+    /// Sets whether requests are streamed to the reasoner.
+    ///
+    /// When enabled, the state's and question's phrases are serialized into the request body
+    /// one-by-one on a blocking thread, which sends it to the reasoner in chunks while it is being
+    /// written. As such, neither the full list of phrases nor its JSON serialization ever exist in
+    /// memory. This is recommended for very large states. Note that the full request is then no
+    /// longer written to the debug log.
+    ///
+    /// # Arguments
+    /// - `streaming`: Whether to stream requests or not. Default is false.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_streaming(mut self, streaming: bool) -> Self {
+        self.streaming = streaming;
+        self
+    }
+
+    /// Sets the HTTP client used to reach the reasoner.
+    ///
+    /// This can be used to configure, e.g., connect timeouts, proxies or TLS. The client is reused
+    /// for all requests, so it pools connections to the reasoner. Note that the default client asks
+    /// for gzip- and deflate-compressed responses, which a custom client has to enable itself.
+    ///
+    /// Has no effect if the reasoner runs as a
+    /// [subprocess](EFlintJsonReasonerConnector::new_subprocess_async()).
+    ///
+    /// # Arguments
+    /// - `client`: The [`reqwest::Client`] to use.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        if let Transport::Http { client: old, .. } = &mut self.transport {
+            *old = client;
+        }
+        self
+    }
+
+    /// Sets how often requests are retried when the reasoner could not be reached, did not reply
+    /// in time or replied with a server error (i.e., a 5xx status code). Client errors (i.e., 4xx
+    /// status codes) are never retried, since they won't go away by asking again.
+    ///
+    /// Retrying is safe because every request carries the full state and question, so the
+    /// reasoner holds nothing between requests.
+    ///
+    /// Has no effect if the reasoner runs as a
+    /// [subprocess](EFlintJsonReasonerConnector::new_subprocess_async()).
+    ///
+    /// # Arguments
+    /// - `retries`: The maximum number of retries after the first attempt. Default is 0.
+    /// - `backoff`: The time to wait before the first retry, doubled for every next one. Default
+    ///   is 100ms.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_retries(mut self, retries: usize, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the time the reasoner has to reply to a request.
+    ///
+    /// If it takes longer, the request fails with an [`Error::ReasonerTimeout`] (after any
+    /// [retries](EFlintJsonReasonerConnector::with_retries())). If the reasoner runs as a
+    /// [subprocess](EFlintJsonReasonerConnector::new_subprocess_async()), it is killed instead and
+    /// the request fails with an [`Error::CommandTimeout`].
+    ///
+    /// # Arguments
+    /// - `timeout`: The timeout to use, or [`None`] to wait indefinitely. Default is [`None`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets whether to tell the reasoner about the connector's deadline.
+    ///
+    /// The header is computed from the timeout set with
+    /// [`with_timeout()`](EFlintJsonReasonerConnector::with_timeout()) whenever a request is sent,
+    /// such that the reasoner can give up at the same time as we do. If no timeout is set, no
+    /// header is sent.
+    ///
+    /// # Arguments
+    /// - `header`: The [`DeadlineHeader`] to send, or [`None`] to not send any. Default is
+    ///   [`None`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_deadline_header(mut self, header: Option<DeadlineHeader>) -> Self {
+        self.deadline_header = header;
+        self
+    }
+
+    /// Sets how the verdict is derived from the reasoner's results.
+    ///
+    /// # Arguments
+    /// - `strategy`: The [`VerdictStrategy`] to use. Default is [`VerdictStrategy::LastResult`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_verdict_strategy(mut self, strategy: VerdictStrategy) -> Self {
+        self.verdict_strategy = strategy;
+        self
+    }
+
+    /// Applies the timeout and deadline header to a request, if configured.
+    ///
+    /// # Arguments
+    /// - `req`: The [`RequestBuilder`] to configure.
+    ///
+    /// # Returns
+    /// The given `req`, configured.
+    fn apply_deadline(&self, mut req: RequestBuilder) -> RequestBuilder {
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+            if let Some(header) = self.deadline_header {
+                let (name, value): (&'static str, String) = header.header(timeout);
+                req = req.header(name, value);
+            }
+        }
+        req
     }
 }
-impl<R, S, Q> ReasonerConnector for EFlintJsonReasonerConnector<R, S, Q>
+impl<R, S, Q> EFlintJsonReasonerConnector<R, S, Q>
+where
+    R: ReasonHandler,
+    S: EFlintable,
+    Q: EFlintable,
+{
+    /// Derives the verdict from a response of the reasoner.
+    ///
+    /// This is what [`ReasonerConnector::consult()`] does after receiving the reasoner's response,
+    /// but without any networking or logging. With the default [`VerdictStrategy::LastResult`],
+    /// the rule is:
+    /// - If the last result is a boolean query, then it must be true;
+    /// - If the last result is a state change, then it must not be violated;
+    /// - If the last result is an instance query, then the policy didn't decide and the verdict is
+    ///   [indeterminate](ReasonerResponse::Indeterminate); and
+    /// - If there are no results at all, then we default to **success**.
+    ///
+    /// See [`VerdictStrategy`] for the rules of the other strategies.
+    ///
+    /// # Arguments
+    /// - `response`: The [`ResponsePhrases`] returned by the reasoner.
+    ///
+    /// # Returns
+    /// The [`ReasonerResponse`] that the response represents.
+    ///
+    /// # Errors
+    /// This function errors if the last result is not a boolean query while the strategy is
+    /// [`VerdictStrategy::RequireBooleanTerminator`], or if the [`ReasonHandler`] failed to
+    /// extract the reasons for a violation or indeterminate verdict.
+    #[inline]
+    pub fn verdict_from_response(&self, response: &ResponsePhrases) -> Result<ReasonerResponse<R::Reason>, Error<R::Error, S::Error, Q::Error>> {
+        self.verdict(response, false)
+    }
+
+    /// Implements [`EFlintJsonReasonerConnector::verdict_from_response()`].
+    ///
+    /// # Arguments
+    /// - `response`: The [`ResponsePhrases`] returned by the reasoner.
+    /// - `instances`: If true, a last result that is an instance query is a **success** instead
+    ///   of indeterminate.
+    ///
+    /// # Returns
+    /// The [`ReasonerResponse`] that the response represents.
+    ///
+    /// # Errors
+    /// This function errors in the same cases as
+    /// [`EFlintJsonReasonerConnector::verdict_from_response()`].
+    fn verdict(&self, response: &ResponsePhrases, instances: bool) -> Result<ReasonerResponse<R::Reason>, Error<R::Error, S::Error, Q::Error>> {
+        // NOTE: `None` means the policy did not decide either way
+        let violated: Option<bool> = match (self.verdict_strategy, response.results.last()) {
+            (VerdictStrategy::AnyViolation, _) => Some(response.results.iter().any(|result| match result {
+                PhraseResult::BooleanQuery(r) => !r.result,
+                PhraseResult::InstanceQuery(_) => false,
+                PhraseResult::StateChange(r) => r.violated,
+            })),
+            (_, Some(PhraseResult::BooleanQuery(r))) => Some(!r.result),
+            (_, Some(PhraseResult::InstanceQuery(_))) if instances => Some(false),
+            (VerdictStrategy::RequireBooleanTerminator, _) => {
+                return Err(Error::ResponseNoBooleanTerminator {
+                    addr: self.addr.clone(),
+                    raw:  serde_json::to_string_pretty(response).unwrap_or_else(|_| "<serialization error>".into()),
+                });
+            },
+            (_, Some(PhraseResult::InstanceQuery(_))) => {
+                warn!("Reasoner at '{}' returned the result of an instance query as last result; verdict is indeterminate", self.addr);
+                None
+            },
+            (_, Some(PhraseResult::StateChange(r))) => Some(r.violated),
+            (_, None) => Some(false),
+        };
+        if violated == Some(false) {
+            return Ok(ReasonerResponse::Success);
+        }
+
+        // Extract the reasons for the violation (or lack of verdict)
+        let reasons: R::Reason = self.reason_handler.extract_reasons(response).map_err(|source| Error::ResponseExtractReasons {
+            addr: self.addr.clone(),
+            raw: serde_json::to_string_pretty(response).unwrap_or_else(|_| "<serialization error>".into()),
+            source,
+        })?;
+        Ok(if violated.is_some() { ReasonerResponse::Violated(reasons) } else { ReasonerResponse::Indeterminate { reasons } })
+    }
+}
+impl<R, S, Q> EFlintJsonReasonerConnector<R, S, Q>
 where
     R: Sync + ReasonHandler,
     R::Reason: Send + Sync + Display,
-    R::Error: 'static,
-    S: Send + Sync + EFlintable + Serialize,
-    S::Error: 'static,
-    Q: Send + Sync + EFlintable + Serialize,
-    Q::Error: 'static,
+    R::Error: 'static + Send,
+    S: 'static + Send + Sync + EFlintable + Serialize,
+    S::Error: 'static + Send,
+    Q: 'static + Send + Sync + EFlintable + Serialize,
+    Q::Error: 'static + Send,
 {
-    type Context = EFlintJsonReasonerContext;
-    type Error = Error<R::Error, S::Error, Q::Error>;
-    type Question = Q;
-    type Reason = R::Reason;
-    type State = S;
+    /// Consults the reasoner like [`ReasonerConnector::consult()`], but also returns the
+    /// instances computed by the policy.
+    ///
+    /// This is useful for policies that compute something (e.g., which actions are permitted)
+    /// instead of merely gating the question. Such policies end with an instance query, which
+    /// [`ReasonerConnector::consult()`] refuses.
+    ///
+    /// # Arguments
+    /// - `state`: The state to reason about.
+    /// - `question`: The question to ask about the `state`.
+    /// - `logger`: A [`SessionedAuditLogger`] to write the question and verdict to.
+    ///
+    /// # Returns
+    /// The reasoner's verdict, paired with the (serialized) result of the last instance query if
+    /// the policy ended with one. Ending with an instance query is always a
+    /// [`ReasonerResponse::Success`].
+    ///
+    /// # Errors
+    /// This function errors in the same cases as [`ReasonerConnector::consult()`], except that it
+    /// accepts a last instance query.
+    #[instrument(name = "EFlintJsonReasonerConnector::consult_with_instances", skip_all, fields(reference = logger.reference()))]
+    pub async fn consult_with_instances<L>(
+        &self,
+        state: S,
+        question: Q,
+        logger: &SessionedAuditLogger<L>,
+    ) -> Result<(ReasonerResponse<R::Reason>, Option<Value>), Error<R::Error, S::Error, Q::Error>>
+    where
+        L: Sync + AuditLogger,
+    {
+        let (response, raw_body): (ResponsePhrases, String) = self.query(state, question, logger).await?;
 
-    fn context(&self) -> Self::Context { EFlintJsonReasonerContext::default() }
+        debug!("Analysing response...");
+        let verdict: ReasonerResponse<R::Reason> = self.verdict(&response, true)?;
+        let instances: Option<Value> = match response.results.last() {
+            Some(PhraseResult::InstanceQuery(r)) => {
+                Some(serde_json::to_value(r).map_err(|source| Error::ResponseInstancesSerialize { addr: self.addr.clone(), source })?)
+            },
+            _ => None,
+        };
 
-    #[instrument(name = "EFlintJsonReasonerConnector::consult", skip_all, fields(reference = logger.reference()))]
-    async fn consult<'a, L>(
-        &'a self,
-        state: Self::State,
-        question: Self::Question,
-        logger: &'a SessionedAuditLogger<L>,
-    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+        // OK, report and return
+        self.log_verdict(&verdict, &raw_body, logger).await?;
+        Ok((verdict, instances))
+    }
+
+    /// Sends the state and question to the reasoner and parses its response.
+    ///
+    /// # Arguments
+    /// - `state`: The state to reason about.
+    /// - `question`: The question to ask about the `state`.
+    /// - `logger`: A [`SessionedAuditLogger`] to write the question to.
+    ///
+    /// # Returns
+    /// The parsed [`ResponsePhrases`], together with the raw response they were parsed from.
+    ///
+    /// # Errors
+    /// This function errors if we failed to log the question, reach the reasoner or parse its
+    /// response.
+    async fn query<L>(
+        &self,
+        state: S,
+        question: Q,
+        logger: &SessionedAuditLogger<L>,
+    ) -> Result<(ResponsePhrases, String), Error<R::Error, S::Error, Q::Error>>
     where
         L: Sync + AuditLogger,
     {
@@ -227,80 +1137,651 @@ where
             .await
             .map_err(|source| Error::LogQuestion { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: source.freeze() })?;
 
-        // Build the full policy
-        debug!("Building full policy...");
-        let mut phrases: Vec<Phrase> = Vec::new();
-        phrases.extend(state.to_eflint().map_err(|source| Error::StateToEFlint { source })?);
-        phrases.extend(question.to_eflint().map_err(|source| Error::QuestionToEFlint { source })?);
-        debug!("Full request length: {} phrase(s)", phrases.len());
+        let body: RequestBody<S, Q> = if self.streaming {
+            // Build the full policy while sending it
+            debug!("Building full policy (streamed)...");
+            RequestBody::Streamed(Arc::new((state, question)))
+        } else {
+            // Build the full policy
+            debug!("Building full policy...");
+            let mut phrases: Vec<Phrase> = Vec::new();
+            phrases.extend(state.to_eflint().map_err(|source| Error::StateToEFlint { source })?);
+            phrases.extend(question.to_eflint().map_err(|source| Error::QuestionToEFlint { source })?);
+            debug!("Full request length: {} phrase(s)", phrases.len());
 
-        // Build the request
-        let request: Request = Request::Phrases(RequestPhrases {
-            common: RequestCommon { version: Version::v0_1_0(), extensions: HashMap::new() },
-            phrases,
-            updates: true,
-        });
-        debug!("Full request:\n\n{}\n\n", serde_json::to_string_pretty(&request).unwrap_or_else(|_| "<serialization failure>".into()));
+            // Build the request
+            let request: Request = Request::Phrases(RequestPhrases {
+                common: RequestCommon { version: Version::v0_1_0(), extensions: HashMap::new() },
+                phrases,
+                updates: true,
+            });
+            debug!("Full request:\n\n{}\n\n", serde_json::to_string_pretty(&request).unwrap_or_else(|_| "<serialization failure>".into()));
+            RequestBody::Full(serde_json::to_vec(&request).map_err(|source| Error::RequestSerialize { source })?)
+        };
 
         // Send it on its way
-        debug!("Sending eFLINT phrases request to '{}'", self.addr);
-        let client = reqwest::Client::new();
-        let res = client.post(&self.addr).json(&request).send().await.map_err(|source| Error::ReasonerRequest { addr: self.addr.clone(), source })?;
-
-        debug!("Awaiting response...");
-        let raw_body = res.text().await.map_err(|source| Error::ReasonerResponse { addr: self.addr.clone(), source })?;
+        let raw_body: String = self.send(body).await?;
 
         debug!("Parsing response...");
         // NOTE: No 'map_err' to avoid moving 'raw_body' out on the happy path
         let response: ResponsePhrases =
             serde_json::from_str(&raw_body).map_err(|source| Error::ResponseParse { addr: self.addr.clone(), raw: raw_body.clone(), source })?;
 
-        debug!("Analysing response...");
-        // TODO proper handle invalid query and unexpected result
-        let verdict: ReasonerResponse<R::Reason> = response
-            .results
-            .last()
-            .map(|r| match r {
-                PhraseResult::BooleanQuery(r) => {
-                    if r.result {
-                        Ok(ReasonerResponse::Success)
-                    } else {
-                        Ok(ReasonerResponse::Violated(self.reason_handler.extract_reasons(&response).map_err(|source| {
-                            Error::ResponseExtractReasons {
-                                addr: self.addr.clone(),
-                                raw: serde_json::to_string_pretty(&response).unwrap_or_else(|_| "<serialization error>".into()),
-                                source,
-                            }
-                        })?))
+        Ok((response, raw_body))
+    }
+
+    /// Sends a request to the reasoner.
+    ///
+    /// # Arguments
+    /// - `body`: The [`RequestBody`] to send.
+    ///
+    /// # Returns
+    /// The raw response of the reasoner.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach or run the reasoner, if it timed out or if we
+    /// failed to serialize or send the request or receive its response.
+    async fn send(&self, body: RequestBody<S, Q>) -> Result<String, Error<R::Error, S::Error, Q::Error>> {
+        match &self.transport {
+            Transport::Http { url, headers, client } => self.send_http(url, headers, client, &body).await,
+            Transport::Subprocess { exec, args } => self.send_subprocess(exec, args, body).await,
+        }
+    }
+
+    /// Sends a request to the reasoner over HTTP, retrying if configured.
+    ///
+    /// Only [transient](Error::is_transient()) errors are retried. Streamed requests are
+    /// serialized anew for every attempt.
+    ///
+    /// # Arguments
+    /// - `url`: The address of the reasoner.
+    /// - `headers`: Additional headers to send along with the request.
+    /// - `client`: The [`reqwest::Client`] to send the request with.
+    /// - `body`: The [`RequestBody`] to send.
+    ///
+    /// # Returns
+    /// The raw response of the reasoner.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize a streamed request, or if the last attempt
+    /// failed to reach the reasoner, timed out, failed to receive its response or received a
+    /// non-success status code.
+    async fn send_http(
+        &self,
+        url: &Url,
+        headers: &RequestHeaders,
+        client: &reqwest::Client,
+        body: &RequestBody<S, Q>,
+    ) -> Result<String, Error<R::Error, S::Error, Q::Error>> {
+        let (mut attempt, mut backoff): (usize, Duration) = (0, self.backoff);
+        loop {
+            let (req_body, writer): (reqwest::Body, Option<JoinHandle<Result<(), Error<R::Error, S::Error, Q::Error>>>>) = match body {
+                RequestBody::Full(raw) => (raw.clone().into(), None),
+                RequestBody::Streamed(input) => {
+                    let (chunks, writer) = spawn_request_writer(input.clone());
+                    let chunks = stream::unfold(chunks, |mut chunks| async move { chunks.recv().await.map(|chunk| (chunk, chunks)) });
+                    (reqwest::Body::wrap_stream(chunks), Some(writer))
+                },
+            };
+
+            debug!("Sending eFLINT phrases request to '{}' (attempt {}/{})", self.addr, attempt + 1, self.retries + 1);
+            let res: Result<String, Error<R::Error, S::Error, Q::Error>> = match self
+                .apply_deadline(client.post(url.clone()))
+                .headers(headers.get())
+                .header(CONTENT_TYPE, "application/json")
+                .body(req_body)
+                .send()
+                .await
+            {
+                Ok(res) => {
+                    let status: StatusCode = res.status();
+                    debug!("Awaiting response ({status})...");
+                    match res.text().await {
+                        Ok(raw_body) if status.is_success() => Ok(raw_body),
+                        Ok(raw_body) => Err(Error::ReasonerStatus { addr: self.addr.clone(), status, raw: raw_body }),
+                        Err(source) if source.is_timeout() => Err(Error::ReasonerTimeout { addr: self.addr.clone(), source }),
+                        Err(source) => Err(Error::ReasonerResponse { addr: self.addr.clone(), source }),
                     }
                 },
-                PhraseResult::InstanceQuery(_) => Err(Error::ResponseIllegalQuery {
-                    addr: self.addr.clone(),
-                    raw:  serde_json::to_string_pretty(&response).unwrap_or_else(|_| "<serialization error>".into()),
-                }),
-                PhraseResult::StateChange(r) => {
-                    if !r.violated {
-                        Ok(ReasonerResponse::Success)
-                    } else {
-                        Ok(ReasonerResponse::Violated(self.reason_handler.extract_reasons(&response).map_err(|source| {
-                            Error::ResponseExtractReasons {
-                                addr: self.addr.clone(),
-                                raw: serde_json::to_string_pretty(&response).unwrap_or_else(|_| "<serialization error>".into()),
-                                source,
-                            }
-                        })?))
+                Err(source) if source.is_timeout() => Err(Error::ReasonerTimeout { addr: self.addr.clone(), source }),
+                Err(source) => Err(Error::ReasonerRequest { addr: self.addr.clone(), source }),
+            };
+
+            // NOTE: If we failed to serialize the request, that's the real reason sending it failed
+            join_request_writer(writer).await?;
+            let err: Error<R::Error, S::Error, Q::Error> = match res {
+                Ok(raw_body) => return Ok(raw_body),
+                Err(err) => err,
+            };
+
+            // Retry if it makes sense, or else report the last error
+            if attempt >= self.retries || !err.is_transient() {
+                return Err(err);
+            }
+            warn!("Request to reasoner at '{}' failed, retrying in {backoff:?}: {}", self.addr, err.trace());
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+            backoff *= 2;
+        }
+    }
+
+    /// Sends a request to the reasoner by running it as a subprocess.
+    ///
+    /// # Arguments
+    /// - `exec`: The program to run.
+    /// - `args`: The arguments to pass to it.
+    /// - `body`: The [`RequestBody`] to write to its stdin.
+    ///
+    /// # Returns
+    /// The raw response of the reasoner, as read from its stdout.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize a streamed request, spawn the reasoner or
+    /// communicate with it, if it timed out or if it exited with a non-zero exit code.
+    async fn send_subprocess(&self, exec: &str, args: &[String], body: RequestBody<S, Q>) -> Result<String, Error<R::Error, S::Error, Q::Error>> {
+        let mut cmd = Command::new(exec);
+        cmd.args(args);
+        cmd.stdin(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        debug!("Calling reasoner with {cmd:?}...");
+        let mut handle = cmd.spawn().map_err(|source| Error::CommandSpawn { cmd: self.addr.clone(), source })?;
+        let mut stdin = handle.stdin.take().expect("No stdin on subprocess even though it's piped!");
+        let mut stdout = handle.stdout.take().expect("No stdout on subprocess even though it's piped!");
+        let mut stderr = handle.stderr.take().expect("No stderr on subprocess even though it's piped!");
+
+        // NOTE: Full requests are sent as a single chunk, such that both are written the same way
+        let (mut chunks, writer): (mpsc::Receiver<Chunk>, Option<JoinHandle<Result<(), Error<R::Error, S::Error, Q::Error>>>>) = match body {
+            RequestBody::Full(raw) => {
+                let (tx, rx): (mpsc::Sender<Chunk>, mpsc::Receiver<Chunk>) = mpsc::channel(1);
+                // NOTE: Can't fail, as the channel is empty and its receiver still alive
+                let _ = tx.try_send(Ok(raw));
+                (rx, None)
+            },
+            RequestBody::Streamed(input) => {
+                let (rx, writer) = spawn_request_writer(input);
+                (rx, Some(writer))
+            },
+        };
+
+        // NOTE: We write the request while reading the response, such that neither blocks on a
+        // full pipe buffer. Closing stdin afterwards tells the reasoner the request is complete.
+        let (mut stdout_buf, mut stderr_buf): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
+        let write = async move {
+            let res: Result<(), std::io::Error> = async {
+                // NOTE: A failed chunk means the writer failed, which it reports itself
+                while let Some(Ok(chunk)) = chunks.recv().await {
+                    stdin.write_all(&chunk).await?;
+                }
+                stdin.shutdown().await
+            }
+            .await;
+            match res {
+                // NOTE: The reasoner closed its input before reading all of it, which means it
+                // (likely) failed. Its exit status and stderr tell us more than the write does.
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => {
+                    debug!("Reasoner closed its input before reading the full request");
+                    Ok(())
+                },
+                res => res,
+            }
+        };
+        let wait = try_join4(write, handle.wait(), stdout.read_to_end(&mut stdout_buf), stderr.read_to_end(&mut stderr_buf));
+        let res: Result<((), ExitStatus, usize, usize), std::io::Error> = match self.timeout {
+            Some(after) => match tokio::time::timeout(after, wait).await {
+                Ok(res) => res,
+                Err(_) => {
+                    // NOTE: `kill()` also waits for the child, so it doesn't linger as a zombie
+                    warn!("Reasoner did not complete within {after:?}, killing it...");
+                    if let Err(err) = handle.kill().await {
+                        warn!("{}", err.trace());
                     }
+                    return Err(Error::CommandTimeout { cmd: self.addr.clone(), after });
                 },
-            })
-            .transpose()?
-            .unwrap_or(ReasonerResponse::Success);
+            },
+            None => wait.await,
+        };
+        join_request_writer(writer).await?;
+        let status: ExitStatus = match res {
+            Ok((_, status, _, _)) => status,
+            Err(source) => return Err(Error::CommandJoin { cmd: self.addr.clone(), source }),
+        };
+        if !status.success() {
+            return Err(Error::CommandFailure { cmd: self.addr.clone(), status, stderr: String::from_utf8_lossy(&stderr_buf).into() });
+        }
+        Ok(String::from_utf8_lossy(&stdout_buf).into())
+    }
 
-        // OK, report and return
+    /// Writes the reasoner's verdict to the audit log.
+    ///
+    /// # Arguments
+    /// - `verdict`: The [`ReasonerResponse`] to log.
+    /// - `raw_body`: The raw response of the reasoner that the verdict was derived from.
+    /// - `logger`: A [`SessionedAuditLogger`] to write the verdict to.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write to the `logger`.
+    async fn log_verdict<L>(
+        &self,
+        verdict: &ReasonerResponse<R::Reason>,
+        raw_body: &str,
+        logger: &SessionedAuditLogger<L>,
+    ) -> Result<(), Error<R::Error, S::Error, Q::Error>>
+    where
+        L: Sync + AuditLogger,
+    {
         logger
-            .log_response(&verdict, Some(&raw_body))
+            .log_response(verdict, Some(raw_body))
             .await
             .map_err(|source| Error::LogResponse { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: source.freeze() })?;
         debug!("Final reasoner verdict: {verdict:?}");
+        Ok(())
+    }
+}
+impl<R, S, Q> ReasonerConnector for EFlintJsonReasonerConnector<R, S, Q>
+where
+    R: Sync + ReasonHandler,
+    R::Reason: Send + Sync + Display,
+    R::Error: 'static + Send,
+    S: 'static + Send + Sync + EFlintable + Serialize,
+    S::Error: 'static + Send,
+    Q: 'static + Send + Sync + EFlintable + Serialize,
+    Q::Error: 'static + Send,
+{
+    type Context = EFlintJsonReasonerContext;
+    type Error = Error<R::Error, S::Error, Q::Error>;
+    type Question = Q;
+    type Reason = R::Reason;
+    type State = S;
+
+    fn context(&self) -> Self::Context { EFlintJsonReasonerContext::default() }
+
+    /// Sends a request without any phrases to the reasoner, and checks that it replies with a
+    /// valid response.
+    ///
+    /// As the request is empty and the reasoner holds no state between requests, this has no
+    /// effect on any policy.
+    #[instrument(name = "EFlintJsonReasonerConnector::health_check", skip_all)]
+    async fn health_check(&self) -> Result<(), Self::Error> {
+        let request: Request = Request::Phrases(RequestPhrases {
+            common:  RequestCommon { version: Version::v0_1_0(), extensions: HashMap::new() },
+            phrases: Vec::new(),
+            updates: false,
+        });
+        let raw_body: String =
+            self.send(RequestBody::Full(serde_json::to_vec(&request).map_err(|source| Error::RequestSerialize { source })?)).await?;
+        serde_json::from_str::<ResponsePhrases>(&raw_body).map_err(|source| Error::ResponseParse {
+            addr: self.addr.clone(),
+            raw: raw_body,
+            source,
+        })?;
+        debug!("Reasoner at '{}' is healthy", self.addr);
+        Ok(())
+    }
+
+    #[instrument(name = "EFlintJsonReasonerConnector::consult", skip_all, fields(reference = logger.reference()))]
+    async fn consult<'a, L>(
+        &'a self,
+        state: Self::State,
+        question: Self::Question,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        let (response, raw_body): (ResponsePhrases, String) = self.query(state, question, logger).await?;
+
+        debug!("Analysing response...");
+        let verdict: ReasonerResponse<R::Reason> = self.verdict_from_response(&response)?;
+
+        // OK, report and return
+        self.log_verdict(&verdict, &raw_body, logger).await?;
         Ok(verdict)
     }
+
+    /// Sends all items to the reasoner concurrently, such that their round trips overlap.
+    ///
+    /// Because the statements of concurrent items interleave, every item is logged under a
+    /// session of its own. Its reference is that of `logger` suffixed with `#` and the item's
+    /// index (e.g., `abc#0`, `abc#1`, ...).
+    #[instrument(name = "EFlintJsonReasonerConnector::consult_batch", skip_all, fields(reference = logger.reference(), items = items.len()))]
+    async fn consult_batch<'a, L>(
+        &'a self,
+        items: Vec<(Self::State, Self::Question)>,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Vec<Result<ReasonerResponse<Self::Reason>, Self::Error>>
+    where
+        Self: Sync,
+        Self::State: 'a + Send,
+        Self::Question: 'a + Send,
+        Self::Reason: Send,
+        Self::Error: Send,
+        L: Sync + AuditLogger,
+    {
+        let sessions: Vec<SessionedAuditLogger<&SessionedAuditLogger<L>>> =
+            (0..items.len()).map(|i| SessionedAuditLogger::new(format!("{}#{i}", logger.reference()), logger)).collect();
+        join_all(items.into_iter().zip(&sessions).map(|((state, question), session)| self.consult(state, question, session))).await
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use spec::reasons::NoReason;
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::reasons::EFlintSilentReasonHandler;
+
+
+    /// An [`AuditLogger`] that records the kind and reference of every statement logged to it.
+    #[derive(Default)]
+    struct RecordingLogger(Mutex<Vec<String>>);
+    impl AuditLogger for RecordingLogger {
+        type Error = Infallible;
+
+        async fn log_context<'a, C>(&'a self, _context: &'a C) -> Result<(), Self::Error>
+        where
+            C: ?Sized + Sync + ReasonerContext,
+        {
+            Ok(())
+        }
+
+        async fn log_response<'a, R>(
+            &'a self,
+            reference: &'a str,
+            _response: &'a ReasonerResponse<R>,
+            _raw: Option<&'a str>,
+        ) -> Result<(), Self::Error>
+        where
+            R: Sync + Display,
+        {
+            self.0.lock().unwrap().push(format!("response {reference}"));
+            Ok(())
+        }
+
+        async fn log_question<'a, S, Q>(&'a self, reference: &'a str, _state: &'a S, _question: &'a Q) -> Result<(), Self::Error>
+        where
+            S: Sync + Serialize,
+            Q: Sync + Serialize,
+        {
+            self.0.lock().unwrap().push(format!("question {reference}"));
+            Ok(())
+        }
+    }
+
+    /// Builds a connector that runs a shell script as its reasoner.
+    ///
+    /// # Arguments
+    /// - `script`: The script to run with `sh -c`. It gets the request on stdin.
+    async fn gen_subprocess(script: &str) -> EFlintJsonReasonerConnector<EFlintSilentReasonHandler, (), ()> {
+        EFlintJsonReasonerConnector::new_subprocess_async(
+            ["sh".into(), "-c".into(), script.into()],
+            EFlintSilentReasonHandler,
+            &RecordingLogger::default(),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Runs an HTTP server that answers one request per connection with the given replies, in order.
+    ///
+    /// # Arguments
+    /// - `replies`: Pairs of a status line (e.g., `503 Service Unavailable`) and how long to wait
+    ///   before sending it.
+    ///
+    /// # Returns
+    /// The address of the server and a counter of the requests it received.
+    async fn serve(replies: Vec<(&'static str, Duration)>) -> (String, Arc<AtomicUsize>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr: String = format!("http://{}", listener.local_addr().unwrap());
+        let count: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+        let server_count: Arc<AtomicUsize> = count.clone();
+        tokio::spawn(async move {
+            for (status, delay) in replies {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                server_count.fetch_add(1, Ordering::SeqCst);
+
+                // Read the full request, so the client doesn't get reset while sending it
+                let mut req: Vec<u8> = Vec::new();
+                let mut buf: [u8; 1024] = [0; 1024];
+                loop {
+                    let n: usize = conn.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    req.extend_from_slice(&buf[..n]);
+                    let Some(end) = req.windows(4).position(|w| w == b"\r\n\r\n") else {
+                        continue;
+                    };
+                    let head: String = String::from_utf8_lossy(&req[..end]).to_lowercase();
+                    let len: usize = head.lines().find_map(|l| l.strip_prefix("content-length:")).map(|l| l.trim().parse().unwrap()).unwrap_or(0);
+                    if req.len() >= end + 4 + len {
+                        break;
+                    }
+                }
+
+                tokio::time::sleep(delay).await;
+                let body: &str = r#"{"success":true,"errors":[],"results":[]}"#;
+                let res: String = format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+                conn.write_all(res.as_bytes()).await.unwrap();
+                conn.shutdown().await.unwrap();
+            }
+        });
+        (addr, count)
+    }
+
+    /// Builds a connector that sends its requests to the given HTTP address.
+    ///
+    /// # Arguments
+    /// - `addr`: The address of the reasoner.
+    async fn gen_http(addr: &str) -> EFlintJsonReasonerConnector<EFlintSilentReasonHandler, (), ()> {
+        EFlintJsonReasonerConnector::new_async(addr, EFlintSilentReasonHandler, &RecordingLogger::default()).await.unwrap()
+    }
+
+    /// Loads the phrases of a serialized [`Request`].
+    ///
+    /// # Arguments
+    /// - `raw`: The serialized request.
+    fn load_phrases(raw: &str) -> Vec<Phrase> {
+        let mut request: Value = serde_json::from_str(raw).unwrap();
+        serde_json::from_value(request["phrases"].take()).unwrap()
+    }
+
+    /// Serializes a request with the given phrases in one go.
+    ///
+    /// # Arguments
+    /// - `phrases`: The phrases to put in the request.
+    fn gen_request(phrases: Vec<Phrase>) -> Vec<u8> {
+        serde_json::to_vec(&Request::Phrases(RequestPhrases {
+            common: RequestCommon { version: Version::v0_1_0(), extensions: HashMap::new() },
+            phrases,
+            updates: true,
+        }))
+        .unwrap()
+    }
+
+    /// A request with a single phrase.
+    const TAUTOLOGY: &str = include_str!("../../../../tests/eflint-json/tautology.json");
+
+    /// A response without any results, which is a success.
+    // NOTE: The script reads the request first, so it doesn't exit while we're still writing it
+    const SUCCESS_SCRIPT: &str = r#"cat >/dev/null; printf '{"success":true,"errors":[],"results":[]}'"#;
+
+
+    #[test]
+    fn test_write_request_streamed() {
+        let phrases: Vec<Phrase> = load_phrases(TAUTOLOGY);
+        for n in [0, 1, 3] {
+            let state: Vec<Phrase> = phrases.iter().cloned().cycle().take(n).collect();
+            let mut streamed: Vec<u8> = Vec::new();
+            write_request_streamed::<Infallible, _, _>(&state, &(), &mut streamed).unwrap();
+            assert_eq!(String::from_utf8(streamed).unwrap(), String::from_utf8(gen_request(state)).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_streamed() {
+        // Enough phrases to be sent in multiple chunks
+        let state: Vec<Phrase> = load_phrases(TAUTOLOGY).into_iter().cycle().take(4096).collect();
+        let full: Vec<u8> = gen_request(state.clone());
+        assert!(full.len() > 2 * CHUNK_SIZE);
+
+        // The reasoner receives the same bytes as if the request was serialized in one go
+        let conn: EFlintJsonReasonerConnector<EFlintSilentReasonHandler, Vec<Phrase>, ()> =
+            EFlintJsonReasonerConnector::new_subprocess_async(["cat".into()], EFlintSilentReasonHandler, &RecordingLogger::default()).await.unwrap();
+        let raw: String = conn.send(RequestBody::Streamed(Arc::new((state, ())))).await.unwrap();
+        assert!(raw.as_bytes() == full.as_slice(), "Streamed request differs from full request");
+    }
+
+    #[tokio::test]
+    async fn test_send_subprocess() {
+        // The request arrives on stdin and the response is read from stdout, even if either
+        // exceeds the pipe buffers
+        let body: Vec<u8> = "x".repeat(1 << 20).into_bytes();
+        let conn = gen_subprocess("cat").await;
+        assert_eq!(conn.send(RequestBody::Full(body.clone())).await.unwrap().as_bytes(), body.as_slice());
+
+        // A reasoner that fails without reading its input reports its own status and stderr
+        let conn = gen_subprocess("echo oops >&2; exit 3").await;
+        let err = conn.send(RequestBody::Full(body)).await.unwrap_err();
+        match err {
+            Error::CommandFailure { status, stderr, .. } => {
+                assert_eq!(status.code(), Some(3));
+                assert_eq!(stderr, "oops\n");
+            },
+            err => panic!("Expected a command failure, got {err:?}"),
+        }
+
+        // A reasoner that hangs is killed
+        let conn = gen_subprocess("sleep 5").await.with_timeout(Some(Duration::from_millis(50)));
+        let err = conn.send(RequestBody::Full(b"{}".to_vec())).await.unwrap_err();
+        assert!(matches!(err, Error::CommandTimeout { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn test_context_tls() {
+        assert!(EFlintJsonReasonerContextFull::new("https://reasoner:8443/").tls);
+        assert!(EFlintJsonReasonerContextFull::new("HTTPS://reasoner").tls);
+        assert!(!EFlintJsonReasonerContextFull::new("http://https.reasoner").tls);
+        assert!(!EFlintJsonReasonerContextFull::new("https-reasoner --serve").tls);
+    }
+
+    #[tokio::test]
+    async fn test_verdict() {
+        const T: &str = r#"{"success":true,"result":true}"#;
+        const F: &str = r#"{"success":true,"result":false}"#;
+        const I: &str = r#"{"success":true,"result":[]}"#;
+        const OK: &str = r#"{"success":true,"changes":[],"triggers":[],"violated":false,"violations":[]}"#;
+        const VIOL: &str = r#"{"success":true,"changes":[],"triggers":[],"violated":true,"violations":[]}"#;
+
+        // Tuples of a strategy, the results, whether instances are accepted and the expected verdict
+        let cases: [(VerdictStrategy, &[&str], bool, &str); 17] = [
+            (VerdictStrategy::LastResult, &[], false, "success"),
+            (VerdictStrategy::LastResult, &[F, T], false, "success"),
+            (VerdictStrategy::LastResult, &[T, F], false, "violated"),
+            (VerdictStrategy::LastResult, &[F, OK], false, "success"),
+            (VerdictStrategy::LastResult, &[T, VIOL], false, "violated"),
+            (VerdictStrategy::LastResult, &[T, I], false, "indeterminate"),
+            (VerdictStrategy::LastResult, &[F, I], true, "success"),
+            (VerdictStrategy::AnyViolation, &[], false, "success"),
+            (VerdictStrategy::AnyViolation, &[T, OK, I], false, "success"),
+            (VerdictStrategy::AnyViolation, &[F, OK], false, "violated"),
+            (VerdictStrategy::AnyViolation, &[VIOL, T], false, "violated"),
+            (VerdictStrategy::AnyViolation, &[T, I], true, "success"),
+            (VerdictStrategy::RequireBooleanTerminator, &[VIOL, T], false, "success"),
+            (VerdictStrategy::RequireBooleanTerminator, &[OK, F], false, "violated"),
+            (VerdictStrategy::RequireBooleanTerminator, &[T, OK], false, "error"),
+            (VerdictStrategy::RequireBooleanTerminator, &[], false, "error"),
+            (VerdictStrategy::RequireBooleanTerminator, &[T, I], true, "success"),
+        ];
+        for (strategy, results, instances, verdict) in cases {
+            let conn = gen_subprocess(SUCCESS_SCRIPT).await.with_verdict_strategy(strategy);
+            let raw: String = format!(r#"{{"success":true,"errors":[],"results":[{}]}}"#, results.join(","));
+            let response: ResponsePhrases = serde_json::from_str(&raw).unwrap_or_else(|err| panic!("Failed to parse {raw:?}: {err}"));
+            let kind: &str = match conn.verdict(&response, instances) {
+                Ok(ReasonerResponse::Success) => "success",
+                Ok(ReasonerResponse::Violated(_)) => "violated",
+                Ok(ReasonerResponse::Indeterminate { .. }) => "indeterminate",
+                Err(Error::ResponseNoBooleanTerminator { .. }) => "error",
+                Err(err) => panic!("Unexpected error for {strategy:?} on {raw}: {err}"),
+            };
+            assert_eq!(kind, verdict, "{strategy:?} on {raw} (instances: {instances})");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consult_batch_sessions() {
+        let conn = gen_subprocess(SUCCESS_SCRIPT).await;
+        let logger = RecordingLogger::default();
+        let res = conn.consult_batch(vec![((), ()); 3], &logger.session("ref")).await;
+        assert_eq!(res.len(), 3);
+        for res in res {
+            assert!(matches!(res, Ok(ReasonerResponse::<NoReason>::Success)), "{res:?}");
+        }
+
+        // Every item has its own session, regardless of how they interleaved
+        let mut logs: Vec<String> = logger.0.into_inner().unwrap();
+        logs.sort();
+        assert_eq!(logs, ["question ref#0", "question ref#1", "question ref#2", "response ref#0", "response ref#1", "response ref#2"]);
+    }
+
+    #[tokio::test]
+    async fn test_send_http_retries_server_errors() {
+        let (addr, count) = serve(vec![("503 Service Unavailable", Duration::ZERO), ("200 OK", Duration::ZERO)]).await;
+        let conn = gen_http(&addr).await.with_retries(2, Duration::from_millis(1));
+        assert_eq!(conn.send(RequestBody::Full(b"{}".to_vec())).await.unwrap(), r#"{"success":true,"errors":[],"results":[]}"#);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_http_returns_last_error() {
+        let (addr, count) = serve(vec![("503 Service Unavailable", Duration::ZERO), ("502 Bad Gateway", Duration::ZERO)]).await;
+        let conn = gen_http(&addr).await.with_retries(1, Duration::from_millis(1));
+        let err = conn.send(RequestBody::Full(b"{}".to_vec())).await.unwrap_err();
+        assert!(matches!(err, Error::ReasonerStatus { status: StatusCode::BAD_GATEWAY, .. }), "{err:?}");
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_http_does_not_retry_client_errors() {
+        let (addr, count) = serve(vec![("400 Bad Request", Duration::ZERO), ("200 OK", Duration::ZERO)]).await;
+        let conn = gen_http(&addr).await.with_retries(2, Duration::from_millis(1));
+        let err = conn.send(RequestBody::Full(b"{}".to_vec())).await.unwrap_err();
+        assert!(matches!(err, Error::ReasonerStatus { status: StatusCode::BAD_REQUEST, .. }), "{err:?}");
+        assert!(!err.is_transient());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_http_timeout() {
+        let (addr, count) = serve(vec![("200 OK", Duration::from_secs(5))]).await;
+        let conn = gen_http(&addr).await.with_timeout(Some(Duration::from_millis(50)));
+        let err = conn.send(RequestBody::Full(b"{}".to_vec())).await.unwrap_err();
+        assert!(matches!(err, Error::ReasonerTimeout { .. }), "{err:?}");
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_http_unreachable() {
+        // Find a port that nobody listens on
+        let addr: String = {
+            let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let conn = gen_http(&addr).await;
+        let err = conn.send(RequestBody::Full(b"{}".to_vec())).await.unwrap_err();
+        assert!(matches!(err, Error::ReasonerRequest { .. }), "{err:?}");
+        assert!(err.is_transient());
+    }
 }
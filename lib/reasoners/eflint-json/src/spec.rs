@@ -4,7 +4,7 @@
 //  Created:
 //    09 Oct 2024, 16:06:18
 //  Last edited:
-//    06 Nov 2024, 15:03:26
+//    15 Oct 2026, 23:40:00
 //  Auto updated?
 //    Yes
 //
@@ -51,6 +51,26 @@ pub trait EFlintable {
     /// # Errors
     /// This function can fail if `self` is not in a right state to be serialized to eFLINT.
     fn to_eflint(&self) -> Result<Vec<Phrase>, Self::Error>;
+
+    /// Converts this state to eFLINT phrases, but emits them one-by-one instead of collecting
+    /// them all first.
+    ///
+    /// The default implementation simply calls [`EFlintable::to_eflint()`] and then emits the
+    /// result. Override it if your type can produce phrases without materializing all of them
+    /// (e.g., containers, which do so per element).
+    ///
+    /// # Arguments
+    /// - `sink`: Some callback that is called for every generated [`Phrase`], in order.
+    ///
+    /// # Errors
+    /// This function can fail if `self` is not in a right state to be serialized to eFLINT.
+    #[inline]
+    fn to_eflint_streamed(&self, sink: &mut dyn FnMut(Phrase)) -> Result<(), Self::Error> {
+        for phrase in self.to_eflint()? {
+            sink(phrase);
+        }
+        Ok(())
+    }
 }
 
 // Practical impls
@@ -75,18 +95,27 @@ impl<T: ?Sized + EFlintable> EFlintable for &T {
 
     #[inline]
     fn to_eflint(&self) -> Result<Vec<Phrase>, Self::Error> { <T as EFlintable>::to_eflint(self) }
+
+    #[inline]
+    fn to_eflint_streamed(&self, sink: &mut dyn FnMut(Phrase)) -> Result<(), Self::Error> { <T as EFlintable>::to_eflint_streamed(self, sink) }
 }
 impl<T: ?Sized + EFlintable> EFlintable for &mut T {
     type Error = T::Error;
 
     #[inline]
     fn to_eflint(&self) -> Result<Vec<Phrase>, Self::Error> { <T as EFlintable>::to_eflint(self) }
+
+    #[inline]
+    fn to_eflint_streamed(&self, sink: &mut dyn FnMut(Phrase)) -> Result<(), Self::Error> { <T as EFlintable>::to_eflint_streamed(self, sink) }
 }
 impl<T: ?Sized + EFlintable + ToOwned> EFlintable for Cow<'_, T> {
     type Error = T::Error;
 
     #[inline]
     fn to_eflint(&self) -> Result<Vec<Phrase>, Self::Error> { <T as EFlintable>::to_eflint(self) }
+
+    #[inline]
+    fn to_eflint_streamed(&self, sink: &mut dyn FnMut(Phrase)) -> Result<(), Self::Error> { <T as EFlintable>::to_eflint_streamed(self, sink) }
 }
 
 // Container impls
@@ -104,6 +133,14 @@ where
         }
         Ok(res)
     }
+
+    #[inline]
+    fn to_eflint_streamed(&self, sink: &mut dyn FnMut(Phrase)) -> Result<(), Self::Error> {
+        for (i, e) in self.iter().enumerate() {
+            e.to_eflint_streamed(sink).map_err(|source| Error { i, source })?;
+        }
+        Ok(())
+    }
 }
 impl<const LEN: usize, T: EFlintable> EFlintable for [T; LEN]
 where
@@ -113,6 +150,9 @@ where
 
     #[inline]
     fn to_eflint(&self) -> Result<Vec<Phrase>, Self::Error> { <[T] as EFlintable>::to_eflint(self) }
+
+    #[inline]
+    fn to_eflint_streamed(&self, sink: &mut dyn FnMut(Phrase)) -> Result<(), Self::Error> { <[T] as EFlintable>::to_eflint_streamed(self, sink) }
 }
 impl<T: EFlintable> EFlintable for Vec<T>
 where
@@ -122,4 +162,7 @@ where
 
     #[inline]
     fn to_eflint(&self) -> Result<Vec<Phrase>, Self::Error> { <[T] as EFlintable>::to_eflint(self) }
+
+    #[inline]
+    fn to_eflint_streamed(&self, sink: &mut dyn FnMut(Phrase)) -> Result<(), Self::Error> { <[T] as EFlintable>::to_eflint_streamed(self, sink) }
 }
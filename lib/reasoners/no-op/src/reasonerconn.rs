@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 16:21:09
 //  Last edited:
-//    06 May 2025, 12:51:00
+//    16 Oct 2026, 01:36:51
 //  Auto updated?
 //    Yes
 //
@@ -52,10 +52,22 @@ pub struct NoOpReasonerContext {
     pub language: String,
     /// The language's version identifier of this reasoner.
     pub language_version: String,
+    /// The reason with which every question is rejected, if the reasoner rejects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reject: Option<String>,
 }
 impl Default for NoOpReasonerContext {
     #[inline]
-    fn default() -> Self { Self { version: env!("CARGO_PKG_VERSION").into(), language: "no-op".into(), language_version: "v1".into() } }
+    fn default() -> Self { Self { version: env!("CARGO_PKG_VERSION").into(), language: "no-op".into(), language_version: "v1".into(), reject: None } }
+}
+impl From<NoOpVerdict> for NoOpReasonerContext {
+    #[inline]
+    fn from(value: NoOpVerdict) -> Self {
+        match value {
+            NoOpVerdict::Accept => Self::default(),
+            NoOpVerdict::Reject(reason) => Self { reject: Some(reason.into()), ..Self::default() },
+        }
+    }
 }
 impl ReasonerContext for NoOpReasonerContext {
     #[inline]
@@ -68,6 +80,30 @@ impl ReasonerContext for NoOpReasonerContext {
     fn language_version(&self) -> Cow<'_, str> { Cow::Borrowed(&self.language_version) }
 }
 
+/// Defines the verdict that the [`NoOpReasonerConnector`] gives to every question.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum NoOpVerdict {
+    /// Every question is approved. This is the default.
+    #[default]
+    Accept,
+    /// Every question is rejected with the given reason.
+    Reject(&'static str),
+}
+impl NoOpVerdict {
+    /// Returns the [`ReasonerResponse`] that this verdict represents.
+    ///
+    /// # Returns
+    /// A [`ReasonerResponse::Success`] for [`NoOpVerdict::Accept`], or a
+    /// [`ReasonerResponse::Violated`] with the reason for [`NoOpVerdict::Reject`].
+    #[inline]
+    pub fn response(&self) -> ReasonerResponse<String> {
+        match self {
+            Self::Accept => ReasonerResponse::Success,
+            Self::Reject(reason) => ReasonerResponse::Violated((*reason).into()),
+        }
+    }
+}
+
 
 
 
@@ -75,8 +111,13 @@ impl ReasonerContext for NoOpReasonerContext {
 /***** LIBRARY *****/
 /// The minimal no-operation reasoner connector, that approves all validation requests by default (it does not check any
 /// policy/permissions).
+///
+/// It can also be configured to reject all requests using
+/// [`NoOpReasonerConnector::new_with_verdict_async()`], which is useful to test deny-paths.
 #[derive(Clone, Copy, Debug)]
 pub struct NoOpReasonerConnector<Q> {
+    /// The verdict given to every question.
+    verdict:   NoOpVerdict,
     /// The completely arbitrary question that can be asked.
     _question: PhantomData<Q>,
 }
@@ -92,11 +133,25 @@ impl<Q> NoOpReasonerConnector<Q> {
     /// This function may error if it failed to log to the given `logger`.
     #[inline]
     pub async fn new_async<L: AuditLogger>(logger: &mut L) -> Result<Self, Error> {
+        Self::new_with_verdict_async(NoOpVerdict::default(), logger).await
+    }
+
+    /// Constructor for the NoOpReasonerConnector that gives the given verdict to every question.
+    ///
+    /// This constructor logs asynchronously.
+    ///
+    /// # Arguments
+    /// - `verdict`: The [`NoOpVerdict`] to give.
+    /// - `logger`: A logger to write this reasoner's context (including the `verdict`) to.
+    ///
+    /// # Errors
+    /// This function may error if it failed to log to the given `logger`.
+    pub async fn new_with_verdict_async<L: AuditLogger>(verdict: NoOpVerdict, logger: &mut L) -> Result<Self, Error> {
         logger
-            .log_context(&NoOpReasonerContext::default())
+            .log_context(&NoOpReasonerContext::from(verdict))
             .await
             .map_err(|err| Error::LogContext { to: std::any::type_name::<L>(), source: err.freeze() })?;
-        Ok(Self { _question: PhantomData })
+        Ok(Self { verdict, _question: PhantomData })
     }
 }
 impl<Q> ReasonerConnector for NoOpReasonerConnector<Q>
@@ -106,13 +161,13 @@ where
     type Context = NoOpReasonerContext;
     type Error = Error;
     type Question = Q;
-    type Reason = ();
+    type Reason = String;
     type State = ();
 
     #[inline]
-    fn context(&self) -> Self::Context { NoOpReasonerContext::default() }
+    fn context(&self) -> Self::Context { NoOpReasonerContext::from(self.verdict) }
 
-    #[instrument(name = "NoOpReasonerContext", skip_all, fields(reference=logger.reference()))]
+    #[instrument(name = "NoOpReasonerConnector::consult", skip_all, fields(reference = logger.reference()))]
     async fn consult<'a, L>(
         &'a self,
         state: Self::State,
@@ -131,11 +186,92 @@ where
             .map_err(|err| Error::LogQuestion { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
 
         // Log the reasoner has been called
+        let response: ReasonerResponse<String> = self.verdict.response();
         logger
-            .log_response::<u8>(&ReasonerResponse::Success, None)
+            .log_response(&response, None)
             .await
             .map_err(|err| Error::LogResponse { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
 
-        Ok(ReasonerResponse::Success)
+        Ok(response)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::fmt::Display;
+    use std::sync::Mutex;
+
+    use serde_json::Value;
+
+    use super::*;
+
+
+    /// An [`AuditLogger`] that records the contexts and responses logged to it.
+    #[derive(Default)]
+    struct RecordingLogger {
+        contexts:  Mutex<Vec<Value>>,
+        responses: Mutex<Vec<String>>,
+    }
+    impl AuditLogger for RecordingLogger {
+        type Error = Infallible;
+
+        async fn log_context<'a, C>(&'a self, context: &'a C) -> Result<(), Self::Error>
+        where
+            C: ?Sized + Sync + ReasonerContext,
+        {
+            self.contexts.lock().unwrap().push(serde_json::to_value(context).unwrap());
+            Ok(())
+        }
+
+        async fn log_response<'a, R>(
+            &'a self,
+            _reference: &'a str,
+            response: &'a ReasonerResponse<R>,
+            _raw: Option<&'a str>,
+        ) -> Result<(), Self::Error>
+        where
+            R: Sync + Display,
+        {
+            self.responses.lock().unwrap().push(match response {
+                ReasonerResponse::Success => "success".into(),
+                ReasonerResponse::Violated(reason) => format!("violated: {reason}"),
+                ReasonerResponse::Indeterminate { reasons } => format!("indeterminate: {reasons}"),
+            });
+            Ok(())
+        }
+
+        async fn log_question<'a, S, Q>(&'a self, _reference: &'a str, _state: &'a S, _question: &'a Q) -> Result<(), Self::Error>
+        where
+            S: Sync + Serialize,
+            Q: Sync + Serialize,
+        {
+            Ok(())
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_verdict() {
+        for (verdict, reject, response) in [(NoOpVerdict::Accept, None, "success"), (NoOpVerdict::Reject("nope"), Some("nope"), "violated: nope")] {
+            let mut logger = RecordingLogger::default();
+            let conn: NoOpReasonerConnector<()> = NoOpReasonerConnector::new_with_verdict_async(verdict, &mut logger).await.unwrap();
+
+            // The verdict is part of the logged context
+            assert_eq!(conn.context().reject.as_deref(), reject);
+            let contexts: Vec<Value> = logger.contexts.lock().unwrap().clone();
+            assert_eq!(contexts, [serde_json::to_value(conn.context()).unwrap()]);
+            assert_eq!(contexts[0].get("reject").and_then(Value::as_str), reject);
+
+            // And it's given to (and logged for) every question
+            let session = SessionedAuditLogger::new("test", &logger);
+            assert_eq!(conn.consult((), (), &session).await.unwrap(), verdict.response());
+            assert_eq!(*logger.responses.lock().unwrap(), [response]);
+        }
     }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    15 Oct 2024, 14:17:44
 //  Last edited:
-//    06 May 2025, 11:42:26
+//    16 Oct 2026, 02:29:03
 //  Auto updated?
 //    Yes
 //
@@ -28,10 +28,85 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
     /// Defines the location ID of this location.
-    pub id:   String,
+    pub id: String,
     /// Defines a map from datasets to where to find them on the disk (that one Harry Potter movie?)
     #[serde(default = "HashMap::new", skip_serializing_if = "HashMap::is_empty")]
     pub data: HashMap<String, DataPolicy>,
+    /// Defines what to do with workflow users that are not in a dataset's `user_map`.
+    #[serde(default)]
+    pub unknown_identity: UnknownIdentityPolicy,
+    /// Defines what to do with workflows that contain calls not planned on any location.
+    #[serde(default)]
+    pub unplaced_calls: UnplacedCallPolicy,
+    /// Defines a map from tasks to the executables (or container images) implementing them on
+    /// disk. Tasks in this map can only be executed by users who may execute that file.
+    #[serde(default = "HashMap::new", skip_serializing_if = "HashMap::is_empty")]
+    pub task_paths: HashMap<String, PathBuf>,
+    /// Maps workflow users to the local identities used to execute tasks in `task_paths`.
+    #[serde(default = "HashMap::new", skip_serializing_if = "HashMap::is_empty")]
+    pub task_user_map: HashMap<String, PosixLocalIdentity>,
+}
+
+
+
+/// Part of the [`Config`]. Defines what happens when a workflow user accesses a dataset (or
+/// executes a task) for which they have no [`PosixLocalIdentity`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownIdentityPolicy {
+    /// The user is treated as any other user, i.e., the file's "others" permissions apply.
+    #[default]
+    AssumeOthers,
+    /// The access is denied outright.
+    Deny,
+}
+
+
+
+/// Part of the [`Config`]. Defines what happens when a workflow contains calls that have not been
+/// planned on any location yet.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnplacedCallPolicy {
+    /// The calls are assumed to run on the special `<unspecified>` location, for which users can
+    /// be mapped explicitly.
+    ///
+    /// Note that this means the calls are not checked at all unless that location is mapped, so
+    /// this must be opted into explicitly.
+    AssumeUnspecified,
+    /// The reasoner cannot reach a verdict, and returns [`Indeterminate`](spec::reasonerconn::ReasonerResponse::Indeterminate).
+    /// This is the default.
+    #[default]
+    Indeterminate,
+    /// The workflow is rejected outright.
+    Deny,
+}
+
+
+
+/// Part of the [`DataPolicy`]. Defines how the permissions of a dataset are checked if its path
+/// is (or goes through) a symbolic link.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    /// Symbolic links are followed, i.e., only the permissions of the file they point to are
+    /// checked.
+    #[default]
+    Follow,
+    /// Symbolic links are not followed, i.e., only the permissions of the link itself are
+    /// checked.
+    ///
+    /// As the mode of a link is meaningless (it is always `0777` on Linux), the link is checked
+    /// by the directory containing it instead. That directory must grant execute permission, so
+    /// the link can be resolved, and write permission too if writing is requested, so the link
+    /// can be replaced.
+    NoFollow,
+    /// Both the permissions of the link itself (see [`SymlinkPolicy::NoFollow`]) and those of the
+    /// file it points to are checked.
+    Both,
+    /// The access is denied outright if the path or any of its parent directories is a symbolic
+    /// link.
+    Deny,
 }
 
 
@@ -41,9 +116,20 @@ pub struct Config {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DataPolicy {
     /// The location where we find this dataset on disk.
-    pub path:     PathBuf,
+    pub path:      PathBuf,
     #[serde(default = "HashMap::new", skip_serializing_if = "HashMap::is_empty")]
-    pub user_map: HashMap<String, PosixLocalIdentity>,
+    pub user_map:  HashMap<String, PosixLocalIdentity>,
+    /// What to do if `path` is (or goes through) a symbolic link.
+    #[serde(default)]
+    pub symlinks:  SymlinkPolicy,
+    /// If `path` is a directory, whether all files within it must satisfy the requested
+    /// permissions too (instead of just the directory itself).
+    #[serde(default)]
+    pub recursive: bool,
+    /// If `recursive` is given, how deep to descend into the directory (where `0` checks the
+    /// directory only, `1` its immediate contents, etc.). Unlimited if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
 }
 
 /// The local identity defines a user id and a list of group ids. The local identity is used on the machine on which a
@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2024, 16:35:23
 //  Last edited:
-//    06 May 2025, 12:50:19
+//    16 Oct 2026, 00:46:23
 //  Auto updated?
 //    Yes
 //
@@ -16,6 +16,8 @@
 // Declare the modules
 pub mod config;
 mod reasonerconn;
+pub mod reasons;
+pub mod stat;
 mod workflow;
 
 // Use some of it
@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2024, 16:54:51
 //  Last edited:
-//    06 May 2025, 12:52:13
+//    16 Oct 2026, 02:32:57
 //  Auto updated?
 //    Yes
 //
@@ -14,30 +14,56 @@
 
 
 use std::borrow::Cow;
+use std::collections::HashSet;
 /***** LIBRARY *****/
 use std::iter::repeat;
 use std::ops::BitOr;
-use std::os::unix::fs::{MetadataExt as _, PermissionsExt as _};
 use std::path::{Path, PathBuf};
 
 use error_trace::{ErrorTrace as _, Trace};
 use serde::{Deserialize, Serialize};
 use spec::auditlogger::{AuditLogger, SessionedAuditLogger};
 use spec::reasonerconn::{ReasonerConnector, ReasonerContext, ReasonerResponse};
-use spec::reasons::NoReason;
+use spec::reasons::ManyReason;
 use thiserror::Error;
-use tokio::fs;
-use tracing::{debug, info};
-use workflow::Workflow;
+use tracing::{debug, info, instrument, warn};
+use workflow::{ElemCall, Workflow};
+
+use crate::config::{Config, DataPolicy, PosixLocalIdentity, SymlinkPolicy, UnknownIdentityPolicy, UnplacedCallPolicy};
+use crate::reasons::{PosixReason, UnplacedCall};
+#[cfg(feature = "acl")]
+use crate::stat::Acl;
+use crate::stat::{CachedFileStat, FileFacts, FileStat, RealFileStat};
+use crate::workflow::{WorkflowDatasets, redact_tasks};
+
+
+/***** CONSTANTS *****/
+/// The `errno` reported when reading ACLs from a filesystem that does not support them.
+///
+/// NOTE: `ENOTSUP` and `EOPNOTSUPP` are the same on Linux, and neither maps to
+/// [`std::io::ErrorKind::Unsupported`].
+#[cfg(feature = "acl")]
+const ENOTSUP: i32 = 95;
+
+
 
-use crate::config::{Config, DataPolicy, PosixLocalIdentity};
-use crate::workflow::WorkflowDatasets;
 
 
 /***** ERRORS *****/
 /// Represents an error that occurs during validation of the policy.
 #[derive(Debug, Error)]
 pub enum Error {
+    /// Failed to retrieve a file's ACL.
+    #[cfg(feature = "acl")]
+    #[error("Failed to get file {} ACL", path.display())]
+    AclRead { path: PathBuf, source: std::io::Error },
+    /// A file lives on a filesystem that does not support ACLs.
+    #[cfg(feature = "acl")]
+    #[error("Cannot get file {} ACL because its filesystem does not support ACLs", path.display())]
+    AclUnsupported { path: PathBuf, source: std::io::Error },
+    /// Failed to list a directory's contents.
+    #[error("Failed to read directory {}", path.display())]
+    DirRead { path: PathBuf, source: std::io::Error },
     /// Failed to retrieve a file's metadata.
     #[error("Failed to get file {} metadata", path.display())]
     FileMetadata { path: PathBuf, source: std::io::Error },
@@ -50,9 +76,6 @@ pub enum Error {
     /// Failed to log the question to the given logger.
     #[error("Failed to log the question to {to}")]
     LogQuestion { to: &'static str, source: Trace },
-    /// The dataset was unknown to us.
-    #[error("Unknown dataset {data:?}")]
-    UnknownDataset { data: String },
 }
 
 
@@ -63,11 +86,228 @@ pub enum Error {
 /// Verifies whether the passed [`PosixLocalIdentity`] has all of the requested permissions (e.g., `Read` and `Write`)
 /// on a particular file (defined by the `path`). The identity's user id and group ids are checked against the file
 /// owner's user id and group id respectively. Additionally, the `Others` class permissions are also checked.
-async fn satisfies_posix_permissions(
+///
+/// # Returns
+/// [`None`] if the permissions are satisfied, or else a [`PosixReason`] (attributed to `dataset`) explaining why not.
+async fn check_posix_permissions(
+    stat: &impl FileStat,
+    dataset: &str,
     path: impl AsRef<Path>,
     local_identity: Option<&PosixLocalIdentity>,
     requested_permissions: PosixFilePermissions,
-) -> Result<bool, Error> {
+) -> Result<Option<PosixReason>, Error> {
+    let path: &Path = path.as_ref();
+    let facts: FileFacts = stat.stat(path).await.map_err(|source| Error::FileMetadata { path: path.into(), source })?;
+
+    // Named ACL entries take precedence over the classic permissions
+    #[cfg(feature = "acl")]
+    {
+        let acl: Option<Acl> = stat.acl(path).await.map_err(|source| {
+            if source.kind() == std::io::ErrorKind::Unsupported || source.raw_os_error() == Some(ENOTSUP) {
+                Error::AclUnsupported { path: path.into(), source }
+            } else {
+                Error::AclRead { path: path.into(), source }
+            }
+        })?;
+        if let Some(res) = acl.and_then(|acl| check_acl(&facts, &acl, dataset, path, local_identity, requested_permissions)) {
+            return Ok(res);
+        }
+    }
+
+    Ok(check_facts(&facts, dataset, path, local_identity, requested_permissions))
+}
+
+/// Verifies whether the passed [`PosixLocalIdentity`] has all of the requested permissions according to the named
+/// entries in the [`Acl`] of the file at `path`. See [`check_posix_permissions()`].
+///
+/// Like `acl(5)`, the permissions granted by named users and any group (including the owning group's entry) are
+/// limited by the ACL's mask, and the owner is never subject to the ACL.
+///
+/// # Returns
+/// [`None`] if no entry of the ACL applies to the identity (such that [`check_facts()`] decides by the owner or
+/// other permissions), or else the outcome of the check as [`check_facts()`] would return it.
+#[cfg(feature = "acl")]
+fn check_acl(
+    facts: &FileFacts,
+    acl: &Acl,
+    dataset: &str,
+    path: &Path,
+    local_identity: Option<&PosixLocalIdentity>,
+    requested_permissions: PosixFilePermissions,
+) -> Option<Option<PosixReason>> {
+    let identity: &PosixLocalIdentity = local_identity?;
+    if identity.uid == facts.uid {
+        return None;
+    }
+
+    let requested: u32 = requested_permissions.as_u8() as u32;
+    let mask: u32 = acl.mask.unwrap_or(0o7);
+    let grants = |perms: u32| perms & mask & requested == requested;
+    let granted: bool = if let Some((_, perms)) = acl.users.iter().find(|(uid, _)| *uid == identity.uid) {
+        debug!("Using named user ACL entry ({perms:o}, mask {mask:o})");
+        grants(*perms)
+    } else {
+        // NOTE: The owning group's entry is just another group entry, and thus also limited by the mask. We cannot
+        //       leave it to `check_facts()`, as a file's mode shows the mask instead of that entry.
+        let owning: Option<u32> = identity.gids.contains(&facts.gid).then_some(acl.group_obj);
+        let mut entries = acl.groups.iter().filter(|(gid, _)| identity.gids.contains(gid)).map(|(_, perms)| *perms).chain(owning).peekable();
+        entries.peek()?;
+        debug!("Using group ACL entries (mask {mask:o})");
+        entries.any(grants)
+    };
+
+    // NOTE: Named entries belong to the group class, which is what a file's mode shows the mask as
+    Some(if granted {
+        None
+    } else {
+        Some(PosixReason::PermissionDenied {
+            dataset:   dataset.into(),
+            path:      path.into(),
+            requested: PosixFileClass::Group.get_mode_bitmask(requested_permissions),
+            mode:      facts.mode,
+        })
+    })
+}
+
+/// Verifies whether the passed [`PosixLocalIdentity`] has all of the requested permissions on a dataset, like
+/// [`check_posix_permissions()`], but taking the dataset's [`SymlinkPolicy`] into account.
+///
+/// If the dataset is `recursive`, then every file in it (up to its `max_depth`) is checked too.
+///
+/// # Returns
+/// [`None`] if the permissions are satisfied, or else a [`PosixReason`] naming the first path that does not satisfy
+/// them.
+async fn check_data_policy(
+    stat: &impl FileStat,
+    dataset: &str,
+    policy: &DataPolicy,
+    local_identity: Option<&PosixLocalIdentity>,
+    requested_permissions: PosixFilePermissions,
+) -> Result<Option<PosixReason>, Error> {
+    if let Some(reason) = check_path_permissions(stat, dataset, &policy.path, policy.symlinks, true, local_identity, requested_permissions).await? {
+        return Ok(Some(reason));
+    }
+    if !policy.recursive {
+        return Ok(None);
+    }
+
+    // Walk the directory, checking all entries of a directory before descending into them
+    // NOTE: We keep track of the directories we've seen to guard against symbolic link loops
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut todo: Vec<(PathBuf, usize)> = vec![(policy.path.clone(), 0)];
+    while let Some((dir, depth)) = todo.pop() {
+        if policy.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            continue;
+        }
+        if policy.symlinks == SymlinkPolicy::NoFollow
+            && stat.symlink_stat(&dir).await.map_err(|source| Error::FileMetadata { path: dir.clone(), source })?.is_some()
+        {
+            continue;
+        }
+        let Some(mut entries) = stat.read_dir(&dir).await.map_err(|source| Error::DirRead { path: dir.clone(), source })? else {
+            continue;
+        };
+        let real: PathBuf = stat.canonicalize(&dir).await.map_err(|source| Error::FileMetadata { path: dir.clone(), source })?;
+        if !visited.insert(real) {
+            debug!("Not descending into '{}' again", dir.display());
+            continue;
+        }
+
+        debug!("Checking {} entries in directory '{}'", entries.len(), dir.display());
+        entries.sort();
+        for entry in &entries {
+            if let Some(reason) = check_path_permissions(stat, dataset, entry, policy.symlinks, false, local_identity, requested_permissions).await? {
+                return Ok(Some(reason));
+            }
+        }
+        todo.extend(entries.into_iter().rev().map(|entry| (entry, depth + 1)));
+    }
+    Ok(None)
+}
+
+/// Verifies whether the passed [`PosixLocalIdentity`] has all of the requested permissions on a single path, like
+/// [`check_posix_permissions()`], but taking the given [`SymlinkPolicy`] into account.
+///
+/// If `check_parents` is false, then [`SymlinkPolicy::Deny`] only considers whether `path` itself is a symbolic link.
+async fn check_path_permissions(
+    stat: &impl FileStat,
+    dataset: &str,
+    path: &Path,
+    symlinks: SymlinkPolicy,
+    check_parents: bool,
+    local_identity: Option<&PosixLocalIdentity>,
+    requested_permissions: PosixFilePermissions,
+) -> Result<Option<PosixReason>, Error> {
+    let symlink_stat = |path: &Path| {
+        let path: PathBuf = path.into();
+        async move { stat.symlink_stat(&path).await.map_err(|source| Error::FileMetadata { path, source }) }
+    };
+
+    match symlinks {
+        SymlinkPolicy::Follow => {},
+        SymlinkPolicy::NoFollow => {
+            if symlink_stat(path).await?.is_some() {
+                debug!("Checking symbolic link '{}' itself", path.display());
+                return check_link_permissions(stat, dataset, path, local_identity, requested_permissions).await;
+            }
+        },
+        SymlinkPolicy::Both => {
+            if symlink_stat(path).await?.is_some() {
+                debug!("Checking symbolic link '{}' itself before its target", path.display());
+                if let Some(reason) = check_link_permissions(stat, dataset, path, local_identity, requested_permissions).await? {
+                    return Ok(Some(reason));
+                }
+            }
+        },
+        SymlinkPolicy::Deny => {
+            let n_ancestors: usize = if check_parents { usize::MAX } else { 1 };
+            for ancestor in path.ancestors().take(n_ancestors).filter(|ancestor| !ancestor.as_os_str().is_empty()) {
+                if symlink_stat(ancestor).await?.is_some() {
+                    debug!("Path '{}' goes through symbolic link '{}'; denying access", path.display(), ancestor.display());
+                    return Ok(Some(PosixReason::Symlink { dataset: dataset.into(), path: path.into(), link: ancestor.into() }));
+                }
+            }
+        },
+    }
+    check_posix_permissions(stat, dataset, path, local_identity, requested_permissions).await
+}
+
+/// Verifies whether the passed [`PosixLocalIdentity`] has all of the requested permissions on the symbolic link at
+/// `path` itself, i.e., without following it.
+///
+/// The mode of a symbolic link is meaningless (on Linux, it is always `0777`), so this checks the directory containing
+/// the link instead. Resolving the link requires execute permission on that directory, and replacing it (if write
+/// permission is requested) write permission too.
+///
+/// # Returns
+/// [`None`] if the permissions are satisfied, or else a [`PosixReason`] naming the directory that does not satisfy
+/// them.
+async fn check_link_permissions(
+    stat: &impl FileStat,
+    dataset: &str,
+    path: &Path,
+    local_identity: Option<&PosixLocalIdentity>,
+    requested_permissions: PosixFilePermissions,
+) -> Result<Option<PosixReason>, Error> {
+    let parent: &Path = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let wants_write: bool = requested_permissions.as_u8() as u32 & PosixFilePermission::Write.to_mode_bit() != 0;
+    let parent_permissions: PosixFilePermissions =
+        if wants_write { PosixFilePermission::Write | PosixFilePermission::Execute } else { PosixFilePermission::Execute.to_set() };
+    check_posix_permissions(stat, dataset, parent, local_identity, parent_permissions).await
+}
+
+/// Verifies whether the passed [`PosixLocalIdentity`] has all of the requested permissions according to the given
+/// [`FileFacts`] of the file at `path`. See [`check_posix_permissions()`].
+fn check_facts(
+    facts: &FileFacts,
+    dataset: &str,
+    path: &Path,
+    local_identity: Option<&PosixLocalIdentity>,
+    requested_permissions: PosixFilePermissions,
+) -> Option<PosixReason> {
     #[inline]
     const fn is_user_owner(owner_id: u32, local_identity: Option<&PosixLocalIdentity>) -> bool {
         if let Some(id) = local_identity { owner_id == id.uid } else { false }
@@ -77,13 +317,10 @@ async fn satisfies_posix_permissions(
         if let Some(id) = local_identity { id.gids.contains(&group_id) } else { false }
     }
 
-    let path: &Path = path.as_ref();
-    let metadata = fs::metadata(path).await.map_err(|source| Error::FileMetadata { path: path.into(), source })?;
-
     // First, get the appropriate UIDs from the file
-    let mode_bits = metadata.permissions().mode();
-    let file_owner_uid = metadata.uid();
-    let file_owner_gid = metadata.gid();
+    let mode_bits = facts.mode;
+    let file_owner_uid = facts.uid;
+    let file_owner_gid = facts.gid;
     debug!("Checking if user {local_identity:?} is owner of file with UID={file_owner_uid},GID={file_owner_gid}");
 
     // Then decide which permissions to base ourselves on
@@ -105,7 +342,11 @@ async fn satisfies_posix_permissions(
     };
 
     // Finally, check if the permissions align
-    Ok(mode_bits & mask == mask)
+    if mode_bits & mask == mask {
+        None
+    } else {
+        Some(PosixReason::PermissionDenied { dataset: dataset.into(), path: path.into(), requested: mask, mode: mode_bits })
+    }
 }
 
 
@@ -230,7 +471,20 @@ pub struct State {
 
 /***** LIBRARY *****/
 /// The POSIX reasoner connector. This connector is used to validate workflows based on POSIX file permissions.
-pub struct PosixReasonerConnector;
+///
+/// The file owners and permissions are retrieved through a [`FileStat`], which defaults to the real
+/// filesystem (see [`PosixReasonerConnector::with_file_stat()`]).
+#[derive(Clone, Debug, Default)]
+pub struct PosixReasonerConnector<F = RealFileStat> {
+    /// How we retrieve the owners and permissions of files.
+    stat: F,
+    /// If given, redacts dataset identifiers before they are written to the audit log.
+    redact_datasets: Option<fn(&str) -> Cow<'_, str>>,
+    /// If given, redacts entity identifiers before they are written to the audit log.
+    redact_entities: Option<fn(&str) -> Cow<'_, str>>,
+    /// If given, redacts task (and call) identifiers before they are written to the audit log.
+    redact_tasks: Option<fn(&str) -> Cow<'_, str>>,
+}
 impl PosixReasonerConnector {
     /// Constructor for the PosixReasonerConnector.
     ///
@@ -247,20 +501,193 @@ impl PosixReasonerConnector {
             .log_context(&PosixReasonerContext::default())
             .await
             .map_err(|err| Error::LogContext { to: std::any::type_name::<L>(), source: err.freeze() })?;
-        Ok(Self)
+        Ok(Self::default())
+    }
+}
+impl<F> PosixReasonerConnector<F> {
+    /// Replaces how this connector retrieves the owners and permissions of files.
+    ///
+    /// This is mostly useful in tests, to run the reasoner against an
+    /// [`InMemoryFileStat`](crate::stat::InMemoryFileStat) instead of the real filesystem.
+    ///
+    /// # Arguments
+    /// - `stat`: The new [`FileStat`] to use.
+    ///
+    /// # Returns
+    /// A new PosixReasonerConnector that uses the given `stat`, but is otherwise the same.
+    #[inline]
+    pub fn with_file_stat<F2>(self, stat: F2) -> PosixReasonerConnector<F2> {
+        PosixReasonerConnector { stat, redact_datasets: self.redact_datasets, redact_entities: self.redact_entities, redact_tasks: self.redact_tasks }
+    }
+
+    /// Configures this connector to redact identifiers before they are written to the audit log.
+    ///
+    /// This keeps audits correlatable without exposing identifiers which may be sensitive
+    /// themselves (e.g., a patient cohort). By default, nothing is redacted.
+    ///
+    /// # Arguments
+    /// - `datasets`: A function that maps dataset identifiers to their redacted counterparts. A
+    ///   common implementation hashes and truncates them.
+    /// - `entities`: A function that maps entity identifiers (i.e., users and locations) to their
+    ///   redacted counterparts. If omitted, these are logged as-is.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_redaction(mut self, datasets: fn(&str) -> Cow<'_, str>, entities: Option<fn(&str) -> Cow<'_, str>>) -> Self {
+        self.redact_datasets = Some(datasets);
+        self.redact_entities = entities;
+        self
+    }
+
+    /// Configures this connector to redact task identifiers before they are written to the audit
+    /// log.
+    ///
+    /// This works like [`PosixReasonerConnector::with_redaction()`], and is configured separately
+    /// because task identifiers are (usually) less sensitive than dataset identifiers. The
+    /// identifiers of calls are redacted with the same function, as they typically contain the
+    /// task's. By default, nothing is redacted.
+    ///
+    /// # Arguments
+    /// - `tasks`: A function that maps task (and call) identifiers to their redacted counterparts.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_task_redaction(mut self, tasks: fn(&str) -> Cow<'_, str>) -> Self {
+        self.redact_tasks = Some(tasks);
+        self
+    }
+
+    /// Redacts the given dataset identifier for logging, if configured to do so.
+    ///
+    /// # Arguments
+    /// - `id`: The dataset identifier to redact.
+    ///
+    /// # Returns
+    /// The (potentially) redacted identifier.
+    #[inline]
+    fn redact_dataset<'s>(&self, id: &'s str) -> Cow<'s, str> { self.redact_datasets.map(|redact| redact(id)).unwrap_or(Cow::Borrowed(id)) }
+
+    /// Redacts the given entity identifier for logging, if configured to do so.
+    ///
+    /// # Arguments
+    /// - `id`: The entity identifier to redact.
+    ///
+    /// # Returns
+    /// The (potentially) redacted identifier.
+    #[inline]
+    fn redact_entity<'s>(&self, id: &'s str) -> Cow<'s, str> { self.redact_entities.map(|redact| redact(id)).unwrap_or(Cow::Borrowed(id)) }
+
+    /// Redacts the given task (or call) identifier for logging, if configured to do so.
+    ///
+    /// # Arguments
+    /// - `id`: The task identifier to redact.
+    ///
+    /// # Returns
+    /// The (potentially) redacted identifier.
+    #[inline]
+    fn redact_task<'s>(&self, id: &'s str) -> Cow<'s, str> { self.redact_tasks.map(|redact| redact(id)).unwrap_or(Cow::Borrowed(id)) }
+
+    /// Redacts the identifiers in the given [`State`] for logging, if configured to do so.
+    ///
+    /// # Arguments
+    /// - `state`: The [`State`] to redact.
+    ///
+    /// # Returns
+    /// The given `state` if there is nothing to redact, or else a redacted copy of it.
+    fn redact_state<'s>(&self, state: &'s State) -> Cow<'s, State> {
+        if self.redact_datasets.is_none() && self.redact_tasks.is_none() {
+            return Cow::Borrowed(state);
+        }
+
+        let mut state: State = state.clone();
+        if let Some(datasets) = self.redact_datasets {
+            state.workflow.redact(datasets, self.redact_entities);
+            state.config.data = state
+                .config
+                .data
+                .into_iter()
+                .map(|(id, mut policy)| {
+                    policy.user_map =
+                        policy.user_map.into_iter().map(|(user, identity)| (self.redact_entity(&user).into_owned(), identity)).collect();
+                    (self.redact_dataset(&id).into_owned(), policy)
+                })
+                .collect();
+            state.config.task_user_map =
+                state.config.task_user_map.into_iter().map(|(user, identity)| (self.redact_entity(&user).into_owned(), identity)).collect();
+        }
+        if let Some(tasks) = self.redact_tasks {
+            redact_tasks(&mut state.workflow, tasks);
+            state.config.task_paths = state.config.task_paths.into_iter().map(|(task, path)| (self.redact_task(&task).into_owned(), path)).collect();
+        }
+        Cow::Owned(state)
+    }
+
+    /// Redacts the identifiers in the reasons of the given [`ReasonerResponse`] for logging, if configured to do so.
+    ///
+    /// # Arguments
+    /// - `res`: The [`ReasonerResponse`] to redact.
+    ///
+    /// # Returns
+    /// The given `res` if there is nothing to redact, or else a redacted copy of it.
+    fn redact_response<'s>(&self, res: &'s ReasonerResponse<ManyReason<PosixReason>>) -> Cow<'s, ReasonerResponse<ManyReason<PosixReason>>> {
+        if self.redact_datasets.is_none() && self.redact_tasks.is_none() {
+            return Cow::Borrowed(res);
+        }
+
+        let redact = |reasons: &ManyReason<PosixReason>| -> ManyReason<PosixReason> {
+            reasons
+                .iter()
+                .map(|reason| match reason.clone() {
+                    PosixReason::PermissionDenied { dataset, path, requested, mode } => {
+                        PosixReason::PermissionDenied { dataset: self.redact_dataset(&dataset).into_owned(), path, requested, mode }
+                    },
+                    PosixReason::TaskPermissionDenied { task, path, requested, mode } => {
+                        PosixReason::TaskPermissionDenied { task: self.redact_task(&task).into_owned(), path, requested, mode }
+                    },
+                    PosixReason::Symlink { dataset, path, link } => {
+                        PosixReason::Symlink { dataset: self.redact_dataset(&dataset).into_owned(), path, link }
+                    },
+                    PosixReason::NoLocalIdentity { user, dataset } => PosixReason::NoLocalIdentity {
+                        user:    self.redact_entity(&user).into_owned(),
+                        dataset: self.redact_dataset(&dataset).into_owned(),
+                    },
+                    PosixReason::NoLocalTaskIdentity { user, task } => {
+                        PosixReason::NoLocalTaskIdentity { user: self.redact_entity(&user).into_owned(), task: self.redact_task(&task).into_owned() }
+                    },
+                    PosixReason::UnplacedCalls { calls } => PosixReason::UnplacedCalls {
+                        calls: calls
+                            .into_iter()
+                            .map(|UnplacedCall { call, task }| UnplacedCall {
+                                call: self.redact_task(&call).into_owned(),
+                                task: self.redact_task(&task).into_owned(),
+                            })
+                            .collect(),
+                    },
+                    PosixReason::UnknownDataset { dataset } => PosixReason::UnknownDataset { dataset: self.redact_dataset(&dataset).into_owned() },
+                })
+                .collect()
+        };
+        Cow::Owned(match res {
+            ReasonerResponse::Success => ReasonerResponse::Success,
+            ReasonerResponse::Violated(reasons) => ReasonerResponse::Violated(redact(reasons)),
+            ReasonerResponse::Indeterminate { reasons } => ReasonerResponse::Indeterminate { reasons: redact(reasons) },
+        })
     }
 }
-impl ReasonerConnector for PosixReasonerConnector {
+impl<F: Sync + FileStat> ReasonerConnector for PosixReasonerConnector<F> {
     type Context = PosixReasonerContext;
     type Error = Error;
     type Question = ();
-    type Reason = NoReason;
+    type Reason = ManyReason<PosixReason>;
     type State = State;
 
     #[inline]
     fn context(&self) -> Self::Context { PosixReasonerContext::default() }
 
     #[inline]
+    #[instrument(name = "PosixReasonerConnector::consult", skip_all, fields(reference = logger.reference()))]
     async fn consult<'a, L>(
         &'a self,
         state: Self::State,
@@ -272,15 +699,46 @@ impl ReasonerConnector for PosixReasonerConnector {
     {
         // Log the input
         logger
-            .log_question(&state, &())
+            .log_question(&*self.redact_state(&state), &())
             .await
             .map_err(|err| Error::LogQuestion { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
 
+        // We cannot check anything for calls that aren't planned yet
+        if state.config.unplaced_calls != UnplacedCallPolicy::AssumeUnspecified {
+            let unplaced: Vec<&ElemCall> = state.workflow.unplaced_calls();
+            if !unplaced.is_empty() {
+                let calls: Vec<UnplacedCall> = unplaced.iter().map(|call| UnplacedCall { call: call.id.clone(), task: call.task.clone() }).collect();
+                warn!(
+                    "Workflow {id:?} has unplaced calls {calls}; cannot decide whether they may be executed",
+                    id = state.workflow.id,
+                    calls = calls.iter().map(UnplacedCall::to_string).collect::<Vec<String>>().join(", ")
+                );
+                let reasons: ManyReason<PosixReason> = [PosixReason::UnplacedCalls { calls }].into_iter().collect();
+                let res: ReasonerResponse<ManyReason<PosixReason>> = if state.config.unplaced_calls == UnplacedCallPolicy::Deny {
+                    ReasonerResponse::Violated(reasons)
+                } else {
+                    ReasonerResponse::Indeterminate { reasons }
+                };
+                logger
+                    .log_response(&*self.redact_response(&res), None)
+                    .await
+                    .map_err(|err| Error::LogResponse { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
+                return Ok(res);
+            }
+        }
+
+        // Only look up every path once while deliberating
+        // NOTE: Not any longer than that, since permissions may change in between requests
+        let stat: CachedFileStat<F> = CachedFileStat::new(&self.stat);
+
         // The datasets used in the workflow. E.g., `st_antonius_ect`.
         let datasets: WorkflowDatasets = WorkflowDatasets::new(&state.config.id, &state.workflow);
         debug!("Found datasets in workflow {id}: {datasets:#?}", id = state.workflow.id);
 
         // Loop to find the permissions on the disk
+        // NOTE: We check everything instead of stopping at the first problem, such that users learn about all of them
+        let mut violations: ManyReason<PosixReason> = ManyReason::new();
+        let mut unknowns: ManyReason<PosixReason> = ManyReason::new();
         for ((location, dataset), permission) in std::iter::empty()
             .chain(datasets.read_sets.iter().zip(repeat(PosixFilePermission::Read.to_set())))
             .chain(datasets.write_sets.iter().zip(repeat(PosixFilePermission::Write.to_set())))
@@ -289,26 +747,514 @@ impl ReasonerConnector for PosixReasonerConnector {
             info!("Testing dataset {id:?} for permission to {permission:?} for user {location:?}", id = dataset.id);
 
             // Find the location of the dataset in the list
+            // NOTE: If we don't know where the dataset lives, we cannot check its permissions either
             let policy: &DataPolicy = match state.config.data.get(&dataset.id) {
                 Some(data) => data,
-                None => return Err(Error::UnknownDataset { data: dataset.id.clone() }),
+                None => {
+                    warn!("Unknown dataset {id:?}; cannot decide whether it may be accessed", id = dataset.id);
+                    unknowns.push(PosixReason::UnknownDataset { dataset: dataset.id.clone() });
+                    continue;
+                },
             };
 
+            // Decide what to do with users we can't map to a local identity
+            let identity: Option<&PosixLocalIdentity> = policy.user_map.get(&location.id);
+            if identity.is_none() && state.config.unknown_identity == UnknownIdentityPolicy::Deny {
+                warn!("User {user:?} has no local identity for dataset {id:?}; denying access", user = location.id, id = dataset.id);
+                violations.push(PosixReason::NoLocalIdentity { user: location.id.clone(), dataset: dataset.id.clone() });
+                continue;
+            }
+
             // Now check the policy!
-            if !satisfies_posix_permissions(&policy.path, policy.user_map.get(&location.id), permission).await? {
-                logger
-                    .log_response(&ReasonerResponse::Violated(NoReason), Some("false"))
-                    .await
-                    .map_err(|err| Error::LogResponse { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
-                return Ok(ReasonerResponse::Violated(NoReason));
+            if let Some(reason) = check_data_policy(&stat, &dataset.id, policy, identity, permission).await? {
+                warn!("Dataset {id:?} does not satisfy permission to {permission:?}: {reason}", id = dataset.id);
+                violations.push(reason);
             }
         }
 
-        // If none of them failed prematurely, then we're done
+        // Then check whether the tasks executed here may be executed at all
+        for (location, task) in &datasets.tasks {
+            // NOTE: Tasks we don't know the executable of are not checked
+            let Some(path) = state.config.task_paths.get(*task) else {
+                continue;
+            };
+            info!("Testing task {task:?} for permission to execute for user {location:?}");
+
+            // Decide what to do with users we can't map to a local identity
+            let identity: Option<&PosixLocalIdentity> = state.config.task_user_map.get(&location.id);
+            if identity.is_none() && state.config.unknown_identity == UnknownIdentityPolicy::Deny {
+                warn!("User {user:?} has no local identity to execute task {task:?}; denying execution", user = location.id);
+                violations.push(PosixReason::NoLocalTaskIdentity { user: location.id.clone(), task: (*task).into() });
+                continue;
+            }
+
+            // Now check the policy!
+            if let Some(reason) = check_posix_permissions(&stat, task, path, identity, PosixFilePermission::Execute.to_set()).await? {
+                warn!("Task {task:?} may not be executed: {reason}");
+                violations.push(match reason {
+                    PosixReason::PermissionDenied { dataset: _, path, requested, mode } => {
+                        PosixReason::TaskPermissionDenied { task: (*task).into(), path, requested, mode }
+                    },
+                    reason => reason,
+                });
+            }
+        }
+
+        // Any violation is decisive; otherwise, unknown datasets leave us undecided
+        let res: ReasonerResponse<ManyReason<PosixReason>> = if !violations.is_empty() {
+            ReasonerResponse::Violated(violations)
+        } else if !unknowns.is_empty() {
+            ReasonerResponse::Indeterminate { reasons: unknowns }
+        } else {
+            ReasonerResponse::Success
+        };
         logger
-            .log_response(&ReasonerResponse::<NoReason>::Success, Some("true"))
+            .log_response(&*self.redact_response(&res), None)
             .await
             .map_err(|err| Error::LogResponse { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
-        Ok(ReasonerResponse::Success)
+        Ok(res)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stat::InMemoryFileStat;
+
+
+    /// Tests whether unplaced calls are only refused if so configured.
+    #[tokio::test]
+    async fn test_consult_unplaced() {
+        use std::collections::HashMap;
+
+        use no_op_logger::MockLogger;
+        use workflow::Elem;
+
+        let workflow = Workflow {
+            id: "wf".into(),
+            start: Elem::Call(ElemCall {
+                id: "call".into(),
+                task: "task".into(),
+                input: vec![],
+                output: vec![],
+                at: None,
+                metadata: vec![],
+                next: Box::new(Elem::Stop),
+            }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+        let config = |unplaced_calls: UnplacedCallPolicy| Config {
+            id: "here".into(),
+            data: HashMap::new(),
+            unknown_identity: UnknownIdentityPolicy::AssumeOthers,
+            unplaced_calls,
+            task_paths: HashMap::new(),
+            task_user_map: HashMap::new(),
+        };
+
+        let conn = PosixReasonerConnector::default().with_file_stat(InMemoryFileStat::new());
+        let logger = SessionedAuditLogger::new("test", MockLogger::new());
+        for (policy, expected) in [
+            (UnplacedCallPolicy::default(), "indeterminate"),
+            (UnplacedCallPolicy::AssumeUnspecified, "success"),
+            (UnplacedCallPolicy::Indeterminate, "indeterminate"),
+            (UnplacedCallPolicy::Deny, "violated"),
+        ] {
+            let res = conn.consult(State { config: config(policy), workflow: workflow.clone() }, (), &logger).await.unwrap();
+            let kind: &str = match &res {
+                ReasonerResponse::Success => "success",
+                ReasonerResponse::Violated(reasons) | ReasonerResponse::Indeterminate { reasons } => {
+                    assert!(matches!(reasons.iter().collect::<Vec<_>>().as_slice(), [PosixReason::UnplacedCalls { .. }]), "{res:?}");
+                    if matches!(res, ReasonerResponse::Violated(_)) { "violated" } else { "indeterminate" }
+                },
+            };
+            assert_eq!(kind, expected, "{policy:?}");
+        }
+
+        // A config that doesn't mention the policy at all refuses to decide too
+        let config: Config = serde_json::from_str(r#"{ "id": "here" }"#).unwrap();
+        assert_eq!(config.unplaced_calls, UnplacedCallPolicy::Indeterminate);
+        match conn.consult(State { config, workflow }, (), &logger).await.unwrap() {
+            ReasonerResponse::Indeterminate { reasons } => {
+                assert!(matches!(reasons.iter().collect::<Vec<_>>().as_slice(), [PosixReason::UnplacedCalls { .. }]))
+            },
+            res => panic!("Expected an indeterminate response, got {res:?}"),
+        }
+    }
+
+    /// Tests whether unknown datasets do not hide violations of other datasets.
+    #[tokio::test]
+    async fn test_consult_unknown_and_violating() {
+        use std::collections::HashMap;
+
+        use no_op_logger::MockLogger;
+        use workflow::{Dataset, Elem, Entity};
+
+        let here = Entity { id: "here".into() };
+        let data = |id: &str| Dataset { id: id.into(), from: Some(here.clone()) };
+        let workflow = Workflow {
+            id: "wf".into(),
+            start: Elem::Call(ElemCall {
+                id: "call".into(),
+                task: "task".into(),
+                input: vec![data("unknown"), data("secret")],
+                output: vec![],
+                at: Some(here.clone()),
+                metadata: vec![],
+                next: Box::new(Elem::Stop),
+            }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+        let policy = DataPolicy {
+            path:      "/secret".into(),
+            user_map:  HashMap::new(),
+            symlinks:  SymlinkPolicy::Follow,
+            recursive: false,
+            max_depth: None,
+        };
+        let config = Config {
+            id: "here".into(),
+            data: HashMap::from([("secret".into(), policy)]),
+            unknown_identity: UnknownIdentityPolicy::AssumeOthers,
+            unplaced_calls: UnplacedCallPolicy::default(),
+            task_paths: HashMap::new(),
+            task_user_map: HashMap::new(),
+        };
+
+        // The unknown dataset comes first, but must not stop the secret one from being checked
+        let stat = InMemoryFileStat::new().with_file("/secret", FileFacts { uid: 1000, gid: 100, mode: 0o600 });
+        let conn = PosixReasonerConnector::default().with_file_stat(stat);
+        let res = conn.consult(State { config, workflow }, (), &SessionedAuditLogger::new("test", MockLogger::new())).await.unwrap();
+        match res {
+            ReasonerResponse::Violated(reasons) => {
+                assert!(
+                    matches!(reasons.iter().collect::<Vec<_>>().as_slice(), [PosixReason::PermissionDenied { dataset, .. }] if dataset == "secret")
+                )
+            },
+            res => panic!("Expected a violation, got {res:?}"),
+        }
+    }
+
+    /// Tests whether every identifier in the reasons is redacted with the function for its kind.
+    #[test]
+    fn test_redact_response() {
+        fn datasets(id: &str) -> Cow<'_, str> { Cow::Owned(format!("d:{id}")) }
+        fn entities(id: &str) -> Cow<'_, str> { Cow::Owned(format!("e:{id}")) }
+        fn tasks(id: &str) -> Cow<'_, str> { Cow::Owned(format!("t:{id}")) }
+
+        let reasons: ManyReason<PosixReason> = [
+            PosixReason::PermissionDenied { dataset: "data".into(), path: "/data".into(), requested: 0o004, mode: 0o600 },
+            PosixReason::TaskPermissionDenied { task: "task".into(), path: "/bin/task".into(), requested: 0o001, mode: 0o700 },
+            PosixReason::Symlink { dataset: "data".into(), path: "/data".into(), link: "/data".into() },
+            PosixReason::NoLocalIdentity { user: "amy".into(), dataset: "data".into() },
+            PosixReason::NoLocalTaskIdentity { user: "amy".into(), task: "task".into() },
+            PosixReason::UnplacedCalls { calls: vec![UnplacedCall { call: "call".into(), task: "task".into() }] },
+            PosixReason::UnknownDataset { dataset: "data".into() },
+        ]
+        .into_iter()
+        .collect();
+        let res: ReasonerResponse<ManyReason<PosixReason>> = ReasonerResponse::Violated(reasons);
+
+        // Nothing is redacted by default
+        let conn = PosixReasonerConnector::default();
+        assert!(matches!(conn.redact_response(&res), Cow::Borrowed(_)));
+
+        // Otherwise, all identifiers are
+        let conn = PosixReasonerConnector::default().with_redaction(datasets, Some(entities)).with_task_redaction(tasks);
+        let ReasonerResponse::Violated(reasons) = conn.redact_response(&res).into_owned() else { panic!("Redaction changed the verdict") };
+        assert_eq!(reasons.iter().cloned().collect::<Vec<PosixReason>>(), [
+            PosixReason::PermissionDenied { dataset: "d:data".into(), path: "/data".into(), requested: 0o004, mode: 0o600 },
+            PosixReason::TaskPermissionDenied { task: "t:task".into(), path: "/bin/task".into(), requested: 0o001, mode: 0o700 },
+            PosixReason::Symlink { dataset: "d:data".into(), path: "/data".into(), link: "/data".into() },
+            PosixReason::NoLocalIdentity { user: "e:amy".into(), dataset: "d:data".into() },
+            PosixReason::NoLocalTaskIdentity { user: "e:amy".into(), task: "t:task".into() },
+            PosixReason::UnplacedCalls { calls: vec![UnplacedCall { call: "t:call".into(), task: "t:task".into() }] },
+            PosixReason::UnknownDataset { dataset: "d:data".into() },
+        ]);
+
+        // Tasks can be redacted on their own
+        let conn = PosixReasonerConnector::default().with_task_redaction(tasks);
+        let ReasonerResponse::Violated(reasons) = conn.redact_response(&res).into_owned() else { panic!("Redaction changed the verdict") };
+        let reasons: Vec<PosixReason> = reasons.iter().cloned().collect();
+        assert_eq!(reasons[0], PosixReason::PermissionDenied {
+            dataset:   "data".into(),
+            path:      "/data".into(),
+            requested: 0o004,
+            mode:      0o600,
+        });
+        assert_eq!(reasons[4], PosixReason::NoLocalTaskIdentity { user: "amy".into(), task: "t:task".into() });
+    }
+
+    /// Tests whether executing a task without a local identity is explained as such.
+    #[tokio::test]
+    async fn test_consult_no_task_identity() {
+        use std::collections::HashMap;
+
+        use no_op_logger::MockLogger;
+        use workflow::{Elem, Entity};
+
+        let workflow = Workflow {
+            id: "wf".into(),
+            start: Elem::Call(ElemCall {
+                id: "call".into(),
+                task: "task".into(),
+                input: vec![],
+                output: vec![],
+                at: Some(Entity { id: "here".into() }),
+                metadata: vec![],
+                next: Box::new(Elem::Stop),
+            }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+        let config = Config {
+            id: "here".into(),
+            data: HashMap::new(),
+            unknown_identity: UnknownIdentityPolicy::Deny,
+            unplaced_calls: UnplacedCallPolicy::default(),
+            task_paths: HashMap::from([("task".into(), "/bin/task".into())]),
+            task_user_map: HashMap::new(),
+        };
+
+        let conn = PosixReasonerConnector::default().with_file_stat(InMemoryFileStat::new());
+        let res = conn.consult(State { config, workflow }, (), &SessionedAuditLogger::new("test", MockLogger::new())).await.unwrap();
+        match res {
+            ReasonerResponse::Violated(reasons) => {
+                let reasons: Vec<&PosixReason> = reasons.iter().collect();
+                assert!(matches!(reasons.as_slice(), [PosixReason::NoLocalTaskIdentity { user, task }] if user == "here" && task == "task"));
+                assert_eq!(reasons[0].to_string(), "No local identity for user 'here' to execute task 'task'");
+            },
+            res => panic!("Expected a violation, got {res:?}"),
+        }
+    }
+
+    /// Tests whether the right file class is used to decide permissions.
+    #[tokio::test]
+    async fn test_check_posix_permissions() {
+        // The file is readable and writable by its owner, readable by its group and executable by others
+        let stat = InMemoryFileStat::new().with_file("/data", FileFacts { uid: 1000, gid: 100, mode: 0o641 });
+        let owner = PosixLocalIdentity { uid: 1000, gids: vec![] };
+        let member = PosixLocalIdentity { uid: 1001, gids: vec![100] };
+        let other = PosixLocalIdentity { uid: 1002, gids: vec![101] };
+
+        let read: PosixFilePermissions = PosixFilePermission::Read.to_set();
+        let read_write: PosixFilePermissions = PosixFilePermission::Read | PosixFilePermission::Write;
+        let exec: PosixFilePermissions = PosixFilePermission::Execute.to_set();
+        for (identity, perms, expected) in [
+            (Some(&owner), read, true),
+            (Some(&owner), read_write, true),
+            (Some(&owner), exec, false),
+            (Some(&member), read, true),
+            (Some(&member), read_write, false),
+            (Some(&member), exec, false),
+            (Some(&other), read, false),
+            (Some(&other), exec, true),
+            (None, read, false),
+            (None, exec, true),
+        ] {
+            assert_eq!(
+                check_posix_permissions(&stat, "data", "/data", identity, perms).await.unwrap().is_none(),
+                expected,
+                "{identity:?} requesting {perms:?}"
+            );
+        }
+
+        // Missing files are errors
+        assert!(matches!(check_posix_permissions(&stat, "data", "/missing", Some(&owner), read).await, Err(Error::FileMetadata { .. })));
+    }
+
+    /// Tests whether symbolic links are treated according to the [`SymlinkPolicy`].
+    #[tokio::test]
+    async fn test_check_data_policy_symlinks() {
+        use std::collections::HashMap;
+        use std::os::unix::fs::{MetadataExt as _, PermissionsExt as _, symlink};
+
+        // A file that only its owner may write, a link to it and a link to its directory
+        // NOTE: Also a link in a directory that its owner may not write, to check links themselves against
+        let dir = tempfile::tempdir().unwrap();
+        let target: PathBuf = dir.path().join("target");
+        std::fs::write(&target, b"data").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o200)).unwrap();
+        let link: PathBuf = dir.path().join("link");
+        symlink(&target, &link).unwrap();
+        let link_dir: PathBuf = dir.path().join("link_dir");
+        symlink(dir.path(), &link_dir).unwrap();
+        let locked: PathBuf = dir.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        let locked_link: PathBuf = locked.join("link");
+        symlink(&target, &locked_link).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o500)).unwrap();
+        let owner = PosixLocalIdentity { uid: std::fs::metadata(&target).unwrap().uid(), gids: vec![] };
+
+        let read: PosixFilePermissions = PosixFilePermission::Read.to_set();
+        let write: PosixFilePermissions = PosixFilePermission::Write.to_set();
+        let mut results: Vec<(String, Result<Option<PosixReason>, Error>, bool)> = Vec::new();
+        for (path, symlinks, perms, expected) in [
+            // The link itself is decided by its directory, which the owner may do anything in, but its target is not
+            (&link, SymlinkPolicy::Follow, read, false),
+            (&link, SymlinkPolicy::NoFollow, read, true),
+            (&link, SymlinkPolicy::Both, read, false),
+            (&link, SymlinkPolicy::Follow, write, true),
+            (&link, SymlinkPolicy::NoFollow, write, true),
+            (&link, SymlinkPolicy::Both, write, true),
+            // A link in a directory that may not be written can be resolved, but not replaced
+            (&locked_link, SymlinkPolicy::NoFollow, read, true),
+            (&locked_link, SymlinkPolicy::NoFollow, write, false),
+            (&locked_link, SymlinkPolicy::Both, write, false),
+            (&locked_link, SymlinkPolicy::Follow, write, true),
+            // Links anywhere in the path are denied if asked
+            (&link, SymlinkPolicy::Deny, write, false),
+            (&link_dir.join("target"), SymlinkPolicy::Deny, write, false),
+            (&target, SymlinkPolicy::Deny, write, true),
+            // Paths without links are the same for every policy
+            (&target, SymlinkPolicy::NoFollow, read, false),
+            (&target, SymlinkPolicy::NoFollow, write, true),
+        ] {
+            let policy = DataPolicy { path: path.clone(), user_map: HashMap::new(), symlinks, recursive: false, max_depth: None };
+            let res = check_data_policy(&RealFileStat, "data", &policy, Some(&owner), perms).await;
+            results.push((format!("{} with {symlinks:?} requesting {perms:?}", path.display()), res, expected));
+        }
+
+        // NOTE: Restore the locked directory before asserting anything, such that the temporary directory can be removed
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o700)).unwrap();
+        for (case, res, expected) in results {
+            assert_eq!(res.unwrap().is_none(), expected, "{case}");
+        }
+    }
+
+    /// Tests whether directories are checked recursively if asked.
+    #[tokio::test]
+    async fn test_check_data_policy_recursive() {
+        use std::collections::HashMap;
+
+        // A readable directory with a readable file, and an unreadable file one level deeper
+        let readable = FileFacts { uid: 1000, gid: 100, mode: 0o755 };
+        let stat = InMemoryFileStat::new()
+            .with_file("/data", readable)
+            .with_file("/data/a", readable)
+            .with_file("/data/b", readable)
+            .with_file("/data/b/c", readable)
+            .with_file("/data/b/d", FileFacts { uid: 1000, gid: 100, mode: 0o700 })
+            .with_symlink("/data/b/e", "/data", readable);
+        let other = PosixLocalIdentity { uid: 1001, gids: vec![] };
+        let read: PosixFilePermissions = PosixFilePermission::Read.to_set();
+
+        for (recursive, max_depth, expected) in [
+            (false, None, None),
+            (true, Some(0), None),
+            (true, Some(1), None),
+            (true, Some(2), Some(PathBuf::from("/data/b/d"))),
+            (true, None, Some(PathBuf::from("/data/b/d"))),
+        ] {
+            let policy = DataPolicy { path: "/data".into(), user_map: HashMap::new(), symlinks: SymlinkPolicy::Follow, recursive, max_depth };
+            assert_eq!(
+                check_data_policy(&stat, "data", &policy, Some(&other), read).await.unwrap().map(|reason| match reason {
+                    PosixReason::PermissionDenied { path, .. } => path,
+                    reason => panic!("Unexpected reason {reason:?}"),
+                }),
+                expected,
+                "recursive={recursive}, max_depth={max_depth:?}"
+            );
+        }
+    }
+
+    /// Tests whether recursive checks terminate on symbolic link loops.
+    #[tokio::test]
+    async fn test_check_data_policy_recursive_loop() {
+        use std::collections::HashMap;
+        use std::os::unix::fs::{MetadataExt as _, symlink};
+
+        // A directory with a file and a link back to the directory itself
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("file"), b"data").unwrap();
+        symlink(dir.path(), dir.path().join("loop")).unwrap();
+        let owner = PosixLocalIdentity { uid: std::fs::metadata(dir.path()).unwrap().uid(), gids: vec![] };
+
+        let policy = DataPolicy {
+            path:      dir.path().into(),
+            user_map:  HashMap::new(),
+            symlinks:  SymlinkPolicy::Follow,
+            recursive: true,
+            max_depth: None,
+        };
+        assert_eq!(check_data_policy(&RealFileStat, "data", &policy, Some(&owner), PosixFilePermission::Read.to_set()).await.unwrap(), None);
+    }
+
+    /// Tests whether named ACL entries take precedence over the classic permissions.
+    #[cfg(feature = "acl")]
+    #[tokio::test]
+    async fn test_check_posix_permissions_acl() {
+        // Only the owner may access the file classically, but the ACL grants others access
+        let acl = Acl { group_obj: 0o0, users: vec![(1001, 0o6)], groups: vec![(200, 0o4)], mask: Some(0o4) };
+        let stat = InMemoryFileStat::new().with_file("/data", FileFacts { uid: 1000, gid: 100, mode: 0o640 }).with_acl("/data", acl);
+        let owner = PosixLocalIdentity { uid: 1000, gids: vec![] };
+        let named_user = PosixLocalIdentity { uid: 1001, gids: vec![] };
+        let named_group = PosixLocalIdentity { uid: 1002, gids: vec![200] };
+        let owning_group = PosixLocalIdentity { uid: 1003, gids: vec![100] };
+
+        let read: PosixFilePermissions = PosixFilePermission::Read.to_set();
+        let write: PosixFilePermissions = PosixFilePermission::Write.to_set();
+        for (identity, perms, expected) in [
+            (&owner, write, true),
+            (&named_user, read, true),
+            // The mask limits what named entries grant
+            (&named_user, write, false),
+            (&named_group, read, true),
+            (&named_group, write, false),
+            // The owning group's entry applies (limited by the mask), not the mode's group bits
+            (&owning_group, read, false),
+        ] {
+            assert_eq!(
+                check_posix_permissions(&stat, "data", "/data", Some(identity), perms).await.unwrap().is_none(),
+                expected,
+                "{identity:?} requesting {perms:?}"
+            );
+        }
+
+        // The owning group's entry grants access only as far as the mask allows
+        for (group_obj, mask, expected) in [(0o4, Some(0o4), true), (0o4, Some(0o0), false), (0o0, Some(0o4), false), (0o4, None, true)] {
+            let acl = Acl { group_obj, users: vec![], groups: vec![], mask };
+            let stat = InMemoryFileStat::new().with_file("/data", FileFacts { uid: 1000, gid: 100, mode: 0o640 }).with_acl("/data", acl);
+            assert_eq!(
+                check_posix_permissions(&stat, "data", "/data", Some(&owning_group), read).await.unwrap().is_none(),
+                expected,
+                "group_obj={group_obj:o}, mask={mask:?}"
+            );
+        }
+    }
+
+    /// Tests whether ACLs set with `setfacl` are respected, if the tool and filesystem support it.
+    #[cfg(all(feature = "acl", target_os = "linux"))]
+    #[tokio::test]
+    async fn test_check_posix_permissions_setfacl() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file: PathBuf = dir.path().join("file");
+        std::fs::write(&file, b"data").unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o600)).unwrap();
+        match std::process::Command::new("setfacl").arg("-m").arg("u:54321:r").arg(&file).status() {
+            Ok(status) if status.success() => {},
+            _ => {
+                eprintln!("Skipping test; cannot set ACLs with setfacl");
+                return;
+            },
+        }
+
+        let named_user = PosixLocalIdentity { uid: 54321, gids: vec![] };
+        let other = PosixLocalIdentity { uid: 54322, gids: vec![] };
+        let read: PosixFilePermissions = PosixFilePermission::Read.to_set();
+        assert!(check_posix_permissions(&RealFileStat, "data", &file, Some(&named_user), read).await.unwrap().is_none());
+        assert!(check_posix_permissions(&RealFileStat, "data", &file, Some(&other), read).await.unwrap().is_some());
     }
 }
@@ -0,0 +1,198 @@
+//  REASONS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:02:14
+//  Last edited:
+//    16 Oct 2026, 02:32:44
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the reasons the POSIX reasoner gives for not approving a
+//!   workflow.
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+
+/***** CONSTANTS *****/
+/// The file classes, with how far their permission bits are shifted in a mode.
+const CLASSES: [(&str, u32); 3] = [("owner", 6), ("group", 3), ("others", 0)];
+
+/// The permissions, with their (unshifted) bit in a mode.
+const PERMISSIONS: [(&str, u32); 3] = [("read", 0o4), ("write", 0o2), ("execute", 0o1)];
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Explains that a file does not grant the requested permissions.
+///
+/// # Arguments
+/// - `f`: The [`Formatter`] to write to.
+/// - `path`: The path of the offending file.
+/// - `requested`: The mode bits that had to be set.
+/// - `mode`: The mode bits that were actually set.
+///
+/// # Errors
+/// This function errors if we failed to write to `f`.
+fn fmt_denied(f: &mut Formatter<'_>, path: &Path, requested: u32, mode: u32) -> FResult {
+    // Collapse the requested bits of all classes to find what was requested at all
+    let perms: u32 = (requested | requested >> 3 | requested >> 6) & 0o7;
+    let names: Vec<&str> = PERMISSIONS.iter().filter(|(_, bit)| perms & bit != 0).map(|(name, _)| *name).collect();
+    let mut what: String = match names.split_last() {
+        Some((last, [])) => (*last).into(),
+        Some((last, init)) => format!("{} and {last}", init.join(", ")),
+        None => "Access".into(),
+    };
+    if let Some(first) = what.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+
+    // Then list the bits that are missing, per class
+    let missing: u32 = requested & !mode;
+    let need: Vec<String> = CLASSES
+        .iter()
+        .flat_map(|(class, shift)| {
+            PERMISSIONS.iter().filter(move |(_, bit)| missing & (bit << shift) != 0).map(move |(perm, _)| format!("{class}-{perm}"))
+        })
+        .collect();
+    write!(f, "{what} denied on '{}': have {:04o}, need {}", path.display(), mode & 0o7777, need.join(", "))
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Describes a call that isn't planned on any location yet.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UnplacedCall {
+    /// The identifier of the call.
+    pub call: String,
+    /// The identifier of the task it executes.
+    pub task: String,
+}
+impl Display for UnplacedCall {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{:?} (task {:?})", self.call, self.task) }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Explains why the POSIX reasoner did not approve (part of) a workflow.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PosixReason {
+    /// A dataset's file does not grant the requested permissions.
+    PermissionDenied {
+        /// The identifier of the dataset the file belongs to.
+        dataset:   String,
+        /// The path of the offending file.
+        path:      PathBuf,
+        /// The mode bits that had to be set (e.g., `0o004` if others had to be able to read).
+        requested: u32,
+        /// The mode bits that were actually set.
+        mode:      u32,
+    },
+    /// A task's executable does not grant the requested permissions.
+    TaskPermissionDenied {
+        /// The identifier of the task the executable implements.
+        task:      String,
+        /// The path of the executable.
+        path:      PathBuf,
+        /// The mode bits that had to be set (e.g., `0o001` if others had to be able to execute).
+        requested: u32,
+        /// The mode bits that were actually set.
+        mode:      u32,
+    },
+    /// A dataset's path goes through a symbolic link, which its policy denies.
+    Symlink {
+        /// The identifier of the dataset.
+        dataset: String,
+        /// The path that was accessed.
+        path:    PathBuf,
+        /// The symbolic link (`path` or one of its parents) that was encountered.
+        link:    PathBuf,
+    },
+    /// A user has no local identity to access a dataset with, which the config denies.
+    NoLocalIdentity {
+        /// The identifier of the workflow user.
+        user:    String,
+        /// The identifier of the dataset accessed.
+        dataset: String,
+    },
+    /// A user has no local identity to execute a task with, which the config denies.
+    NoLocalTaskIdentity {
+        /// The identifier of the workflow user.
+        user: String,
+        /// The identifier of the task executed.
+        task: String,
+    },
+    /// The workflow contains calls that aren't planned on any location yet.
+    UnplacedCalls {
+        /// Every unplaced call.
+        calls: Vec<UnplacedCall>,
+    },
+    /// The workflow uses a dataset for which there is no policy.
+    UnknownDataset {
+        /// The identifier of the dataset.
+        dataset: String,
+    },
+}
+impl Display for PosixReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::PermissionDenied { dataset: _, path, requested, mode } | Self::TaskPermissionDenied { task: _, path, requested, mode } => {
+                fmt_denied(f, path, *requested, *mode)
+            },
+            Self::Symlink { dataset: _, path, link } => {
+                write!(f, "Access denied on '{}': goes through symbolic link '{}'", path.display(), link.display())
+            },
+            Self::NoLocalIdentity { user, dataset } => write!(f, "No local identity for user '{user}' to access '{dataset}'"),
+            Self::NoLocalTaskIdentity { user, task } => write!(f, "No local identity for user '{user}' to execute task '{task}'"),
+            Self::UnplacedCalls { calls } => {
+                write!(f, "Calls {} are not planned on any location", calls.iter().map(UnplacedCall::to_string).collect::<Vec<String>>().join(", "))
+            },
+            Self::UnknownDataset { dataset } => write!(f, "Unknown dataset '{dataset}'"),
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests whether denied permissions are explained in terms of the missing bits.
+    #[test]
+    fn test_permission_denied_display() {
+        let reason = |requested: u32, mode: u32| PosixReason::PermissionDenied { dataset: "x".into(), path: "/data/x".into(), requested, mode };
+        assert_eq!(reason(0o004, 0o100640).to_string(), "Read denied on '/data/x': have 0640, need others-read");
+        assert_eq!(reason(0o050, 0o640).to_string(), "Read and execute denied on '/data/x': have 0640, need group-execute");
+        assert_eq!(reason(0o600, 0o000).to_string(), "Read and write denied on '/data/x': have 0000, need owner-read, owner-write");
+        let reason = PosixReason::TaskPermissionDenied { task: "x".into(), path: "/bin/x".into(), requested: 0o001, mode: 0o700 };
+        assert_eq!(reason.to_string(), "Execute denied on '/bin/x': have 0700, need others-execute");
+    }
+
+    /// Tests whether unplaced calls are listed with their tasks.
+    #[test]
+    fn test_unplaced_calls_display() {
+        let call = |call: &str, task: &str| UnplacedCall { call: call.into(), task: task.into() };
+        let reason = PosixReason::UnplacedCalls { calls: vec![call("a", "foo"), call("b", "bar")] };
+        assert_eq!(reason.to_string(), r#"Calls "a" (task "foo"), "b" (task "bar") are not planned on any location"#);
+    }
+}
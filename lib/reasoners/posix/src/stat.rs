@@ -0,0 +1,501 @@
+//  STAT.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:05:55
+//  Last edited:
+//    16 Oct 2026, 00:48:13
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Abstracts over how the POSIX reasoner learns who owns a file and
+//!   what its permissions are, such that it can run against something
+//!   else than the real filesystem (e.g., in tests).
+//
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::future::Future;
+use std::io;
+use std::os::unix::fs::{MetadataExt as _, PermissionsExt as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tokio::fs;
+
+
+/***** CONSTANTS *****/
+/// The name of the extended attribute that holds a file's access ACL.
+#[cfg(all(feature = "acl", target_os = "linux"))]
+const ACL_XATTR: &str = "system.posix_acl_access";
+
+
+
+
+
+/***** AUXILLARY *****/
+/// The facts about a file that the POSIX reasoner bases its decisions on.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FileFacts {
+    /// The user identifier of the file's owner.
+    pub uid:  u32,
+    /// The group identifier of the file's owning group.
+    pub gid:  u32,
+    /// The file's mode bits (e.g., `0o640`).
+    pub mode: u32,
+}
+
+
+
+/// The extended POSIX ACL entries of a file (see `acl(5)`).
+///
+/// The entries for the owner and others are omitted, since they are reflected in the file's mode
+/// bits anyway.
+#[cfg(feature = "acl")]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Acl {
+    /// The permissions (e.g., `0o6` for read and write) of the owning group.
+    pub group_obj: u32,
+    /// The permissions of named users, as `(uid, permissions)`-pairs.
+    pub users:     Vec<(u32, u32)>,
+    /// The permissions of named groups, as `(gid, permissions)`-pairs.
+    pub groups:    Vec<(u32, u32)>,
+    /// The maximum permissions that named users and any groups can be granted, if any.
+    pub mask:      Option<u32>,
+}
+#[cfg(feature = "acl")]
+impl Acl {
+    /// Parses an Acl from the raw value of the `system.posix_acl_access` extended attribute.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw value to parse.
+    ///
+    /// # Returns
+    /// The parsed Acl, or [`None`] if `raw` is not a valid ACL.
+    pub fn from_xattr(raw: &[u8]) -> Option<Self> {
+        const VERSION: u32 = 0x0002;
+        const USER_OBJ: u16 = 0x01;
+        const USER: u16 = 0x02;
+        const GROUP_OBJ: u16 = 0x04;
+        const GROUP: u16 = 0x08;
+        const MASK: u16 = 0x10;
+        const OTHER: u16 = 0x20;
+
+        // The value is a header followed by 8-byte entries, all little-endian
+        let (header, entries) = raw.split_first_chunk::<4>()?;
+        if u32::from_le_bytes(*header) != VERSION || entries.len() % 8 != 0 {
+            return None;
+        }
+        let mut acl = Self::default();
+        for entry in entries.chunks_exact(8) {
+            let tag: u16 = u16::from_le_bytes([entry[0], entry[1]]);
+            let perms: u32 = u32::from(u16::from_le_bytes([entry[2], entry[3]])) & 0o7;
+            let id: u32 = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+            match tag {
+                USER_OBJ | OTHER => {},
+                USER => acl.users.push((id, perms)),
+                GROUP_OBJ => acl.group_obj = perms,
+                GROUP => acl.groups.push((id, perms)),
+                MASK => acl.mask = Some(perms),
+                _ => return None,
+            }
+        }
+        Some(acl)
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines how the POSIX reasoner retrieves [`FileFacts`] for a path.
+pub trait FileStat {
+    /// Retrieves the facts about a particular file.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to retrieve the facts of.
+    ///
+    /// # Returns
+    /// The [`FileFacts`] of the file.
+    ///
+    /// # Errors
+    /// This function errors if the file does not exist or its facts could not be retrieved.
+    fn stat<'a>(&'a self, path: &'a Path) -> impl 'a + Send + Future<Output = io::Result<FileFacts>>;
+
+    /// Retrieves the facts about a particular file without following symbolic links.
+    ///
+    /// By default, this assumes there are no symbolic links at all.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to retrieve the facts of.
+    ///
+    /// # Returns
+    /// The [`FileFacts`] of the symbolic link itself if `path` is one, or [`None`] if it isn't.
+    ///
+    /// # Errors
+    /// This function errors if the file does not exist or its facts could not be retrieved.
+    fn symlink_stat<'a>(&'a self, _path: &'a Path) -> impl 'a + Send + Future<Output = io::Result<Option<FileFacts>>> { async { Ok(None) } }
+
+    /// Lists the contents of a particular directory.
+    ///
+    /// By default, this assumes there are no directories at all.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the directory to list.
+    ///
+    /// # Returns
+    /// The paths of all entries in the directory (joined to `path`), or [`None`] if `path` is not
+    /// a directory.
+    ///
+    /// # Errors
+    /// This function errors if the file does not exist or the directory could not be listed.
+    fn read_dir<'a>(&'a self, _path: &'a Path) -> impl 'a + Send + Future<Output = io::Result<Option<Vec<PathBuf>>>> { async { Ok(None) } }
+
+    /// Resolves a path to its canonical form, i.e., with all symbolic links resolved.
+    ///
+    /// By default, this assumes there are no symbolic links at all and returns the path as-is.
+    ///
+    /// # Arguments
+    /// - `path`: The path to resolve.
+    ///
+    /// # Returns
+    /// The canonical path of the file.
+    ///
+    /// # Errors
+    /// This function errors if the file does not exist or the path could not be resolved.
+    fn canonicalize<'a>(&'a self, path: &'a Path) -> impl 'a + Send + Future<Output = io::Result<PathBuf>> { async move { Ok(path.into()) } }
+
+    /// Retrieves the extended ACL entries of a particular file.
+    ///
+    /// By default, this assumes there are no ACLs at all.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to retrieve the ACL of.
+    ///
+    /// # Returns
+    /// The [`Acl`] of the file, or [`None`] if it has none.
+    ///
+    /// # Errors
+    /// This function errors if the ACL could not be retrieved. In particular, if the filesystem
+    /// does not support ACLs, the error is of kind [`io::ErrorKind::Unsupported`].
+    #[cfg(feature = "acl")]
+    fn acl<'a>(&'a self, _path: &'a Path) -> impl 'a + Send + Future<Output = io::Result<Option<Acl>>> { async { Ok(None) } }
+}
+
+
+
+/// A [`FileStat`] that reads the facts from the real filesystem. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RealFileStat;
+impl FileStat for RealFileStat {
+    #[inline]
+    async fn stat<'a>(&'a self, path: &'a Path) -> io::Result<FileFacts> {
+        let metadata = fs::metadata(path).await?;
+        Ok(FileFacts { uid: metadata.uid(), gid: metadata.gid(), mode: metadata.permissions().mode() })
+    }
+
+    #[inline]
+    async fn symlink_stat<'a>(&'a self, path: &'a Path) -> io::Result<Option<FileFacts>> {
+        let metadata = fs::symlink_metadata(path).await?;
+        if !metadata.file_type().is_symlink() {
+            return Ok(None);
+        }
+        Ok(Some(FileFacts { uid: metadata.uid(), gid: metadata.gid(), mode: metadata.permissions().mode() }))
+    }
+
+    async fn read_dir<'a>(&'a self, path: &'a Path) -> io::Result<Option<Vec<PathBuf>>> {
+        if !fs::metadata(path).await?.is_dir() {
+            return Ok(None);
+        }
+        let mut entries = fs::read_dir(path).await?;
+        let mut paths: Vec<PathBuf> = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            paths.push(entry.path());
+        }
+        Ok(Some(paths))
+    }
+
+    #[inline]
+    async fn canonicalize<'a>(&'a self, path: &'a Path) -> io::Result<PathBuf> { fs::canonicalize(path).await }
+
+    #[cfg(feature = "acl")]
+    async fn acl<'a>(&'a self, path: &'a Path) -> io::Result<Option<Acl>> {
+        #[cfg(target_os = "linux")]
+        {
+            let owned: PathBuf = path.into();
+            let raw: Option<Vec<u8>> = tokio::task::spawn_blocking(move || xattr::get(owned, ACL_XATTR)).await.map_err(io::Error::other)??;
+            match raw {
+                Some(raw) => Acl::from_xattr(&raw)
+                    .map(Some)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid ACL on '{}'", path.display()))),
+                None => Ok(None),
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("Cannot read ACL of '{}': ACLs are only supported on Linux", path.display())))
+    }
+}
+
+
+
+/// A [`FileStat`] that reads the facts from an in-memory table of files.
+///
+/// This allows the permission logic of the reasoner to be tested without a real filesystem (and
+/// without the root privileges needed to create files with arbitrary owners).
+///
+/// Any known path with other known paths directly beneath it is considered to be a directory.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryFileStat {
+    /// The known files.
+    files:    HashMap<PathBuf, FileFacts>,
+    /// The known symbolic links, with the path they point to.
+    symlinks: HashMap<PathBuf, (PathBuf, FileFacts)>,
+    /// The ACLs of the known files.
+    #[cfg(feature = "acl")]
+    acls:     HashMap<PathBuf, Acl>,
+}
+impl InMemoryFileStat {
+    /// Constructor for the InMemoryFileStat that initializes it without any files.
+    ///
+    /// # Returns
+    /// A new InMemoryFileStat for which every path is missing.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds a file to this InMemoryFileStat.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file.
+    /// - `facts`: The [`FileFacts`] to return for it.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_file(mut self, path: impl Into<PathBuf>, facts: FileFacts) -> Self {
+        self.files.insert(path.into(), facts);
+        self
+    }
+
+    /// Adds a symbolic link to this InMemoryFileStat.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the link.
+    /// - `target`: The (absolute) path the link points to.
+    /// - `facts`: The [`FileFacts`] of the link itself.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_symlink(mut self, path: impl Into<PathBuf>, target: impl Into<PathBuf>, facts: FileFacts) -> Self {
+        self.symlinks.insert(path.into(), (target.into(), facts));
+        self
+    }
+
+    /// Adds an ACL to a file in this InMemoryFileStat.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file.
+    /// - `acl`: The [`Acl`] to return for it.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[cfg(feature = "acl")]
+    #[inline]
+    pub fn with_acl(mut self, path: impl Into<PathBuf>, acl: Acl) -> Self {
+        self.acls.insert(path.into(), acl);
+        self
+    }
+
+    /// Follows symbolic links until a known file is found.
+    ///
+    /// # Arguments
+    /// - `path`: The path to resolve.
+    ///
+    /// # Returns
+    /// The path of the known file that `path` (eventually) points to.
+    ///
+    /// # Errors
+    /// This function errors if `path` does not point to a known file, or if it points to a cycle
+    /// of links.
+    fn resolve<'a>(&'a self, mut path: &'a Path) -> io::Result<&'a Path> {
+        // Follow any links (but not forever)
+        for _ in 0..=self.symlinks.len() {
+            match self.symlinks.get(path) {
+                Some((target, _)) => path = target,
+                None if self.files.contains_key(path) => return Ok(path),
+                None => return Err(io::Error::new(io::ErrorKind::NotFound, format!("No such file '{}'", path.display()))),
+            }
+        }
+        Err(io::Error::other(format!("Too many levels of symbolic links at '{}'", path.display())))
+    }
+}
+impl FileStat for InMemoryFileStat {
+    #[inline]
+    async fn stat<'a>(&'a self, path: &'a Path) -> io::Result<FileFacts> { Ok(self.files[self.resolve(path)?]) }
+
+    // NOTE: Only whole paths are looked up, so links in the parent directories of `path` are not considered
+    #[inline]
+    async fn symlink_stat<'a>(&'a self, path: &'a Path) -> io::Result<Option<FileFacts>> { Ok(self.symlinks.get(path).map(|(_, facts)| *facts)) }
+
+    async fn read_dir<'a>(&'a self, path: &'a Path) -> io::Result<Option<Vec<PathBuf>>> {
+        let dir: &Path = self.resolve(path)?;
+        let entries: Vec<PathBuf> = self
+            .files
+            .keys()
+            .chain(self.symlinks.keys())
+            .filter(|entry| entry.parent() == Some(dir))
+            .filter_map(|entry| entry.file_name())
+            .map(|name: &OsStr| path.join(name))
+            .collect();
+        Ok(if entries.is_empty() { None } else { Some(entries) })
+    }
+
+    #[inline]
+    async fn canonicalize<'a>(&'a self, path: &'a Path) -> io::Result<PathBuf> { self.resolve(path).map(PathBuf::from) }
+
+    #[cfg(feature = "acl")]
+    #[inline]
+    async fn acl<'a>(&'a self, path: &'a Path) -> io::Result<Option<Acl>> { Ok(self.acls.get(self.resolve(path)?).cloned()) }
+}
+
+
+
+/// A [`FileStat`] that remembers the facts retrieved by another [`FileStat`], such that every path is only looked
+/// up once.
+///
+/// Since permissions may change at any time, this is meant to live for a single deliberation only. Failed lookups
+/// are not remembered.
+#[derive(Debug)]
+pub struct CachedFileStat<'f, F> {
+    /// The [`FileStat`] that does the actual lookups.
+    inner: &'f F,
+    /// The facts retrieved by [`FileStat::stat()`].
+    stats: Mutex<HashMap<PathBuf, FileFacts>>,
+    /// The facts retrieved by [`FileStat::symlink_stat()`].
+    symlink_stats: Mutex<HashMap<PathBuf, Option<FileFacts>>>,
+    /// The ACLs retrieved by [`FileStat::acl()`].
+    #[cfg(feature = "acl")]
+    acls: Mutex<HashMap<PathBuf, Option<Acl>>>,
+}
+impl<'f, F> CachedFileStat<'f, F> {
+    /// Constructor for the CachedFileStat that initializes it with an empty cache.
+    ///
+    /// # Arguments
+    /// - `inner`: The [`FileStat`] that does the actual lookups.
+    ///
+    /// # Returns
+    /// A new CachedFileStat that hasn't remembered anything yet.
+    #[inline]
+    pub fn new(inner: &'f F) -> Self {
+        Self {
+            inner,
+            stats: Mutex::new(HashMap::new()),
+            symlink_stats: Mutex::new(HashMap::new()),
+            #[cfg(feature = "acl")]
+            acls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+impl<'f, F: Sync + FileStat> FileStat for CachedFileStat<'f, F> {
+    async fn stat<'a>(&'a self, path: &'a Path) -> io::Result<FileFacts> {
+        let cached: Option<FileFacts> = self.stats.lock().unwrap().get(path).copied();
+        if let Some(facts) = cached {
+            return Ok(facts);
+        }
+        let facts: FileFacts = self.inner.stat(path).await?;
+        self.stats.lock().unwrap().insert(path.into(), facts);
+        Ok(facts)
+    }
+
+    async fn symlink_stat<'a>(&'a self, path: &'a Path) -> io::Result<Option<FileFacts>> {
+        let cached: Option<Option<FileFacts>> = self.symlink_stats.lock().unwrap().get(path).copied();
+        if let Some(facts) = cached {
+            return Ok(facts);
+        }
+        let facts: Option<FileFacts> = self.inner.symlink_stat(path).await?;
+        self.symlink_stats.lock().unwrap().insert(path.into(), facts);
+        Ok(facts)
+    }
+
+    #[inline]
+    async fn read_dir<'a>(&'a self, path: &'a Path) -> io::Result<Option<Vec<PathBuf>>> { self.inner.read_dir(path).await }
+
+    #[inline]
+    async fn canonicalize<'a>(&'a self, path: &'a Path) -> io::Result<PathBuf> { self.inner.canonicalize(path).await }
+
+    #[cfg(feature = "acl")]
+    async fn acl<'a>(&'a self, path: &'a Path) -> io::Result<Option<Acl>> {
+        let cached: Option<Option<Acl>> = self.acls.lock().unwrap().get(path).cloned();
+        if let Some(acl) = cached {
+            return Ok(acl);
+        }
+        let acl: Option<Acl> = self.inner.acl(path).await?;
+        self.acls.lock().unwrap().insert(path.into(), acl.clone());
+        Ok(acl)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+
+    /// A [`FileStat`] that counts how often it's asked for facts.
+    struct CountingFileStat {
+        inner: InMemoryFileStat,
+        count: AtomicUsize,
+    }
+    impl FileStat for CountingFileStat {
+        async fn stat<'a>(&'a self, path: &'a Path) -> io::Result<FileFacts> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            self.inner.stat(path).await
+        }
+    }
+
+
+    /// Tests whether every path is only looked up once.
+    #[tokio::test]
+    async fn test_cached_file_stat() {
+        let facts = FileFacts { uid: 1000, gid: 100, mode: 0o640 };
+        let counting = CountingFileStat { inner: InMemoryFileStat::new().with_file("/a", facts).with_file("/b", facts), count: AtomicUsize::new(0) };
+        let stat = CachedFileStat::new(&counting);
+        for path in ["/a", "/b", "/a", "/b", "/a"] {
+            assert_eq!(stat.stat(Path::new(path)).await.unwrap(), facts);
+        }
+        assert_eq!(counting.count.load(Ordering::SeqCst), 2);
+
+        // Failures are not remembered
+        assert!(stat.stat(Path::new("/c")).await.is_err());
+        assert!(stat.stat(Path::new("/c")).await.is_err());
+        assert_eq!(counting.count.load(Ordering::SeqCst), 4);
+    }
+
+    /// Tests whether ACLs are parsed from their extended attribute representation.
+    #[cfg(feature = "acl")]
+    #[test]
+    fn test_acl_from_xattr() {
+        // user::rw-, user:1001:r--, group::r--, group:100:rwx, mask::r-x, other::---
+        let mut raw: Vec<u8> = 2u32.to_le_bytes().to_vec();
+        for (tag, perms, id) in
+            [(0x01u16, 6u16, u32::MAX), (0x02, 4, 1001), (0x04, 4, u32::MAX), (0x08, 7, 100), (0x10, 5, u32::MAX), (0x20, 0, u32::MAX)]
+        {
+            raw.extend(tag.to_le_bytes());
+            raw.extend(perms.to_le_bytes());
+            raw.extend(id.to_le_bytes());
+        }
+        assert_eq!(Acl::from_xattr(&raw), Some(Acl { group_obj: 0o4, users: vec![(1001, 0o4)], groups: vec![(100, 0o7)], mask: Some(0o5) }));
+
+        // Wrong versions or truncated entries are rejected
+        assert_eq!(Acl::from_xattr(&1u32.to_le_bytes()), None);
+        assert_eq!(Acl::from_xattr(&raw[..raw.len() - 1]), None);
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2024, 16:54:04
 //  Last edited:
-//    22 Oct 2024, 10:54:08
+//    16 Oct 2026, 02:32:44
 //  Auto updated?
 //    Yes
 //
@@ -12,11 +12,12 @@
 //!   Deals with analysing the reasoner's "generic" [`Workflow`] AST.
 //
 
+use std::borrow::Cow;
 use std::convert::Infallible;
 use std::sync::LazyLock;
 
 use tracing::{debug, instrument};
-use workflow::visitor::Visitor;
+use workflow::visitor::{Visitor, VisitorMut};
 use workflow::{Dataset, Elem, ElemCall, Entity, Workflow};
 
 
@@ -48,10 +49,13 @@ struct DatasetCollector<'w> {
     read_sets: Vec<(&'w Entity, &'w Dataset)>,
     write_sets: Vec<(&'w Entity, &'w Dataset)>,
     execute_sets: Vec<(&'w Entity, &'w Dataset)>,
+    tasks: Vec<(&'w Entity, &'w str)>,
 }
 impl<'w> DatasetCollector<'w> {
     #[inline]
-    fn new(here: &'w str) -> Self { Self { here, read_sets: Default::default(), write_sets: Default::default(), execute_sets: Default::default() } }
+    fn new(here: &'w str) -> Self {
+        Self { here, read_sets: Default::default(), write_sets: Default::default(), execute_sets: Default::default(), tasks: Default::default() }
+    }
 }
 impl<'w> Visitor<'w> for DatasetCollector<'w> {
     type Error = Infallible;
@@ -106,6 +110,8 @@ impl<'w> Visitor<'w> for DatasetCollector<'w> {
         // We only consider written sets that are created at our location
         if location.id == self.here {
             self.write_sets.extend(elem.output.iter().map(|d| (location, d)));
+            // Similarly, we only consider tasks executed at our location
+            self.tasks.push((location, &elem.task));
         }
 
         // Also visit the next one before returning, lol
@@ -113,6 +119,22 @@ impl<'w> Visitor<'w> for DatasetCollector<'w> {
     }
 }
 
+/// Redacts the identifiers of all calls and the tasks they execute in a [`Workflow`].
+struct TaskRedactor {
+    /// The function redacting task (and call) identifiers.
+    redact: fn(&str) -> Cow<'_, str>,
+}
+impl<'w> VisitorMut<'w> for TaskRedactor {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w mut ElemCall) -> Result<Option<&'w mut Elem>, Self::Error> {
+        elem.id = (self.redact)(&elem.id).into_owned();
+        elem.task = (self.redact)(&elem.task).into_owned();
+        Ok(Some(&mut elem.next))
+    }
+}
+
 
 
 
@@ -121,9 +143,11 @@ impl<'w> Visitor<'w> for DatasetCollector<'w> {
 /// The datasets accessed and/or modified in a workflow. These are grouped by file permission type
 #[derive(Clone, Debug)]
 pub struct WorkflowDatasets<'w> {
-    pub read_sets:    Vec<(&'w Entity, &'w Dataset)>,
-    pub write_sets:   Vec<(&'w Entity, &'w Dataset)>,
+    pub read_sets: Vec<(&'w Entity, &'w Dataset)>,
+    pub write_sets: Vec<(&'w Entity, &'w Dataset)>,
     pub execute_sets: Vec<(&'w Entity, &'w Dataset)>,
+    /// The tasks executed at this location, paired with who executes them.
+    pub tasks: Vec<(&'w Entity, &'w str)>,
 }
 impl<'w> WorkflowDatasets<'w> {
     #[inline]
@@ -137,6 +161,15 @@ impl<'w> WorkflowDatasets<'w> {
             wf.visit(&mut visitor).unwrap_unchecked();
         }
 
-        WorkflowDatasets { read_sets: visitor.read_sets, write_sets: visitor.write_sets, execute_sets: visitor.execute_sets }
+        WorkflowDatasets { read_sets: visitor.read_sets, write_sets: visitor.write_sets, execute_sets: visitor.execute_sets, tasks: visitor.tasks }
     }
 }
+
+
+
+/// Redacts the identifiers of all calls and the tasks they execute in a [`Workflow`].
+///
+/// # Arguments
+/// - `wf`: The [`Workflow`] to redact.
+/// - `redact`: The function that maps task (and call) identifiers to their redacted counterparts.
+pub fn redact_tasks(wf: &mut Workflow, redact: fn(&str) -> Cow<'_, str>) { wf.visit_mut(TaskRedactor { redact }).unwrap(); }
@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 15:54:54
 //  Last edited:
-//    10 Oct 2024, 15:57:17
+//    16 Oct 2026, 00:37:57
 //  Auto updated?
 //    Yes
 //
@@ -14,6 +14,10 @@
 
 // Declare modules
 mod resolver;
+#[cfg(feature = "watch")]
+mod watching;
 
 // Bring it into this namespace
 pub use resolver::*;
+#[cfg(feature = "watch")]
+pub use watching::*;
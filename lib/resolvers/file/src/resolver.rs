@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 15:55:23
 //  Last edited:
-//    05 Nov 2024, 11:15:26
+//    16 Oct 2026, 01:25:33
 //  Auto updated?
 //    Yes
 //
@@ -13,9 +13,10 @@
 //
 
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
+use serde_json::Value;
 use spec::AuditLogger;
 use spec::auditlogger::SessionedAuditLogger;
 use spec::stateresolver::StateResolver;
@@ -33,6 +34,90 @@ pub enum Error {
     /// Failed to read the target file.
     #[error("Failed to read file {}", path.display())]
     FileRead { path: PathBuf, source: std::io::Error },
+    /// A file defines something differently than the files before it.
+    #[error("File {} conflicts with previous files at {at}", path.display())]
+    MergeConflict { path: PathBuf, at: String },
+    /// A merged [`FileResolver`] was created without any files.
+    #[error("Cannot create a FileResolver without any files")]
+    NoFiles,
+    /// Failed to watch the target file for changes.
+    #[cfg(feature = "watch")]
+    #[error("Failed to watch file {} for changes", path.display())]
+    Watch { path: PathBuf, source: notify::Error },
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses the contents of a state file.
+///
+/// # Arguments
+/// - `path`: The path of the file, for debugging purposes.
+/// - `raw`: The contents of the file.
+///
+/// # Returns
+/// The parsed state.
+///
+/// # Errors
+/// This function errors if `raw` is not valid JSON for an `R`.
+pub(crate) fn parse<R: for<'de> Deserialize<'de>>(path: &Path, raw: &str) -> Result<R, Error> {
+    debug!("Parsing input file '{}'...", path.display());
+    serde_json::from_str(raw).map_err(|source| Error::FileDeserialize { to: std::any::type_name::<R>(), path: path.into(), source })
+}
+
+/// Merges one parsed state file into another.
+///
+/// Objects are merged key-by-key, and arrays are concatenated. Array elements that occur in both
+/// are kept once, where objects with an `id`-field are considered the same element if they have
+/// the same identifier. Anything else must be equal in both.
+///
+/// # Arguments
+/// - `into`: The [`Value`] merged so far.
+/// - `from`: The [`Value`] to merge into it.
+/// - `path`: The path of the file that `from` was parsed from, for debugging purposes.
+/// - `at`: A JSON pointer to where `into` and `from` live in the state, for debugging purposes.
+///
+/// # Errors
+/// This function errors if `from` defines something differently than `into`, including if two
+/// array elements share an identifier but are otherwise different.
+fn merge(into: &mut Value, from: Value, path: &Path, at: &str) -> Result<(), Error> {
+    match (into, from) {
+        (Value::Object(into), Value::Object(from)) => {
+            for (key, value) in from {
+                match into.get_mut(&key) {
+                    Some(existing) => merge(existing, value, path, &format!("{at}/{key}"))?,
+                    None => {
+                        into.insert(key, value);
+                    },
+                }
+            }
+            Ok(())
+        },
+        (Value::Array(into), Value::Array(from)) => {
+            for elem in from {
+                let existing: Option<&Value> = match elem.get("id") {
+                    Some(id) => into.iter().find(|other| other.get("id") == Some(id)),
+                    None => into.iter().find(|other| **other == elem),
+                };
+                match existing {
+                    Some(existing) if *existing == elem => continue,
+                    Some(_) => {
+                        let id: String = match &elem["id"] {
+                            Value::String(id) => id.clone(),
+                            id => id.to_string(),
+                        };
+                        return Err(Error::MergeConflict { path: path.into(), at: format!("{at}/{id}") });
+                    },
+                    None => into.push(elem),
+                }
+            }
+            Ok(())
+        },
+        (into, from) if *into == from => Ok(()),
+        _ => Err(Error::MergeConflict { path: path.into(), at: if at.is_empty() { "/".into() } else { at.into() } }),
+    }
 }
 
 
@@ -41,11 +126,11 @@ pub enum Error {
 
 /***** LIBRARY *****/
 /// Defines a [`StateResolver`] that resolves a [`serde`]-[`Deserialize`]able state from an
-/// arbitrary file.
+/// arbitrary file, or from multiple files merged together.
 #[derive(Clone, Debug)]
 pub struct FileResolver<R> {
-    /// The file to resolve from.
-    path:      PathBuf,
+    /// The file(s) to resolve from.
+    paths:     Vec<PathBuf>,
     /// Remembers what we're resolving to.
     _resolved: PhantomData<R>,
 }
@@ -58,7 +143,31 @@ impl<R> FileResolver<R> {
     /// # Returns
     /// A new FileResolver ready for resolution.
     #[inline]
-    pub fn new(path: impl Into<PathBuf>) -> Self { Self { path: path.into(), _resolved: PhantomData } }
+    pub fn new(path: impl Into<PathBuf>) -> Self { Self { paths: vec![path.into()], _resolved: PhantomData } }
+
+    /// Constructor for the FileResolver that merges the state from multiple files.
+    ///
+    /// The files are merged as JSON before the result is parsed as state. Objects are merged
+    /// key-by-key, and arrays are concatenated. Array elements defined in multiple files are kept
+    /// once, where objects with an `id`-field are identified by it. Defining the same identifier
+    /// (or any other field) differently in two files is an error.
+    ///
+    /// # Arguments
+    /// - `paths`: The paths to the files that we're resolving from.
+    ///
+    /// # Returns
+    /// A new FileResolver ready for resolution.
+    ///
+    /// # Errors
+    /// This function errors if `paths` is empty.
+    #[inline]
+    pub fn new_merged(paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Result<Self, Error> {
+        let paths: Vec<PathBuf> = paths.into_iter().map(Into::into).collect();
+        if paths.is_empty() {
+            return Err(Error::NoFiles);
+        }
+        Ok(Self { paths, _resolved: PhantomData })
+    }
 }
 impl<R: Sync + for<'de> Deserialize<'de>> StateResolver for FileResolver<R> {
     type Error = Error;
@@ -70,18 +179,90 @@ impl<R: Sync + for<'de> Deserialize<'de>> StateResolver for FileResolver<R> {
     where
         L: Sync + AuditLogger,
     {
-        // Read the file in one go// Read the file in one go
-        debug!("Opening input file '{}'...", self.path.display());
-        let state_str: String = fs::read_to_string(&self.path).await.map_err(|source| Error::FileRead { path: self.path.clone(), source })?;
-
-        // Parse it as JSON
-        debug!("Parsing input file '{}'...", self.path.display());
-        let state = serde_json::from_str(&state_str).map_err(|source| Error::FileDeserialize {
-            to: std::any::type_name::<R>(),
-            path: self.path.clone(),
-            source,
-        })?;
-
-        Ok(state)
+        if let [path] = self.paths.as_slice() {
+            // Read the file in one go
+            debug!("Opening input file '{}'...", path.display());
+            let state_str: String = fs::read_to_string(path).await.map_err(|source| Error::FileRead { path: path.clone(), source })?;
+
+            // Parse it as JSON
+            return parse(path, &state_str);
+        }
+
+        // Read & merge all the files
+        let mut state: Option<Value> = None;
+        for path in &self.paths {
+            debug!("Opening input file '{}'...", path.display());
+            let state_str: String = fs::read_to_string(path).await.map_err(|source| Error::FileRead { path: path.clone(), source })?;
+            let value: Value = parse(path, &state_str)?;
+            match &mut state {
+                Some(state) => merge(state, value, path, "")?,
+                None => state = Some(value),
+            }
+        }
+
+        // Parse the result as the state
+        // NOTE: Unwrap is OK because the constructors ensure there is at least one path
+        let last: &PathBuf = self.paths.last().unwrap();
+        serde_json::from_value(state.unwrap()).map_err(|source| Error::FileDeserialize { to: std::any::type_name::<R>(), path: last.clone(), source })
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+
+    /// Merges `from` into `into` as if `from` was read from `b.json`.
+    fn merged(mut into: Value, from: Value) -> Result<Value, Error> {
+        merge(&mut into, from, Path::new("b.json"), "")?;
+        Ok(into)
+    }
+
+
+    #[test]
+    fn test_merge_objects() {
+        assert_eq!(
+            merged(json!({ "a": 1, "nested": { "b": true } }), json!({ "c": "foo", "nested": { "d": null } })).unwrap(),
+            json!({ "a": 1, "c": "foo", "nested": { "b": true, "d": null } })
+        );
+        assert_eq!(merged(json!({ "a": 1 }), json!({ "a": 1 })).unwrap(), json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn test_merge_arrays() {
+        // Elements are deduplicated by their identifier, or else by their value
+        assert_eq!(
+            merged(
+                json!({ "users": [{ "id": "amy", "age": 42 }, "foo"] }),
+                json!({ "users": [{ "id": "bob" }, { "id": "amy", "age": 42 }, "foo", "bar"] })
+            )
+            .unwrap(),
+            json!({ "users": [{ "id": "amy", "age": 42 }, "foo", { "id": "bob" }, "bar"] })
+        );
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let at = |res: Result<Value, Error>| match res {
+            Err(Error::MergeConflict { path, at }) if path == Path::new("b.json") => at,
+            res => panic!("Expected a merge conflict, got {res:?}"),
+        };
+        assert_eq!(at(merged(json!({ "nested": { "a": 1 } }), json!({ "nested": { "a": 2 } }))), "/nested/a");
+        assert_eq!(at(merged(json!({ "users": [{ "id": "amy", "age": 42 }] }), json!({ "users": [{ "id": "amy", "age": 43 }] }))), "/users/amy");
+        assert_eq!(at(merged(json!({ "users": [{ "id": 7 }] }), json!({ "users": [{ "id": 7, "age": 43 }] }))), "/users/7");
+        assert_eq!(at(merged(json!([]), json!({}))), "/");
+    }
+
+    #[test]
+    fn test_new_merged_without_files() {
+        assert!(matches!(FileResolver::<Value>::new_merged(Vec::<PathBuf>::new()), Err(Error::NoFiles)));
+        assert!(FileResolver::<Value>::new_merged(["a.json", "b.json"]).is_ok());
     }
 }
@@ -0,0 +1,196 @@
+//  WATCHING.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 11:52:40
+//  Last edited:
+//    16 Oct 2026, 01:45:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a [`StateResolver`] that keeps a file's state in memory and
+//!   reloads it whenever the file changes.
+//
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use notify::event::EventKind;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as _};
+use serde::Deserialize;
+use spec::AuditLogger;
+use spec::auditlogger::SessionedAuditLogger;
+use spec::stateresolver::StateResolver;
+use tracing::{debug, info, instrument, warn};
+
+use crate::resolver::{Error, parse};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reloads the state from the file after it changed.
+///
+/// If the file cannot be read or parsed, the previous state is kept.
+///
+/// # Arguments
+/// - `path`: The path of the file to reload.
+/// - `state`: The state to replace.
+fn reload<R: for<'de> Deserialize<'de>>(path: &Path, state: &RwLock<R>) {
+    debug!("Reloading state file '{}'...", path.display());
+    let new: R = match std::fs::read_to_string(path).map_err(|source| Error::FileRead { path: path.into(), source }).and_then(|raw| parse(path, &raw))
+    {
+        Ok(new) => new,
+        Err(err) => {
+            warn!("Keeping previous state because reloading state file '{}' failed: {err}", path.display());
+            return;
+        },
+    };
+    // NOTE: The lock is only poisoned if a panic occurred while holding it, which our code doesn't do
+    *state.write().unwrap() = new;
+    info!("Reloaded state file '{}'", path.display());
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a [`StateResolver`] that resolves a [`serde`]-[`Deserialize`]able state from a file,
+/// like the [`FileResolver`](crate::FileResolver), but keeps it in memory and reloads it whenever
+/// the file changes.
+///
+/// If the changed file cannot be parsed, a warning is emitted and the previous state is kept.
+#[derive(Debug)]
+pub struct WatchingFileResolver<R> {
+    /// The file to resolve from.
+    path:     PathBuf,
+    /// The last successfully parsed state.
+    state:    Arc<RwLock<R>>,
+    /// Watches the file for changes for as long as we live.
+    _watcher: RecommendedWatcher,
+}
+impl<R: 'static + Send + Sync + for<'de> Deserialize<'de>> WatchingFileResolver<R> {
+    /// Constructor for the WatchingFileResolver.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the file that we're resolving from.
+    ///
+    /// # Returns
+    /// A new WatchingFileResolver ready for resolution.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or parse the file initially, or to start
+    /// watching it.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path: PathBuf = path.into();
+        debug!("Loading state file '{}'...", path.display());
+        let raw: String = std::fs::read_to_string(&path).map_err(|source| Error::FileRead { path: path.clone(), source })?;
+        let state: Arc<RwLock<R>> = Arc::new(RwLock::new(parse(&path, &raw)?));
+
+        // NOTE: We watch the parent directory, because editors often replace files instead of writing to them
+        let dir: PathBuf = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.into(),
+            _ => PathBuf::from("."),
+        };
+        let mut watcher: RecommendedWatcher = {
+            let path: PathBuf = path.clone();
+            let state: Arc<RwLock<R>> = state.clone();
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                        && event.paths.iter().any(|p| p.file_name() == path.file_name())
+                    {
+                        reload(&path, &state);
+                    }
+                },
+                Err(err) => warn!("Failed to watch state file '{}': {err}", path.display()),
+            })
+            .map_err(|source| Error::Watch { path: path.clone(), source })?
+        };
+        watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(|source| Error::Watch { path: path.clone(), source })?;
+
+        Ok(Self { path, state, _watcher: watcher })
+    }
+
+    /// Returns the path of the file being watched.
+    ///
+    /// # Returns
+    /// A [`Path`] to the state file.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+}
+impl<R: Send + Sync + Clone> StateResolver for WatchingFileResolver<R> {
+    type Error = Error;
+    type Resolved = R;
+    type State = ();
+
+    #[instrument(name = "WatchingFileResolver::resolve", skip_all, fields(reference=logger.reference()))]
+    async fn resolve<'a, L>(&'a self, _state: Self::State, logger: &'a SessionedAuditLogger<L>) -> Result<Self::Resolved, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        Ok(self.state.read().unwrap().clone())
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::{Value, json};
+    use tempfile::TempDir;
+
+    use super::*;
+
+
+    /// Waits until the resolver's state satisfies the given predicate, panicking after a while.
+    fn wait_for(resolver: &WatchingFileResolver<Value>, pred: impl Fn(&Value) -> bool) {
+        let start: Instant = Instant::now();
+        while !pred(&resolver.state.read().unwrap()) {
+            if start.elapsed() > Duration::from_secs(10) {
+                panic!("State never changed; it is still {}", resolver.state.read().unwrap());
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+
+    #[test]
+    fn test_reload_on_change() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("state.json");
+        std::fs::write(&path, r#"{ "a": 1 }"#).unwrap();
+        let resolver: WatchingFileResolver<Value> = WatchingFileResolver::new(&path).unwrap();
+        assert_eq!(*resolver.state.read().unwrap(), json!({ "a": 1 }));
+
+        std::fs::write(&path, r#"{ "a": 2 }"#).unwrap();
+        wait_for(&resolver, |state| *state == json!({ "a": 2 }));
+
+        // Other files in the same directory are ignored
+        std::fs::write(dir.path().join("other.json"), r#"{ "a": 3 }"#).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(*resolver.state.read().unwrap(), json!({ "a": 2 }));
+    }
+
+    #[test]
+    fn test_keep_state_on_invalid_change() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("state.json");
+        std::fs::write(&path, r#"{ "a": 1 }"#).unwrap();
+        let resolver: WatchingFileResolver<Value> = WatchingFileResolver::new(&path).unwrap();
+
+        // The invalid write is rejected...
+        std::fs::write(&path, r#"{ "a": "#).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(*resolver.state.read().unwrap(), json!({ "a": 1 }));
+
+        // ...but doesn't stop us from picking up the next valid one
+        std::fs::write(&path, r#"{ "a": 3 }"#).unwrap();
+        wait_for(&resolver, |state| *state == json!({ "a": 3 }));
+    }
+}
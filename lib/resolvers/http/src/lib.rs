@@ -0,0 +1,19 @@
+//  LIB.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 12:10:02
+//  Last edited:
+//    16 Oct 2026, 12:10:02
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Resolves state by fetching it as `serde` JSON from an HTTP endpoint.
+//
+
+// Declare modules
+mod resolver;
+
+// Bring it into this namespace
+pub use resolver::*;
@@ -0,0 +1,329 @@
+//  RESOLVER.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 12:10:31
+//  Last edited:
+//    16 Oct 2026, 02:29:59
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the actual [`StateResolver`].
+//
+
+use std::fmt::{Debug, Formatter, Result as FResult};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use spec::AuditLogger;
+use spec::auditlogger::SessionedAuditLogger;
+use spec::stateresolver::StateResolver;
+use tracing::{debug, instrument};
+
+
+/***** CONSTANTS *****/
+/// The maximum number of bytes of a response body that is included in errors.
+const MAX_ERROR_BODY_LEN: usize = 1024;
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors that are occurring in the [`HttpResolver`].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Failed to build the HTTP client.
+    #[error("Failed to build HTTP client")]
+    ClientBuild { source: reqwest::Error },
+    /// The use case cannot be used in a URL, as it would be resolved as a relative path.
+    #[error("Use case {use_case:?} is not allowed, as it would refer to another path than a use case")]
+    IllegalUseCase { use_case: String },
+    /// Failed to deserialize the body of the response.
+    #[error("Failed to deserialize response from {url:?} as {to}")]
+    ResponseDeserialize { to: &'static str, url: String, source: serde_json::Error },
+    /// Failed to receive the body of the response.
+    #[error("Failed to receive response from {url:?}")]
+    ResponseReceive { url: String, source: reqwest::Error },
+    /// The endpoint responded with a non-success status code.
+    #[error("Endpoint {url:?} returned {status}{}", if body.is_empty() { String::new() } else { format!(": {body}") })]
+    ResponseStatus { url: String, status: StatusCode, body: String },
+    /// Failed to send the request.
+    #[error("Failed to send request to {url:?}")]
+    Request { url: String, source: reqwest::Error },
+}
+impl Error {
+    /// Returns whether this error is transient, i.e., whether retrying the resolution later may
+    /// succeed.
+    ///
+    /// This is the case for failures to reach the endpoint, timeouts and server-side (5xx) errors,
+    /// but not for, e.g., unknown use cases (404) or malformed state.
+    ///
+    /// # Returns
+    /// True if this error is transient, or false otherwise.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::ClientBuild { .. } | Self::IllegalUseCase { .. } | Self::ResponseDeserialize { .. } => false,
+            Self::ResponseReceive { source, .. } | Self::Request { source, .. } => source.is_connect() || source.is_timeout(),
+            Self::ResponseStatus { status, .. } => status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// Returns whether this error means that the endpoint doesn't know the use case.
+    ///
+    /// # Returns
+    /// True if the endpoint returned `404 Not Found`, or false otherwise.
+    #[inline]
+    pub fn is_unknown_use_case(&self) -> bool { matches!(self, Self::ResponseStatus { status: StatusCode::NOT_FOUND, .. }) }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Percent-encodes a string such that it can be used as (part of) a URL path segment or query
+/// value.
+///
+/// # Arguments
+/// - `value`: The string to encode.
+///
+/// # Returns
+/// The `value` with everything but unreserved characters (RFC 3986, section 2.3) percent-encoded.
+fn percent_encode(value: &str) -> String {
+    let mut res: String = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            res.push(b as char);
+        } else {
+            res.push_str(&format!("%{b:02X}"));
+        }
+    }
+    res
+}
+
+/// Bounds the body of a response to include it in an error.
+///
+/// # Arguments
+/// - `body`: The full body of the response.
+///
+/// # Returns
+/// The first [`MAX_ERROR_BODY_LEN`] bytes of the `body` (rounded down to a character boundary),
+/// with a note of how much was omitted if anything.
+fn bounded_body(mut body: String) -> String {
+    if body.len() <= MAX_ERROR_BODY_LEN {
+        return body;
+    }
+    let mut end: usize = MAX_ERROR_BODY_LEN;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    let omitted: usize = body.len() - end;
+    body.truncate(end);
+    body.push_str(&format!("\n<{omitted} more bytes omitted>"));
+    body
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a [`StateResolver`] that resolves a [`serde`]-[`Deserialize`]able state for a
+/// particular use case by fetching it from an HTTP endpoint.
+///
+/// The body of the response is parsed as JSON, i.e., the same representation as expected by the
+/// `FileResolver`.
+///
+/// Its [`Debug`]-implementation does not show the bearer token, if any.
+#[derive(Clone)]
+pub struct HttpResolver<R> {
+    /// The URL to fetch from, where `{use_case}` is replaced by the use case.
+    url: String,
+    /// The HTTP client used to fetch the state.
+    client: reqwest::Client,
+    /// The time the endpoint has to respond, if any.
+    timeout: Option<Duration>,
+    /// The token to authenticate with, if any.
+    token: Option<String>,
+    /// Remembers what we're resolving to.
+    _resolved: PhantomData<R>,
+}
+impl<R> Debug for HttpResolver<R> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        f.debug_struct("HttpResolver")
+            .field("url", &self.url)
+            .field("client", &self.client)
+            .field("timeout", &self.timeout)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+impl<R> HttpResolver<R> {
+    /// Constructor for the HttpResolver.
+    ///
+    /// # Arguments
+    /// - `url`: The URL to fetch the state from. Any occurrence of `{use_case}` is replaced by
+    ///   the (percent-encoded) use case being resolved, e.g., `https://registry/state/{use_case}`.
+    ///
+    /// # Returns
+    /// A new HttpResolver ready for resolution.
+    ///
+    /// # Errors
+    /// This function errors if we failed to build an HTTP client.
+    #[inline]
+    pub fn new(url: impl Into<String>) -> Result<Self, Error> {
+        let client: reqwest::Client = reqwest::Client::builder().build().map_err(|source| Error::ClientBuild { source })?;
+        Ok(Self { url: url.into(), client, timeout: None, token: None, _resolved: PhantomData })
+    }
+
+    /// Sets the time the endpoint has to respond.
+    ///
+    /// # Arguments
+    /// - `timeout`: The timeout to use, or [`None`] to wait indefinitely. Default is [`None`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets a bearer token to authenticate to the endpoint with.
+    ///
+    /// # Arguments
+    /// - `token`: The token to send in an `Authorization: Bearer ...`-header, or [`None`] to
+    ///   send none. Default is [`None`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_bearer_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Returns the URL that the state of a particular use case is fetched from.
+    ///
+    /// # Arguments
+    /// - `use_case`: The use case to fetch the state of.
+    ///
+    /// # Returns
+    /// The URL given at construction with `{use_case}` replaced.
+    ///
+    /// # Errors
+    /// This function errors if the `use_case` is `.` or `..`, as those would be resolved as
+    /// relative paths (e.g., `https://registry/state/..` becomes `https://registry/`).
+    pub fn url(&self, use_case: &str) -> Result<String, Error> {
+        if use_case == "." || use_case == ".." {
+            return Err(Error::IllegalUseCase { use_case: use_case.into() });
+        }
+        Ok(self.url.replace("{use_case}", &percent_encode(use_case)))
+    }
+}
+impl<R: Sync + for<'de> Deserialize<'de>> StateResolver for HttpResolver<R> {
+    type Error = Error;
+    type Resolved = R;
+    type State = String;
+
+    #[instrument(name = "HttpResolver::resolve", skip_all, fields(reference=logger.reference()))]
+    async fn resolve<'a, L>(&'a self, state: Self::State, logger: &'a SessionedAuditLogger<L>) -> Result<Self::Resolved, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        // Build the request
+        let url: String = self.url(&state)?;
+        let mut req = self.client.get(&url);
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+        if let Some(token) = &self.token {
+            req = req.bearer_auth(token);
+        }
+
+        // Send it
+        debug!("Fetching state for use case {state:?} from '{url}'...");
+        let res = req.send().await.map_err(|source| Error::Request { url: url.clone(), source })?;
+        let status: StatusCode = res.status();
+        let body: String = res.text().await.map_err(|source| Error::ResponseReceive { url: url.clone(), source })?;
+        if !status.is_success() {
+            return Err(Error::ResponseStatus { url, status, body: bounded_body(body) });
+        }
+
+        // Parse it as JSON
+        debug!("Parsing state from '{url}'...");
+        serde_json::from_str(&body).map_err(|source| Error::ResponseDeserialize { to: std::any::type_name::<R>(), url, source })
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("abc-XYZ_0.9~"), "abc-XYZ_0.9~");
+        assert_eq!(percent_encode("a b/c?d=e&f"), "a%20b%2Fc%3Fd%3De%26f");
+        assert_eq!(percent_encode("é"), "%C3%A9");
+        assert_eq!(percent_encode(""), "");
+    }
+
+    #[test]
+    fn test_url() {
+        let resolver: HttpResolver<()> = HttpResolver::new("https://registry/state/{use_case}?again={use_case}").unwrap();
+        assert_eq!(resolver.url("central").unwrap(), "https://registry/state/central?again=central");
+        assert_eq!(resolver.url("../admin").unwrap(), "https://registry/state/..%2Fadmin?again=..%2Fadmin");
+        assert_eq!(resolver.url("...").unwrap(), "https://registry/state/...?again=...");
+        let resolver: HttpResolver<()> = HttpResolver::new("https://registry/state").unwrap();
+        assert_eq!(resolver.url("central").unwrap(), "https://registry/state");
+
+        // Use cases that would be resolved as relative paths are refused
+        assert!(matches!(resolver.url("."), Err(Error::IllegalUseCase { use_case }) if use_case == "."));
+        assert!(matches!(resolver.url(".."), Err(Error::IllegalUseCase { use_case }) if use_case == ".."));
+    }
+
+    #[test]
+    fn test_bounded_body() {
+        assert_eq!(bounded_body("short".into()), "short");
+        let body: String = "é".repeat(MAX_ERROR_BODY_LEN);
+        let bounded: String = bounded_body(body.clone());
+        assert_eq!(bounded, format!("{}\n<{} more bytes omitted>", "é".repeat(MAX_ERROR_BODY_LEN / 2), body.len() - MAX_ERROR_BODY_LEN));
+    }
+
+    #[test]
+    fn test_debug_redacts_token() {
+        let resolver: HttpResolver<()> = HttpResolver::new("https://registry").unwrap().with_bearer_token(Some("hunter2".into()));
+        let debug: String = format!("{resolver:?}");
+        assert!(!debug.contains("hunter2"), "{debug}");
+        assert!(debug.contains("<redacted>"), "{debug}");
+    }
+
+    #[tokio::test]
+    async fn test_is_transient() {
+        let status = |status: StatusCode| Error::ResponseStatus { url: String::new(), status, body: String::new() };
+        assert!(status(StatusCode::INTERNAL_SERVER_ERROR).is_transient());
+        assert!(status(StatusCode::SERVICE_UNAVAILABLE).is_transient());
+        assert!(status(StatusCode::TOO_MANY_REQUESTS).is_transient());
+        assert!(!status(StatusCode::NOT_FOUND).is_transient());
+        assert!(status(StatusCode::NOT_FOUND).is_unknown_use_case());
+        assert!(!status(StatusCode::UNAUTHORIZED).is_transient());
+
+        let source: serde_json::Error = serde_json::from_str::<u8>("x").unwrap_err();
+        assert!(!Error::ResponseDeserialize { to: "u8", url: String::new(), source }.is_transient());
+
+        // NOTE: Nothing listens on port 1, so this fails to connect
+        let source: reqwest::Error = reqwest::Client::new().get("http://127.0.0.1:1").send().await.unwrap_err();
+        assert!(Error::Request { url: String::new(), source }.is_transient());
+    }
+}
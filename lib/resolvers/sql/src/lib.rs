@@ -0,0 +1,19 @@
+//  LIB.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:48:30
+//  Last edited:
+//    15 Oct 2026, 23:48:30
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Resolves state from a SQL database by running configurable queries.
+//
+
+// Declare modules
+mod resolver;
+
+// Bring it into this namespace
+pub use resolver::*;
@@ -2,12 +2,44 @@ pub mod formatters;
 
 use std::borrow::Cow;
 use std::convert::Infallible;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
 use miette::{Context as _, IntoDiagnostic as _};
 use tempfile::NamedTempFile;
 use tokio::{fs as tfs, io as tio};
 
+/// The environment variable listing additional eFLINT include directories.
+pub const EFLINT_PATH_ENV: &str = "EFLINT_PATH";
+
+/// Returns the include directories to use when resolving eFLINT `#include`s and `#require`s.
+///
+/// The directories are, in order of precedence:
+/// 1. The directories in `extra` (e.g., given on the command line or in a config file); then
+/// 2. The directories in the [`EFLINT_PATH`](EFLINT_PATH_ENV)-environment variable, separated
+///    like `PATH` is on this platform (i.e., `:` on Unix and `;` on Windows).
+///
+/// Empty entries and duplicates are removed, keeping the first occurrence. Note that the
+/// directory of the including file itself is not part of this list; consumers search there first.
+///
+/// # Arguments
+/// - `extra`: Explicitly given include directories, which take precedence over the environment.
+///
+/// # Returns
+/// The merged list of include directories.
+pub fn eflint_include_dirs(extra: &[PathBuf]) -> Vec<PathBuf> { merge_include_dirs(extra, std::env::var_os(EFLINT_PATH_ENV).as_deref()) }
+
+/// Implements [`eflint_include_dirs()`] for a given value of the environment variable.
+fn merge_include_dirs(extra: &[PathBuf], env: Option<&OsStr>) -> Vec<PathBuf> {
+    let mut res: Vec<PathBuf> = Vec::with_capacity(extra.len());
+    for dir in extra.iter().cloned().chain(env.into_iter().flat_map(std::env::split_paths)) {
+        if !dir.as_os_str().is_empty() && !res.contains(&dir) {
+            res.push(dir);
+        }
+    }
+    res
+}
+
 #[derive(Clone, Debug)]
 pub enum InputFile {
     Stdin,
@@ -34,11 +66,35 @@ impl InputFile {
         }
     }
 
-    pub async fn as_file(&self) -> miette::Result<MaybeTempFile> {
+    /// Returns a path to the input, writing stdin to a temporary file in the system's temporary
+    /// directory if necessary.
+    ///
+    /// See [`InputFile::as_file_in()`] for details.
+    pub async fn as_file(&self) -> miette::Result<MaybeTempFile> { self.as_file_in(None).await }
+
+    /// Returns a path to the input, writing stdin to a temporary file if necessary.
+    ///
+    /// Temporary files are named `policy-reasoner-*.stdin` and are removed when the returned
+    /// [`MaybeTempFile`] is dropped, including when unwinding from a panic. However, they are
+    /// **not** removed if the process is killed (e.g., by `SIGKILL` or the OOM-killer) or aborts,
+    /// in which case they are left behind in `dir` and must be cleaned up by other means.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory to write the temporary file to. If [`None`], uses the system's
+    ///   temporary directory (which respects the `TMPDIR`-environment variable). Useful in, e.g.,
+    ///   containers where that directory is small or read-only.
+    pub async fn as_file_in(&self, dir: Option<&Path>) -> miette::Result<MaybeTempFile> {
         match self {
             InputFile::File(path_buf) => Ok(MaybeTempFile::File(path_buf.clone())),
             InputFile::Stdin => {
-                let file = tempfile::Builder::new().suffix(".stdin").tempfile().into_diagnostic().context("Could not create temp file")?;
+                let mut builder = tempfile::Builder::new();
+                builder.prefix("policy-reasoner-").suffix(".stdin");
+                let file = match dir {
+                    Some(dir) => {
+                        builder.tempfile_in(dir).into_diagnostic().with_context(|| format!("Could not create temp file in '{}'", dir.display()))?
+                    },
+                    None => builder.tempfile().into_diagnostic().context("Could not create temp file")?,
+                };
                 let mut handle = tfs::File::create(&file)
                     .await
                     .into_diagnostic()
@@ -55,6 +111,7 @@ impl InputFile {
     }
 }
 
+/// Either a path to an existing file, or a temporary file that is removed when this is dropped.
 pub enum MaybeTempFile {
     File(PathBuf),
     Stdin(NamedTempFile),
@@ -70,3 +127,17 @@ impl std::ops::Deref for MaybeTempFile {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_include_dirs() {
+        assert_eq!(merge_include_dirs(&[], None), Vec::<PathBuf>::new());
+        assert_eq!(merge_include_dirs(&["/a".into(), "/b".into(), "/a".into()], None), vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+
+        let env = std::env::join_paths(["/c", "", "/b"]).unwrap();
+        assert_eq!(merge_include_dirs(&["/a".into(), "/b".into()], Some(&env)), vec![PathBuf::from("/a"), PathBuf::from("/b"), PathBuf::from("/c")]);
+    }
+}
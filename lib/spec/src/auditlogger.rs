@@ -4,7 +4,7 @@
 //  Created:
 //    09 Oct 2024, 13:38:41
 //  Last edited:
-//    06 May 2025, 11:32:28
+//    16 Oct 2026, 01:46:23
 //  Auto updated?
 //    Yes
 //
@@ -16,12 +16,33 @@
 use std::error::Error;
 use std::fmt::Display;
 use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::Serialize;
 
 use crate::reasonerconn::{ReasonerContext, ReasonerResponse};
 
 
+/***** HELPER FUNCTIONS *****/
+/// Generates a session reference that is unique within this process.
+///
+/// This is the generator used by [`SessionedAuditLogger::generated()`]. It combines the current
+/// time with a process-wide counter, such that references never repeat, not even if two are
+/// generated in the same nanosecond.
+///
+/// # Returns
+/// A new, unique reference.
+pub fn unique_reference() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let now: u128 = SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_nanos()).unwrap_or(0);
+    format!("{now:x}-{}", COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+
+
+
+
 /***** AUXILLARY *****/
 /// Defines a wrapper around any [`AuditLogger`] that extends it with some kind of
 /// [`tracing`](https://github.com/tokio-rs/tracing)-like session information.
@@ -44,6 +65,34 @@ impl<L> SessionedAuditLogger<L> {
     #[inline]
     pub fn new(reference: impl Into<String>, logger: L) -> Self { Self { reference: reference.into(), logger } }
 
+    /// Constructor for the SessionedAuditLogger that generates a fresh reference.
+    ///
+    /// The reference is produced by [`unique_reference()`]. Use
+    /// [`SessionedAuditLogger::generated_with()`] to replace it with a deterministic generator.
+    ///
+    /// # Arguments
+    /// - `logger`: The nested logger.
+    ///
+    /// # Returns
+    /// A new instance of Self, ready for logging.
+    #[inline]
+    pub fn generated(logger: L) -> Self { Self::generated_with(unique_reference, logger) }
+
+    /// Constructor for the SessionedAuditLogger that generates its reference with the given
+    /// function.
+    ///
+    /// This is mostly useful in tests, where a fixed generator makes the emitted log statements
+    /// byte-for-byte deterministic.
+    ///
+    /// # Arguments
+    /// - `generator`: A function returning a new reference.
+    /// - `logger`: The nested logger.
+    ///
+    /// # Returns
+    /// A new instance of Self, ready for logging.
+    #[inline]
+    pub fn generated_with(generator: fn() -> String, logger: L) -> Self { Self::new(generator(), logger) }
+
     /// Provides read-only access to the internal reference.
     #[inline]
     pub fn reference(&self) -> &str { &self.reference }
@@ -82,6 +131,18 @@ impl<L: AuditLogger> SessionedAuditLogger<L> {
     {
         L::log_question(&self.logger, &self.reference, state, question)
     }
+
+    /// Logs some arbitrary event that occurred during this session.
+    ///
+    /// # Arguments
+    /// - `tag`: Some name that identifies what kind of event this is (e.g., `trace`).
+    /// - `value`: Some serializable value describing the event.
+    pub fn log_event<'a, E>(&'a self, tag: &'a str, value: &'a E) -> impl 'a + Send + Future<Output = Result<(), <Self as AuditLogger>::Error>>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        L::log_event(&self.logger, &self.reference, tag, value)
+    }
 }
 impl<L: AuditLogger> AuditLogger for SessionedAuditLogger<L> {
     type Error = L::Error;
@@ -117,6 +178,13 @@ impl<L: AuditLogger> AuditLogger for SessionedAuditLogger<L> {
     {
         L::log_question(&self.logger, reference, state, question)
     }
+
+    fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> impl 'a + Send + Future<Output = Result<(), Self::Error>>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        L::log_event(&self.logger, reference, tag, value)
+    }
 }
 
 
@@ -170,6 +238,43 @@ pub trait AuditLogger {
     where
         S: Sync + Serialize,
         Q: Sync + Serialize;
+
+    /// Logs some arbitrary event that occurred while answering a question.
+    ///
+    /// This can be used by reasoners to record intermediate diagnostics (e.g., the full spec
+    /// submitted to a backend reasoner, or its raw trace). By default, events are discarded.
+    ///
+    /// # Arguments
+    /// - `reference`: Some reference that links the event to a particular question.
+    /// - `tag`: Some name that identifies what kind of event this is (e.g., `trace`).
+    /// - `value`: Some serializable value describing the event.
+    #[inline]
+    fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> impl 'a + Send + Future<Output = Result<(), Self::Error>>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        let _ = (reference, tag, value);
+        async { Ok(()) }
+    }
+
+
+    /// Starts a new session on this logger.
+    ///
+    /// This is a convenience wrapper around [`SessionedAuditLogger::new()`] that borrows this
+    /// logger instead of taking ownership of it.
+    ///
+    /// # Arguments
+    /// - `reference`: The reference used to recognize the logs of this session (e.g., a request ID).
+    ///
+    /// # Returns
+    /// A new [`SessionedAuditLogger`] that logs to this logger under the given `reference`.
+    #[inline]
+    fn session(&self, reference: impl Into<String>) -> SessionedAuditLogger<&Self>
+    where
+        Self: Sized,
+    {
+        SessionedAuditLogger::new(reference, self)
+    }
 }
 
 // Standard impls
@@ -210,6 +315,14 @@ impl<T: AuditLogger> AuditLogger for &T {
     {
         <T as AuditLogger>::log_question(self, reference, state, question)
     }
+
+    #[inline]
+    fn log_event<'s, E>(&'s self, reference: &'s str, tag: &'s str, value: &'s E) -> impl 's + Send + Future<Output = Result<(), Self::Error>>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        <T as AuditLogger>::log_event(self, reference, tag, value)
+    }
 }
 impl<T: AuditLogger> AuditLogger for &mut T {
     type Error = T::Error;
@@ -248,4 +361,37 @@ impl<T: AuditLogger> AuditLogger for &mut T {
     {
         <T as AuditLogger>::log_question(self, reference, state, question)
     }
+
+    #[inline]
+    fn log_event<'s, E>(&'s self, reference: &'s str, tag: &'s str, value: &'s E) -> impl 's + Send + Future<Output = Result<(), Self::Error>>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        <T as AuditLogger>::log_event(self, reference, tag, value)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_generated_reference() {
+        // Generated references are unique...
+        let first: SessionedAuditLogger<()> = SessionedAuditLogger::generated(());
+        let second: SessionedAuditLogger<()> = SessionedAuditLogger::generated(());
+        assert_ne!(first.reference(), second.reference());
+
+        // ...unless a deterministic generator is injected
+        let first: SessionedAuditLogger<()> = SessionedAuditLogger::generated_with(|| "session".into(), ());
+        let second: SessionedAuditLogger<()> = SessionedAuditLogger::generated_with(|| "session".into(), ());
+        assert_eq!(first.reference(), "session");
+        assert_eq!(first.reference(), second.reference());
+    }
 }
@@ -0,0 +1,259 @@
+//  CACHING.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 11:31:15
+//  Last edited:
+//    16 Oct 2026, 01:25:17
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a [`StateResolver`] that remembers the results of another
+//!   one for some time.
+//
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
+use crate::stateresolver::StateResolver;
+
+
+/***** HELPERS *****/
+/// A single entry in the cache.
+#[derive(Debug)]
+struct Slot<T> {
+    /// When this slot was added to the cache.
+    created: Instant,
+    /// The last resolved value and when it was resolved, if any.
+    ///
+    /// Resolving happens while holding this lock, such that concurrent resolutions of the same
+    /// state wait for the first instead of all hitting the wrapped resolver.
+    value:   Arc<Mutex<Option<(Instant, T)>>>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Wraps a [`StateResolver`] such that its results are remembered for some time.
+///
+/// Results are cached per input state. Once a result is older than the time-to-live, the next
+/// resolution of that state transparently resolves it again. If the same state is resolved
+/// multiple times at once while it's not cached, only the first resolution reaches the wrapped
+/// resolver; the others wait for and share its result.
+///
+/// Errors are never cached, and are returned as-is. The state's slot is dropped too, such that a
+/// failing state doesn't take up room in the cache.
+#[derive(Debug)]
+pub struct CachingStateResolver<R: StateResolver> {
+    /// The wrapped resolver.
+    inner: R,
+    /// How long a result remains valid.
+    ttl: Duration,
+    /// The maximum number of states to remember.
+    max_entries: usize,
+    /// The cache itself.
+    cache: std::sync::Mutex<HashMap<R::State, Slot<R::Resolved>>>,
+}
+impl<R: StateResolver> CachingStateResolver<R> {
+    /// Constructor for the CachingStateResolver.
+    ///
+    /// # Arguments
+    /// - `inner`: The [`StateResolver`] to wrap.
+    /// - `ttl`: How long a result remains valid.
+    /// - `max_entries`: The maximum number of states to remember. If a new state is resolved
+    ///   while the cache is full, the state that was added first is forgotten. Clamped to at
+    ///   least 1.
+    ///
+    /// # Returns
+    /// A new CachingStateResolver with an empty cache.
+    #[inline]
+    pub fn new(inner: R, ttl: Duration, max_entries: usize) -> Self {
+        Self { inner, ttl, max_entries: max_entries.max(1), cache: std::sync::Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the wrapped resolver.
+    ///
+    /// # Returns
+    /// A reference to the wrapped [`StateResolver`].
+    #[inline]
+    pub const fn inner(&self) -> &R { &self.inner }
+
+    /// Forgets everything in the cache.
+    #[inline]
+    pub fn clear(&self) {
+        // NOTE: The lock is only poisoned if a panic occurred while holding it, which our code doesn't do
+        self.cache.lock().unwrap().clear();
+    }
+}
+impl<R> CachingStateResolver<R>
+where
+    R: StateResolver,
+    R::State: Eq + Hash,
+{
+    /// Forgets the result for a particular state, if any.
+    ///
+    /// # Arguments
+    /// - `state`: The state to forget the result of.
+    #[inline]
+    pub fn invalidate(&self, state: &R::State) { self.cache.lock().unwrap().remove(state); }
+}
+impl<R> StateResolver for CachingStateResolver<R>
+where
+    R: Sync + StateResolver,
+    R::State: Send + Sync + Clone + Eq + Hash,
+    R::Resolved: Send + Clone,
+{
+    type Error = R::Error;
+    type Resolved = R::Resolved;
+    type State = R::State;
+
+    async fn resolve<'a, L>(&'a self, state: Self::State, logger: &'a SessionedAuditLogger<L>) -> Result<Self::Resolved, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        // Find the slot for this state, making one if there's none
+        let slot: Arc<Mutex<Option<(Instant, R::Resolved)>>> = {
+            let mut cache = self.cache.lock().unwrap();
+            if !cache.contains_key(&state) && cache.len() >= self.max_entries {
+                let oldest: Option<R::State> = cache.iter().min_by_key(|(_, slot)| slot.created).map(|(state, _)| state.clone());
+                if let Some(oldest) = oldest {
+                    cache.remove(&oldest);
+                }
+            }
+            cache.entry(state.clone()).or_insert_with(|| Slot { created: Instant::now(), value: Arc::new(Mutex::new(None)) }).value.clone()
+        };
+
+        // Return the cached value if it's still fresh, or else resolve it (again)
+        let mut value = slot.lock().await;
+        if let Some((resolved_at, resolved)) = &*value {
+            if resolved_at.elapsed() < self.ttl {
+                return Ok(resolved.clone());
+            }
+        }
+        let resolved: R::Resolved = match self.inner.resolve(state.clone(), logger).await {
+            Ok(resolved) => resolved,
+            Err(err) => {
+                // NOTE: Only remove the slot if it's still ours, i.e., it wasn't evicted and replaced meanwhile
+                *value = None;
+                let mut cache = self.cache.lock().unwrap();
+                if cache.get(&state).is_some_and(|other| Arc::ptr_eq(&other.value, &slot)) {
+                    cache.remove(&state);
+                }
+                return Err(err);
+            },
+        };
+        *value = Some((Instant::now(), resolved.clone()));
+        Ok(resolved)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use thiserror::Error;
+
+    use super::*;
+    use crate::testing::RecordingLogger;
+
+
+    /// The error returned by a failing [`Counting`] resolver.
+    #[derive(Debug, Error)]
+    #[error("Resolving failed")]
+    struct CountingError;
+
+    /// A [`StateResolver`] that takes a second to multiply its state by ten, counting how often it's called.
+    #[derive(Debug, Default)]
+    struct Counting {
+        /// The number of times we've been called.
+        calls: AtomicUsize,
+        /// Whether to fail instead.
+        fail:  AtomicBool,
+    }
+    impl StateResolver for Counting {
+        type Error = CountingError;
+        type Resolved = u32;
+        type State = u32;
+
+        async fn resolve<'a, L>(&'a self, state: Self::State, _logger: &'a SessionedAuditLogger<L>) -> Result<Self::Resolved, Self::Error>
+        where
+            L: Sync + AuditLogger,
+        {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            if self.fail.load(Ordering::SeqCst) { Err(CountingError) } else { Ok(state * 10) }
+        }
+    }
+
+    /// Returns how often the wrapped [`Counting`] resolver was called.
+    fn calls(resolver: &CachingStateResolver<Counting>) -> usize { resolver.inner().calls.load(Ordering::SeqCst) }
+
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ttl() {
+        let (logger, resolver) = (RecordingLogger::default(), CachingStateResolver::new(Counting::default(), Duration::from_secs(10), 8));
+        let logger = logger.session("ref");
+        assert_eq!(resolver.resolve(1, &logger).await.unwrap(), 10);
+        assert_eq!(resolver.resolve(1, &logger).await.unwrap(), 10);
+        assert_eq!(calls(&resolver), 1);
+
+        // Expired results are resolved again
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(resolver.resolve(1, &logger).await.unwrap(), 10);
+        assert_eq!(calls(&resolver), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_eviction() {
+        let (logger, resolver) = (RecordingLogger::default(), CachingStateResolver::new(Counting::default(), Duration::from_secs(3600), 2));
+        let logger = logger.session("ref");
+        for state in [1, 2, 3] {
+            resolver.resolve(state, &logger).await.unwrap();
+        }
+        assert_eq!(calls(&resolver), 3);
+
+        // The first state was forgotten to make room for the third
+        resolver.resolve(2, &logger).await.unwrap();
+        resolver.resolve(3, &logger).await.unwrap();
+        assert_eq!(calls(&resolver), 3);
+        resolver.resolve(1, &logger).await.unwrap();
+        assert_eq!(calls(&resolver), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_single_flight() {
+        let (logger, resolver) = (RecordingLogger::default(), CachingStateResolver::new(Counting::default(), Duration::from_secs(3600), 8));
+        let logger = logger.session("ref");
+        let (first, second, other) = tokio::join!(resolver.resolve(1, &logger), resolver.resolve(1, &logger), resolver.resolve(2, &logger));
+        assert_eq!((first.unwrap(), second.unwrap(), other.unwrap()), (10, 10, 20));
+        assert_eq!(calls(&resolver), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_errors_not_cached() {
+        let (logger, resolver) = (RecordingLogger::default(), CachingStateResolver::new(Counting::default(), Duration::from_secs(3600), 8));
+        let logger = logger.session("ref");
+        resolver.inner().fail.store(true, Ordering::SeqCst);
+        assert!(resolver.resolve(1, &logger).await.is_err());
+        assert!(resolver.cache.lock().unwrap().is_empty());
+
+        // The next attempt reaches the wrapped resolver again
+        resolver.inner().fail.store(false, Ordering::SeqCst);
+        assert_eq!(resolver.resolve(1, &logger).await.unwrap(), 10);
+        assert_eq!(calls(&resolver), 2);
+    }
+}
@@ -0,0 +1,284 @@
+//  CAPPED.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:03:21
+//  Last edited:
+//    16 Oct 2026, 01:22:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a [`ReasonerConnector`] wrapper that limits how many
+//!   reasons the wrapped reasoner can return.
+//
+
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Mutex, PoisonError};
+
+use error_trace::{ErrorTrace as _, Trace};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
+use crate::reasonerconn::{ReasonerConnector, ReasonerContext, ReasonerResponse};
+use crate::reasons::ManyReason;
+
+
+/***** ERRORS *****/
+/// Defines the errors returned by the [`CappedReasonsReasoner`].
+#[derive(Debug, Error)]
+pub enum Error<E> {
+    /// The wrapped reasoner failed.
+    #[error(transparent)]
+    Inner(E),
+    /// Failed to log the capped response to the given logger.
+    #[error("Failed to log the capped response to {to}")]
+    LogResponse { to: &'static str, source: Trace },
+}
+
+
+
+
+
+/***** HELPERS *****/
+/// An [`AuditLogger`] that holds back any response logged by the wrapped reasoner, and forwards
+/// everything else.
+///
+/// This allows the [`CappedReasonsReasoner`] to log the capped response instead.
+struct HoldResponse<'l, L> {
+    /// The logger to forward to.
+    logger: &'l SessionedAuditLogger<L>,
+    /// The raw response of the last response held back, or [`None`] if nothing was logged.
+    raw:    Mutex<Option<Option<String>>>,
+}
+impl<'l, L: Sync + AuditLogger> AuditLogger for HoldResponse<'l, L> {
+    type Error = L::Error;
+
+    #[inline]
+    fn log_context<'a, C>(&'a self, context: &'a C) -> impl 'a + Send + Future<Output = Result<(), Self::Error>>
+    where
+        C: ?Sized + Sync + ReasonerContext,
+    {
+        self.logger.log_context(context)
+    }
+
+    #[inline]
+    fn log_response<'a, R>(
+        &'a self,
+        _reference: &'a str,
+        _response: &'a ReasonerResponse<R>,
+        raw: Option<&'a str>,
+    ) -> impl 'a + Send + Future<Output = Result<(), Self::Error>>
+    where
+        R: Sync + Display,
+    {
+        *self.raw.lock().unwrap_or_else(PoisonError::into_inner) = Some(raw.map(String::from));
+        async { Ok(()) }
+    }
+
+    #[inline]
+    fn log_question<'a, S, Q>(
+        &'a self,
+        reference: &'a str,
+        state: &'a S,
+        question: &'a Q,
+    ) -> impl 'a + Send + Future<Output = Result<(), Self::Error>>
+    where
+        S: Sync + Serialize,
+        Q: Sync + Serialize,
+    {
+        <SessionedAuditLogger<L> as AuditLogger>::log_question(self.logger, reference, state, question)
+    }
+
+    #[inline]
+    fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> impl 'a + Send + Future<Output = Result<(), Self::Error>>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        <SessionedAuditLogger<L> as AuditLogger>::log_event(self.logger, reference, tag, value)
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Abstracts over reason types that are collections of reasons, such that they can be capped.
+pub trait CappableReason {
+    /// Truncates the reasons in this collection to at most the given number.
+    ///
+    /// If any are removed, a marker saying how many were omitted is appended in their place.
+    ///
+    /// # Arguments
+    /// - `max`: The maximum number of reasons to keep (excluding the marker).
+    fn cap(&mut self, max: usize);
+}
+impl<R: From<String>> CappableReason for ManyReason<R> {
+    #[inline]
+    fn cap(&mut self, max: usize) {
+        if self.len() <= max {
+            return;
+        }
+        let omitted: usize = self.len() - max;
+        self.truncate(max);
+        self.push(R::from(format!("and {omitted} more omitted")));
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A [`ReasonerConnector`] that truncates the reasons returned by another reasoner to a
+/// configurable maximum.
+///
+/// This is useful for policies that may produce very many reasons for a single verdict, which
+/// would otherwise flood logs and responses to clients.
+///
+/// The response logged by the wrapped reasoner is held back, and the capped response is logged
+/// in its place. Its raw response, if any, is kept as-is.
+#[derive(Clone, Debug)]
+pub struct CappedReasonsReasoner<C> {
+    /// The wrapped reasoner.
+    inner: C,
+    /// The maximum number of reasons to return.
+    max:   usize,
+}
+impl<C> CappedReasonsReasoner<C> {
+    /// Constructor for the CappedReasonsReasoner.
+    ///
+    /// # Arguments
+    /// - `inner`: The reasoner to wrap.
+    /// - `max`: The maximum number of reasons to return. Any excess reasons are replaced by a
+    ///   single "and N more omitted" marker.
+    ///
+    /// # Returns
+    /// A new CappedReasonsReasoner.
+    #[inline]
+    pub const fn new(inner: C, max: usize) -> Self { Self { inner, max } }
+
+    /// Returns the wrapped reasoner.
+    ///
+    /// # Returns
+    /// A reference to the wrapped reasoner.
+    #[inline]
+    pub const fn inner(&self) -> &C { &self.inner }
+}
+impl<C> ReasonerConnector for CappedReasonsReasoner<C>
+where
+    C: Sync + ReasonerConnector,
+    C::State: Send,
+    C::Question: Send,
+    C::Reason: Sync + Display + CappableReason,
+{
+    type Context = C::Context;
+    type Error = Error<C::Error>;
+    type Question = C::Question;
+    type Reason = C::Reason;
+    type State = C::State;
+
+    #[inline]
+    fn context(&self) -> Self::Context { self.inner.context() }
+
+    #[inline]
+    async fn health_check(&self) -> Result<(), Self::Error> { self.inner.health_check().await.map_err(Error::Inner) }
+
+    async fn consult<'a, L>(
+        &'a self,
+        state: Self::State,
+        question: Self::Question,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        let held: HoldResponse<L> = HoldResponse { logger, raw: Mutex::new(None) };
+        let mut res: ReasonerResponse<Self::Reason> =
+            self.inner.consult(state, question, &SessionedAuditLogger::new(logger.reference(), &held)).await.map_err(Error::Inner)?;
+        match &mut res {
+            ReasonerResponse::Success => {},
+            ReasonerResponse::Violated(reasons) | ReasonerResponse::Indeterminate { reasons } => reasons.cap(self.max),
+        }
+
+        // Log the capped response in place of the one held back
+        if let Some(raw) = held.raw.into_inner().unwrap_or_else(PoisonError::into_inner) {
+            logger
+                .log_response(&res, raw.as_deref())
+                .await
+                .map_err(|err| Error::LogResponse { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
+        }
+        Ok(res)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+    use crate::testing::{RecordingLogger, TestContext};
+
+
+    /// A reasoner that always violates with the given number of reasons.
+    struct ManyReasons(usize);
+    impl ReasonerConnector for ManyReasons {
+        type Context = TestContext;
+        type Error = Infallible;
+        type Question = ();
+        type Reason = ManyReason<String>;
+        type State = ();
+
+        fn context(&self) -> Self::Context { TestContext }
+
+        async fn consult<'a, L>(
+            &'a self,
+            state: Self::State,
+            question: Self::Question,
+            logger: &'a SessionedAuditLogger<L>,
+        ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+        where
+            L: Sync + AuditLogger,
+        {
+            logger.log_question(&state, &question).await.unwrap();
+            let res: ReasonerResponse<Self::Reason> = ReasonerResponse::Violated((0..self.0).map(|i| format!("reason{i}")).collect());
+            logger.log_response(&res, Some("raw")).await.unwrap();
+            Ok(res)
+        }
+    }
+
+
+    #[test]
+    fn test_cap() {
+        let mut reasons: ManyReason<String> = ["a", "b", "c"].into_iter().map(String::from).collect();
+        reasons.cap(3);
+        assert_eq!(reasons.len(), 3);
+        reasons.cap(1);
+        assert_eq!(reasons.iter().map(String::as_str).collect::<Vec<&str>>(), ["a", "and 2 more omitted"]);
+        let mut reasons: ManyReason<String> = ManyReason::new();
+        reasons.cap(0);
+        assert!(reasons.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_consult_logs_capped_response() {
+        let logger = RecordingLogger::default();
+        let res = CappedReasonsReasoner::new(ManyReasons(4), 2).consult((), (), &logger.session("ref")).await.unwrap();
+        let ReasonerResponse::Violated(reasons) = res else { panic!("Expected a violation, got {res:?}") };
+        assert_eq!(reasons.iter().map(String::as_str).collect::<Vec<&str>>(), ["reason0", "reason1", "and 2 more omitted"]);
+
+        // The capped response is logged exactly once, in place of the uncapped one
+        assert_eq!(logger.logs(), [
+            "question ref".to_string(),
+            format!("response ref: {} (raw: Some(\"raw\"))", ReasonerResponse::Violated(reasons)),
+        ]);
+    }
+}
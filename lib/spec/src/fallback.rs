@@ -0,0 +1,188 @@
+//  FALLBACK.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:52:40
+//  Last edited:
+//    16 Oct 2026, 01:24:03
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a [`StateResolver`] that falls back to another resolver if
+//!   the first one fails.
+//
+
+use error_trace::{ErrorTrace as _, Trace};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
+use crate::stateresolver::StateResolver;
+
+
+/***** ERRORS *****/
+/// Defines the errors returned by the [`FallbackStateResolver`].
+#[derive(Debug, Error)]
+pub enum Error<P, S> {
+    /// The primary resolver failed in a way that the secondary resolver can't fix.
+    #[error("Failed to resolve state with primary resolver")]
+    Primary { source: P },
+    /// Both resolvers failed.
+    #[error("Failed to resolve state with both primary and secondary resolver (secondary resolver: {secondary})")]
+    Both {
+        #[source]
+        primary:   P,
+        secondary: S,
+    },
+    /// Failed to log that we fell back to the secondary resolver.
+    #[error("Failed to log \"state-fallback\"-event to {to}")]
+    LogEvent { to: &'static str, source: Trace },
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A [`StateResolver`] that resolves with a primary resolver, and falls back to a secondary
+/// resolver if that fails.
+///
+/// A typical use is to fall back to a last-known-good state on disk when a remote API is down.
+///
+/// Whenever it falls back, the error of the primary resolver is logged as a warning and written to
+/// the audit trail as a `state-fallback`-event. This way, a verdict reached on fallback state can
+/// be told apart from one reached on fresh state.
+#[derive(Clone, Debug)]
+pub struct FallbackStateResolver<P: StateResolver, S> {
+    /// The resolver to try first.
+    pub primary:   P,
+    /// The resolver to try if the primary one fails.
+    pub secondary: S,
+    /// Decides which errors of the primary resolver are returned as-is instead of falling back.
+    propagate:     fn(&P::Error) -> bool,
+}
+impl<P: StateResolver, S> FallbackStateResolver<P, S> {
+    /// Constructor for the FallbackStateResolver.
+    ///
+    /// # Arguments
+    /// - `primary`: The resolver to try first.
+    /// - `secondary`: The resolver to try if `primary` fails.
+    ///
+    /// # Returns
+    /// A new FallbackStateResolver that falls back on every error of the `primary` resolver.
+    #[inline]
+    pub fn new(primary: P, secondary: S) -> Self { Self { primary, secondary, propagate: |_| false } }
+
+    /// Configures which errors of the primary resolver are returned immediately instead of
+    /// falling back.
+    ///
+    /// This is useful for errors that the secondary resolver would only repeat or, worse, mask
+    /// with stale state. For example, a use case unknown to the primary resolver should not be
+    /// resolved by the secondary one.
+    ///
+    /// # Arguments
+    /// - `propagate`: A function that returns true for errors that should not be fallen back
+    ///   from (e.g., `http_resolver::Error::is_unknown_use_case`).
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_propagate(mut self, propagate: fn(&P::Error) -> bool) -> Self {
+        self.propagate = propagate;
+        self
+    }
+}
+impl<P, S> StateResolver for FallbackStateResolver<P, S>
+where
+    P: Sync + StateResolver,
+    P::State: Send + Clone,
+    S: Sync + StateResolver<State = P::State, Resolved = P::Resolved>,
+{
+    type Error = Error<P::Error, S::Error>;
+    type Resolved = P::Resolved;
+    type State = P::State;
+
+    async fn resolve<'a, L>(&'a self, state: Self::State, logger: &'a SessionedAuditLogger<L>) -> Result<Self::Resolved, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        let primary: P::Error = match self.primary.resolve(state.clone(), logger).await {
+            Ok(resolved) => return Ok(resolved),
+            Err(err) if (self.propagate)(&err) => return Err(Error::Primary { source: err }),
+            Err(err) => err,
+        };
+
+        // Leave a trace that the state may be stale
+        let reason: String = primary.trace().to_string();
+        warn!("Primary state resolver failed, falling back to secondary resolver: {reason}");
+        logger
+            .log_event("state-fallback", &reason)
+            .await
+            .map_err(|err| Error::LogEvent { to: std::any::type_name::<SessionedAuditLogger<L>>(), source: err.freeze() })?;
+        self.secondary.resolve(state, logger).await.map_err(|secondary| Error::Both { primary, secondary })
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::RecordingLogger;
+
+
+    /// Some error of a [`Fixed`] resolver.
+    #[derive(Clone, Debug, Eq, Error, PartialEq)]
+    #[error("{0}")]
+    struct FixedError(&'static str);
+
+    /// A [`StateResolver`] that always resolves to the same result.
+    struct Fixed(Result<u32, FixedError>);
+    impl StateResolver for Fixed {
+        type Error = FixedError;
+        type Resolved = u32;
+        type State = ();
+
+        async fn resolve<'a, L>(&'a self, _state: Self::State, _logger: &'a SessionedAuditLogger<L>) -> Result<Self::Resolved, Self::Error>
+        where
+            L: Sync + AuditLogger,
+        {
+            self.0.clone()
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let resolve = |primary: Result<u32, FixedError>, secondary: Result<u32, FixedError>| async move {
+            let logger = RecordingLogger::default();
+            let resolver = FallbackStateResolver::new(Fixed(primary), Fixed(secondary)).with_propagate(|err| err.0 == "unknown");
+            let res = resolver.resolve((), &logger.session("ref")).await;
+            (res, logger.logs())
+        };
+
+        // The primary resolver is used if it works
+        let (res, logs) = resolve(Ok(1), Ok(2)).await;
+        assert_eq!(res.unwrap(), 1);
+        assert!(logs.is_empty(), "{logs:?}");
+
+        // Otherwise, we fall back and leave a trace of it
+        let (res, logs) = resolve(Err(FixedError("down")), Ok(2)).await;
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(logs, ["event ref: state-fallback"]);
+
+        // ...unless the error is propagated
+        let (res, logs) = resolve(Err(FixedError("unknown")), Ok(2)).await;
+        assert!(matches!(res, Err(Error::Primary { source: FixedError("unknown") })), "{res:?}");
+        assert!(logs.is_empty(), "{logs:?}");
+
+        // ...or the secondary resolver fails too
+        let (res, logs) = resolve(Err(FixedError("down")), Err(FixedError("missing"))).await;
+        assert!(matches!(res, Err(Error::Both { primary: FixedError("down"), secondary: FixedError("missing") })), "{res:?}");
+        assert_eq!(logs, ["event ref: state-fallback"]);
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    09 Oct 2024, 13:37:15
 //  Last edited:
-//    02 Dec 2024, 14:20:39
+//    16 Oct 2026, 01:22:18
 //  Auto updated?
 //    Yes
 //
@@ -14,9 +14,22 @@
 
 // Declare the modules
 pub mod auditlogger;
+#[cfg(feature = "caching")]
+pub mod caching;
+pub mod capped;
+pub mod fallback;
+pub mod map;
+#[cfg(feature = "workflow")]
+pub mod question;
+#[cfg(feature = "rate-limit")]
+pub mod ratelimit;
 pub mod reasonerconn;
 pub mod reasons;
+pub mod routing;
 pub mod stateresolver;
+pub mod tee;
+#[cfg(test)]
+mod testing;
 
 // Bring some of it into the namespace.
 pub use auditlogger::AuditLogger;
@@ -0,0 +1,192 @@
+//  MAP.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:41:08
+//  Last edited:
+//    16 Oct 2026, 01:50:48
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a [`ReasonerConnector`] wrapper that converts the state
+//!   and question given to it before passing them to the wrapped
+//!   reasoner.
+//
+
+use std::fmt::{Debug, Formatter, Result as FResult};
+use std::marker::PhantomData;
+
+use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
+use crate::reasonerconn::{ReasonerConnector, ReasonerResponse};
+
+
+/***** LIBRARY *****/
+/// A [`ReasonerConnector`] that accepts a different state and question than another reasoner,
+/// and converts them before delegating to it.
+///
+/// This allows a reasoner to be driven by a pipeline that produces different (but convertible)
+/// inputs, without converting them at every call site.
+///
+/// # Generics
+/// - `C`: The wrapped [`ReasonerConnector`].
+/// - `Sf`: The function converting `S` into the wrapped reasoner's state.
+/// - `Qf`: The function converting `Q` into the wrapped reasoner's question.
+/// - `S`: The state accepted by this reasoner.
+/// - `Q`: The question accepted by this reasoner.
+pub struct MapConnector<C, Sf, Qf, S, Q> {
+    /// The wrapped reasoner.
+    inner: C,
+    /// Converts the state.
+    state_fn: Sf,
+    /// Converts the question.
+    question_fn: Qf,
+    /// Remembers which state and question we accept.
+    _inputs: PhantomData<fn(S, Q)>,
+}
+impl<C, Sf, Qf, S, Q> MapConnector<C, Sf, Qf, S, Q>
+where
+    C: ReasonerConnector,
+    Sf: Fn(S) -> C::State,
+    Qf: Fn(Q) -> C::Question,
+{
+    /// Constructor for the MapConnector.
+    ///
+    /// # Arguments
+    /// - `inner`: The reasoner to wrap.
+    /// - `state_fn`: A function that converts the states given to this reasoner into those of
+    ///   `inner`.
+    /// - `question_fn`: A function that converts the questions given to this reasoner into those
+    ///   of `inner`.
+    ///
+    /// # Returns
+    /// A new MapConnector.
+    #[inline]
+    pub const fn new(inner: C, state_fn: Sf, question_fn: Qf) -> Self { Self { inner, state_fn, question_fn, _inputs: PhantomData } }
+}
+impl<C, Sf, Qf, S, Q> MapConnector<C, Sf, Qf, S, Q> {
+    /// Returns the wrapped reasoner.
+    ///
+    /// # Returns
+    /// A reference to the wrapped reasoner.
+    #[inline]
+    pub const fn inner(&self) -> &C { &self.inner }
+}
+impl<C: Clone, Sf: Clone, Qf: Clone, S, Q> Clone for MapConnector<C, Sf, Qf, S, Q> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), state_fn: self.state_fn.clone(), question_fn: self.question_fn.clone(), _inputs: PhantomData }
+    }
+}
+impl<C: Debug, Sf, Qf, S, Q> Debug for MapConnector<C, Sf, Qf, S, Q> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { f.debug_struct("MapConnector").field("inner", &self.inner).finish_non_exhaustive() }
+}
+impl<C, Sf, Qf, S, Q> ReasonerConnector for MapConnector<C, Sf, Qf, S, Q>
+where
+    C: Sync + ReasonerConnector,
+    Sf: Sync + Fn(S) -> C::State,
+    Qf: Sync + Fn(Q) -> C::Question,
+    S: Send,
+    Q: Send,
+{
+    type Context = C::Context;
+    type Error = C::Error;
+    type Question = Q;
+    type Reason = C::Reason;
+    type State = S;
+
+    #[inline]
+    fn context(&self) -> Self::Context { self.inner.context() }
+
+    #[inline]
+    async fn health_check(&self) -> Result<(), Self::Error> { self.inner.health_check().await }
+
+    #[inline]
+    async fn consult<'a, L>(
+        &'a self,
+        state: Self::State,
+        question: Self::Question,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        self.inner.consult((self.state_fn)(state), (self.question_fn)(question), logger).await
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use thiserror::Error;
+
+    use super::*;
+    use crate::testing::{RecordingLogger, TestContext};
+
+
+    /// Some error of a [`Checker`] reasoner.
+    #[derive(Debug, Error)]
+    #[error("Cannot check question {0:?}")]
+    struct CheckError(String);
+
+    /// A reasoner that violates if the state exceeds the question, and fails on negative questions.
+    struct Checker;
+    impl ReasonerConnector for Checker {
+        type Context = TestContext;
+        type Error = CheckError;
+        type Question = String;
+        type Reason = String;
+        type State = u32;
+
+        fn context(&self) -> Self::Context { TestContext }
+
+        async fn consult<'a, L>(
+            &'a self,
+            state: Self::State,
+            question: Self::Question,
+            logger: &'a SessionedAuditLogger<L>,
+        ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+        where
+            L: Sync + AuditLogger,
+        {
+            logger.log_question(&state, &question).await.unwrap();
+            let limit: u32 = question.parse().map_err(|_| CheckError(question.clone()))?;
+            let res: ReasonerResponse<Self::Reason> =
+                if state > limit { ReasonerResponse::Violated(format!("{state} > {limit}")) } else { ReasonerResponse::Success };
+            logger.log_response(&res, None).await.unwrap();
+            Ok(res)
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_consult() {
+        let logger = RecordingLogger::default();
+        let conn = MapConnector::new(Checker, |state: &str| state.len() as u32, |question: i32| question.to_string());
+        let _: TestContext = conn.context();
+
+        // Both the state and question are converted, and the reason is passed back as-is
+        let res = conn.consult("abcd", 3, &logger.session("ref")).await.unwrap();
+        assert!(matches!(&res, ReasonerResponse::Violated(reason) if reason == "4 > 3"), "{res:?}");
+        let res = conn.consult("abc", 3, &logger.session("ref")).await.unwrap();
+        assert!(matches!(res, ReasonerResponse::Success), "{res:?}");
+
+        // Errors are passed back as-is too
+        let res = conn.consult("abc", -1, &logger.session("ref")).await;
+        assert!(matches!(&res, Err(CheckError(question)) if question == "-1"), "{res:?}");
+
+        // The wrapped reasoner logs to the given logger
+        assert_eq!(logger.logs(), [
+            "question ref",
+            "response ref: VIOLATION(4 > 3) (raw: None)",
+            "question ref",
+            "response ref: SUCCESS (raw: None)",
+            "question ref",
+        ]);
+    }
+}
@@ -0,0 +1,86 @@
+//  QUESTION.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:54:44
+//  Last edited:
+//    15 Oct 2026, 23:54:44
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines interfaces for questions that can be derived from a
+//!   [`Workflow`].
+//
+
+use std::future::Future;
+
+use workflow::Workflow;
+
+use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
+use crate::reasonerconn::{ReasonerConnector, ReasonerResponse};
+
+
+/***** LIBRARY *****/
+/// Defines questions that can be derived from a [`Workflow`].
+///
+/// This is the common "is this whole workflow allowed?"-question. Reasoners whose
+/// [`ReasonerConnector::Question`] implements this can be consulted about a workflow directly
+/// using [`ReasonerConnectorExt::consult_workflow()`].
+pub trait FromWorkflow {
+    /// Constructs the question asking whether the given workflow is allowed.
+    ///
+    /// # Arguments
+    /// - `wf`: The [`Workflow`] to ask about.
+    ///
+    /// # Returns
+    /// A new instance of Self that represents the question.
+    fn from_workflow(wf: &Workflow) -> Self;
+}
+
+// Default impls
+impl FromWorkflow for () {
+    /// Reasoners that don't need a question (e.g., because the workflow is part of their state)
+    /// get nothing.
+    #[inline]
+    fn from_workflow(_wf: &Workflow) -> Self {}
+}
+impl FromWorkflow for Workflow {
+    #[inline]
+    fn from_workflow(wf: &Workflow) -> Self { wf.clone() }
+}
+
+
+
+/// Extends a [`ReasonerConnector`] with convenience functions.
+pub trait ReasonerConnectorExt: ReasonerConnector {
+    /// Asks the reasoner whether the given workflow is allowed.
+    ///
+    /// This is a shorthand for calling [`ReasonerConnector::consult()`] with a question built
+    /// using [`FromWorkflow::from_workflow()`].
+    ///
+    /// # Arguments
+    /// - `state`: The [`ReasonerConnector::State`] that describes the state to check in the reasoner.
+    /// - `wf`: The [`Workflow`] to ask about.
+    /// - `logger`: A [`SessionedAuditLogger`] wrapping some [`AuditLogger`] that is used to write to the audit trail as the question's being asked.
+    ///
+    /// # Returns
+    /// A [`ReasonerResponse`] that describes whether the workflow is allowed.
+    ///
+    /// # Errors
+    /// This function errors if [`ReasonerConnector::consult()`] does.
+    #[inline]
+    fn consult_workflow<'a, L>(
+        &'a self,
+        state: Self::State,
+        wf: &'a Workflow,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> impl 'a + Send + Future<Output = Result<ReasonerResponse<Self::Reason>, Self::Error>>
+    where
+        Self::Question: FromWorkflow,
+        L: Sync + AuditLogger,
+    {
+        self.consult(state, <Self::Question as FromWorkflow>::from_workflow(wf), logger)
+    }
+}
+impl<T: ?Sized + ReasonerConnector> ReasonerConnectorExt for T {}
@@ -0,0 +1,297 @@
+//  RATELIMIT.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:59:40
+//  Last edited:
+//    16 Oct 2026, 02:30:27
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a [`ReasonerConnector`] wrapper that limits how often the
+//!   wrapped reasoner is consulted.
+//
+
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
+use crate::reasonerconn::{ReasonerConnector, ReasonerResponse};
+
+
+/***** ERRORS *****/
+/// Defines the errors returned by the [`RateLimitedReasoner`].
+#[derive(Debug, Error)]
+pub enum Error<E> {
+    /// The wrapped reasoner failed.
+    #[error(transparent)]
+    Inner(E),
+    /// We waited too long for our turn to consult the reasoner.
+    #[error("Rate limit exceeded; waited {}ms for a turn to consult the reasoner", waited.as_millis())]
+    RateLimited { waited: Duration },
+}
+
+
+
+
+
+/***** HELPERS *****/
+/// The state of the token bucket.
+#[derive(Debug)]
+struct Bucket {
+    /// The number of tokens currently in the bucket.
+    tokens: f64,
+    /// The last time the bucket was refilled.
+    last:   Instant,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Wraps a [`ReasonerConnector`] such that it is consulted at most a particular number of times
+/// per second, across all callers.
+///
+/// This is implemented as a token bucket: it holds at most `burst` tokens, which refill at
+/// `rate` tokens per second, and every consultation takes one token. If the bucket is empty,
+/// consultations wait for a token in the order they arrived rather than failing (unless a maximum
+/// queue wait is configured).
+///
+/// This composes freely with other wrappers, e.g., ones limiting concurrency.
+#[derive(Debug)]
+pub struct RateLimitedReasoner<C> {
+    /// The wrapped reasoner.
+    inner:    C,
+    /// The number of tokens added per second.
+    rate:     f64,
+    /// The maximum number of tokens in the bucket.
+    burst:    f64,
+    /// The maximum time to wait for a token, if any.
+    max_wait: Option<Duration>,
+    /// The bucket itself.
+    ///
+    /// Note that waiting for a token happens while holding this lock. Because tokio's mutex is
+    /// fair, this makes waiting consultations get their tokens in order.
+    bucket:   Mutex<Bucket>,
+}
+impl<C> RateLimitedReasoner<C> {
+    /// Constructor for the RateLimitedReasoner.
+    ///
+    /// # Arguments
+    /// - `inner`: The [`ReasonerConnector`] to wrap.
+    /// - `rate`: The number of consultations allowed per second on average.
+    /// - `burst`: The number of consultations that may happen at once after a period of quiet.
+    ///   Clamped to at least 1.
+    ///
+    /// # Returns
+    /// A new RateLimitedReasoner that starts with a full bucket.
+    ///
+    /// # Panics
+    /// This function panics if `rate` is not a positive, finite number.
+    #[inline]
+    pub fn new(inner: C, rate: f64, burst: u32) -> Self {
+        assert!(rate.is_finite() && rate > 0.0, "Rate must be a positive, finite number (got {rate})");
+        let burst: f64 = burst.max(1) as f64;
+        Self { inner, rate, burst, max_wait: None, bucket: Mutex::new(Bucket { tokens: burst, last: Instant::now() }) }
+    }
+
+    /// Sets the maximum time a consultation waits for its turn.
+    ///
+    /// # Arguments
+    /// - `max_wait`: The maximum time to wait before failing with [`Error::RateLimited`], or
+    ///   [`None`] to wait indefinitely. Default is [`None`].
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_max_wait(mut self, max_wait: Option<Duration>) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Returns the wrapped reasoner.
+    ///
+    /// # Returns
+    /// A reference to the wrapped [`ReasonerConnector`].
+    #[inline]
+    pub const fn inner(&self) -> &C { &self.inner }
+
+    /// Waits until a token is available and takes it.
+    async fn take(&self) {
+        let mut bucket = self.bucket.lock().await;
+        loop {
+            // Refill the bucket with whatever accumulated since last time
+            let now: Instant = Instant::now();
+            bucket.tokens = (bucket.tokens + now.duration_since(bucket.last).as_secs_f64() * self.rate).min(self.burst);
+            bucket.last = now;
+
+            // Take a token if there is one, or else wait until there is
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return;
+            }
+            tokio::time::sleep(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate)).await;
+        }
+    }
+}
+impl<C> ReasonerConnector for RateLimitedReasoner<C>
+where
+    C: Sync + ReasonerConnector,
+    C::State: Send,
+    C::Question: Send,
+{
+    type Context = C::Context;
+    type Error = Error<C::Error>;
+    type Question = C::Question;
+    type Reason = C::Reason;
+    type State = C::State;
+
+    #[inline]
+    fn context(&self) -> Self::Context { self.inner.context() }
+
+    #[inline]
+    async fn health_check(&self) -> Result<(), Self::Error> {
+        // NOTE: Health checks are not rate limited, as they never reach the policy
+        self.inner.health_check().await.map_err(Error::Inner)
+    }
+
+    async fn consult<'a, L>(
+        &'a self,
+        state: Self::State,
+        question: Self::Question,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        match self.max_wait {
+            Some(max_wait) => tokio::time::timeout(max_wait, self.take()).await.map_err(|_| Error::RateLimited { waited: max_wait })?,
+            None => self.take().await,
+        }
+        self.inner.consult(state, question, logger).await.map_err(Error::Inner)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::testing::{RecordingLogger, TestContext};
+
+
+    /// A reasoner that counts how often it is consulted and checked.
+    #[derive(Debug, Default)]
+    struct Counter {
+        /// The number of consultations so far.
+        consults: AtomicUsize,
+        /// The number of health checks so far.
+        checks:   AtomicUsize,
+    }
+    impl ReasonerConnector for Counter {
+        type Context = TestContext;
+        type Error = Infallible;
+        type Question = ();
+        type Reason = String;
+        type State = ();
+
+        fn context(&self) -> Self::Context { TestContext }
+
+        async fn health_check(&self) -> Result<(), Self::Error> {
+            self.checks.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn consult<'a, L>(
+            &'a self,
+            _state: Self::State,
+            _question: Self::Question,
+            _logger: &'a SessionedAuditLogger<L>,
+        ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+        where
+            L: Sync + AuditLogger,
+        {
+            self.consults.fetch_add(1, Ordering::SeqCst);
+            Ok(ReasonerResponse::Success)
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_consult_burst_and_refill() {
+        tokio::time::pause();
+        let logger = RecordingLogger::default();
+        let logger = logger.session("ref");
+        let conn = RateLimitedReasoner::new(Counter::default(), 2.0, 3);
+
+        // A full bucket allows a burst without waiting
+        let start: Instant = Instant::now();
+        for _ in 0..3 {
+            conn.consult((), (), &logger).await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // The next one has to wait for a token
+        conn.consult((), (), &logger).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(500), "Took {:?}", start.elapsed());
+        assert_eq!(conn.inner().consults.load(Ordering::SeqCst), 4);
+
+        // After a quiet period, the bucket is full again (but not fuller)
+        tokio::time::advance(Duration::from_secs(10)).await;
+        let start: Instant = Instant::now();
+        for _ in 0..3 {
+            conn.consult((), (), &logger).await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        conn.consult((), (), &logger).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(500), "Took {:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_consult_max_wait() {
+        tokio::time::pause();
+        let logger = RecordingLogger::default();
+        let logger = logger.session("ref");
+        let conn = RateLimitedReasoner::new(Counter::default(), 1.0, 1).with_max_wait(Some(Duration::from_millis(100)));
+
+        // The first takes the only token, the second would have to wait a second for the next
+        conn.consult((), (), &logger).await.unwrap();
+        let start: Instant = Instant::now();
+        assert!(matches!(conn.consult((), (), &logger).await, Err(Error::RateLimited { waited }) if waited == Duration::from_millis(100)));
+        assert!(start.elapsed() >= Duration::from_millis(100), "Took {:?}", start.elapsed());
+        assert!(start.elapsed() < Duration::from_secs(1), "Took {:?}", start.elapsed());
+        assert_eq!(conn.inner().consults.load(Ordering::SeqCst), 1);
+
+        // Once refilled, consulting succeeds again
+        tokio::time::advance(Duration::from_secs(1)).await;
+        conn.consult((), (), &logger).await.unwrap();
+        assert_eq!(conn.inner().consults.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_bypasses_limit() {
+        tokio::time::pause();
+        let logger = RecordingLogger::default();
+        let conn = RateLimitedReasoner::new(Counter::default(), 1.0, 1).with_max_wait(Some(Duration::from_millis(100)));
+        conn.consult((), (), &logger.session("ref")).await.unwrap();
+
+        // The bucket is empty, but health checks neither wait nor take a token
+        let start: Instant = Instant::now();
+        for _ in 0..5 {
+            conn.health_check().await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(conn.inner().checks.load(Ordering::SeqCst), 5);
+    }
+}
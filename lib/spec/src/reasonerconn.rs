@@ -4,7 +4,7 @@
 //  Created:
 //    09 Oct 2024, 13:35:41
 //  Last edited:
-//    01 May 2025, 16:48:24
+//    16 Oct 2026, 02:29:42
 //  Auto updated?
 //    Yes
 //
@@ -17,6 +17,8 @@ use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::future::Future;
 
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
@@ -58,11 +60,50 @@ pub trait ReasonerContext: Serialize {
 /// # Generics
 /// - `R`: A type that describes the reason(s) for the query being violating.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub enum ReasonerResponse<R> {
     /// The state is compliant to the policy w.r.t. the question.
     Success,
     /// The state is _not_ compliant to the policy w.r.t. the question.
     Violated(R),
+    /// The reasoner could not decide whether the state is compliant to the policy w.r.t. the
+    /// question (e.g., because the state was insufficient).
+    ///
+    /// Use [`ReasonerResponse::resolve_indeterminate()`] to collapse this into one of the other
+    /// two outcomes according to some [`IndeterminateDefault`].
+    Indeterminate {
+        /// The reason(s) why the reasoner could not reach a verdict.
+        reasons: R,
+    },
+}
+impl<R> ReasonerResponse<R> {
+    /// Returns whether this response is [`ReasonerResponse::Indeterminate`].
+    ///
+    /// # Returns
+    /// True if the reasoner could not reach a verdict, or false otherwise.
+    #[inline]
+    pub const fn is_indeterminate(&self) -> bool { matches!(self, Self::Indeterminate { .. }) }
+
+    /// Collapses a [`ReasonerResponse::Indeterminate`] into either [`ReasonerResponse::Success`]
+    /// or [`ReasonerResponse::Violated`], depending on the given `default`.
+    ///
+    /// Responses that already carry a verdict are returned as-is.
+    ///
+    /// # Arguments
+    /// - `default`: The [`IndeterminateDefault`] that decides what to do with undecided responses.
+    ///
+    /// # Returns
+    /// A ReasonerResponse that is guaranteed not to be [`ReasonerResponse::Indeterminate`].
+    #[inline]
+    pub fn resolve_indeterminate(self, default: IndeterminateDefault) -> Self {
+        match self {
+            Self::Indeterminate { reasons } => match default {
+                IndeterminateDefault::FailOpen => Self::Success,
+                IndeterminateDefault::FailClosed => Self::Violated(reasons),
+            },
+            res => res,
+        }
+    }
 }
 impl<R: Display> Display for ReasonerResponse<R> {
     #[inline]
@@ -72,10 +113,25 @@ impl<R: Display> Display for ReasonerResponse<R> {
             Self::Violated(r) => {
                 write!(f, "VIOLATION({r})")
             },
+            Self::Indeterminate { reasons } => {
+                write!(f, "INDETERMINATE({reasons})")
+            },
         }
     }
 }
 
+/// Defines what to do with a [`ReasonerResponse::Indeterminate`] when a definitive verdict is
+/// required.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum IndeterminateDefault {
+    /// Undecided responses are treated as [`ReasonerResponse::Success`].
+    FailOpen,
+    /// Undecided responses are treated as [`ReasonerResponse::Violated`].
+    #[default]
+    FailClosed,
+}
+
 
 
 
@@ -105,6 +161,16 @@ pub trait ReasonerConnector {
     /// A [`Context`](ReasonerConnector::Context) that describes this context.
     fn context(&self) -> Self::Context;
 
+    /// Checks whether the backend reasoner is available, without asking it anything.
+    ///
+    /// This is meant as a cheap liveness probe (e.g., for a `/healthz` endpoint). It never
+    /// consults any policy, and nothing is written to the audit trail. By default, the reasoner is
+    /// always assumed to be healthy.
+    ///
+    /// # Errors
+    /// This function errors if the reasoner is unavailable.
+    fn health_check<'a>(&'a self) -> impl 'a + Send + Future<Output = Result<(), Self::Error>> { async { Ok(()) } }
+
     /// Sends a policy to the backend reasoner.
     ///
     /// # Arguments
@@ -125,4 +191,83 @@ pub trait ReasonerConnector {
     ) -> impl 'a + Send + Future<Output = Result<ReasonerResponse<Self::Reason>, Self::Error>>
     where
         L: Sync + AuditLogger;
+
+    /// Sends multiple independent policies to the backend reasoner.
+    ///
+    /// By default, this simply [consults](ReasonerConnector::consult()) the reasoner for every
+    /// item in turn. Backends may override this to batch the items more efficiently (e.g., by
+    /// sending them concurrently).
+    ///
+    /// # Arguments
+    /// - `items`: The pairs of [`ReasonerConnector::State`] and [`ReasonerConnector::Question`] to check.
+    /// - `logger`: A [`SessionedAuditLogger`] wrapping some [`AuditLogger`] that is used to write to the audit trail as the questions are being asked.
+    ///   Note that all items are logged under the same session. Backends that consult items
+    ///   concurrently should give every item a session of its own, such that their statements
+    ///   can't be confused.
+    ///
+    /// # Returns
+    /// The result of consulting the reasoner for every item, in the same order as `items`. One
+    /// item failing does not affect the others.
+    fn consult_batch<'a, L>(
+        &'a self,
+        items: Vec<(Self::State, Self::Question)>,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> impl 'a + Send + Future<Output = Vec<Result<ReasonerResponse<Self::Reason>, Self::Error>>>
+    where
+        Self: Sync,
+        Self::State: 'a + Send,
+        Self::Question: 'a + Send,
+        Self::Reason: Send,
+        Self::Error: Send,
+        L: Sync + AuditLogger,
+    {
+        async move {
+            let mut res: Vec<Result<ReasonerResponse<Self::Reason>, Self::Error>> = Vec::with_capacity(items.len());
+            for (state, question) in items {
+                res.push(self.consult(state, question, logger).await);
+            }
+            res
+        }
+    }
+
+    /// Asks multiple questions about the same state to the backend reasoner.
+    ///
+    /// By default, this simply [consults](ReasonerConnector::consult()) the reasoner for every
+    /// question in turn, each with a clone of the `state`. Backends may override this to share the
+    /// work of preparing the state between the questions.
+    ///
+    /// Not to be confused with [`ReasonerConnector::consult_batch()`], which checks independent
+    /// pairs of state and question instead; hence the different name.
+    ///
+    /// # Arguments
+    /// - `state`: The [`ReasonerConnector::State`] that describes the state to check in the reasoner.
+    /// - `questions`: The [`ReasonerConnector::Question`]s that select exactly what kinds of compliance are being checked.
+    /// - `logger`: A [`SessionedAuditLogger`] wrapping some [`AuditLogger`] that is used to write to the audit trail as the questions are being asked.
+    ///   Note that all questions are logged under the same session.
+    ///
+    /// # Returns
+    /// The result of consulting the reasoner for every question, in the same order as
+    /// `questions`. One question failing does not affect the others.
+    fn consult_questions<'a, L>(
+        &'a self,
+        state: Self::State,
+        questions: Vec<Self::Question>,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> impl 'a + Send + Future<Output = Vec<Result<ReasonerResponse<Self::Reason>, Self::Error>>>
+    where
+        Self: Sync,
+        Self::State: 'a + Send + Clone,
+        Self::Question: 'a + Send,
+        Self::Reason: Send,
+        Self::Error: Send,
+        L: Sync + AuditLogger,
+    {
+        async move {
+            let mut res: Vec<Result<ReasonerResponse<Self::Reason>, Self::Error>> = Vec::with_capacity(questions.len());
+            for question in questions {
+                res.push(self.consult(state.clone(), question, logger).await);
+            }
+            res
+        }
+    }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2024, 09:53:49
 //  Last edited:
-//    06 May 2025, 12:45:11
+//    16 Oct 2026, 00:50:26
 //  Auto updated?
 //    Yes
 //
@@ -15,12 +15,15 @@
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::ops::{Deref, DerefMut};
 
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 
 /***** LIBRARY ****/
 /// Represents that no reason is used.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct NoReason;
 impl Display for NoReason {
     #[inline]
@@ -29,6 +32,7 @@ impl Display for NoReason {
 
 /// Represents that multiple reasons can be given.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ManyReason<R>(Vec<R>);
 impl<R> Default for ManyReason<R> {
     #[inline]
@@ -92,3 +96,40 @@ impl<R> FromIterator<R> for ManyReason<R> {
     #[inline]
     fn from_iter<T: IntoIterator<Item = R>>(iter: T) -> Self { Self(iter.into_iter().collect()) }
 }
+
+/// Classifies how serious a reason is.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The reason is merely informative.
+    Info,
+    /// The reason points at something that should be addressed, but need not be blocking.
+    Warning,
+    /// The reason is blocking.
+    Deny,
+}
+impl Display for Severity {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// Represents a reason that is tagged with a [`Severity`].
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct SeverityReason<R> {
+    /// How serious the reason is.
+    pub severity: Severity,
+    /// The reason itself.
+    pub reason:   R,
+}
+impl<R: Display> Display for SeverityReason<R> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "[{}] {}", self.severity, self.reason) }
+}
@@ -0,0 +1,231 @@
+//  ROUTING.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:59:58
+//  Last edited:
+//    16 Oct 2026, 02:30:41
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a [`ReasonerConnector`] that dispatches questions to one of
+//!   multiple reasoners based on some key in the question.
+//
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::auditlogger::{AuditLogger, SessionedAuditLogger};
+use crate::reasonerconn::{ReasonerConnector, ReasonerContext, ReasonerResponse};
+
+
+/***** ERRORS *****/
+/// Defines the errors returned by the [`RoutingReasoner`].
+#[derive(Debug, Error)]
+pub enum Error<E> {
+    /// The reasoner that was routed to failed.
+    #[error(transparent)]
+    Inner(E),
+    /// There was no reasoner for the question's key.
+    #[error("No reasoner registered for {}", if let Some(key) = key { format!("key {key:?}") } else { "questions without a key".into() })]
+    NoRoute { key: Option<String> },
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the context of a [`RoutingReasoner`], which is simply that of all its routes.
+#[derive(Clone, Debug, Serialize)]
+pub struct RoutingContext<C> {
+    /// The context of every route's reasoner.
+    pub routes: BTreeMap<String, C>,
+}
+impl<C> RoutingContext<C> {
+    /// Joins a property of all routes into a single string.
+    ///
+    /// # Arguments
+    /// - `prop`: A closure retrieving the property from a route's context.
+    ///
+    /// # Returns
+    /// A string of the shape `key1=prop1, key2=prop2, ...`.
+    fn join<'s>(&'s self, prop: impl Fn(&'s C) -> Cow<'s, str>) -> Cow<'s, str> {
+        Cow::Owned(self.routes.iter().map(|(key, ctx)| format!("{key}={}", prop(ctx))).collect::<Vec<String>>().join(", "))
+    }
+}
+impl<C: ReasonerContext> ReasonerContext for RoutingContext<C> {
+    #[inline]
+    fn version(&self) -> Cow<'_, str> { self.join(C::version) }
+
+    #[inline]
+    fn language(&self) -> Cow<'_, str> { self.join(C::language) }
+
+    #[inline]
+    fn language_version(&self) -> Cow<'_, str> { self.join(C::language_version) }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A [`ReasonerConnector`] that consults one of multiple reasoners based on a key extracted from
+/// the question (e.g., a use case or tenant identifier).
+///
+/// All routes share the same reasoner type. To route to different kinds of reasoners, wrap them
+/// in an enum that implements [`ReasonerConnector`] by dispatching to its variants.
+#[derive(Clone, Debug)]
+pub struct RoutingReasoner<C: ReasonerConnector> {
+    /// The reasoners to route to, by key.
+    routes: HashMap<String, C>,
+    /// Extracts the key to route on from a question.
+    key:    fn(&C::Question) -> Option<&str>,
+}
+impl<C: ReasonerConnector> RoutingReasoner<C> {
+    /// Constructor for the RoutingReasoner.
+    ///
+    /// # Arguments
+    /// - `routes`: The reasoners to route to, by key.
+    /// - `key`: A function that extracts the key to route on from a question. If it returns
+    ///   [`None`], the question cannot be routed.
+    ///
+    /// # Returns
+    /// A new RoutingReasoner.
+    #[inline]
+    pub fn new(routes: impl IntoIterator<Item = (String, C)>, key: fn(&C::Question) -> Option<&str>) -> Self {
+        Self { routes: routes.into_iter().collect(), key }
+    }
+
+    /// Returns the reasoner that a particular key routes to.
+    ///
+    /// # Arguments
+    /// - `key`: The key to find the reasoner of.
+    ///
+    /// # Returns
+    /// The reasoner for that key, or [`None`] if there is none.
+    #[inline]
+    pub fn route(&self, key: &str) -> Option<&C> { self.routes.get(key) }
+}
+impl<C> ReasonerConnector for RoutingReasoner<C>
+where
+    C: Sync + ReasonerConnector,
+    C::State: Send,
+    C::Question: Send,
+{
+    type Context = RoutingContext<C::Context>;
+    type Error = Error<C::Error>;
+    type Question = C::Question;
+    type Reason = C::Reason;
+    type State = C::State;
+
+    #[inline]
+    fn context(&self) -> Self::Context { RoutingContext { routes: self.routes.iter().map(|(key, c)| (key.clone(), c.context())).collect() } }
+
+    /// Checks every route, failing on the first unhealthy one.
+    #[inline]
+    async fn health_check(&self) -> Result<(), Self::Error> {
+        for inner in self.routes.values() {
+            inner.health_check().await.map_err(Error::Inner)?;
+        }
+        Ok(())
+    }
+
+    async fn consult<'a, L>(
+        &'a self,
+        state: Self::State,
+        question: Self::Question,
+        logger: &'a SessionedAuditLogger<L>,
+    ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+    where
+        L: Sync + AuditLogger,
+    {
+        let key: Option<&str> = (self.key)(&question);
+        let Some(inner) = key.and_then(|key| self.routes.get(key)) else {
+            return Err(Error::NoRoute { key: key.map(String::from) });
+        };
+        inner.consult(state, question, logger).await.map_err(Error::Inner)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::testing::{RecordingLogger, TestContext};
+
+
+    /// A reasoner that answers with its own name and counts how often it is consulted.
+    #[derive(Debug)]
+    struct Named {
+        /// The name to answer with.
+        name:     &'static str,
+        /// The number of consultations so far.
+        consults: AtomicUsize,
+    }
+    impl Named {
+        fn new(name: &'static str) -> Self { Self { name, consults: AtomicUsize::new(0) } }
+    }
+    impl ReasonerConnector for Named {
+        type Context = TestContext;
+        type Error = Infallible;
+        type Question = String;
+        type Reason = String;
+        type State = ();
+
+        fn context(&self) -> Self::Context { TestContext }
+
+        async fn consult<'a, L>(
+            &'a self,
+            _state: Self::State,
+            _question: Self::Question,
+            _logger: &'a SessionedAuditLogger<L>,
+        ) -> Result<ReasonerResponse<Self::Reason>, Self::Error>
+        where
+            L: Sync + AuditLogger,
+        {
+            self.consults.fetch_add(1, Ordering::SeqCst);
+            Ok(ReasonerResponse::Violated(self.name.into()))
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_consult() {
+        let logger = RecordingLogger::default();
+        let logger = logger.session("ref");
+        // NOTE: Routes on the question itself, where an empty question has no key
+        let conn = RoutingReasoner::new([("a".to_string(), Named::new("a")), ("b".to_string(), Named::new("b"))], |question| {
+            (!question.is_empty()).then_some(question.as_str())
+        });
+        let consults = |key: &str| conn.route(key).unwrap().consults.load(Ordering::SeqCst);
+
+        // Questions go to the reasoner of their key only
+        assert!(matches!(conn.consult((), "b".into(), &logger).await.unwrap(), ReasonerResponse::Violated(name) if name == "b"));
+        assert!(matches!(conn.consult((), "a".into(), &logger).await.unwrap(), ReasonerResponse::Violated(name) if name == "a"));
+        assert!(matches!(conn.consult((), "b".into(), &logger).await.unwrap(), ReasonerResponse::Violated(name) if name == "b"));
+        assert_eq!((consults("a"), consults("b")), (1, 2));
+
+        // Unknown or missing keys reach no reasoner at all
+        assert!(matches!(conn.consult((), "c".into(), &logger).await, Err(Error::NoRoute { key: Some(key) }) if key == "c"));
+        assert!(matches!(conn.consult((), String::new(), &logger).await, Err(Error::NoRoute { key: None })));
+        assert_eq!((consults("a"), consults("b")), (1, 2));
+    }
+
+    #[test]
+    fn test_no_route_display() {
+        assert_eq!(Error::<Infallible>::NoRoute { key: Some("c".into()) }.to_string(), "No reasoner registered for key \"c\"");
+        assert_eq!(Error::<Infallible>::NoRoute { key: None }.to_string(), "No reasoner registered for questions without a key");
+    }
+}
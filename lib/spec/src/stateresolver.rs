@@ -4,7 +4,7 @@
 //  Created:
 //    10 Oct 2024, 14:57:24
 //  Last edited:
-//    05 Nov 2024, 10:55:01
+//    15 Oct 2026, 23:47:00
 //  Auto updated?
 //    Yes
 //
@@ -93,6 +93,11 @@ pub trait StateResolver {
 
     /// Resolves the given state.
     ///
+    /// Implementations are encouraged to resolve in a [`tracing`](https://github.com/tokio-rs/tracing)
+    /// span with a `reference`-field set to [`SessionedAuditLogger::reference()`], like the
+    /// reasoners and loggers do. That way, a single reference ties together the state resolution,
+    /// reasoning and logging of one request in the trace output.
+    ///
     /// # Arguments
     /// - `state`: The [`StateResolver::State`] that we already have and want to (further) resolve.
     /// - `logger`: A [`SessionedAuditLogger`] wrapping some [`AuditLogger`] that is used to write to the audit trail as the question's being asked.
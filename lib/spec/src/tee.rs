@@ -0,0 +1,245 @@
+//  TEE.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 11:08:47
+//  Last edited:
+//    16 Oct 2026, 02:31:00
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines an [`AuditLogger`] that writes to two other loggers at the
+//!   same time.
+//
+
+use std::fmt::Display;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::auditlogger::AuditLogger;
+use crate::reasonerconn::{ReasonerContext, ReasonerResponse};
+
+
+/***** ERRORS *****/
+/// Defines the errors returned by the [`TeeLogger`].
+#[derive(Debug, Error)]
+pub enum Error<A, B> {
+    /// Only the first logger failed.
+    #[error("Failed to log to first logger")]
+    First { source: A },
+    /// Only the second logger failed.
+    #[error("Failed to log to second logger")]
+    Second { source: B },
+    /// Both loggers failed.
+    #[error("Failed to log to both loggers (second logger: {second})")]
+    Both {
+        #[source]
+        first:  A,
+        second: B,
+    },
+}
+
+/// Combines the results of logging to both loggers of a [`TeeLogger`].
+///
+/// # Arguments
+/// - `first`: The result of the first logger.
+/// - `second`: The result of the second logger.
+///
+/// # Errors
+/// This function errors if either or both results are errors.
+fn combine<A, B>(first: Result<(), A>, second: Result<(), B>) -> Result<(), Error<A, B>> {
+    match (first, second) {
+        (Ok(()), Ok(())) => Ok(()),
+        (Err(source), Ok(())) => Err(Error::First { source }),
+        (Ok(()), Err(source)) => Err(Error::Second { source }),
+        (Err(first), Err(second)) => Err(Error::Both { first, second }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// An [`AuditLogger`] that forwards every statement to two other loggers.
+///
+/// Every statement is always given to both loggers, even if the first fails. Nest TeeLoggers to
+/// write to more than two loggers.
+#[derive(Clone, Debug)]
+pub struct TeeLogger<A, B> {
+    /// The first logger to write to.
+    pub first:  A,
+    /// The second logger to write to.
+    pub second: B,
+}
+impl<A, B> TeeLogger<A, B> {
+    /// Constructor for the TeeLogger.
+    ///
+    /// # Arguments
+    /// - `first`: The first logger to write to.
+    /// - `second`: The second logger to write to.
+    ///
+    /// # Returns
+    /// A new TeeLogger writing to both.
+    #[inline]
+    pub const fn new(first: A, second: B) -> Self { Self { first, second } }
+}
+impl<A, B> AuditLogger for TeeLogger<A, B>
+where
+    A: Sync + AuditLogger,
+    A::Error: Send + 'static,
+    B: Sync + AuditLogger,
+    B::Error: 'static,
+{
+    type Error = Error<A::Error, B::Error>;
+
+    #[inline]
+    async fn log_context<'a, C>(&'a self, context: &'a C) -> Result<(), Self::Error>
+    where
+        C: ?Sized + Sync + ReasonerContext,
+    {
+        let first: Result<(), A::Error> = self.first.log_context(context).await;
+        combine(first, self.second.log_context(context).await)
+    }
+
+    #[inline]
+    async fn log_response<'a, R>(&'a self, reference: &'a str, response: &'a ReasonerResponse<R>, raw: Option<&'a str>) -> Result<(), Self::Error>
+    where
+        R: Sync + Display,
+    {
+        let first: Result<(), A::Error> = self.first.log_response(reference, response, raw).await;
+        combine(first, self.second.log_response(reference, response, raw).await)
+    }
+
+    #[inline]
+    async fn log_question<'a, S, Q>(&'a self, reference: &'a str, state: &'a S, question: &'a Q) -> Result<(), Self::Error>
+    where
+        S: Sync + Serialize,
+        Q: Sync + Serialize,
+    {
+        let first: Result<(), A::Error> = self.first.log_question(reference, state, question).await;
+        combine(first, self.second.log_question(reference, state, question).await)
+    }
+
+    #[inline]
+    async fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> Result<(), Self::Error>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        let first: Result<(), A::Error> = self.first.log_event(reference, tag, value).await;
+        combine(first, self.second.log_event(reference, tag, value).await)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+    use crate::testing::{RecordingLogger, TestContext};
+
+
+    /// The error of a [`Flaky`] logger.
+    #[derive(Debug, Error)]
+    #[error("{0} failed")]
+    struct FlakyError(&'static str);
+
+    /// A logger that records every statement, but fails afterwards if so configured.
+    #[derive(Debug)]
+    struct Flaky {
+        /// The logger recording the statements.
+        logger: RecordingLogger,
+        /// The name to fail with, if we fail.
+        fail:   Option<&'static str>,
+    }
+    impl Flaky {
+        fn result(&self) -> Result<(), FlakyError> { self.fail.map_or(Ok(()), |name| Err(FlakyError(name))) }
+    }
+    impl AuditLogger for Flaky {
+        type Error = FlakyError;
+
+        async fn log_context<'a, C>(&'a self, context: &'a C) -> Result<(), Self::Error>
+        where
+            C: ?Sized + Sync + ReasonerContext,
+        {
+            self.logger.log_context(context).await.unwrap();
+            self.result()
+        }
+
+        async fn log_response<'a, R>(&'a self, reference: &'a str, response: &'a ReasonerResponse<R>, raw: Option<&'a str>) -> Result<(), Self::Error>
+        where
+            R: Sync + Display,
+        {
+            self.logger.log_response(reference, response, raw).await.unwrap();
+            self.result()
+        }
+
+        async fn log_question<'a, S, Q>(&'a self, reference: &'a str, state: &'a S, question: &'a Q) -> Result<(), Self::Error>
+        where
+            S: Sync + Serialize,
+            Q: Sync + Serialize,
+        {
+            self.logger.log_question(reference, state, question).await.unwrap();
+            self.result()
+        }
+
+        async fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, value: &'a E) -> Result<(), Self::Error>
+        where
+            E: ?Sized + Sync + Serialize,
+        {
+            self.logger.log_event(reference, tag, value).await.unwrap();
+            self.result()
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_errors() {
+        let tee = |first: Option<&'static str>, second: Option<&'static str>| {
+            TeeLogger::new(Flaky { logger: RecordingLogger::default(), fail: first }, Flaky { logger: RecordingLogger::default(), fail: second })
+        };
+
+        assert!(tee(None, None).log_context(&TestContext).await.is_ok());
+        match tee(Some("a"), None).log_context(&TestContext).await {
+            Err(err @ Error::First { .. }) => assert_eq!(err.source().unwrap().to_string(), "a failed"),
+            res => panic!("Expected only the first logger to fail, got {res:?}"),
+        }
+        match tee(None, Some("b")).log_context(&TestContext).await {
+            Err(err @ Error::Second { .. }) => assert_eq!(err.source().unwrap().to_string(), "b failed"),
+            res => panic!("Expected only the second logger to fail, got {res:?}"),
+        }
+        match tee(Some("a"), Some("b")).log_context(&TestContext).await {
+            Err(err @ Error::Both { .. }) => {
+                assert_eq!(err.to_string(), "Failed to log to both loggers (second logger: b failed)");
+                assert_eq!(err.source().unwrap().to_string(), "a failed");
+            },
+            res => panic!("Expected both loggers to fail, got {res:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logs_to_both() {
+        for (first, second) in [(None, None), (Some("a"), None), (None, Some("b")), (Some("a"), Some("b"))] {
+            let tee = TeeLogger::new(Flaky { logger: RecordingLogger::default(), fail: first }, Flaky {
+                logger: RecordingLogger::default(),
+                fail:   second,
+            });
+            let _ = tee.log_context(&TestContext).await;
+            let _ = tee.log_question("ref", &(), &()).await;
+            let _ = tee.log_response::<String>("ref", &ReasonerResponse::Success, None).await;
+            let _ = tee.log_event("ref", "tag", &()).await;
+
+            // Both loggers see every statement, regardless of which failed
+            let expected: Vec<String> = tee.first.logger.logs();
+            assert_eq!(expected.len(), 4, "{first:?}, {second:?}");
+            assert_eq!(tee.second.logger.logs(), expected, "{first:?}, {second:?}");
+        }
+    }
+}
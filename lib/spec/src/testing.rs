@@ -0,0 +1,97 @@
+//  TESTING.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 15:12:09
+//  Last edited:
+//    16 Oct 2026, 15:12:09
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines helpers shared by the tests of the various wrappers in
+//!   this crate.
+//
+
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::auditlogger::AuditLogger;
+use crate::reasonerconn::{ReasonerContext, ReasonerResponse};
+
+
+/***** LIBRARY *****/
+/// A [`ReasonerContext`] for reasoners that only exist in tests.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct TestContext;
+impl ReasonerContext for TestContext {
+    #[inline]
+    fn version(&self) -> Cow<'_, str> { Cow::Borrowed("test") }
+
+    #[inline]
+    fn language(&self) -> Cow<'_, str> { Cow::Borrowed("test") }
+
+    #[inline]
+    fn language_version(&self) -> Cow<'_, str> { Cow::Borrowed("test") }
+}
+
+
+
+/// An [`AuditLogger`] that records a one-line summary of every statement logged to it.
+#[derive(Debug, Default)]
+pub struct RecordingLogger {
+    /// The statements logged so far.
+    logs: Mutex<Vec<String>>,
+}
+impl RecordingLogger {
+    /// Returns the statements logged so far.
+    ///
+    /// # Returns
+    /// A list of one-line summaries, in the order they were logged.
+    #[inline]
+    pub fn logs(&self) -> Vec<String> { self.logs.lock().unwrap().clone() }
+}
+impl AuditLogger for RecordingLogger {
+    type Error = Infallible;
+
+    #[inline]
+    async fn log_context<'a, C>(&'a self, context: &'a C) -> Result<(), Self::Error>
+    where
+        C: ?Sized + Sync + ReasonerContext,
+    {
+        self.logs.lock().unwrap().push(format!("context {}", context.version()));
+        Ok(())
+    }
+
+    #[inline]
+    async fn log_response<'a, R>(&'a self, reference: &'a str, response: &'a ReasonerResponse<R>, raw: Option<&'a str>) -> Result<(), Self::Error>
+    where
+        R: Sync + Display,
+    {
+        self.logs.lock().unwrap().push(format!("response {reference}: {response} (raw: {raw:?})"));
+        Ok(())
+    }
+
+    #[inline]
+    async fn log_question<'a, S, Q>(&'a self, reference: &'a str, _state: &'a S, _question: &'a Q) -> Result<(), Self::Error>
+    where
+        S: Sync + Serialize,
+        Q: Sync + Serialize,
+    {
+        self.logs.lock().unwrap().push(format!("question {reference}"));
+        Ok(())
+    }
+
+    #[inline]
+    async fn log_event<'a, E>(&'a self, reference: &'a str, tag: &'a str, _value: &'a E) -> Result<(), Self::Error>
+    where
+        E: ?Sized + Sync + Serialize,
+    {
+        self.logs.lock().unwrap().push(format!("event {reference}: {tag}"));
+        Ok(())
+    }
+}
@@ -0,0 +1,523 @@
+//  ANALYZE.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 10:31:07
+//  Last edited:
+//    16 Oct 2026, 00:18:30
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements various analyses over a [`Workflow`] that collect
+//!   information from it.
+//
+
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+
+use crate::visitor::Visitor;
+use crate::{Dataset, Elem, ElemBranch, ElemCall, ElemLoop, ElemParallel, Entity, Metadata, Workflow};
+
+
+/***** VISITORS *****/
+/// Counts the nodes in a workflow.
+#[derive(Debug, Default)]
+struct NodeCounter {
+    /// The number of nodes counted so far.
+    count: usize,
+}
+impl<'w> Visitor<'w> for NodeCounter {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w ElemCall) -> Result<Option<&'w Elem>, Self::Error> {
+        self.count += 1;
+        Ok(Some(&elem.next))
+    }
+
+    #[inline]
+    fn visit_branch(&mut self, elem: &'w ElemBranch) -> Result<Option<&'w Elem>, Self::Error> {
+        self.count += 1;
+        for b in &elem.branches {
+            self.visit(b)?;
+        }
+        Ok(Some(&elem.next))
+    }
+
+    #[inline]
+    fn visit_parallel(&mut self, elem: &'w ElemParallel) -> Result<Option<&'w Elem>, Self::Error> {
+        self.count += 1;
+        for b in &elem.branches {
+            self.visit(b)?;
+        }
+        Ok(Some(&elem.next))
+    }
+
+    #[inline]
+    fn visit_loop(&mut self, elem: &'w ElemLoop) -> Result<Option<&'w Elem>, Self::Error> {
+        self.count += 1;
+        self.visit(&elem.body)?;
+        Ok(Some(&elem.next))
+    }
+}
+
+/// Finds a particular call in a workflow.
+#[derive(Debug)]
+struct CallFinder<'i, 'w> {
+    /// The identifier of the call to find.
+    id:    &'i str,
+    /// The call, once found.
+    found: Option<&'w ElemCall>,
+}
+impl<'w> Visitor<'w> for CallFinder<'_, 'w> {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w ElemCall) -> Result<Option<&'w Elem>, Self::Error> {
+        if self.found.is_some() {
+            return Ok(None);
+        }
+        if elem.id == self.id {
+            self.found = Some(elem);
+            return Ok(None);
+        }
+        Ok(Some(&elem.next))
+    }
+}
+
+/// Collects the metadata of all calls in a workflow.
+#[derive(Debug, Default)]
+struct MetadataCollector<'w> {
+    /// The metadata found so far.
+    metadata: Vec<&'w Metadata>,
+}
+impl<'w> Visitor<'w> for MetadataCollector<'w> {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w ElemCall) -> Result<Option<&'w Elem>, Self::Error> {
+        self.metadata.extend(elem.metadata.iter());
+        Ok(Some(&elem.next))
+    }
+}
+
+/// Collects all calls in a workflow.
+#[derive(Debug, Default)]
+struct CallCollector<'w> {
+    /// The calls found so far.
+    calls: Vec<&'w ElemCall>,
+}
+impl<'w> Visitor<'w> for CallCollector<'w> {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w ElemCall) -> Result<Option<&'w Elem>, Self::Error> {
+        self.calls.push(elem);
+        Ok(Some(&elem.next))
+    }
+}
+
+/// Collects all datasets read or written by calls in a workflow.
+#[derive(Debug, Default)]
+struct DatasetCollector<'w> {
+    /// The datasets used as input so far.
+    inputs:  Vec<&'w Dataset>,
+    /// The datasets produced as output so far.
+    outputs: Vec<&'w Dataset>,
+}
+impl<'w> Visitor<'w> for DatasetCollector<'w> {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w ElemCall) -> Result<Option<&'w Elem>, Self::Error> {
+        self.inputs.extend(elem.input.iter());
+        self.outputs.extend(elem.output.iter());
+        Ok(Some(&elem.next))
+    }
+}
+
+/// Collects all calls in a workflow that have not been planned on a location.
+#[derive(Debug, Default)]
+struct UnplacedCallCollector<'w> {
+    /// The calls found so far.
+    calls: Vec<&'w ElemCall>,
+}
+impl<'w> Visitor<'w> for UnplacedCallCollector<'w> {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w ElemCall) -> Result<Option<&'w Elem>, Self::Error> {
+        if elem.at.is_none() {
+            self.calls.push(elem);
+        }
+        Ok(Some(&elem.next))
+    }
+}
+
+/// Collects where every task in a workflow is planned to be executed.
+#[derive(Debug, Default)]
+struct TaskPlacementCollector {
+    /// Maps task identifiers to the locations they are executed on.
+    placements: HashMap<String, HashSet<Option<Entity>>>,
+}
+impl<'w> Visitor<'w> for TaskPlacementCollector {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w ElemCall) -> Result<Option<&'w Elem>, Self::Error> {
+        self.placements.entry(elem.task.clone()).or_default().insert(elem.at.clone());
+        Ok(Some(&elem.next))
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Deduplicates a list of datasets by identifier and sorts them.
+///
+/// # Arguments
+/// - `datasets`: The datasets to deduplicate, in the order they were visited.
+///
+/// # Returns
+/// Clones of the datasets, sorted by [`Dataset::id`]. Of duplicates, the first one visited is kept.
+fn dedup_datasets(mut datasets: Vec<&Dataset>) -> Vec<Dataset> {
+    // NOTE: Sorting is stable, so the first of every duplicate stays first
+    datasets.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    datasets.dedup_by(|lhs, rhs| lhs.id == rhs.id);
+    datasets.into_iter().cloned().collect()
+}
+
+
+
+
+
+/***** LIBRARY *****/
+impl Workflow {
+    /// Returns whether this workflow does nothing at all.
+    ///
+    /// # Returns
+    /// True if the workflow immediately terminates (i.e., its start is an [`Elem::Next`] or
+    /// [`Elem::Stop`]), or false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool { matches!(self.start, Elem::Next | Elem::Stop) }
+
+    /// Counts the nodes in this workflow.
+    ///
+    /// Only calls, branches, parallels and loops are counted; the terminating [`Elem::Next`]s and
+    /// [`Elem::Stop`]s are not.
+    ///
+    /// # Returns
+    /// The number of nodes in this workflow. This is `0` if and only if it [is empty](Workflow::is_empty()).
+    pub fn node_count(&self) -> usize {
+        let mut counter = NodeCounter::default();
+        // NOTE: The counter's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut counter).unwrap();
+        counter.count
+    }
+
+    /// Iterates over all calls in this workflow.
+    ///
+    /// Calls are yielded in execution order. The branches of [`Elem::Branch`]es and
+    /// [`Elem::Parallel`]s are visited one after another in the order they are defined, and the
+    /// body of an [`Elem::Loop`] is visited once.
+    ///
+    /// # Returns
+    /// An iterator over every [`ElemCall`] in this workflow.
+    pub fn calls(&self) -> impl Iterator<Item = &ElemCall> {
+        let mut collector = CallCollector::default();
+        // NOTE: The collector's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut collector).unwrap();
+        collector.calls.into_iter()
+    }
+
+    /// Collects all datasets that are used as input by calls in this workflow.
+    ///
+    /// # Returns
+    /// Every input [`Dataset`] once (by [`Dataset::id`]), sorted by identifier.
+    pub fn input_datasets(&self) -> Vec<Dataset> {
+        let mut collector = DatasetCollector::default();
+        // NOTE: The collector's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut collector).unwrap();
+        dedup_datasets(collector.inputs)
+    }
+
+    /// Collects all datasets that are produced as output by calls in this workflow.
+    ///
+    /// # Returns
+    /// Every output [`Dataset`] once (by [`Dataset::id`]), sorted by identifier.
+    pub fn output_datasets(&self) -> Vec<Dataset> {
+        let mut collector = DatasetCollector::default();
+        // NOTE: The collector's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut collector).unwrap();
+        dedup_datasets(collector.outputs)
+    }
+
+    /// Collects all metadata tags in this workflow.
+    ///
+    /// # Returns
+    /// The [`Metadata`] of the workflow as a whole, followed by that of every call (in the order
+    /// they are visited).
+    pub fn metadata_tags(&self) -> Vec<&Metadata> {
+        let mut collector = MetadataCollector { metadata: self.metadata.iter().collect() };
+        // NOTE: The collector's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut collector).unwrap();
+        collector.metadata
+    }
+
+    /// Collects all metadata tags in this workflow that start with a particular prefix.
+    ///
+    /// # Arguments
+    /// - `prefix`: The prefix that [`Metadata::tag`]s must start with (e.g., `classification:`).
+    ///
+    /// # Returns
+    /// The same as [`Workflow::metadata_tags()`], but only the matching [`Metadata`].
+    pub fn metadata_with_prefix(&self, prefix: &str) -> Vec<&Metadata> {
+        let mut tags: Vec<&Metadata> = self.metadata_tags();
+        tags.retain(|metadata| metadata.tag.starts_with(prefix));
+        tags
+    }
+
+    /// Extracts the sub-workflow rooted at a particular call.
+    ///
+    /// This is the call itself and everything that follows it, up to the end of the enclosing
+    /// branch, loop body or workflow.
+    ///
+    /// # Arguments
+    /// - `call_id`: The [`ElemCall::id`] of the call to extract the sub-workflow of.
+    ///
+    /// # Returns
+    /// A clone of the [`Elem::Call`] and its descendants, or [`None`] if no call with the given
+    /// identifier exists in this workflow.
+    pub fn subgraph_from(&self, call_id: &str) -> Option<Elem> {
+        let mut finder = CallFinder { id: call_id, found: None };
+        // NOTE: The finder's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut finder).unwrap();
+        finder.found.map(|call| Elem::Call(call.clone()))
+    }
+
+    /// Collects which tasks are executed where in this workflow.
+    ///
+    /// # Returns
+    /// A map from [`ElemCall::task`]s to the set of locations ([`ElemCall::at`]) they are planned
+    /// on. Unplanned calls show up as [`None`] in this set.
+    pub fn task_placements(&self) -> HashMap<String, HashSet<Option<Entity>>> {
+        let mut collector = TaskPlacementCollector::default();
+        // NOTE: The collector's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut collector).unwrap();
+        collector.placements
+    }
+
+    /// Collects all calls in this workflow that have not been planned yet.
+    ///
+    /// Such workflows typically cannot be fully evaluated by reasoners that decide based on where
+    /// tasks are executed.
+    ///
+    /// # Returns
+    /// All [`ElemCall`]s of which [`ElemCall::at`] is [`None`], in the order they are visited.
+    pub fn unplaced_calls(&self) -> Vec<&ElemCall> {
+        let mut collector = UnplacedCallCollector::default();
+        // NOTE: The collector's error is `Infallible`, so this unwrap never fails
+        self.visit(&mut collector).unwrap();
+        collector.calls
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Generates a call to a specific package at a specific location, nothing else.
+    #[inline]
+    fn gen_placed_call(task: impl Into<String>, at: Option<&str>, next: Elem) -> Elem {
+        Elem::Call(ElemCall {
+            id: String::new(),
+            task: task.into(),
+            input: vec![],
+            output: vec![],
+            at: at.map(|id| Entity { id: id.into() }),
+            metadata: vec![],
+            next: Box::new(next),
+        })
+    }
+
+
+    /// Tests whether calls are iterated in execution order.
+    #[test]
+    fn test_calls() {
+        let wf = Workflow {
+            id: "Test".into(),
+            start: gen_placed_call(
+                "Foo",
+                None,
+                Elem::Loop(ElemLoop {
+                    body: Box::new(Elem::Parallel(ElemParallel {
+                        branches: vec![gen_placed_call("Bar", None, Elem::Next), gen_placed_call("Baz", None, Elem::Next)],
+                        next:     Box::new(Elem::Next),
+                    })),
+                    next: Box::new(Elem::Branch(ElemBranch {
+                        branches: vec![gen_placed_call("Qux", None, Elem::Next), Elem::Next],
+                        next:     Box::new(gen_placed_call("Quz", None, Elem::Stop)),
+                    })),
+                }),
+            ),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+
+        let tasks: Vec<&str> = wf.calls().map(|call| call.task.as_str()).collect();
+        assert_eq!(tasks, vec!["Foo", "Bar", "Baz", "Qux", "Quz"]);
+    }
+
+    /// Tests whether datasets are collected, deduplicated and sorted.
+    #[test]
+    fn test_datasets() {
+        let data = |id: &str| Dataset { id: id.into(), from: None };
+        let mut foo = gen_placed_call("Foo", None, Elem::Next);
+        if let Elem::Call(call) = &mut foo {
+            call.input = vec![data("zeta"), data("alpha")];
+            call.output = vec![data("beta")];
+        }
+        let mut bar = gen_placed_call("Bar", None, Elem::Stop);
+        if let Elem::Call(call) = &mut bar {
+            call.input = vec![data("beta"), data("alpha")];
+            call.output = vec![data("gamma")];
+        }
+        let wf = Workflow {
+            id: "Test".into(),
+            start: Elem::Branch(ElemBranch { branches: vec![foo], next: Box::new(bar) }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+
+        let ids = |datasets: Vec<Dataset>| datasets.into_iter().map(|d| d.id).collect::<Vec<String>>();
+        assert_eq!(ids(wf.input_datasets()), vec!["alpha", "beta", "zeta"]);
+        assert_eq!(ids(wf.output_datasets()), vec!["beta", "gamma"]);
+    }
+
+    /// Tests whether metadata is collected from the workflow and nested calls.
+    #[test]
+    fn test_metadata_tags() {
+        let tag = |tag: &str| Metadata { tag: tag.into(), signature: None };
+        let mut call = gen_placed_call("Foo", None, Elem::Next);
+        if let Elem::Call(call) = &mut call {
+            call.metadata = vec![tag("classification:secret"), tag("owner:amy")];
+        }
+        let wf = Workflow {
+            id: "Test".into(),
+            start: Elem::Parallel(ElemParallel { branches: vec![call], next: Box::new(Elem::Stop) }),
+            user: None,
+            metadata: vec![tag("classification:public")],
+            signature: None,
+        };
+
+        let tags: Vec<&str> = wf.metadata_tags().into_iter().map(|m| m.tag.as_str()).collect();
+        assert_eq!(tags, vec!["classification:public", "classification:secret", "owner:amy"]);
+        let tags: Vec<&str> = wf.metadata_with_prefix("classification:").into_iter().map(|m| m.tag.as_str()).collect();
+        assert_eq!(tags, vec!["classification:public", "classification:secret"]);
+    }
+
+    /// Tests whether nodes are counted correctly.
+    #[test]
+    fn test_node_count() {
+        let gen_wf = |start: Elem| Workflow { id: "Test".into(), start, user: None, metadata: vec![], signature: None };
+
+        // Empty workflows
+        let wf = gen_wf(Elem::Stop);
+        assert!(wf.is_empty());
+        assert_eq!(wf.node_count(), 0);
+        assert!(gen_wf(Elem::Next).is_empty());
+
+        // A single call
+        let wf = gen_wf(gen_placed_call("Foo", None, Elem::Stop));
+        assert!(!wf.is_empty());
+        assert_eq!(wf.node_count(), 1);
+
+        // Nested elements
+        let wf = gen_wf(Elem::Loop(ElemLoop {
+            body: Box::new(Elem::Parallel(ElemParallel {
+                branches: vec![gen_placed_call("Foo", None, Elem::Next), gen_placed_call("Bar", None, gen_placed_call("Baz", None, Elem::Next))],
+                next:     Box::new(Elem::Next),
+            })),
+            next: Box::new(gen_placed_call("Quz", None, Elem::Stop)),
+        }));
+        assert!(!wf.is_empty());
+        assert_eq!(wf.node_count(), 6);
+    }
+
+    /// Tests whether sub-workflows are extracted from nested calls.
+    #[test]
+    fn test_subgraph_from() {
+        let mut foo = gen_placed_call("Foo", None, Elem::Next);
+        if let Elem::Call(call) = &mut foo {
+            call.id = "foo".into();
+        }
+        let bar = gen_placed_call("Bar", None, foo);
+        let wf = Workflow {
+            id: "Test".into(),
+            start: Elem::Parallel(ElemParallel { branches: vec![bar], next: Box::new(Elem::Stop) }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+
+        assert!(matches!(wf.subgraph_from("foo"), Some(Elem::Call(call)) if call.task == "Foo" && matches!(*call.next, Elem::Next)));
+        assert!(wf.subgraph_from("baz").is_none());
+    }
+
+    /// Tests whether task placements are collected across nested elements.
+    #[test]
+    fn test_task_placements() {
+        let wf = Workflow {
+            id: "Test".into(),
+            start: gen_placed_call(
+                "Foo",
+                Some("amy"),
+                Elem::Parallel(ElemParallel {
+                    branches: vec![gen_placed_call("Foo", Some("bob"), Elem::Next), gen_placed_call("Bar", None, Elem::Next)],
+                    next:     Box::new(Elem::Stop),
+                }),
+            ),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+
+        let placements = wf.task_placements();
+        assert_eq!(placements.len(), 2);
+        assert_eq!(placements["Foo"], HashSet::from([Some(Entity { id: "amy".into() }), Some(Entity { id: "bob".into() })]));
+        assert_eq!(placements["Bar"], HashSet::from([None]));
+    }
+
+    /// Tests whether unplaced calls are found across nested elements.
+    #[test]
+    fn test_unplaced_calls() {
+        let wf = Workflow {
+            id: "Test".into(),
+            start: gen_placed_call(
+                "Foo",
+                Some("amy"),
+                Elem::Branch(ElemBranch {
+                    branches: vec![gen_placed_call("Bar", None, Elem::Next), gen_placed_call("Baz", Some("bob"), Elem::Next)],
+                    next:     Box::new(gen_placed_call("Quz", None, Elem::Stop)),
+                }),
+            ),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+
+        let tasks: Vec<&str> = wf.unplaced_calls().into_iter().map(|call| call.task.as_str()).collect();
+        assert_eq!(tasks, vec!["Bar", "Quz"]);
+    }
+}
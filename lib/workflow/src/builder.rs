@@ -0,0 +1,352 @@
+//  BUILDER.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 14:40:37
+//  Last edited:
+//    16 Oct 2026, 14:40:37
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines a builder for constructing [`Workflow`]s programmatically
+//!   without having to wire [`Elem`]s by hand.
+//
+
+use crate::{Dataset, Elem, ElemBranch, ElemCall, ElemLoop, ElemParallel, Entity, Metadata, Workflow};
+
+
+/***** HELPERS *****/
+/// Defines a single step in a [`WorkflowBuilder`], which is an [`Elem`] without its `next`.
+#[derive(Clone, Debug)]
+enum Step {
+    Call(CallBuilder),
+    Branch(Vec<Elem>),
+    Parallel(Vec<Elem>),
+    Loop(Elem),
+}
+
+
+
+/// Folds a list of steps into a graph of [`Elem`]s.
+///
+/// # Arguments
+/// - `steps`: The [`Step`]s to fold, in order of execution.
+/// - `end`: The terminator (i.e., [`Elem::Next`] or [`Elem::Stop`]) after the last step.
+///
+/// # Returns
+/// The [`Elem`] representing the first step.
+fn fold_steps(steps: Vec<Step>, end: Elem) -> Elem {
+    steps.into_iter().rev().fold(end, |next: Elem, step: Step| -> Elem {
+        let next: Box<Elem> = Box::new(next);
+        match step {
+            Step::Call(call) => Elem::Call(ElemCall {
+                id: call.id,
+                task: call.task,
+                input: call.input,
+                output: call.output,
+                at: call.at,
+                metadata: call.metadata,
+                next,
+            }),
+            Step::Branch(branches) => Elem::Branch(ElemBranch { branches, next }),
+            Step::Parallel(branches) => Elem::Parallel(ElemParallel { branches, next }),
+            Step::Loop(body) => Elem::Loop(ElemLoop { body: Box::new(body), next }),
+        }
+    })
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a subgraph that can be nested in a [branch](WorkflowBuilder::branch()),
+/// [parallel](WorkflowBuilder::parallel()) or [loop](WorkflowBuilder::loop_body()).
+///
+/// Obtained from a [`WorkflowBuilder`], which ends in [`Elem::Next`] (i.e., continues after the
+/// parent), or from a [`StoppedWorkflowBuilder`], which ends in [`Elem::Stop`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Subgraph(Elem);
+impl From<WorkflowBuilder> for Subgraph {
+    #[inline]
+    fn from(value: WorkflowBuilder) -> Self { Self(fold_steps(value.steps, Elem::Next)) }
+}
+impl From<StoppedWorkflowBuilder> for Subgraph {
+    #[inline]
+    fn from(value: StoppedWorkflowBuilder) -> Self { Self(fold_steps(value.steps, Elem::Stop)) }
+}
+impl From<Subgraph> for Elem {
+    #[inline]
+    fn from(value: Subgraph) -> Self { value.0 }
+}
+
+
+
+/// Builds a single [`ElemCall`] to add to a [`WorkflowBuilder`].
+#[derive(Clone, Debug)]
+pub struct CallBuilder {
+    /// The identifier of the call.
+    id: String,
+    /// The task executed.
+    task: String,
+    /// The datasets read.
+    input: Vec<Dataset>,
+    /// The datasets written.
+    output: Vec<Dataset>,
+    /// Where the task is executed, if known.
+    at: Option<Entity>,
+    /// The metadata attached to the call.
+    metadata: Vec<Metadata>,
+}
+impl CallBuilder {
+    /// Constructor for the CallBuilder.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the call.
+    /// - `task`: The task executed by the call.
+    ///
+    /// # Returns
+    /// A new CallBuilder for a call without any inputs, outputs, location or metadata.
+    #[inline]
+    pub fn new(id: impl Into<String>, task: impl Into<String>) -> Self {
+        Self { id: id.into(), task: task.into(), input: Vec::new(), output: Vec::new(), at: None, metadata: Vec::new() }
+    }
+
+    /// Adds an input dataset to the call.
+    ///
+    /// # Arguments
+    /// - `data`: The [`Dataset`] read by the call.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_input(mut self, data: Dataset) -> Self {
+        self.input.push(data);
+        self
+    }
+
+    /// Adds an output dataset to the call.
+    ///
+    /// # Arguments
+    /// - `data`: The [`Dataset`] written by the call.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_output(mut self, data: Dataset) -> Self {
+        self.output.push(data);
+        self
+    }
+
+    /// Sets where the call is executed.
+    ///
+    /// # Arguments
+    /// - `at`: The [`Entity`] executing the call.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_at(mut self, at: Entity) -> Self {
+        self.at = Some(at);
+        self
+    }
+
+    /// Adds metadata to the call.
+    ///
+    /// # Arguments
+    /// - `metadata`: The [`Metadata`] to attach.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata.push(metadata);
+        self
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Builds a [`Workflow`] (or a [`Subgraph`] thereof) step by step.
+///
+/// The builder wires all `next`-pointers itself, so the resulting graph is always well-formed. By
+/// default, a workflow ends in [`Elem::Stop`] and a nested subgraph in [`Elem::Next`]; use
+/// [`WorkflowBuilder::stop()`] to explicitly stop a subgraph.
+///
+/// # Example
+/// ```rust
+/// use workflow::builder::{CallBuilder, WorkflowBuilder};
+///
+/// let wf = WorkflowBuilder::new()
+///     .call(CallBuilder::new("a", "Foo"))
+///     .branch([WorkflowBuilder::new().call(CallBuilder::new("b", "Bar")), WorkflowBuilder::new()])
+///     .build("wf", None);
+/// assert_eq!(wf.calls().count(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct WorkflowBuilder {
+    /// The steps added so far.
+    steps: Vec<Step>,
+}
+impl WorkflowBuilder {
+    /// Constructor for the WorkflowBuilder.
+    ///
+    /// # Returns
+    /// A new WorkflowBuilder without any steps.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds a call to the graph.
+    ///
+    /// # Arguments
+    /// - `call`: A [`CallBuilder`] describing the call.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn call(mut self, call: CallBuilder) -> Self {
+        self.steps.push(Step::Call(call));
+        self
+    }
+
+    /// Adds a branch to the graph, of which only one subgraph is taken.
+    ///
+    /// # Arguments
+    /// - `branches`: The [`Subgraph`]s to choose from.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn branch(mut self, branches: impl IntoIterator<Item = impl Into<Subgraph>>) -> Self {
+        self.steps.push(Step::Branch(branches.into_iter().map(|b| Into::<Subgraph>::into(b).0).collect()));
+        self
+    }
+
+    /// Adds a parallel to the graph, of which all subgraphs are taken concurrently.
+    ///
+    /// # Arguments
+    /// - `branches`: The [`Subgraph`]s to take.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn parallel(mut self, branches: impl IntoIterator<Item = impl Into<Subgraph>>) -> Self {
+        self.steps.push(Step::Parallel(branches.into_iter().map(|b| Into::<Subgraph>::into(b).0).collect()));
+        self
+    }
+
+    /// Adds a loop to the graph.
+    ///
+    /// # Arguments
+    /// - `body`: The [`Subgraph`] that is repeated.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn loop_body(mut self, body: impl Into<Subgraph>) -> Self {
+        self.steps.push(Step::Loop(Into::<Subgraph>::into(body).0));
+        self
+    }
+
+    /// Ends the graph with an explicit [`Elem::Stop`].
+    ///
+    /// No further steps can be added after this.
+    ///
+    /// # Returns
+    /// A [`StoppedWorkflowBuilder`] that can be built or nested.
+    #[inline]
+    pub fn stop(self) -> StoppedWorkflowBuilder { StoppedWorkflowBuilder { steps: self.steps } }
+
+    /// Builds the [`Workflow`], ending it in [`Elem::Stop`].
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the workflow.
+    /// - `user`: The [`Entity`] on whose behalf the workflow is executed, if any.
+    ///
+    /// # Returns
+    /// A new [`Workflow`] without metadata or signature.
+    #[inline]
+    pub fn build(self, id: impl Into<String>, user: Option<Entity>) -> Workflow { self.stop().build(id, user) }
+}
+
+
+
+/// A [`WorkflowBuilder`] that has been [stopped](WorkflowBuilder::stop()).
+#[derive(Clone, Debug)]
+pub struct StoppedWorkflowBuilder {
+    /// The steps before the stop.
+    steps: Vec<Step>,
+}
+impl StoppedWorkflowBuilder {
+    /// Builds the [`Workflow`].
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the workflow.
+    /// - `user`: The [`Entity`] on whose behalf the workflow is executed, if any.
+    ///
+    /// # Returns
+    /// A new [`Workflow`] without metadata or signature.
+    #[inline]
+    pub fn build(self, id: impl Into<String>, user: Option<Entity>) -> Workflow {
+        Workflow { id: id.into(), start: fold_steps(self.steps, Elem::Stop), user, metadata: Vec::new(), signature: None }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_builder() {
+        let wf: Workflow = WorkflowBuilder::new()
+            .call(CallBuilder::new("a", "Foo").with_at(Entity { id: "amy".into() }))
+            .parallel([WorkflowBuilder::new().call(CallBuilder::new("b", "Bar")), WorkflowBuilder::new()])
+            .loop_body(WorkflowBuilder::new().branch([WorkflowBuilder::new().stop()]))
+            .build("wf", Some(Entity { id: "bob".into() }));
+
+        let expected = Workflow {
+            id: "wf".into(),
+            start: Elem::Call(ElemCall {
+                id: "a".into(),
+                task: "Foo".into(),
+                input: vec![],
+                output: vec![],
+                at: Some(Entity { id: "amy".into() }),
+                metadata: vec![],
+                next: Box::new(Elem::Parallel(ElemParallel {
+                    branches: vec![
+                        Elem::Call(ElemCall {
+                            id: "b".into(),
+                            task: "Bar".into(),
+                            input: vec![],
+                            output: vec![],
+                            at: None,
+                            metadata: vec![],
+                            next: Box::new(Elem::Next),
+                        }),
+                        Elem::Next,
+                    ],
+                    next:     Box::new(Elem::Loop(ElemLoop {
+                        body: Box::new(Elem::Branch(ElemBranch { branches: vec![Elem::Stop], next: Box::new(Elem::Next) })),
+                        next: Box::new(Elem::Stop),
+                    })),
+                })),
+            }),
+            user: Some(Entity { id: "bob".into() }),
+            metadata: vec![],
+            signature: None,
+        };
+        assert_eq!(wf, expected);
+        assert!(WorkflowBuilder::new().build("empty", None).is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+//  COMPARE.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:18:40
+//  Last edited:
+//    16 Oct 2026, 00:18:40
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements structural comparison and hashing of [`Workflow`]s,
+//!   optionally ignoring identifiers.
+//
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::{Elem, ElemCall, Workflow};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Hashes the semantically relevant fields of a call, but not the next element.
+///
+/// # Arguments
+/// - `call`: The [`ElemCall`] to hash.
+/// - `ignore_ids`: Whether to skip [`ElemCall::id`].
+/// - `state`: The [`Hasher`] to hash into.
+fn hash_call(call: &ElemCall, ignore_ids: bool, state: &mut impl Hasher) {
+    if !ignore_ids {
+        call.id.hash(state);
+    }
+    call.task.hash(state);
+    call.input.hash(state);
+    call.output.hash(state);
+    call.at.hash(state);
+    call.metadata.hash(state);
+}
+
+/// Compares the semantically relevant fields of two calls, but not their next elements.
+///
+/// # Arguments
+/// - `lhs`: The first [`ElemCall`] to compare.
+/// - `rhs`: The second [`ElemCall`] to compare.
+/// - `ignore_ids`: Whether to skip [`ElemCall::id`].
+///
+/// # Returns
+/// True if they are equal, false otherwise.
+fn call_eq(lhs: &ElemCall, rhs: &ElemCall, ignore_ids: bool) -> bool {
+    (ignore_ids || lhs.id == rhs.id)
+        && lhs.task == rhs.task
+        && lhs.input == rhs.input
+        && lhs.output == rhs.output
+        && lhs.at == rhs.at
+        && lhs.metadata == rhs.metadata
+}
+
+/// Hashes an element and everything that follows it.
+///
+/// # Arguments
+/// - `elem`: The [`Elem`] to hash.
+/// - `ignore_ids`: Whether to skip [`ElemCall::id`]s.
+/// - `state`: The [`Hasher`] to hash into.
+fn hash_elem(mut elem: &Elem, ignore_ids: bool, state: &mut impl Hasher) {
+    // NOTE: Like the visitors, we iterate over `next`s to save stack space
+    loop {
+        std::mem::discriminant(elem).hash(state);
+        elem = match elem {
+            Elem::Call(call) => {
+                hash_call(call, ignore_ids, state);
+                &call.next
+            },
+            Elem::Branch(branch) => {
+                branch.branches.len().hash(state);
+                for b in &branch.branches {
+                    hash_elem(b, ignore_ids, state);
+                }
+                &branch.next
+            },
+            Elem::Parallel(parallel) => {
+                parallel.branches.len().hash(state);
+                for b in &parallel.branches {
+                    hash_elem(b, ignore_ids, state);
+                }
+                &parallel.next
+            },
+            Elem::Loop(l) => {
+                hash_elem(&l.body, ignore_ids, state);
+                &l.next
+            },
+            Elem::Next | Elem::Stop => return,
+        };
+    }
+}
+
+/// Compares two elements and everything that follows them.
+///
+/// # Arguments
+/// - `lhs`: The first [`Elem`] to compare.
+/// - `rhs`: The second [`Elem`] to compare.
+/// - `ignore_ids`: Whether to skip [`ElemCall::id`]s.
+///
+/// # Returns
+/// True if they are equal, false otherwise.
+fn elem_eq(mut lhs: &Elem, mut rhs: &Elem, ignore_ids: bool) -> bool {
+    // NOTE: Like the visitors, we iterate over `next`s to save stack space
+    loop {
+        (lhs, rhs) = match (lhs, rhs) {
+            (Elem::Call(l), Elem::Call(r)) => {
+                if !call_eq(l, r, ignore_ids) {
+                    return false;
+                }
+                (&l.next, &r.next)
+            },
+            (Elem::Branch(l), Elem::Branch(r)) => {
+                if l.branches.len() != r.branches.len() || !l.branches.iter().zip(&r.branches).all(|(l, r)| elem_eq(l, r, ignore_ids)) {
+                    return false;
+                }
+                (&l.next, &r.next)
+            },
+            (Elem::Parallel(l), Elem::Parallel(r)) => {
+                if l.branches.len() != r.branches.len() || !l.branches.iter().zip(&r.branches).all(|(l, r)| elem_eq(l, r, ignore_ids)) {
+                    return false;
+                }
+                (&l.next, &r.next)
+            },
+            (Elem::Loop(l), Elem::Loop(r)) => {
+                if !elem_eq(&l.body, &r.body, ignore_ids) {
+                    return false;
+                }
+                (&l.next, &r.next)
+            },
+            (Elem::Next, Elem::Next) | (Elem::Stop, Elem::Stop) => return true,
+            _ => return false,
+        };
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+impl Workflow {
+    /// Computes a hash of the structure of this workflow.
+    ///
+    /// This covers the shape of the graph (including the order of branches) and all fields of the
+    /// workflow and its calls. Note that [`Dataset`](crate::Dataset)s and
+    /// [`Entity`](crate::Entity)s are hashed by identifier only.
+    ///
+    /// The hash is only stable within a single build of this crate, so don't persist it.
+    ///
+    /// # Arguments
+    /// - `ignore_ids`: If true, ignores [`Workflow::id`] and every [`ElemCall::id`], such that
+    ///   workflows differing only in their identifiers hash the same.
+    ///
+    /// # Returns
+    /// A hash that is the same for workflows that are [structurally equal](Workflow::structural_eq()).
+    pub fn structural_hash(&self, ignore_ids: bool) -> u64 {
+        let mut state = DefaultHasher::new();
+        if !ignore_ids {
+            self.id.hash(&mut state);
+        }
+        self.user.hash(&mut state);
+        self.metadata.hash(&mut state);
+        self.signature.hash(&mut state);
+        hash_elem(&self.start, ignore_ids, &mut state);
+        state.finish()
+    }
+
+    /// Compares this workflow to another, optionally ignoring identifiers.
+    ///
+    /// With `ignore_ids` set to false, this is the same as [`PartialEq`] (but it doesn't recurse
+    /// into long chains of elements).
+    ///
+    /// # Arguments
+    /// - `other`: The other [`Workflow`] to compare to.
+    /// - `ignore_ids`: If true, ignores [`Workflow::id`] and every [`ElemCall::id`].
+    ///
+    /// # Returns
+    /// True if both workflows are structurally the same, false otherwise.
+    pub fn structural_eq(&self, other: &Self, ignore_ids: bool) -> bool {
+        (ignore_ids || self.id == other.id)
+            && self.user == other.user
+            && self.metadata == other.metadata
+            && self.signature == other.signature
+            && elem_eq(&self.start, &other.start, ignore_ids)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ElemBranch, Entity};
+
+
+    /// Generates a call with a particular identifier to a particular task.
+    #[inline]
+    fn gen_call(id: impl Into<String>, task: impl Into<String>, next: Elem) -> Elem {
+        Elem::Call(ElemCall {
+            id: id.into(),
+            task: task.into(),
+            input: vec![],
+            output: vec![],
+            at: Some(Entity { id: "amy".into() }),
+            metadata: vec![],
+            next: Box::new(next),
+        })
+    }
+
+    /// Generates a workflow branching between two calls.
+    #[inline]
+    fn gen_wf(id: impl Into<String>, branches: Vec<Elem>) -> Workflow {
+        Workflow {
+            id: id.into(),
+            start: Elem::Branch(ElemBranch { branches, next: Box::new(Elem::Stop) }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        }
+    }
+
+
+    /// Tests whether structural comparison and hashing respect identifiers and branch order.
+    #[test]
+    fn test_structural_eq_hash() {
+        let wf1 = gen_wf("wf1", vec![gen_call("a", "Foo", Elem::Next), gen_call("b", "Bar", Elem::Next)]);
+        let wf2 = gen_wf("wf2", vec![gen_call("c", "Foo", Elem::Next), gen_call("d", "Bar", Elem::Next)]);
+        let wf3 = gen_wf("wf1", vec![gen_call("b", "Bar", Elem::Next), gen_call("a", "Foo", Elem::Next)]);
+
+        // Identical workflows are always equal
+        assert_eq!(wf1, wf1.clone());
+        assert!(wf1.structural_eq(&wf1.clone(), false));
+        assert_eq!(wf1.structural_hash(false), wf1.clone().structural_hash(false));
+
+        // Different identifiers only matter if we want them to
+        assert_ne!(wf1, wf2);
+        assert!(!wf1.structural_eq(&wf2, false));
+        assert_ne!(wf1.structural_hash(false), wf2.structural_hash(false));
+        assert!(wf1.structural_eq(&wf2, true));
+        assert_eq!(wf1.structural_hash(true), wf2.structural_hash(true));
+
+        // Branch order always matters
+        assert_ne!(wf1, wf3);
+        assert!(!wf1.structural_eq(&wf3, true));
+        assert_ne!(wf1.structural_hash(true), wf3.structural_hash(true));
+    }
+}
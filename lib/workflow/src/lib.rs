@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2024, 16:16:26
 //  Last edited:
-//    06 May 2025, 12:48:36
+//    16 Oct 2026, 01:03:33
 //  Auto updated?
 //    Yes
 //
@@ -13,7 +13,14 @@
 //
 
 // Declare modules
+mod analyze;
+pub mod builder;
+mod compare;
 mod optimize;
+mod redact;
+#[cfg(feature = "schemars")]
+pub mod schema;
+pub mod signing;
 pub mod visitor;
 #[cfg(feature = "visualize")]
 pub mod visualize;
@@ -22,6 +29,8 @@ use std::hash::{Hash, Hasher};
 
 // Imports
 use enum_debug::EnumDebug;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -33,11 +42,15 @@ use serde::{Deserialize, Serialize};
 /// function of the dataset's id; i.e., two datasets with the same name but different sources are
 /// considered the same.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Dataset {
     /// Some identifier of the dataset.
     pub id:   String,
     /// If planned, the location where the dataset is transferred from.
+    ///
+    /// This may be omitted when deserializing, in which case it's [`None`].
+    #[cfg_attr(feature = "serde", serde(default))]
     pub from: Option<Entity>,
 }
 impl Eq for Dataset {}
@@ -55,6 +68,7 @@ impl PartialEq for Dataset {
 /// Note that its uniqueness (i.e., what is considered in [`Hash`] and [`PartialEq`]) is purely a
 /// function of the entity's id.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Entity {
     /// Some identifier of this domain.
@@ -71,7 +85,8 @@ impl PartialEq for Entity {
 }
 
 /// Represents a "tag" and everything we need to know.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Metadata {
     /// The arbitrary data embedded as metadata.
@@ -86,7 +101,8 @@ pub struct Metadata {
 
 /***** LIBRARY *****/
 /// Defines the workflow's toplevel view.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Workflow {
     /// The identifier of this workflow as a whole.
@@ -103,7 +119,8 @@ pub struct Workflow {
 }
 
 /// Defines an element in the graph. This is either a _Node_, which defines a task execution, or an _Edge_, which defines how next tasks may be reached.
-#[derive(Clone, Debug, EnumDebug)]
+#[derive(Clone, Debug, EnumDebug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
 pub enum Elem {
@@ -149,7 +166,8 @@ impl From<ElemLoop> for Elem {
 /// Defines a task node in the graph consisting of [`Elem`]s, which defines data access.
 ///
 /// Yeah so basically represents a task execution, with all checker-relevant information.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ElemCall {
     /// Some identifier for this call specifically.
@@ -177,7 +195,8 @@ pub struct ElemCall {
 /// Defines a branching connection between graph [`Elem`]ents.
 ///
 /// Or rather, defines a linear connection between two nodes, with a set of branches in between them.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ElemBranch {
     /// The branches of which one _must_ be taken, but we don't know which one.
@@ -189,7 +208,8 @@ pub struct ElemBranch {
 /// Defines a parallel connection between graph [`Elem`]ents.
 ///
 /// Is like a [branch](ElemBranch), except that _all_ branches are taken _concurrently_ instead of only one.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ElemParallel {
     /// The branches, _all_ of which but be taken _concurrently_.
@@ -201,7 +221,8 @@ pub struct ElemParallel {
 /// Defines a looping connection between graph [`Elem`]ents.
 ///
 /// Simply defines a branch that is taken repeatedly. Any condition that was there is embedded in the branching part, since that's how the branch is dynamically taken and we can't know how often any of them is taken anyway.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ElemLoop {
     /// The body (and embedded condition) of the loop.
@@ -209,3 +230,25 @@ pub struct ElemLoop {
     /// The next graph element that this parallel edge connects to.
     pub next: Box<Elem>,
 }
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+
+    /// Tests whether datasets serialized without a source can still be read.
+    #[test]
+    fn test_dataset_from_optional() {
+        let data: Dataset = serde_json::from_str(r#"{"id":"cohort"}"#).unwrap();
+        assert_eq!(data.id, "cohort");
+        assert_eq!(data.from, None);
+
+        let data: Dataset = serde_json::from_str(r#"{"id":"cohort","from":{"id":"amy"}}"#).unwrap();
+        assert_eq!(data.from, Some(Entity { id: "amy".into() }));
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2024, 17:34:14
 //  Last edited:
-//    05 May 2025, 12:04:21
+//    16 Oct 2026, 00:20:31
 //  Auto updated?
 //    Yes
 //
@@ -15,7 +15,33 @@
 use std::convert::Infallible;
 
 use crate::visitor::VisitorOwned;
-use crate::{Elem, ElemBranch, Workflow};
+use crate::{Elem, ElemBranch, ElemParallel, Workflow};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Finds the element that terminates a chain of elements.
+///
+/// # Arguments
+/// - `elem`: The first [`Elem`] in the chain.
+///
+/// # Returns
+/// The [`Elem::Next`] or [`Elem::Stop`] found by following `next`-fields (but not descending into
+/// branches or loop bodies).
+fn tail_mut(mut elem: &mut Elem) -> &mut Elem {
+    while !matches!(elem, Elem::Next | Elem::Stop) {
+        elem = match elem {
+            Elem::Call(call) => &mut call.next,
+            Elem::Branch(branch) => &mut branch.next,
+            Elem::Parallel(parallel) => &mut parallel.next,
+            Elem::Loop(l) => &mut l.next,
+            Elem::Next | Elem::Stop => unreachable!(),
+        };
+    }
+    elem
+}
+
+
+
 
 
 /***** HELPERS *****/
@@ -162,6 +188,84 @@ impl VisitorOwned for EmptyBranchRemover {
 
 
 
+// Single branches
+/// Simplifies the workflow graph by inlining [`Elem::Branch`]es and [`Elem::Parallel`]s with only
+/// one branch, and dropping branches that do nothing.
+struct BranchInliner {
+    /// Keeps track of whether this optimizer has done anything.
+    ///
+    /// Used to saturate the process.
+    optimized: bool,
+}
+impl Default for BranchInliner {
+    #[inline]
+    fn default() -> Self { Self { optimized: false } }
+}
+impl BranchInliner {
+    /// Simplifies the (already visited) branches of a branch or parallel.
+    ///
+    /// # Arguments
+    /// - `branches`: The branches to simplify.
+    /// - `next`: What comes after the branches.
+    /// - `rebuild`: Rebuilds the original element if nothing can be inlined.
+    ///
+    /// # Returns
+    /// The simplified element.
+    fn inline(&mut self, mut branches: Vec<Elem>, next: Box<Elem>, rebuild: impl FnOnce(Vec<Elem>, Box<Elem>) -> Elem) -> Elem {
+        // Drop branches that immediately continue
+        let old_len: usize = branches.len();
+        branches.retain(|b| !matches!(b, Elem::Next));
+        self.optimized |= branches.len() != old_len;
+
+        // Then see if there's anything to inline
+        match branches.len() {
+            0 => {
+                self.optimized = true;
+                *next
+            },
+            1 => {
+                // NOTE: If the branch stops, we leave it be to not lose the (unreachable) rest
+                let mut branch: Elem = branches.pop().unwrap();
+                let tail: &mut Elem = tail_mut(&mut branch);
+                if matches!(tail, Elem::Next) {
+                    *tail = *next;
+                    self.optimized = true;
+                    branch
+                } else {
+                    rebuild(vec![branch], next)
+                }
+            },
+            _ => rebuild(branches, next),
+        }
+    }
+}
+impl Optimizer for BranchInliner {
+    #[inline]
+    fn has_optimized(&self) -> bool { self.optimized }
+}
+impl VisitorOwned for BranchInliner {
+    type Error = Infallible;
+
+    fn visit_branch(&mut self, mut elem: ElemBranch) -> Result<Elem, Self::Error> {
+        for b in &mut elem.branches {
+            self.visit_mut(b)?;
+        }
+        self.visit_mut(&mut elem.next)?;
+        Ok(self.inline(elem.branches, elem.next, |branches, next| Elem::Branch(ElemBranch { branches, next })))
+    }
+
+    fn visit_parallel(&mut self, mut elem: ElemParallel) -> Result<Elem, Self::Error> {
+        for b in &mut elem.branches {
+            self.visit_mut(b)?;
+        }
+        self.visit_mut(&mut elem.next)?;
+        Ok(self.inline(elem.branches, elem.next, |branches, next| Elem::Parallel(ElemParallel { branches, next })))
+    }
+}
+
+
+
+
 
 /***** LIBRARY *****/
 impl Workflow {
@@ -174,6 +278,16 @@ impl Workflow {
             saturated = !(BranchFlattener::optimize(self) | DeadBranchPruner::optimize(self) | EmptyBranchRemover::optimize(self));
         }
     }
+
+    /// Normalizes the workflow graph by inlining [`Elem::Branch`]es and [`Elem::Parallel`]s that
+    /// have only one branch, and dropping branches that do nothing (and then branches and
+    /// parallels without any branches left).
+    ///
+    /// Inlined branches are chained to whatever followed the branch or parallel. Branches that end
+    /// in an [`Elem::Stop`] are never inlined, so nothing (e.g., metadata) is dropped.
+    ///
+    /// Simplifying is idempotent, i.e., simplifying twice is the same as simplifying once.
+    pub fn simplify(&mut self) { while BranchInliner::optimize(self) {} }
 }
 
 
@@ -273,6 +387,35 @@ mod tests {
         assert!(compares(&pred, &gen_wf("Truth", gen_branch([gen_void_call("foo", "Foo", Elem::Next),], Elem::Stop))));
     }
 
+    /// Tests whether single branches are inlined, and whether doing so is idempotent.
+    #[test]
+    fn test_simplify() {
+        let mut tagged: Elem = gen_void_call("qux", "Qux", Elem::Stop);
+        if let Elem::Call(call) = &mut tagged {
+            call.metadata = vec![crate::Metadata { tag: "owner:amy".into(), signature: None }];
+        }
+        let mut pred: Workflow = gen_wf(
+            "Prediction",
+            Elem::Parallel(ElemParallel {
+                branches: vec![gen_branch([gen_void_call("foo", "Foo", Elem::Next), Elem::Next], Elem::Next)],
+                next:     Box::new(gen_branch(
+                    [gen_void_call("bar", "Bar", Elem::Stop)],
+                    gen_branch([gen_void_call("baz", "Baz", Elem::Next), Elem::Next], tagged.clone()),
+                )),
+            }),
+        );
+        pred.simplify();
+        let truth: Workflow = gen_wf(
+            "Prediction",
+            gen_void_call("foo", "Foo", gen_branch([gen_void_call("bar", "Bar", Elem::Stop)], gen_void_call("baz", "Baz", tagged))),
+        );
+        assert_eq!(pred, truth);
+
+        // Simplifying again doesn't change anything
+        pred.simplify();
+        assert_eq!(pred, truth);
+    }
+
     /// Tests whether empty branches are removed.
     #[test]
     fn test_empty_branch_remover() {
@@ -0,0 +1,265 @@
+//  REDACT.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 11:41:53
+//  Last edited:
+//    16 Oct 2026, 00:06:54
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Redacts sensitive identifiers in a [`Workflow`], e.g., before it is
+//!   written to an audit log, or rewrites its entities altogether.
+//
+
+use std::borrow::Cow;
+use std::convert::Infallible;
+
+use crate::visitor::VisitorMut;
+use crate::{Dataset, Elem, ElemCall, Entity, Metadata, Workflow};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Applies a redaction function to the given identifier in-place.
+///
+/// # Arguments
+/// - `id`: The identifier to redact.
+/// - `redact`: The function doing the redacting.
+#[inline]
+fn redact_id(id: &mut String, redact: fn(&str) -> Cow<'_, str>) {
+    let new_id: String = match redact(id) {
+        Cow::Borrowed(new_id) if new_id == id.as_str() => return,
+        new_id => new_id.into_owned(),
+    };
+    *id = new_id;
+}
+
+/// Redacts the entity in the given [`Entity`], if there is a redaction function for it.
+///
+/// # Arguments
+/// - `entity`: The [`Entity`] to redact.
+/// - `redact`: The function doing the redacting, if any.
+#[inline]
+fn redact_entity(entity: &mut Entity, redact: Option<fn(&str) -> Cow<'_, str>>) {
+    if let Some(redact) = redact {
+        redact_id(&mut entity.id, redact);
+    }
+}
+
+/// Redacts the signer of the given [`Metadata`], if there is a redaction function for it.
+///
+/// # Arguments
+/// - `metadata`: The [`Metadata`] to redact.
+/// - `redact`: The function doing the redacting, if any.
+#[inline]
+fn redact_metadata(metadata: &mut Metadata, redact: Option<fn(&str) -> Cow<'_, str>>) {
+    if let Some((assigner, _)) = &mut metadata.signature {
+        redact_entity(assigner, redact);
+    }
+}
+
+/// Redacts the given [`Dataset`].
+///
+/// # Arguments
+/// - `dataset`: The [`Dataset`] to redact.
+/// - `datasets`: The function redacting dataset identifiers.
+/// - `entities`: The function redacting entity identifiers, if any.
+#[inline]
+fn redact_dataset(dataset: &mut Dataset, datasets: fn(&str) -> Cow<'_, str>, entities: Option<fn(&str) -> Cow<'_, str>>) {
+    redact_id(&mut dataset.id, datasets);
+    if let Some(from) = &mut dataset.from {
+        redact_entity(from, entities);
+    }
+}
+
+
+
+
+
+/***** VISITORS *****/
+/// Redacts identifiers in all calls of a workflow.
+#[derive(Debug)]
+struct Redactor {
+    /// The function redacting dataset identifiers.
+    datasets: fn(&str) -> Cow<'_, str>,
+    /// The function redacting entity identifiers, if any.
+    entities: Option<fn(&str) -> Cow<'_, str>>,
+}
+impl<'w> VisitorMut<'w> for Redactor {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w mut ElemCall) -> Result<Option<&'w mut Elem>, Self::Error> {
+        for dataset in elem.input.iter_mut().chain(elem.output.iter_mut()) {
+            redact_dataset(dataset, self.datasets, self.entities);
+        }
+        if let Some(at) = &mut elem.at {
+            redact_entity(at, self.entities);
+        }
+        for metadata in &mut elem.metadata {
+            redact_metadata(metadata, self.entities);
+        }
+        Ok(Some(&mut elem.next))
+    }
+}
+
+/// Rewrites all entities in the calls of a workflow.
+struct EntityRewriter<'f, F> {
+    /// The function doing the rewriting.
+    f: &'f mut F,
+}
+impl<'w, F: FnMut(&mut Entity)> VisitorMut<'w> for EntityRewriter<'_, F> {
+    type Error = Infallible;
+
+    #[inline]
+    fn visit_call(&mut self, elem: &'w mut ElemCall) -> Result<Option<&'w mut Elem>, Self::Error> {
+        for from in elem.input.iter_mut().chain(elem.output.iter_mut()).filter_map(|dataset| dataset.from.as_mut()) {
+            (self.f)(from);
+        }
+        if let Some(at) = &mut elem.at {
+            (self.f)(at);
+        }
+        for (signer, _) in elem.metadata.iter_mut().filter_map(|metadata| metadata.signature.as_mut()) {
+            (self.f)(signer);
+        }
+        Ok(Some(&mut elem.next))
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+impl Workflow {
+    /// Redacts the identifiers of all datasets (and optionally, entities) in this workflow.
+    ///
+    /// This is useful to keep audit logs correlatable without exposing raw identifiers that may be
+    /// sensitive themselves (e.g., a patient cohort). A typical redaction function hashes the
+    /// identifier and truncates the result.
+    ///
+    /// # Arguments
+    /// - `datasets`: A function that maps dataset identifiers to their redacted counterparts.
+    /// - `entities`: A function that maps entity identifiers (i.e., users, locations and signers)
+    ///   to their redacted counterparts. If omitted, these are left as-is.
+    pub fn redact(&mut self, datasets: fn(&str) -> Cow<'_, str>, entities: Option<fn(&str) -> Cow<'_, str>>) {
+        if let Some(user) = &mut self.user {
+            redact_entity(user, entities);
+        }
+        for metadata in &mut self.metadata {
+            redact_metadata(metadata, entities);
+        }
+        if let Some((signer, _)) = &mut self.signature {
+            redact_entity(signer, entities);
+        }
+
+        // NOTE: The redactor's error is `Infallible`, so this unwrap never fails
+        self.visit_mut(Redactor { datasets, entities }).unwrap();
+    }
+
+    /// Rewrites every [`Entity`] in this workflow.
+    ///
+    /// This covers the workflow's user, the signers of its metadata and signature, and in every
+    /// call, the location it is planned on, the sources of its datasets and the signers of its
+    /// metadata. Useful to, e.g., anonymize a workflow before sharing it, or to run a production
+    /// workflow as some test entity.
+    ///
+    /// # Arguments
+    /// - `f`: A function that rewrites an [`Entity`] in-place.
+    pub fn rewrite_entities(&mut self, mut f: impl FnMut(&mut Entity)) {
+        if let Some(user) = &mut self.user {
+            f(user);
+        }
+        for (signer, _) in self.metadata.iter_mut().filter_map(|metadata| metadata.signature.as_mut()) {
+            f(signer);
+        }
+        if let Some((signer, _)) = &mut self.signature {
+            f(signer);
+        }
+
+        // NOTE: The rewriter's error is `Infallible`, so this unwrap never fails
+        self.visit_mut(EntityRewriter { f: &mut f }).unwrap();
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Redacts everything to a fixed string.
+    fn redact_all(_id: &str) -> Cow<'_, str> { Cow::Borrowed("<redacted>") }
+
+
+    /// Tests whether datasets (and only datasets) are redacted by default.
+    #[test]
+    fn test_redact() {
+        let mut wf = Workflow {
+            id: "Test".into(),
+            start: Elem::Call(ElemCall {
+                id: "foo".into(),
+                task: "Foo".into(),
+                input: vec![Dataset { id: "cohort".into(), from: Some(Entity { id: "amy".into() }) }],
+                output: vec![],
+                at: Some(Entity { id: "bob".into() }),
+                metadata: vec![],
+                next: Box::new(Elem::Stop),
+            }),
+            user: Some(Entity { id: "amy".into() }),
+            metadata: vec![],
+            signature: None,
+        };
+
+        wf.redact(|id| Cow::Owned(format!("#{}", id.len())), None);
+        let Elem::Call(call) = &wf.start else { panic!("Expected call") };
+        assert_eq!(call.input[0].id, "#6");
+        assert_eq!(call.input[0].from, Some(Entity { id: "amy".into() }));
+        assert_eq!(call.at, Some(Entity { id: "bob".into() }));
+
+        wf.redact(redact_all, Some(redact_all));
+        let Elem::Call(call) = &wf.start else { panic!("Expected call") };
+        assert_eq!(call.input[0].id, "<redacted>");
+        assert_eq!(call.input[0].from, Some(Entity { id: "<redacted>".into() }));
+        assert_eq!(wf.user, Some(Entity { id: "<redacted>".into() }));
+    }
+
+    /// Tests whether all entities are rewritten.
+    #[test]
+    fn test_rewrite_entities() {
+        let signed = |id: &str| Metadata { tag: "owner".into(), signature: Some((Entity { id: id.into() }, "sig".into())) };
+        let mut wf = Workflow {
+            id: "Test".into(),
+            start: Elem::Call(ElemCall {
+                id: "foo".into(),
+                task: "Foo".into(),
+                input: vec![Dataset { id: "cohort".into(), from: Some(Entity { id: "amy".into() }) }],
+                output: vec![Dataset { id: "result".into(), from: None }],
+                at: Some(Entity { id: "bob".into() }),
+                metadata: vec![signed("cho")],
+                next: Box::new(Elem::Stop),
+            }),
+            user: Some(Entity { id: "dan".into() }),
+            metadata: vec![signed("eve")],
+            signature: Some((Entity { id: "fay".into() }, "sig".into())),
+        };
+
+        let mut seen: Vec<String> = Vec::new();
+        wf.rewrite_entities(|entity| {
+            seen.push(entity.id.clone());
+            entity.id = "test".into();
+        });
+        seen.sort();
+        assert_eq!(seen, vec!["amy", "bob", "cho", "dan", "eve", "fay"]);
+        let Elem::Call(call) = &wf.start else { panic!("Expected call") };
+        assert_eq!(call.input[0].from, Some(Entity { id: "test".into() }));
+        assert_eq!(call.output[0].from, None);
+        assert_eq!(call.at, Some(Entity { id: "test".into() }));
+        assert_eq!(wf.user, Some(Entity { id: "test".into() }));
+    }
+}
@@ -0,0 +1,50 @@
+//  SCHEMA.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:31:12
+//  Last edited:
+//    16 Oct 2026, 00:31:12
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Generates the JSON Schema of a [`Workflow`], such that
+//!   non-Rust clients can validate payloads and generate types.
+//
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::Workflow;
+
+
+/***** LIBRARY *****/
+/// Returns the JSON Schema of a [`Workflow`].
+///
+/// The recursive elements of the workflow graph (e.g., the [`Box`]ed `next` of every
+/// [`Elem`](crate::Elem)) are expressed as `$ref`s to the schema's `definitions`.
+///
+/// # Returns
+/// The [`RootSchema`] describing a serialized [`Workflow`].
+#[inline]
+pub fn workflow_schema() -> RootSchema { schema_for!(Workflow) }
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests whether the recursive elements end up as definitions.
+    #[test]
+    fn test_workflow_schema() {
+        let schema: RootSchema = workflow_schema();
+        assert_eq!(schema.schema.metadata.as_ref().and_then(|m| m.title.as_deref()), Some("Workflow"));
+        assert!(schema.definitions.contains_key("Elem"));
+    }
+}
@@ -0,0 +1,385 @@
+//  SIGNING.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 14:02:11
+//  Last edited:
+//    16 Oct 2026, 01:25:56
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Verifies the signatures embedded in a [`Workflow`] and its
+//!   [`Metadata`].
+//
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+
+use crate::{Dataset, Elem, Entity, Metadata, Workflow};
+
+
+/***** ERRORS *****/
+/// Defines the errors emitted by [`verify_workflow()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SignatureError {
+    /// The workflow has no signature at all.
+    WorkflowUnsigned { workflow: String },
+    /// The signature of the workflow is not valid.
+    Workflow { workflow: String, signer: String },
+    /// The signature of a piece of metadata is not valid.
+    Metadata {
+        /// The call the metadata belongs to, or [`None`] if it belongs to the workflow as a whole.
+        call:   Option<String>,
+        tag:    String,
+        signer: String,
+    },
+}
+impl Display for SignatureError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::WorkflowUnsigned { workflow } => write!(f, "Workflow {workflow:?} is not signed"),
+            Self::Workflow { workflow, signer } => write!(f, "Signature of {signer:?} on workflow {workflow:?} is invalid"),
+            Self::Metadata { call: Some(call), tag, signer } => {
+                write!(f, "Signature of {signer:?} on metadata {tag:?} of call {call:?} is invalid")
+            },
+            Self::Metadata { call: None, tag, signer } => write!(f, "Signature of {signer:?} on workflow metadata {tag:?} is invalid"),
+        }
+    }
+}
+impl Error for SignatureError {}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Writes a length-prefixed string to a canonical encoding.
+///
+/// # Arguments
+/// - `value`: The string to encode.
+/// - `buf`: The buffer to write to.
+#[inline]
+fn encode_str(value: &str, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// Writes an optional entity to a canonical encoding.
+///
+/// # Arguments
+/// - `value`: The entity to encode, if any.
+/// - `buf`: The buffer to write to.
+#[inline]
+fn encode_entity(value: Option<&Entity>, buf: &mut Vec<u8>) {
+    match value {
+        Some(entity) => {
+            buf.push(1);
+            encode_str(&entity.id, buf);
+        },
+        None => buf.push(0),
+    }
+}
+
+/// Writes a list of datasets to a canonical encoding.
+///
+/// # Arguments
+/// - `value`: The datasets to encode.
+/// - `buf`: The buffer to write to.
+fn encode_datasets(value: &[Dataset], buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    for data in value {
+        encode_str(&data.id, buf);
+        encode_entity(data.from.as_ref(), buf);
+    }
+}
+
+/// Writes a list of metadata, including their signatures, to a canonical encoding.
+///
+/// # Arguments
+/// - `value`: The metadata to encode.
+/// - `buf`: The buffer to write to.
+fn encode_metadata(value: &[Metadata], buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    for meta in value {
+        encode_str(&meta.tag, buf);
+        match &meta.signature {
+            Some((signer, signature)) => {
+                buf.push(1);
+                encode_str(&signer.id, buf);
+                encode_str(signature, buf);
+            },
+            None => buf.push(0),
+        }
+    }
+}
+
+/// Writes an element, and everything that follows it, to a canonical encoding.
+///
+/// # Arguments
+/// - `elem`: The [`Elem`] to encode.
+/// - `buf`: The buffer to write to.
+fn encode_elem(mut elem: &Elem, buf: &mut Vec<u8>) {
+    loop {
+        match elem {
+            Elem::Call(call) => {
+                buf.push(0);
+                encode_str(&call.id, buf);
+                encode_str(&call.task, buf);
+                encode_datasets(&call.input, buf);
+                encode_datasets(&call.output, buf);
+                encode_entity(call.at.as_ref(), buf);
+                encode_metadata(&call.metadata, buf);
+                elem = &call.next;
+            },
+            Elem::Branch(branch) => {
+                buf.push(1);
+                buf.extend_from_slice(&(branch.branches.len() as u64).to_le_bytes());
+                for branch in &branch.branches {
+                    encode_elem(branch, buf);
+                }
+                elem = &branch.next;
+            },
+            Elem::Parallel(parallel) => {
+                buf.push(2);
+                buf.extend_from_slice(&(parallel.branches.len() as u64).to_le_bytes());
+                for branch in &parallel.branches {
+                    encode_elem(branch, buf);
+                }
+                elem = &parallel.next;
+            },
+            Elem::Loop(l) => {
+                buf.push(3);
+                encode_elem(&l.body, buf);
+                elem = &l.next;
+            },
+            Elem::Next => {
+                buf.push(4);
+                return;
+            },
+            Elem::Stop => {
+                buf.push(5);
+                return;
+            },
+        }
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a backend that can verify signatures (e.g., ed25519 or HMAC).
+pub trait VerifyKey {
+    /// Verifies a signature.
+    ///
+    /// # Arguments
+    /// - `signer`: The [`Entity`] claiming to have made the signature, e.g., to look up their
+    ///   public key.
+    /// - `message`: The bytes that were signed.
+    /// - `signature`: The signature as embedded in the workflow.
+    ///
+    /// # Returns
+    /// True if `signature` is a valid signature of `signer` on `message`, or false otherwise
+    /// (including if `signer` is unknown).
+    fn verify(&self, signer: &Entity, message: &[u8], signature: &str) -> bool;
+}
+
+
+
+
+
+/***** LIBRARY *****/
+impl Workflow {
+    /// Returns the bytes covered by the workflow's [signature](Workflow::signature).
+    ///
+    /// This is a canonical encoding of everything in the workflow except that signature, including
+    /// the signatures of any [`Metadata`]. Any change to the workflow thus invalidates it.
+    ///
+    /// # Returns
+    /// The bytes to sign.
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::new();
+        encode_str(&self.id, &mut buf);
+        encode_entity(self.user.as_ref(), &mut buf);
+        encode_metadata(&self.metadata, &mut buf);
+        encode_elem(&self.start, &mut buf);
+        buf
+    }
+}
+
+impl Metadata {
+    /// Returns the bytes covered by the metadata's [signature](Metadata::signature).
+    ///
+    /// This is a canonical encoding of the tag _and_ of where the metadata is attached. As such,
+    /// a signed tag cannot be copied to another workflow or call without invalidating it.
+    ///
+    /// # Arguments
+    /// - `workflow`: The identifier of the [`Workflow`] the metadata belongs to.
+    /// - `call`: The identifier of the call the metadata is attached to, or [`None`] if it's
+    ///   attached to the workflow as a whole.
+    ///
+    /// # Returns
+    /// The bytes to sign.
+    pub fn signed_bytes(&self, workflow: &str, call: Option<&str>) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::new();
+        encode_str(workflow, &mut buf);
+        match call {
+            Some(call) => {
+                buf.push(1);
+                encode_str(call, &mut buf);
+            },
+            None => buf.push(0),
+        }
+        encode_str(&self.tag, &mut buf);
+        buf
+    }
+}
+
+
+
+/// Verifies all signatures in a [`Workflow`].
+///
+/// The workflow itself must be signed, while [`Metadata`] (of the workflow or any of its calls) is
+/// only checked if it is signed.
+///
+/// **Note that this only checks that every signature was made by the entity it claims to be
+/// made by.** It does _not_ check whether that entity may sign the workflow or metadata in
+/// question; any entity known to `key` will do. Callers must check the signers themselves (e.g.,
+/// that the workflow is signed by its [user](Workflow::user)).
+///
+/// # Arguments
+/// - `wf`: The [`Workflow`] to verify.
+/// - `key`: The [`VerifyKey`] to verify the signatures with.
+///
+/// # Errors
+/// This function errors if the workflow is unsigned, or if any signature is invalid. The first
+/// invalid signature is reported, starting with the workflow's own.
+pub fn verify_workflow(wf: &Workflow, key: &dyn VerifyKey) -> Result<(), SignatureError> {
+    // Check the workflow's signature first
+    let Some((signer, signature)) = &wf.signature else {
+        return Err(SignatureError::WorkflowUnsigned { workflow: wf.id.clone() });
+    };
+    if !key.verify(signer, &wf.signed_bytes(), signature) {
+        return Err(SignatureError::Workflow { workflow: wf.id.clone(), signer: signer.id.clone() });
+    }
+
+    // Then the metadata of the workflow and of all calls
+    let metadata =
+        wf.metadata.iter().map(|meta| (None, meta)).chain(wf.calls().flat_map(|call| call.metadata.iter().map(move |meta| (Some(call), meta))));
+    for (call, meta) in metadata {
+        let Some((signer, signature)) = &meta.signature else { continue };
+        if !key.verify(signer, &meta.signed_bytes(&wf.id, call.map(|call| call.id.as_str())), signature) {
+            return Err(SignatureError::Metadata { call: call.map(|call| call.id.clone()), tag: meta.tag.clone(), signer: signer.id.clone() });
+        }
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash as _, Hasher as _};
+
+    use super::*;
+    use crate::ElemCall;
+
+
+    /// Toy key where every entity signs by writing its name and a hash of the message.
+    struct ToyKey;
+    impl ToyKey {
+        fn sign(signer: &str, message: &[u8]) -> String {
+            let mut hasher = DefaultHasher::new();
+            message.hash(&mut hasher);
+            format!("{signer}:{:x}", hasher.finish())
+        }
+    }
+    impl VerifyKey for ToyKey {
+        fn verify(&self, signer: &Entity, message: &[u8], signature: &str) -> bool {
+            signer.id != "mallory" && signature == Self::sign(&signer.id, message)
+        }
+    }
+
+    /// Signs a workflow with the [`ToyKey`].
+    fn sign(wf: &mut Workflow, signer: &str) {
+        let signature: String = ToyKey::sign(signer, &wf.signed_bytes());
+        wf.signature = Some((Entity { id: signer.into() }, signature));
+    }
+
+    /// Creates a piece of metadata signed with the [`ToyKey`].
+    fn meta(tag: &str, signer: &str, workflow: &str, call: Option<&str>) -> Metadata {
+        let mut meta = Metadata { tag: tag.into(), signature: None };
+        let signature: String = ToyKey::sign(signer, &meta.signed_bytes(workflow, call));
+        meta.signature = Some((Entity { id: signer.into() }, signature));
+        meta
+    }
+
+
+    #[test]
+    fn test_verify_workflow() {
+        let mut wf = Workflow {
+            id: "wf".into(),
+            start: Elem::Call(ElemCall {
+                id: "call".into(),
+                task: "task".into(),
+                input: vec![],
+                output: vec![],
+                at: None,
+                metadata: vec![meta("tag", "amy", "wf", Some("call"))],
+                next: Box::new(Elem::Stop),
+            }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+        assert_eq!(verify_workflow(&wf, &ToyKey), Err(SignatureError::WorkflowUnsigned { workflow: "wf".into() }));
+
+        // Valid signatures
+        sign(&mut wf, "amy");
+        assert_eq!(verify_workflow(&wf, &ToyKey), Ok(()));
+        sign(&mut wf, "mallory");
+        assert_eq!(verify_workflow(&wf, &ToyKey), Err(SignatureError::Workflow { workflow: "wf".into(), signer: "mallory".into() }));
+
+        // Invalid metadata signature
+        if let Elem::Call(call) = &mut wf.start {
+            call.metadata[0].signature.as_mut().unwrap().1.push('0');
+        }
+        sign(&mut wf, "amy");
+        assert_eq!(
+            verify_workflow(&wf, &ToyKey),
+            Err(SignatureError::Metadata { call: Some("call".into()), tag: "tag".into(), signer: "amy".into() })
+        );
+    }
+
+    #[test]
+    fn test_metadata_bound_to_location() {
+        let mut wf = Workflow { id: "wf".into(), start: Elem::Stop, user: None, metadata: vec![meta("tag", "amy", "wf", None)], signature: None };
+        sign(&mut wf, "amy");
+        assert_eq!(verify_workflow(&wf, &ToyKey), Ok(()));
+
+        // Metadata signed for another workflow or for a call is rejected
+        for meta in [meta("tag", "amy", "other", None), meta("tag", "amy", "wf", Some("call"))] {
+            wf.metadata = vec![meta];
+            sign(&mut wf, "amy");
+            assert_eq!(verify_workflow(&wf, &ToyKey), Err(SignatureError::Metadata { call: None, tag: "tag".into(), signer: "amy".into() }));
+        }
+    }
+
+    #[test]
+    fn test_signed_bytes_cover_graph() {
+        let mut wf = Workflow { id: "wf".into(), start: Elem::Stop, user: None, metadata: vec![], signature: None };
+        let before: Vec<u8> = wf.signed_bytes();
+        wf.start = Elem::Next;
+        assert_ne!(wf.signed_bytes(), before);
+        wf.signature = Some((Entity { id: "amy".into() }, "sig".into()));
+        wf.start = Elem::Stop;
+        assert_eq!(wf.signed_bytes(), before);
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    31 Oct 2023, 14:30:00
 //  Last edited:
-//    21 Oct 2024, 13:33:05
+//    16 Oct 2026, 00:19:54
 //  Auto updated?
 //    Yes
 //
@@ -13,9 +13,9 @@
 //!   neatly to some writer.
 //
 
-use std::fmt::{Display, Formatter, Result as FResult};
+use std::fmt::{Display, Formatter, Result as FResult, Write as _};
 
-use super::{Elem, ElemBranch, ElemCall, ElemLoop, ElemParallel, Workflow};
+use super::{Dataset, Elem, ElemBranch, ElemCall, ElemLoop, ElemParallel, Workflow};
 
 
 /***** HELPER MACROS *****/
@@ -137,6 +137,130 @@ fn print_elem(f: &mut Formatter, elem: &Elem, prefix: &dyn Display) -> FResult {
 
 
 /***** HELPERS *****/
+/// Builds a Graphviz DOT graph, handing out unique node identifiers.
+#[derive(Debug, Default)]
+struct DotBuilder {
+    /// The statements written so far.
+    out:     String,
+    /// The number of nodes generated so far.
+    n_nodes: usize,
+}
+impl DotBuilder {
+    /// Adds a new node to the graph.
+    ///
+    /// # Arguments
+    /// - `attrs`: The (already escaped) attributes of the node.
+    ///
+    /// # Returns
+    /// The identifier of the new node.
+    fn node(&mut self, attrs: &str) -> String {
+        let id: String = format!("n{}", self.n_nodes);
+        self.n_nodes += 1;
+        // NOTE: Writing to a string never fails
+        writeln!(self.out, "    {id} [{attrs}];").unwrap();
+        id
+    }
+
+    /// Adds edges from a set of nodes to another node.
+    ///
+    /// # Arguments
+    /// - `from`: The nodes to draw edges from.
+    /// - `to`: The node to draw edges to.
+    /// - `attrs`: Any (already escaped) attributes of the edges.
+    fn edges(&mut self, from: &[String], to: &str, attrs: &str) {
+        for from in from {
+            if attrs.is_empty() {
+                writeln!(self.out, "    {from} -> {to};").unwrap();
+            } else {
+                writeln!(self.out, "    {from} -> {to} [{attrs}];").unwrap();
+            }
+        }
+    }
+
+    /// Adds an element and everything that follows it to the graph.
+    ///
+    /// # Arguments
+    /// - `elem`: The [`Elem`] to add.
+    /// - `preds`: The nodes that lead to this element.
+    /// - `stop`: The node that [`Elem::Stop`]s lead to.
+    ///
+    /// # Returns
+    /// The nodes that lead to whatever comes after this element (i.e., where it ends in an
+    /// [`Elem::Next`]).
+    fn elem(&mut self, mut elem: &Elem, mut preds: Vec<String>, stop: &str) -> Vec<String> {
+        // NOTE: Like the visitors, we iterate over `next`s to save stack space
+        loop {
+            elem = match elem {
+                Elem::Call(ElemCall { task, input, output, at, next, .. }) => {
+                    let datasets = |data: &[Dataset]| write_iter!(data.iter().map(|data| data.id.clone()), ", ");
+                    let label: String = format!("{task}\nin: {}\nout: {}", datasets(input), datasets(output));
+                    let mut attrs: String = format!("shape=box, label={}", DotEscape(&label));
+                    if let Some(at) = at {
+                        write!(attrs, ", xlabel={}", DotEscape(&format!("@{}", at.id))).unwrap();
+                    }
+                    let node: String = self.node(&attrs);
+                    self.edges(&preds, &node, "");
+                    preds = vec![node];
+                    next
+                },
+                Elem::Branch(ElemBranch { branches, next }) => {
+                    let fork: String = self.node("shape=diamond, label=\"\"");
+                    self.edges(&preds, &fork, "");
+                    let join: String = self.node("shape=diamond, label=\"\"");
+                    for branch in branches {
+                        let ends: Vec<String> = self.elem(branch, vec![fork.clone()], stop);
+                        self.edges(&ends, &join, "");
+                    }
+                    preds = vec![join];
+                    next
+                },
+                Elem::Parallel(ElemParallel { branches, next }) => {
+                    const BAR: &str = "shape=box, style=filled, fillcolor=black, label=\"\", height=0.05, width=1";
+                    let fork: String = self.node(BAR);
+                    self.edges(&preds, &fork, "");
+                    let join: String = self.node(BAR);
+                    for branch in branches {
+                        let ends: Vec<String> = self.elem(branch, vec![fork.clone()], stop);
+                        self.edges(&ends, &join, "");
+                    }
+                    preds = vec![join];
+                    next
+                },
+                Elem::Loop(ElemLoop { body, next }) => {
+                    let head: String = self.node("shape=circle, label=\"loop\"");
+                    self.edges(&preds, &head, "");
+                    let ends: Vec<String> = self.elem(body, vec![head.clone()], stop);
+                    self.edges(&ends, &head, "style=dashed, constraint=false");
+                    preds = vec![head];
+                    next
+                },
+                Elem::Next => return preds,
+                Elem::Stop => {
+                    self.edges(&preds, stop, "");
+                    return vec![];
+                },
+            };
+        }
+    }
+}
+
+/// Writes a string as a quoted DOT string, where newlines become line breaks.
+struct DotEscape<'s>(&'s str);
+impl Display for DotEscape<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '\\' => write!(f, "\\\\")?,
+                '"' => write!(f, "\\\"")?,
+                '\n' => write!(f, "\\n")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
 /// Writes two display things successively.
 struct Pair<D1, D2>(D1, D2);
 impl<D1: Display, D2: Display> Display for Pair<D1, D2> {
@@ -207,3 +331,76 @@ impl Workflow {
     #[inline]
     pub fn visualize(&self) -> WorkflowFormatter<'_> { WorkflowFormatter { wf: self } }
 }
+
+
+
+/// Renders a [`Workflow`] as a Graphviz DOT graph.
+///
+/// Calls become boxes labelled with their task and datasets (and their location as external
+/// label, if planned). Branches are drawn as diamonds, parallels as bars and loops as a node with
+/// a dashed back-edge from the end of its body. The output can be piped straight into, e.g.,
+/// `dot -Tsvg`.
+///
+/// # Arguments
+/// - `wf`: The [`Workflow`] to render.
+///
+/// # Returns
+/// The DOT source of the graph.
+pub fn to_dot(wf: &Workflow) -> String {
+    let mut builder = DotBuilder::default();
+    let start: String = builder.node(&format!("shape=circle, label={}", DotEscape(&wf.id)));
+    let stop: String = builder.node("shape=doublecircle, label=\"stop\"");
+    let ends: Vec<String> = builder.elem(&wf.start, vec![start], &stop);
+    builder.edges(&ends, &stop, "");
+    format!("digraph workflow {{\n{}}}\n", builder.out)
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Entity;
+
+
+    /// Tests whether DOT output escapes labels and draws loops with back-edges.
+    #[test]
+    fn test_to_dot() {
+        let wf = Workflow {
+            id: "Test".into(),
+            start: Elem::Loop(ElemLoop {
+                body: Box::new(Elem::Call(ElemCall {
+                    id: String::new(),
+                    task: "say \"hi\"".into(),
+                    input: vec![Dataset { id: "a b".into(), from: None }],
+                    output: vec![],
+                    at: Some(Entity { id: "amy".into() }),
+                    metadata: vec![],
+                    next: Box::new(Elem::Next),
+                })),
+                next: Box::new(Elem::Stop),
+            }),
+            user: None,
+            metadata: vec![],
+            signature: None,
+        };
+
+        assert_eq!(
+            to_dot(&wf),
+            r#"digraph workflow {
+    n0 [shape=circle, label="Test"];
+    n1 [shape=doublecircle, label="stop"];
+    n2 [shape=circle, label="loop"];
+    n0 -> n2;
+    n3 [shape=box, label="say \"hi\"\nin: a b\nout: <none>", xlabel="@amy"];
+    n2 -> n3;
+    n3 -> n2 [style=dashed, constraint=false];
+    n2 -> n1;
+}
+"#
+        );
+    }
+}
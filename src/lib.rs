@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2024, 16:13:30
 //  Last edited:
-//    29 Apr 2025, 15:15:34
+//    16 Oct 2026, 00:38:25
 //  Auto updated?
 //    Yes
 //
@@ -13,6 +13,10 @@
 //!   determine if a particular workflow is allowed by policy or not.
 //
 
+// Declare modules
+#[cfg(feature = "schema")]
+pub mod schema;
+
 /// Contains the backend reasoners.
 pub mod reasoners {
     #[cfg(feature = "eflint-haskell-reasoner")]
@@ -30,11 +34,17 @@ pub mod loggers {
     pub use file_logger as file;
     #[cfg(feature = "no-op-logger")]
     pub use no_op_logger as no_op;
+    #[cfg(feature = "syslog-logger")]
+    pub use syslog_logger as syslog;
 }
 /// Contains any state resolvers.
 pub mod resolvers {
     #[cfg(feature = "file-resolver")]
     pub use file_resolver as file;
+    #[cfg(feature = "http-resolver")]
+    pub use http_resolver as http;
+    #[cfg(feature = "sql-resolver")]
+    pub use sql_resolver as sql;
 }
 #[cfg(feature = "eflint-to-json")]
 pub use eflint_to_json;
@@ -0,0 +1,84 @@
+//  SCHEMA.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 10:12:41
+//  Last edited:
+//    16 Oct 2026, 00:20:54
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Exports JSON Schemas of the public types exchanged with the
+//!   reasoner, such that non-Rust clients can generate types and validate
+//!   payloads.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use spec::reasonerconn::{IndeterminateDefault, ReasonerResponse};
+use spec::reasons::{ManyReason, NoReason};
+
+
+/***** ERRORS *****/
+/// Defines the errors emitted by [`export()`].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Failed to create the output directory.
+    #[error("Failed to create output directory {}", path.display())]
+    DirCreate { path: PathBuf, source: std::io::Error },
+    /// Failed to write a schema to its file.
+    #[error("Failed to write schema file {}", path.display())]
+    FileWrite { path: PathBuf, source: std::io::Error },
+    /// Failed to serialize a schema.
+    #[error("Failed to serialize schema of {what}")]
+    SchemaSerialize { what: &'static str, source: serde_json::Error },
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns the JSON Schemas of all public types, paired with the name of the file they're written
+/// to by [`export()`].
+///
+/// Note that recursive types (e.g., the [`Box`]ed `next` of workflow elements) are expressed as
+/// references to the schema's `definitions`.
+///
+/// # Returns
+/// A list of `(filename, schema)`-pairs.
+pub fn schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("workflow.json", workflow::schema::workflow_schema()),
+        ("reasoner-response.json", schema_for!(ReasonerResponse<ManyReason<String>>)),
+        ("reasoner-response-no-reason.json", schema_for!(ReasonerResponse<NoReason>)),
+        ("indeterminate-default.json", schema_for!(IndeterminateDefault)),
+        ("no-reason.json", schema_for!(NoReason)),
+        ("many-reason.json", schema_for!(ManyReason<String>)),
+    ]
+}
+
+/// Writes the JSON Schemas of all public types to the given directory.
+///
+/// See [`schemas()`] for which files are written.
+///
+/// # Arguments
+/// - `dir`: The directory to write the schemas to. Will be created if it does not exist.
+///
+/// # Errors
+/// This function errors if we failed to create the directory or write any of the files.
+pub fn export(dir: impl AsRef<Path>) -> Result<(), Error> {
+    let dir: &Path = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|source| Error::DirCreate { path: dir.into(), source })?;
+
+    for (name, schema) in schemas() {
+        let path: PathBuf = dir.join(name);
+        let raw: String = serde_json::to_string_pretty(&schema).map_err(|source| Error::SchemaSerialize { what: name, source })?;
+        fs::write(&path, raw).map_err(|source| Error::FileWrite { path, source })?;
+    }
+    Ok(())
+}